@@ -0,0 +1,51 @@
+-- User Wallets - persistent storage for registered and verified wallets
+-- Replaces the in-memory WalletRepository so wallets survive restarts
+-- and can be shared between server instances
+
+CREATE TYPE wallet_status AS ENUM ('unverified', 'verified', 'suspended');
+
+CREATE TABLE user_wallets (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+
+    -- Chain and on-chain address
+    chain chain_type NOT NULL,
+    address TEXT NOT NULL,
+
+    -- Verification state
+    status wallet_status NOT NULL DEFAULT 'unverified',
+    verified_at TIMESTAMP WITH TIME ZONE,
+
+    -- Timestamps
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_synced TIMESTAMP WITH TIME ZONE,
+
+    -- SECURITY: An address can only be registered once per chain
+    CONSTRAINT unique_wallet_chain_address UNIQUE (chain, address),
+    CONSTRAINT verified_requires_timestamp CHECK (
+        (status = 'verified' AND verified_at IS NOT NULL) OR status != 'verified'
+    )
+);
+
+CREATE INDEX idx_user_wallets_user ON user_wallets(user_id);
+CREATE INDEX idx_user_wallets_user_chain ON user_wallets(user_id, chain);
+CREATE INDEX idx_user_wallets_status ON user_wallets(status);
+
+-- Wallet balances (per wallet, per token)
+CREATE TABLE wallet_balances (
+    wallet_id UUID NOT NULL REFERENCES user_wallets(id) ON DELETE CASCADE,
+    token_address TEXT NOT NULL,
+    token_symbol TEXT NOT NULL,
+
+    -- Amounts in display units
+    balance NUMERIC NOT NULL DEFAULT 0 CHECK (balance >= 0),
+    balance_usd NUMERIC,
+
+    last_updated TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_id, token_address)
+);
+
+CREATE INDEX idx_wallet_balances_wallet ON wallet_balances(wallet_id);
+
+COMMENT ON TABLE user_wallets IS 'User wallets per chain. (chain, address) is globally unique.';
+COMMENT ON COLUMN user_wallets.verified_at IS 'Set when the wallet owner proves control via signature verification';
@@ -0,0 +1,74 @@
+-- Trades - persistent storage for DEX trades initiated via /trade endpoints
+-- Replaces the in-memory TradeRepository so trade history survives restarts
+
+CREATE TYPE trade_status AS ENUM (
+    'pending',
+    'quote_accepted',
+    'payment_received',
+    'executing_swap',
+    'swap_completed',
+    'settlement_in_progress',
+    'completed',
+    'failed',
+    'cancelled'
+);
+
+CREATE TABLE trades (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL,
+
+    -- Wallets used on each side
+    source_wallet_id UUID NOT NULL REFERENCES user_wallets(id),
+    destination_wallet_id UUID NOT NULL REFERENCES user_wallets(id),
+
+    -- Chains
+    source_chain chain_type NOT NULL,
+    destination_chain chain_type NOT NULL,
+
+    -- Assets (full AssetInfo as JSON)
+    asset_in JSONB NOT NULL,
+    asset_out JSONB NOT NULL,
+
+    -- Amounts (display units)
+    amount_in NUMERIC NOT NULL CHECK (amount_in > 0),
+    amount_out_expected NUMERIC NOT NULL CHECK (amount_out_expected >= 0),
+    amount_out_actual NUMERIC,
+
+    dex_used TEXT NOT NULL,
+    status trade_status NOT NULL DEFAULT 'pending',
+    quote_id TEXT NOT NULL,
+
+    -- Chain transaction identifiers
+    source_tx_hash TEXT,
+    swap_tx_hash TEXT,
+    destination_tx_hash TEXT,
+
+    -- Execution results
+    gas_fees_paid NUMERIC,
+    slippage_actual NUMERIC,
+    execution_price NUMERIC,
+
+    -- Timestamps
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    executed_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE,
+
+    error_message TEXT,
+
+    CONSTRAINT swap_tx_required_when_executing CHECK (
+        (status IN ('executing_swap', 'swap_completed', 'settlement_in_progress', 'completed')
+            AND swap_tx_hash IS NOT NULL)
+        OR status NOT IN ('executing_swap', 'swap_completed', 'settlement_in_progress', 'completed')
+    )
+);
+
+CREATE INDEX idx_trades_user ON trades(user_id, created_at DESC);
+CREATE INDEX idx_trades_status ON trades(status);
+CREATE INDEX idx_trades_user_status ON trades(user_id, status);
+CREATE INDEX idx_trades_source_chain ON trades(source_chain);
+CREATE INDEX idx_trades_destination_chain ON trades(destination_chain);
+CREATE INDEX idx_trades_user_source_chain ON trades(user_id, source_chain);
+CREATE INDEX idx_trades_settlement_pending ON trades(created_at)
+    WHERE status = 'settlement_in_progress';
+
+COMMENT ON TABLE trades IS 'DEX trades. Status transitions are guarded by the current status in TradeRepository.';
@@ -0,0 +1,46 @@
+-- Funding Monitors - chain watchers that detect quote payments
+-- Checkpoints let each monitor resume where it stopped after a restart
+-- Funding payments record every detected transfer to a quote payment address
+
+-- Resume position per chain and scope
+-- scope: Stellar collection account, 'blocks' for NEAR, Solana address or token account
+-- cursor: Horizon paging token, block height or newest signature read
+CREATE TABLE funding_checkpoints (
+    chain chain_type NOT NULL,
+    scope TEXT NOT NULL,
+    cursor TEXT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain, scope)
+);
+
+CREATE TABLE funding_payments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+
+    chain chain_type NOT NULL,
+    transaction_hash TEXT NOT NULL,
+
+    -- Quote the payment was matched to
+    quote_id UUID REFERENCES quotes(id) ON DELETE SET NULL,
+    payment_address TEXT NOT NULL,
+
+    -- Amount in display units of the funding asset
+    asset TEXT NOT NULL,
+    amount NUMERIC NOT NULL CHECK (amount >= 0),
+
+    status TEXT NOT NULL,
+    reason TEXT,
+
+    detected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    -- SECURITY: A chain transaction can only fund one quote, once
+    CONSTRAINT unique_funding_payment UNIQUE (chain, transaction_hash),
+    CONSTRAINT valid_funding_payment_status CHECK (status IN ('matched', 'underpaid', 'rejected'))
+);
+
+CREATE INDEX idx_funding_payments_quote ON funding_payments(quote_id) WHERE quote_id IS NOT NULL;
+CREATE INDEX idx_funding_payments_status ON funding_payments(status);
+CREATE INDEX idx_funding_payments_detected ON funding_payments(detected_at DESC);
+
+-- Pending quotes are looked up by payment address on every detected transfer
+CREATE INDEX idx_quotes_payment_address ON quotes(funding_chain, payment_address)
+    WHERE status = 'pending' AND payment_address IS NOT NULL;
@@ -0,0 +1,39 @@
+-- Settlement Reconciliation
+-- Successful executions must be backed by a verified funding payment.
+-- The reconciler records verified settlements and flags executions whose
+-- funding payment cannot be found on the source chain.
+
+ALTER TYPE audit_event_type ADD VALUE IF NOT EXISTS 'settlement_discrepancy';
+
+-- Funding amounts are display units (e.g. 12.5 XLM), not integers
+ALTER TABLE settlements ALTER COLUMN funding_amount TYPE NUMERIC;
+
+-- NEAR transaction status lookups are routed by the sender account
+ALTER TABLE funding_payments ADD COLUMN sender TEXT;
+
+CREATE TABLE settlement_discrepancies (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+
+    execution_id UUID NOT NULL REFERENCES executions(id) ON DELETE CASCADE,
+    quote_id UUID NOT NULL REFERENCES quotes(id) ON DELETE CASCADE,
+
+    funding_chain chain_type NOT NULL,
+    payment_address TEXT,
+    expected_amount NUMERIC NOT NULL,
+
+    -- Funding transaction, if one was found but failed verification
+    funding_txn_hash TEXT,
+    reason TEXT NOT NULL,
+
+    detected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+
+    -- SECURITY: One open record per execution, the audit event is raised once
+    CONSTRAINT unique_discrepancy_per_execution UNIQUE (execution_id)
+);
+
+CREATE INDEX idx_settlement_discrepancies_open ON settlement_discrepancies(detected_at DESC)
+    WHERE resolved_at IS NULL;
+CREATE INDEX idx_settlement_discrepancies_chain ON settlement_discrepancies(funding_chain);
+
+COMMENT ON TABLE settlement_discrepancies IS 'Successful executions with no verified funding payment after the reconciliation grace period';
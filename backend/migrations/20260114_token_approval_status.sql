@@ -0,0 +1,57 @@
+-- Token Approval Status - typed state machine
+-- Status becomes a Postgres enum mapped to ApprovalStatus, and every
+-- transition is recorded in token_approval_status_history.
+-- Allowed transitions are enforced in LedgerRepository.
+
+CREATE TYPE approval_status AS ENUM (
+    'pending',
+    'signed',
+    'submitted',
+    'confirmed',
+    'executed',
+    'failed',
+    'expired',
+    'cancelled'
+);
+
+-- Objects that reference the text column are rebuilt after the type change
+DROP INDEX IF EXISTS idx_token_approvals_expires;
+ALTER TABLE token_approvals DROP CONSTRAINT IF EXISTS valid_status;
+ALTER TABLE token_approvals DROP CONSTRAINT IF EXISTS signature_required_when_signed;
+ALTER TABLE token_approvals DROP CONSTRAINT IF EXISTS confirmation_requires_tx;
+
+ALTER TABLE token_approvals ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE token_approvals
+    ALTER COLUMN status TYPE approval_status USING status::approval_status;
+ALTER TABLE token_approvals ALTER COLUMN status SET DEFAULT 'pending';
+
+-- SECURITY: A signature must be present once an approval leaves 'pending'
+-- (the previous constraint only allowed 'pending' and 'signed')
+ALTER TABLE token_approvals ADD CONSTRAINT signature_required_when_signed CHECK (
+    (status IN ('signed', 'submitted', 'confirmed', 'executed') AND signature IS NOT NULL)
+    OR status IN ('pending', 'failed', 'expired', 'cancelled')
+);
+ALTER TABLE token_approvals ADD CONSTRAINT confirmation_requires_tx CHECK (
+    (status IN ('submitted', 'confirmed', 'executed') AND transaction_hash IS NOT NULL)
+    OR status IN ('pending', 'signed', 'failed', 'cancelled', 'expired')
+);
+
+CREATE INDEX idx_token_approvals_expires ON token_approvals(expires_at)
+WHERE status IN ('pending', 'signed');
+
+-- Status history (one row per transition, including creation)
+CREATE TABLE token_approval_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    approval_id UUID NOT NULL REFERENCES token_approvals(id) ON DELETE CASCADE,
+
+    -- NULL for the initial 'pending' row
+    from_status approval_status,
+    to_status approval_status NOT NULL,
+    reason TEXT,
+
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_token_approval_history_approval ON token_approval_status_history(approval_id, changed_at);
+
+COMMENT ON TABLE token_approval_status_history IS 'Audit trail of token approval status transitions';
@@ -0,0 +1,80 @@
+-- Double-entry Journal
+-- Every quote commit, successful execution and settlement posts a balanced
+-- journal entry in the same transaction as the ledger row it describes.
+-- Amounts are in the units of the (chain, asset) they are booked against;
+-- debits must equal credits per asset within each entry.
+
+CREATE TYPE journal_account_kind AS ENUM (
+    'user_funding',
+    'treasury',
+    'service_fee_revenue',
+    'gas_expense',
+    'settlement_in_transit'
+);
+
+CREATE TYPE journal_event AS ENUM (
+    'quote_committed',
+    'execution_completed',
+    'settlement_recorded'
+);
+
+CREATE TABLE journal_entries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+
+    event journal_event NOT NULL,
+    -- Quote id for commits/executions, settlement id for settlements
+    reference_id UUID NOT NULL,
+    quote_id UUID NOT NULL REFERENCES quotes(id) ON DELETE CASCADE,
+    description TEXT,
+
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    -- SECURITY: Each ledger event is posted exactly once
+    CONSTRAINT unique_journal_event UNIQUE (event, reference_id)
+);
+
+CREATE TABLE journal_lines (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    entry_id UUID NOT NULL REFERENCES journal_entries(id) ON DELETE CASCADE,
+
+    account journal_account_kind NOT NULL,
+    chain chain_type NOT NULL,
+    asset TEXT NOT NULL,
+
+    debit NUMERIC NOT NULL DEFAULT 0,
+    credit NUMERIC NOT NULL DEFAULT 0,
+
+    -- Exactly one side per line, never negative
+    CONSTRAINT one_sided_line CHECK (
+        debit >= 0 AND credit >= 0 AND ((debit = 0) <> (credit = 0))
+    )
+);
+
+CREATE INDEX idx_journal_lines_entry ON journal_lines(entry_id);
+CREATE INDEX idx_journal_lines_account ON journal_lines(chain, asset, account);
+CREATE INDEX idx_journal_entries_quote ON journal_entries(quote_id);
+
+-- SECURITY: Reject unbalanced entries at commit time, even if a caller
+-- bypasses LedgerRepository
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM journal_lines
+        WHERE entry_id = NEW.entry_id
+        GROUP BY chain, asset
+        HAVING SUM(debit) <> SUM(credit)
+    ) THEN
+        RAISE EXCEPTION 'Journal entry % is not balanced', NEW.entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE CONSTRAINT TRIGGER journal_entry_balanced
+    AFTER INSERT OR UPDATE ON journal_lines
+    DEFERRABLE INITIALLY DEFERRED
+    FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+
+COMMENT ON TABLE journal_entries IS 'Double-entry journal entries posted by LedgerRepository';
+COMMENT ON TABLE journal_lines IS 'Debit/credit lines of journal entries, balanced per (chain, asset)';
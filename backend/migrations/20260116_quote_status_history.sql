@@ -0,0 +1,27 @@
+-- Quote Status History
+-- QuoteStatus::Settled had no matching SQL enum value, and quote
+-- transitions left no trace. Allowed transitions live in QuoteStatus
+-- (Rust); every transition is recorded here by LedgerRepository.
+
+ALTER TYPE quote_status ADD VALUE IF NOT EXISTS 'settled';
+
+CREATE TABLE quote_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    quote_id UUID NOT NULL REFERENCES quotes(id) ON DELETE CASCADE,
+
+    -- NULL for the initial 'pending' row
+    from_status quote_status,
+    to_status quote_status NOT NULL,
+    reason TEXT,
+
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_quote_status_history_quote ON quote_status_history(quote_id, changed_at);
+
+-- Existing quotes start their history at their current status
+INSERT INTO quote_status_history (quote_id, from_status, to_status, reason, changed_at)
+SELECT id, NULL, status, 'backfilled', updated_at
+FROM quotes;
+
+COMMENT ON TABLE quote_status_history IS 'Audit trail of quote status transitions';
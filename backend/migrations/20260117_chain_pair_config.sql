@@ -0,0 +1,26 @@
+-- Chain Pair Configuration
+-- supported_chain_pairs becomes the source of truth for which pairs the
+-- quote engine and execution router accept, with per-pair limits and fee
+-- overrides that admins can change at runtime.
+
+ALTER TYPE audit_event_type ADD VALUE IF NOT EXISTS 'chain_pair_updated';
+
+-- Limits are in funding asset display units (e.g. 12.5 XLM), not integers
+ALTER TABLE supported_chain_pairs ALTER COLUMN min_amount TYPE NUMERIC;
+ALTER TABLE supported_chain_pairs ALTER COLUMN max_amount TYPE NUMERIC;
+
+-- Overrides QuoteConfig::service_fee_rate when set
+ALTER TABLE supported_chain_pairs ADD COLUMN service_fee_rate NUMERIC;
+ALTER TABLE supported_chain_pairs ADD COLUMN updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
+
+ALTER TABLE supported_chain_pairs ADD CONSTRAINT valid_pair_limits CHECK (
+    min_amount >= 0 AND (max_amount IS NULL OR max_amount >= min_amount)
+);
+ALTER TABLE supported_chain_pairs ADD CONSTRAINT valid_pair_fee_rate CHECK (
+    service_fee_rate IS NULL OR (service_fee_rate >= 0 AND service_fee_rate < 1)
+);
+
+CREATE TRIGGER update_supported_chain_pairs_updated_at BEFORE UPDATE ON supported_chain_pairs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+COMMENT ON TABLE supported_chain_pairs IS 'Enabled funding -> execution chain pairs with per-pair limits and fee overrides';
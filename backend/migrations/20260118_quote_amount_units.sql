@@ -0,0 +1,11 @@
+-- Quote Amount Units
+-- The quote engine computes amounts with the unit-safe Amount type and
+-- stores them in display units (e.g. 0.000012 XLM). NUMERIC(78, 0) would
+-- round those to 0 and fail the execution_cost > 0 check.
+
+ALTER TABLE quotes ALTER COLUMN max_funding_amount TYPE NUMERIC;
+ALTER TABLE quotes ALTER COLUMN execution_cost TYPE NUMERIC;
+ALTER TABLE quotes ALTER COLUMN service_fee TYPE NUMERIC;
+
+COMMENT ON COLUMN quotes.execution_cost IS 'Execution chain native asset, display units';
+COMMENT ON COLUMN quotes.service_fee IS 'Execution chain native asset, display units';
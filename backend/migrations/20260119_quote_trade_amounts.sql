@@ -0,0 +1,25 @@
+-- Quote Trade Amounts
+-- Quotes carry the trade itself, not only its execution cost: the user asks
+-- for an exact input (funding asset spent) or an exact output (execution
+-- asset delivered) and the engine prices the other side.
+--
+-- max_funding_amount remains the total the user must pay on the funding
+-- chain: funding_amount plus execution cost and service fee converted to
+-- the funding asset, with the slippage buffer.
+
+CREATE TYPE quote_mode AS ENUM ('exact_input', 'exact_output');
+
+-- Existing quotes only covered execution costs: no trade amount
+ALTER TABLE quotes ADD COLUMN mode quote_mode NOT NULL DEFAULT 'exact_input';
+ALTER TABLE quotes ADD COLUMN funding_amount NUMERIC NOT NULL DEFAULT 0;
+ALTER TABLE quotes ADD COLUMN execution_amount NUMERIC NOT NULL DEFAULT 0;
+
+-- SECURITY: The trade input is always part of what the user pays
+ALTER TABLE quotes ADD CONSTRAINT valid_trade_amounts CHECK (
+    funding_amount >= 0
+    AND execution_amount >= 0
+    AND max_funding_amount >= funding_amount
+);
+
+COMMENT ON COLUMN quotes.funding_amount IS 'Funding asset spent on the trade (exact for exact_input)';
+COMMENT ON COLUMN quotes.execution_amount IS 'Execution asset delivered (exact for exact_output, minimum for exact_input)';
@@ -0,0 +1,32 @@
+-- Idempotency Keys
+-- Mutating v1 endpoints accept an Idempotency-Key header. The first request
+-- with a key claims it and stores its response; retries with the same body
+-- replay that response, retries with a different body are rejected (409).
+-- Keys are scoped per client (a hash of the Authorization credential; a key
+-- sent without one is rejected), so two clients picking the same key neither
+-- collide nor see each other's responses.
+-- Records expire after a TTL and are purged by the hourly cleanup task.
+
+CREATE TABLE idempotency_keys (
+    -- SHA-256 (hex) of the client's Authorization header
+    client_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+
+    -- SHA-256 (hex) of method, path and body of the first request
+    request_hash TEXT NOT NULL,
+
+    -- NULL while the first request is still being handled
+    response_status INTEGER,
+    response_body BYTEA,
+    response_content_type TEXT,
+
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+
+    PRIMARY KEY (client_id, key),
+    CONSTRAINT valid_idempotency_key CHECK (length(key) BETWEEN 1 AND 255)
+);
+
+CREATE INDEX idx_idempotency_keys_expires ON idempotency_keys(expires_at);
+
+COMMENT ON TABLE idempotency_keys IS 'Idempotency-Key request hashes and stored responses for mutating v1 endpoints';
@@ -0,0 +1,23 @@
+-- Quote Refresh
+-- A pending quote can be refreshed: prices and fees are fetched again and
+-- the quote is either extended (new total within tolerance) or superseded
+-- by a new quote at the new price. The replacement keeps the nonce and the
+-- payment address, so a payment already on its way still matches.
+
+ALTER TYPE audit_event_type ADD VALUE IF NOT EXISTS 'quote_refreshed';
+
+-- Deferred so the old quote can point at its replacement before the
+-- replacement row is inserted
+ALTER TABLE quotes ADD COLUMN superseded_by UUID
+    REFERENCES quotes(id) DEFERRABLE INITIALLY DEFERRED;
+
+-- SECURITY: A nonce is still unique among live quotes; only a quote and
+-- the quotes it was superseded by share one
+ALTER TABLE quotes DROP CONSTRAINT quotes_nonce_key;
+CREATE UNIQUE INDEX idx_quotes_live_nonce ON quotes(nonce) WHERE superseded_by IS NULL;
+
+ALTER TABLE quotes ADD CONSTRAINT valid_superseded_by CHECK (superseded_by IS NULL OR superseded_by <> id);
+
+CREATE INDEX idx_quotes_superseded_by ON quotes(superseded_by) WHERE superseded_by IS NOT NULL;
+
+COMMENT ON COLUMN quotes.superseded_by IS 'Quote that replaced this one when a refresh repriced it outside tolerance';
@@ -0,0 +1,68 @@
+-- Fee Schedules
+-- Service fee rates per chain pair, asset and user volume tier, replacing
+-- the single global rate. Each schedule only applies where its selectors
+-- match; NULL selectors match anything. Promotional schedules override
+-- regular ones while inside their validity window.
+--
+-- Resolution order for a quote (see quote_engine::fee_schedule):
+--   1. promotional before regular
+--   2. more selectors set before fewer
+--   3. higher volume tier before lower
+-- With no matching schedule the pair's service_fee_rate, then the global
+-- default applies.
+
+ALTER TYPE audit_event_type ADD VALUE IF NOT EXISTS 'fee_schedule_updated';
+
+CREATE TABLE fee_schedules (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name TEXT NOT NULL,
+
+    -- Selectors (NULL = any)
+    funding_chain chain_type,
+    execution_chain chain_type,
+    funding_asset TEXT,
+    execution_asset TEXT,
+
+    -- Rolling 30-day executed volume of the user, in USD
+    min_volume_usd NUMERIC NOT NULL DEFAULT 0,
+
+    -- Fraction of the execution cost (0.1% = 0.001)
+    fee_rate NUMERIC NOT NULL,
+    min_fee_usd NUMERIC,
+    max_fee_usd NUMERIC,
+
+    promotional BOOLEAN NOT NULL DEFAULT FALSE,
+    valid_from TIMESTAMP WITH TIME ZONE,
+    valid_until TIMESTAMP WITH TIME ZONE,
+
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT valid_fee_schedule_rate CHECK (fee_rate >= 0 AND fee_rate < 1),
+    CONSTRAINT valid_fee_schedule_volume CHECK (min_volume_usd >= 0),
+    CONSTRAINT valid_fee_schedule_caps CHECK (
+        (min_fee_usd IS NULL OR min_fee_usd >= 0)
+        AND (max_fee_usd IS NULL OR max_fee_usd >= 0)
+        AND (min_fee_usd IS NULL OR max_fee_usd IS NULL OR max_fee_usd >= min_fee_usd)
+    ),
+    CONSTRAINT valid_fee_schedule_window CHECK (
+        valid_from IS NULL OR valid_until IS NULL OR valid_until > valid_from
+    ),
+    -- Promotions must end
+    CONSTRAINT valid_fee_schedule_promotion CHECK (NOT promotional OR valid_until IS NOT NULL)
+);
+
+CREATE TRIGGER update_fee_schedules_updated_at BEFORE UPDATE ON fee_schedules
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Schedule applied to each quote; NULL = pair or global default rate
+ALTER TABLE quotes ADD COLUMN fee_schedule_id UUID REFERENCES fee_schedules(id);
+
+CREATE INDEX idx_quotes_fee_schedule ON quotes(fee_schedule_id) WHERE fee_schedule_id IS NOT NULL;
+
+-- Rolling volume lookups
+CREATE INDEX idx_quotes_user_created ON quotes(user_id, created_at);
+
+COMMENT ON TABLE fee_schedules IS 'Service fee schedules per chain pair, asset and 30-day volume tier, with USD caps and promotions';
+COMMENT ON COLUMN quotes.fee_schedule_id IS 'Fee schedule that priced the service fee of this quote';
@@ -0,0 +1,49 @@
+-- Pyth Price Feeds
+-- Hermes price feed IDs per network and asset, replacing the mapping that
+-- was compiled into PythPriceFeedIds. Each feed is the asset's USD price;
+-- a row without a chain applies on every chain, a row with one overrides
+-- it there.
+--
+-- The backend validates every enabled ID at startup (32-byte hex, optional
+-- 0x prefix) and reloads this table on SIGHUP or POST
+-- /admin/price-feeds/reload. Only mainnet feeds are seeded: testnet
+-- deployments price from hermes-beta, whose IDs must be inserted here
+-- before the backend starts; it refuses to start for a network without
+-- any enabled feed.
+
+ALTER TYPE audit_event_type ADD VALUE IF NOT EXISTS 'price_feed_updated';
+
+CREATE TABLE pyth_price_feeds (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    network TEXT NOT NULL,
+    -- NULL = every chain
+    chain chain_type,
+    asset TEXT NOT NULL,
+    feed_id TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT valid_price_feed_network CHECK (network IN ('mainnet', 'testnet')),
+    CONSTRAINT valid_price_feed_asset CHECK (asset <> '' AND asset = upper(asset))
+);
+
+-- One feed per network and asset for every chain, and one per chain override
+CREATE UNIQUE INDEX idx_pyth_price_feeds_all_chains
+    ON pyth_price_feeds(network, asset) WHERE chain IS NULL;
+CREATE UNIQUE INDEX idx_pyth_price_feeds_chain
+    ON pyth_price_feeds(network, chain, asset) WHERE chain IS NOT NULL;
+
+CREATE TRIGGER update_pyth_price_feeds_updated_at BEFORE UPDATE ON pyth_price_feeds
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO pyth_price_feeds (network, chain, asset, feed_id) VALUES
+    ('mainnet', NULL, 'SOL',  '0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d'),
+    ('mainnet', NULL, 'USDC', '0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a'),
+    ('mainnet', NULL, 'USDT', '0x2b89b9dc8fdf9f34709a5b106b472f0f39bb6ca9ce04b0fd7f2e971688e2e53b'),
+    ('mainnet', NULL, 'ETH',  '0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace'),
+    ('mainnet', NULL, 'BTC',  '0xe62df6c8b4a85fe1a67db44dc12de5db330f7ac66b72dc658afedf0f4a415b43'),
+    ('mainnet', NULL, 'XLM',  '0xb7a8eba68a997cd0210c2e1e4ee811ad2d174b3611c22d9ebf16f4cb7e9ba850'),
+    ('mainnet', NULL, 'NEAR', '0xc415de8d2eba7db216527dff4b60e8f3a5311c740dadb233e13e12547e226750');
+
+COMMENT ON TABLE pyth_price_feeds IS 'Pyth Hermes price feed IDs per network, chain and asset, reloaded on SIGHUP';
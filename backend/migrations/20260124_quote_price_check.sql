@@ -0,0 +1,14 @@
+-- Quote Price Check
+-- Records the outcome of the price staleness and confidence policy for the
+-- price each quote was made at (see quote_engine::price_policy). Quotes are
+-- only stored when the check passed; rejected prices are audited as
+-- quote_price_rejected. A refresh that extends a quote replaces its check.
+--
+-- Shape: {"passed", "age_secs", "max_age_secs", "confidence_pct",
+-- "max_confidence_pct", "reason"}. NULL for quotes made before this.
+
+ALTER TYPE audit_event_type ADD VALUE IF NOT EXISTS 'quote_price_rejected';
+
+ALTER TABLE quotes ADD COLUMN price_check JSONB;
+
+COMMENT ON COLUMN quotes.price_check IS 'Price policy outcome for the quoted price';
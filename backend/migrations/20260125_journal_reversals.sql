@@ -0,0 +1,7 @@
+-- Journal Reversals and Deliveries
+-- A committed quote that fails or expires posts a reversing entry so
+-- settlement_in_transit and user_funding close; a successful execution
+-- also books the execution asset delivered out of the treasury.
+
+ALTER TYPE journal_event ADD VALUE IF NOT EXISTS 'quote_reversed';
+ALTER TYPE journal_account_kind ADD VALUE IF NOT EXISTS 'trade_delivery';
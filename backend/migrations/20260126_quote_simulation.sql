@@ -0,0 +1,9 @@
+-- Quote Simulation
+-- Records whether a quote's execution instructions were dry-run on the
+-- execution chain when it was issued. Refreshing a quote re-runs the
+-- simulation only for quotes that had one, so a quote issued without
+-- simulation is never rejected on refresh by a dry-run it did not ask for.
+
+ALTER TABLE quotes ADD COLUMN simulated BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN quotes.simulated IS 'Execution instructions were simulated when the quote was issued';
@@ -0,0 +1,27 @@
+-- Split trade legs - each DEX leg of a same-chain trade with its own outcome
+-- A trade where some legs were submitted and others failed is partially
+-- filled rather than failed; the submitted legs are not undone.
+
+ALTER TYPE trade_status ADD VALUE IF NOT EXISTS 'partially_filled' AFTER 'executing_swap';
+
+CREATE TABLE trade_legs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    trade_id UUID NOT NULL REFERENCES trades(id) ON DELETE CASCADE,
+    dex TEXT NOT NULL,
+    -- Display units of the trade's input asset
+    amount_in NUMERIC NOT NULL CHECK (amount_in > 0),
+    status TEXT NOT NULL,
+    tx_hash TEXT,
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT valid_trade_leg_status CHECK (status IN ('submitted', 'failed')),
+    CONSTRAINT trade_leg_outcome CHECK (
+        (status = 'submitted' AND tx_hash IS NOT NULL)
+        OR (status = 'failed' AND error_message IS NOT NULL)
+    )
+);
+
+CREATE INDEX idx_trade_legs_trade ON trade_legs(trade_id, created_at);
+
+COMMENT ON TABLE trade_legs IS 'DEX legs of split trades, one row per leg with its transaction hash or error.';
@@ -0,0 +1,349 @@
+// AMM pricing from pool state
+//
+// Adapters that can read a pool's reserves (or, for concentrated liquidity,
+// its price, active liquidity and initialized ticks) hand them here to get
+// the exact output of a swap and how far it moves the price. The constant
+// product maths follows the pool programs: the trade fee is taken from the
+// input, the protocol fee from the output.
+//
+// Pools are described in the direction of the swap: prices are units of
+// the output asset per unit of the input asset.
+
+use crate::error::{AppError, AppResult};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+const BPS: u32 = 10_000;
+
+/// Pool fees, in basis points
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeeTier {
+    /// Taken from the input, stays in the pool (30 = 0.3%)
+    pub trade_fee_bps: u32,
+    /// Taken from the output
+    pub protocol_fee_bps: u32,
+}
+
+impl FeeTier {
+    pub const fn new(trade_fee_bps: u32, protocol_fee_bps: u32) -> Self {
+        Self {
+            trade_fee_bps,
+            protocol_fee_bps,
+        }
+    }
+
+    /// What a swap too small to move the pool gets at `price`, both fees
+    /// taken
+    pub fn net_price(&self, price: Decimal) -> Decimal {
+        let bps = Decimal::from(BPS);
+        price * (bps - Decimal::from(self.trade_fee_bps)) / bps
+            * (bps - Decimal::from(self.protocol_fee_bps))
+            / bps
+    }
+
+    fn trade_fee(&self, amount_in: Decimal) -> Decimal {
+        amount_in * Decimal::from(self.trade_fee_bps) / Decimal::from(BPS)
+    }
+
+    fn protocol_fee(&self, amount_out: Decimal) -> Decimal {
+        amount_out * Decimal::from(self.protocol_fee_bps) / Decimal::from(BPS)
+    }
+}
+
+/// x * y = k pool
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConstantProductPool {
+    pub reserve_in: Decimal,
+    pub reserve_out: Decimal,
+    pub fee: FeeTier,
+}
+
+/// Point where the active liquidity changes as the price falls through it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidityBoundary {
+    pub sqrt_price: Decimal,
+    /// Added to the active liquidity when crossed (negative when a range ends)
+    pub liquidity_delta: Decimal,
+}
+
+/// Concentrated-liquidity pool around its current price
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConcentratedLiquidityPool {
+    /// Square root of the current price
+    pub sqrt_price: Decimal,
+    /// Liquidity active at the current price
+    pub liquidity: Decimal,
+    /// Initialized ticks below the current price, highest first
+    pub boundaries: Vec<LiquidityBoundary>,
+    pub fee: FeeTier,
+}
+
+/// Pool state an adapter read for a pair
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PoolState {
+    ConstantProduct(ConstantProductPool),
+    ConcentratedLiquidity(ConcentratedLiquidityPool),
+}
+
+/// Exact outcome of swapping into a pool
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AmmQuote {
+    pub amount_in: Decimal,
+    /// After both fees
+    pub amount_out: Decimal,
+    /// In the input asset
+    pub trade_fee: Decimal,
+    /// In the output asset
+    pub protocol_fee: Decimal,
+    /// Price before the swap
+    pub spot_price: Decimal,
+    /// Price after the swap
+    pub marginal_price: Decimal,
+    /// amount_out / amount_in
+    pub execution_price: Decimal,
+    /// Distance of the fee-free average price from the spot price; the
+    /// same as the distance of execution_price from the spot price after
+    /// fees
+    pub price_impact_bps: Decimal,
+}
+
+impl PoolState {
+    pub fn fee(&self) -> &FeeTier {
+        match self {
+            PoolState::ConstantProduct(pool) => &pool.fee,
+            PoolState::ConcentratedLiquidity(pool) => &pool.fee,
+        }
+    }
+
+    /// Swap `amount_in` into the pool
+    pub fn quote(&self, amount_in: Decimal) -> AppResult<AmmQuote> {
+        if amount_in <= Decimal::ZERO {
+            return Err(AppError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
+        }
+
+        match self {
+            PoolState::ConstantProduct(pool) => pool.quote(amount_in),
+            PoolState::ConcentratedLiquidity(pool) => pool.quote(amount_in),
+        }
+    }
+}
+
+impl ConstantProductPool {
+    fn quote(&self, amount_in: Decimal) -> AppResult<AmmQuote> {
+        if self.reserve_in <= Decimal::ZERO || self.reserve_out <= Decimal::ZERO {
+            return Err(AppError::NoLiquidityAvailable(
+                "Pool has no reserves".to_string(),
+            ));
+        }
+
+        let trade_fee = self.fee.trade_fee(amount_in);
+        let swapped = amount_in - trade_fee;
+        let gross_out = swapped * self.reserve_out / (self.reserve_in + swapped);
+
+        // The trade fee stays in the pool
+        let marginal_price = (self.reserve_out - gross_out) / (self.reserve_in + amount_in);
+        Ok(finish(
+            amount_in,
+            swapped,
+            gross_out,
+            trade_fee,
+            self.reserve_out / self.reserve_in,
+            marginal_price,
+            &self.fee,
+        ))
+    }
+}
+
+impl ConcentratedLiquidityPool {
+    /// Walk the price down through the boundaries; inside a range the pool
+    /// behaves like a constant product pool with liquidity L: selling dx
+    /// moves 1/sqrt(P) up by dx / L and pays L * (sqrt(P) - sqrt(P'))
+    fn quote(&self, amount_in: Decimal) -> AppResult<AmmQuote> {
+        if self.sqrt_price <= Decimal::ZERO {
+            return Err(AppError::NoLiquidityAvailable(
+                "Pool has no price".to_string(),
+            ));
+        }
+
+        let trade_fee = self.fee.trade_fee(amount_in);
+        let swapped = amount_in - trade_fee;
+
+        let mut remaining = swapped;
+        let mut sqrt_price = self.sqrt_price;
+        let mut liquidity = self.liquidity;
+        let mut gross_out = Decimal::ZERO;
+        let mut boundaries = self.boundaries.iter();
+
+        while remaining > Decimal::ZERO {
+            if liquidity <= Decimal::ZERO {
+                return Err(AppError::NoLiquidityAvailable(format!(
+                    "Pool runs out of liquidity with {} left to swap",
+                    remaining
+                )));
+            }
+
+            let next = boundaries.next();
+            let to_boundary =
+                next.map(|b| liquidity * (Decimal::ONE / b.sqrt_price - Decimal::ONE / sqrt_price));
+            match (next, to_boundary) {
+                (Some(boundary), Some(needed)) if needed < remaining => {
+                    gross_out += liquidity * (sqrt_price - boundary.sqrt_price);
+                    remaining -= needed;
+                    sqrt_price = boundary.sqrt_price;
+                    liquidity += boundary.liquidity_delta;
+                }
+                _ => {
+                    let next_sqrt_price =
+                        Decimal::ONE / (Decimal::ONE / sqrt_price + remaining / liquidity);
+                    gross_out += liquidity * (sqrt_price - next_sqrt_price);
+                    remaining = Decimal::ZERO;
+                    sqrt_price = next_sqrt_price;
+                }
+            }
+        }
+
+        Ok(finish(
+            amount_in,
+            swapped,
+            gross_out,
+            trade_fee,
+            self.sqrt_price * self.sqrt_price,
+            sqrt_price * sqrt_price,
+            &self.fee,
+        ))
+    }
+}
+
+fn finish(
+    amount_in: Decimal,
+    swapped: Decimal,
+    gross_out: Decimal,
+    trade_fee: Decimal,
+    spot_price: Decimal,
+    marginal_price: Decimal,
+    fee: &FeeTier,
+) -> AmmQuote {
+    let protocol_fee = fee.protocol_fee(gross_out);
+    let amount_out = gross_out - protocol_fee;
+    let price_impact_bps = (spot_price - gross_out / swapped) / spot_price * Decimal::from(BPS);
+
+    AmmQuote {
+        amount_in,
+        amount_out,
+        trade_fee,
+        protocol_fee,
+        spot_price,
+        marginal_price,
+        execution_price: amount_out / amount_in,
+        price_impact_bps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn constant_product(fee: FeeTier) -> PoolState {
+        PoolState::ConstantProduct(ConstantProductPool {
+            reserve_in: dec!(1000),
+            reserve_out: dec!(1000),
+            fee,
+        })
+    }
+
+    fn concentrated(boundaries: Vec<LiquidityBoundary>, fee: FeeTier) -> PoolState {
+        PoolState::ConcentratedLiquidity(ConcentratedLiquidityPool {
+            sqrt_price: Decimal::ONE,
+            liquidity: dec!(1000),
+            boundaries,
+            fee,
+        })
+    }
+
+    #[test]
+    fn test_constant_product_output_and_impact() {
+        let quote = constant_product(FeeTier::new(30, 0))
+            .quote(dec!(100))
+            .unwrap();
+
+        // 99.7 after the 0.3% fee, 99.7 * 1000 / 1099.7 out
+        assert_eq!(quote.trade_fee, dec!(0.3));
+        assert_eq!(quote.amount_out.round_dp(6), dec!(90.661089));
+        assert_eq!(quote.spot_price, Decimal::ONE);
+        assert_eq!(quote.marginal_price.round_dp(4), dec!(0.8267));
+        assert_eq!(quote.price_impact_bps.round_dp(2), dec!(906.61));
+
+        let with_protocol_fee = constant_product(FeeTier::new(30, 1))
+            .quote(dec!(100))
+            .unwrap();
+        assert_eq!(
+            with_protocol_fee.protocol_fee,
+            quote.amount_out / dec!(10000)
+        );
+        assert!(with_protocol_fee.amount_out < quote.amount_out);
+    }
+
+    #[test]
+    fn test_impact_excludes_fees() {
+        // Both fees scale the spot and the execution price alike
+        let quote = constant_product(FeeTier::new(30, 5))
+            .quote(dec!(100))
+            .unwrap();
+        let net_spot = FeeTier::new(30, 5).net_price(quote.spot_price);
+
+        assert_eq!(net_spot, dec!(0.9965015));
+        assert_eq!(
+            ((net_spot - quote.execution_price) / net_spot * dec!(10000)).round_dp(12),
+            quote.price_impact_bps.round_dp(12)
+        );
+    }
+
+    #[test]
+    fn test_single_range_matches_constant_product() {
+        // L = 1000 at price 1 has virtual reserves of 1000 and 1000
+        let concentrated = concentrated(vec![], FeeTier::new(30, 0))
+            .quote(dec!(100))
+            .unwrap();
+        let constant_product = constant_product(FeeTier::new(30, 0))
+            .quote(dec!(100))
+            .unwrap();
+
+        assert_eq!(
+            concentrated.amount_out.round_dp(12),
+            constant_product.amount_out.round_dp(12)
+        );
+        assert_eq!(
+            concentrated.price_impact_bps.round_dp(8),
+            constant_product.price_impact_bps.round_dp(8)
+        );
+    }
+
+    #[test]
+    fn test_concentrated_liquidity_crosses_ticks() {
+        let boundary = |liquidity_delta| LiquidityBoundary {
+            sqrt_price: dec!(0.95),
+            liquidity_delta,
+        };
+
+        // 52.63 takes the price to the boundary for 50 out, the rest swaps
+        // against twice the liquidity
+        let deeper = concentrated(vec![boundary(dec!(1000))], FeeTier::new(0, 0))
+            .quote(dec!(100))
+            .unwrap();
+        assert_eq!(deeper.amount_out.round_dp(6), dec!(91.809291));
+        assert_eq!(deeper.marginal_price.round_dp(6), dec!(0.863218));
+        assert_eq!(deeper.price_impact_bps.round_dp(2), dec!(819.07));
+
+        // The only range ends at the boundary
+        let ended = concentrated(vec![boundary(dec!(-1000))], FeeTier::new(0, 0));
+        assert!(ended.quote(dec!(50)).is_ok());
+        assert!(matches!(
+            ended.quote(dec!(100)),
+            Err(AppError::NoLiquidityAvailable(_))
+        ));
+    }
+}
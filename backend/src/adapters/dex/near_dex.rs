@@ -1,18 +1,63 @@
+use crate::adapters::amm::{ConstantProductPool, FeeTier, PoolState};
 use crate::adapters::traits::{AssetInfo, DexAdapter, PriceQuote, SwapRequest, SwapResult, SwapStatus};
 use crate::error::{AppError, AppResult, ChainError, ExecutionError};
+use crate::ledger::amount::TokenAmount;
 use async_trait::async_trait;
 use near_jsonrpc_client::{JsonRpcClient, methods};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::types::{AccountId, BlockReference, Finality, FunctionArgs};
+use near_primitives::views::QueryRequest;
 use rust_decimal::Decimal;
+use serde_json::{json, Value};
 use crate::ledger::models::Chain;
 
 
 pub struct NearDexAdapter {
     rpc_url: String,
+    /// Ref Finance exchange contract and the pools read for pool state
+    pools: Option<(AccountId, Vec<u64>)>,
 }
 
 impl NearDexAdapter {
     pub fn new(rpc_url: String) -> Self {
-        Self { rpc_url }
+        Self { rpc_url, pools: None }
+    }
+
+    /// Read pool state from these pools of the Ref Finance exchange
+    ///
+    /// Ref Finance has no lookup by pair, so the pools to consider are
+    /// configured.
+    pub fn with_pools(mut self, contract_id: &str, pool_ids: Vec<u64>) -> AppResult<Self> {
+        let contract_id = contract_id.parse::<AccountId>().map_err(|e| {
+            AppError::Config(format!("Invalid Ref Finance contract {}: {}", contract_id, e))
+        })?;
+        self.pools = Some((contract_id, pool_ids));
+        Ok(self)
+    }
+
+    /// `get_pool` view of the exchange contract
+    async fn fetch_pool(&self, contract_id: &AccountId, pool_id: u64) -> AppResult<Value> {
+        let client = JsonRpcClient::connect(self.rpc_url.clone());
+        let request = methods::query::RpcQueryRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::CallFunction {
+                account_id: contract_id.clone(),
+                method_name: "get_pool".to_string(),
+                args: FunctionArgs::from(json!({ "pool_id": pool_id }).to_string().into_bytes()),
+            },
+        };
+
+        let response = client.call(request).await.map_err(|e| {
+            AppError::ExternalError(format!("Ref Finance get_pool({}) failed: {}", pool_id, e))
+        })?;
+        match response.kind {
+            QueryResponseKind::CallResult(result) => serde_json::from_slice(&result.result).map_err(|e| {
+                AppError::ExternalError(format!("Invalid Ref Finance pool {}: {}", pool_id, e))
+            }),
+            _ => Err(AppError::ExternalError(
+                "Unexpected query response type".to_string(),
+            )),
+        }
     }
 
     fn validate_near_account(&self, address: &str) -> AppResult<()> {
@@ -160,6 +205,62 @@ impl DexAdapter for NearDexAdapter {
         Ok(Decimal::from_f64_retain(0.00001).unwrap())
     }
 
+    /// Deepest configured simple (constant product) pool of the pair;
+    /// Ref Finance takes its total fee from the input
+    async fn get_pool_state(&self, asset_in: &AssetInfo, asset_out: &AssetInfo) -> AppResult<Option<PoolState>> {
+        let (contract_id, pool_ids) = match &self.pools {
+            Some(pools) => pools,
+            None => return Ok(None),
+        };
+
+        let pools = futures::future::join_all(
+            pool_ids.iter().map(|id| self.fetch_pool(contract_id, *id)),
+        )
+        .await;
+
+        let mut best: Option<ConstantProductPool> = None;
+        for pool in pools {
+            let pool = pool?;
+            if pool["pool_kind"].as_str() != Some("SIMPLE_POOL") {
+                continue;
+            }
+            let tokens: Vec<&str> = pool["token_account_ids"]
+                .as_array()
+                .map(|ids| ids.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let position = |asset: &AssetInfo| tokens.iter().position(|id| *id == asset.address);
+            let (index_in, index_out) = match (position(asset_in), position(asset_out)) {
+                (Some(index_in), Some(index_out)) => (index_in, index_out),
+                _ => continue,
+            };
+
+            let reserve = |index: usize, asset: &AssetInfo| -> AppResult<Decimal> {
+                let base = pool["amounts"][index]
+                    .as_str()
+                    .and_then(|amount| amount.parse::<u128>().ok())
+                    .ok_or_else(|| {
+                        AppError::ExternalError(format!("Ref Finance pool has no {} amount", asset.symbol))
+                    })?;
+                TokenAmount::new(Chain::Near, &asset.symbol, asset.decimals as u32, base).to_display()
+            };
+            let trade_fee_bps = pool["total_fee"]
+                .as_u64()
+                .and_then(|bps| u32::try_from(bps).ok())
+                .ok_or_else(|| AppError::ExternalError("Ref Finance pool has no total_fee".to_string()))?;
+
+            let candidate = ConstantProductPool {
+                reserve_in: reserve(index_in, asset_in)?,
+                reserve_out: reserve(index_out, asset_out)?,
+                fee: FeeTier::new(trade_fee_bps, 0),
+            };
+            if best.as_ref().is_none_or(|best| candidate.reserve_out > best.reserve_out) {
+                best = Some(candidate);
+            }
+        }
+
+        Ok(best.map(PoolState::ConstantProduct))
+    }
+
     async fn is_available(&self) -> AppResult<bool> {
         // Basic health check: try to reach RPC
         let client = JsonRpcClient::connect(self.rpc_url.clone());
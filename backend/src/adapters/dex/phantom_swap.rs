@@ -1,18 +1,45 @@
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use crate::adapters::amm::{ConstantProductPool, FeeTier, PoolState};
 use crate::adapters::traits::{AssetInfo, DexAdapter, PriceQuote, SwapRequest, SwapResult, SwapStatus};
-use crate::error::{AppResult, ExecutionError};
+use crate::error::{AppError, AppResult, ExecutionError};
 use crate::ledger::models::Chain;
 use chrono::Utc;
+use serde_json::Value;
 use std::str::FromStr;
 
 pub struct PhantomSwapAdapter {
     rpc_url: String,
+    http: reqwest::Client,
 }
 
 impl PhantomSwapAdapter {
     pub fn new(rpc_url: String) -> Self {
-        Self { rpc_url }
+        Self {
+            rpc_url: rpc_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Horizon record of the pair's liquidity pool
+    ///
+    /// Assets are named as Horizon names them: "native" or "CODE:ISSUER".
+    async fn fetch_pool(&self, asset_in: &AssetInfo, asset_out: &AssetInfo) -> AppResult<Option<Value>> {
+        let response: Value = self
+            .http
+            .get(format!("{}/liquidity_pools", self.rpc_url))
+            .query(&[
+                ("reserves", format!("{},{}", asset_in.address, asset_out.address)),
+                ("limit", "1".to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Horizon liquidity_pools request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Invalid Horizon liquidity_pools response: {}", e)))?;
+
+        Ok(response.pointer("/_embedded/records/0").cloned())
     }
     
     fn get_common_tokens() -> Vec<AssetInfo> {
@@ -119,4 +146,38 @@ impl DexAdapter for PhantomSwapAdapter {
     async fn estimate_gas(&self, _asset_in: &AssetInfo, _asset_out: &AssetInfo) -> AppResult<Decimal> {
         Ok(Decimal::from_str("0.00001").unwrap())
     }
+
+    /// Reserves of the pair's Stellar liquidity pool; its fee is taken from
+    /// the input
+    async fn get_pool_state(&self, asset_in: &AssetInfo, asset_out: &AssetInfo) -> AppResult<Option<PoolState>> {
+        let pool = match self.fetch_pool(asset_in, asset_out).await? {
+            Some(pool) => pool,
+            None => return Ok(None),
+        };
+
+        let reserve = |asset: &AssetInfo| -> AppResult<Decimal> {
+            pool["reserves"]
+                .as_array()
+                .and_then(|reserves| {
+                    reserves
+                        .iter()
+                        .find(|reserve| reserve["asset"].as_str() == Some(asset.address.as_str()))
+                })
+                .and_then(|reserve| reserve["amount"].as_str())
+                .and_then(|amount| Decimal::from_str(amount).ok())
+                .ok_or_else(|| {
+                    AppError::ExternalError(format!("Liquidity pool has no {} reserve", asset.symbol))
+                })
+        };
+        let trade_fee_bps = pool["fee_bp"]
+            .as_u64()
+            .and_then(|bps| u32::try_from(bps).ok())
+            .ok_or_else(|| AppError::ExternalError("Liquidity pool has no fee_bp".to_string()))?;
+
+        Ok(Some(PoolState::ConstantProduct(ConstantProductPool {
+            reserve_in: reserve(asset_in)?,
+            reserve_out: reserve(asset_out)?,
+            fee: FeeTier::new(trade_fee_bps, 0),
+        })))
+    }
 }
@@ -1,18 +1,25 @@
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use crate::adapters::amm::{ConstantProductPool, FeeTier, PoolState};
 use crate::adapters::traits::{AssetInfo, DexAdapter, PriceQuote, SwapRequest, SwapResult, SwapStatus};
-use crate::error::{AppResult, ExecutionError};
+use crate::error::{AppError, AppResult, ExecutionError};
 use crate::ledger::models::Chain;
 use crate::execution::solana::SolanaExecutor;
 use chrono::Utc;
 use std::str::FromStr;
 use std::sync::Arc;
 use solana_sdk::pubkey::Pubkey;
+use serde_json::Value;
 use tracing::info;
 use rust_decimal::prelude::ToPrimitive;
 
+/// Raydium's public API, which indexes pool reserves and fees
+const RAYDIUM_API_URL: &str = "https://api-v3.raydium.io";
+
 pub struct RaydiumAdapter {
     rpc_url: String,
+    api_url: String,
+    http: reqwest::Client,
     solana_executor: Option<Arc<SolanaExecutor>>,
 }
 
@@ -20,10 +27,18 @@ impl RaydiumAdapter {
     pub fn new(rpc_url: String) -> Self {
         Self { 
             rpc_url,
+            api_url: RAYDIUM_API_URL.to_string(),
+            http: reqwest::Client::new(),
             solana_executor: None,
         }
     }
 
+    /// Read pools from another Raydium API deployment
+    pub fn with_api_url(mut self, api_url: String) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
     /// Set the Solana executor to use for smart contract calls
     pub fn with_executor(mut self, executor: Arc<SolanaExecutor>) -> Self {
         self.solana_executor = Some(executor);
@@ -71,6 +86,33 @@ impl RaydiumAdapter {
         ]
     }
 
+    /// Deepest standard (constant product) pool of the pair
+    async fn fetch_pool(&self, asset_in: &AssetInfo, asset_out: &AssetInfo) -> AppResult<Option<Value>> {
+        let response: Value = self
+            .http
+            .get(format!("{}/pools/info/mint", self.api_url))
+            .query(&[
+                ("mint1", asset_in.address.as_str()),
+                ("mint2", asset_out.address.as_str()),
+                ("poolType", "standard"),
+                ("poolSortField", "liquidity"),
+                ("sortType", "desc"),
+                ("pageSize", "1"),
+                ("page", "1"),
+            ])
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Raydium pool request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Invalid Raydium pool response: {}", e)))?;
+
+        if response["success"].as_bool() != Some(true) {
+            return Err(AppError::ExternalError(format!("Raydium pool lookup failed: {}", response)));
+        }
+        Ok(response.pointer("/data/data/0").cloned())
+    }
+
     /// Get quote from the deployed smart contract
     async fn get_quote_from_contract(
         &self,
@@ -317,4 +359,43 @@ impl DexAdapter for RaydiumAdapter {
     async fn estimate_gas(&self, _asset_in: &AssetInfo, _asset_out: &AssetInfo) -> AppResult<Decimal> {
         Ok(Decimal::from_str("0.00025").unwrap())
     }
+
+    /// Reserves of the pair's deepest standard pool; the whole trade fee
+    /// (LP and buyback share) is taken from the input
+    async fn get_pool_state(&self, asset_in: &AssetInfo, asset_out: &AssetInfo) -> AppResult<Option<PoolState>> {
+        let pool = match self.fetch_pool(asset_in, asset_out).await? {
+            Some(pool) => pool,
+            None => return Ok(None),
+        };
+
+        let invalid = |field: &str| AppError::ExternalError(format!("Raydium pool has no valid {}", field));
+        let amount_a = api_decimal(&pool["mintAmountA"]).ok_or_else(|| invalid("mintAmountA"))?;
+        let amount_b = api_decimal(&pool["mintAmountB"]).ok_or_else(|| invalid("mintAmountB"))?;
+        let (reserve_in, reserve_out) = if pool["mintA"]["address"].as_str() == Some(asset_in.address.as_str()) {
+            (amount_a, amount_b)
+        } else {
+            (amount_b, amount_a)
+        };
+        let trade_fee_bps = api_decimal(&pool["feeRate"])
+            .and_then(|rate| (rate * Decimal::from(10_000)).round().to_u32())
+            .ok_or_else(|| invalid("feeRate"))?;
+
+        Ok(Some(PoolState::ConstantProduct(ConstantProductPool {
+            reserve_in,
+            reserve_out,
+            fee: FeeTier::new(trade_fee_bps, 0),
+        })))
+    }
+}
+
+/// Raydium API amounts are JSON numbers in display units
+fn api_decimal(value: &Value) -> Option<Decimal> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        _ => return None,
+    };
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
 }
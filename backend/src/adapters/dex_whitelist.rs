@@ -4,6 +4,7 @@ use rust_decimal::prelude::FromPrimitive;
 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use crate::adapters::traits::AssetInfo;
 use crate::ledger::models::Chain;
 use crate::error::AppResult;
 use rust_decimal::Decimal;
@@ -67,6 +68,19 @@ pub struct WhitelistedToken {
     pub supported_dexes: Vec<SupportedDex>,
 }
 
+impl From<WhitelistedToken> for AssetInfo {
+    fn from(token: WhitelistedToken) -> Self {
+        AssetInfo {
+            chain: token.chain,
+            address: token.token_id,
+            symbol: token.symbol,
+            name: token.name,
+            decimals: token.decimals,
+            logo_url: None,
+        }
+    }
+}
+
 /// DEX whitelist manager
 pub struct DexWhitelist {
     /// Tokens by chain and symbol
@@ -1,4 +1,5 @@
 pub mod traits;
+pub mod amm;
 pub mod registry;
 pub mod dex;
 pub mod dex_whitelist;
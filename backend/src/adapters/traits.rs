@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use crate::adapters::amm::PoolState;
 use crate::error::AppResult;
 use crate::execution::router::Executor;
 use crate::ledger::models::Chain;
@@ -76,6 +77,12 @@ pub trait DexAdapter: Send + Sync {
     async fn swap(&self, request: SwapRequest) -> AppResult<SwapResult>;
     
     async fn estimate_gas(&self, asset_in: &AssetInfo, asset_out: &AssetInfo) -> AppResult<Decimal>;
+
+    /// Reserves and fees of the pool the DEX swaps the pair in, oriented
+    /// from `asset_in` to `asset_out`; None if the DEX cannot read them
+    async fn get_pool_state(&self, _asset_in: &AssetInfo, _asset_out: &AssetInfo) -> AppResult<Option<PoolState>> {
+        Ok(None)
+    }
     
     async fn is_available(&self) -> AppResult<bool> {
        Ok(true)
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use tokio::spawn;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 use crate::error::{AppResult};
 use crate::{
@@ -95,11 +95,18 @@ impl AsyncWebhookProcessor {
             .await?;
 
         // Step 4: Update quote status based on webhook status
+        // In-flight statuses leave the committed quote untouched
         let new_quote_status = match payload.status.as_str() {
             "success" | "completed" => QuoteStatus::Executed,
             "failed" | "error" => QuoteStatus::Failed,
-            "pending" | "confirming" => QuoteStatus::Pending,
-            _ => QuoteStatus::Pending,
+            "pending" | "confirming" => {
+                info!("Quote {} still in flight ({})", payload.quote_id, payload.status);
+                return Ok(());
+            }
+            other => {
+                warn!("Unknown webhook status '{}' for quote {}", other, payload.quote_id);
+                return Ok(());
+            }
         };
 
         let mut tx = ledger.begin_tx().await?;
@@ -113,6 +120,8 @@ impl AsyncWebhookProcessor {
             )
             .await?;
 
+        tx.commit().await?;
+
         // Step 5: Log settlement record for accounting
         // Note: settlement is recorded separately through the settlement module
         info!("Settlement execution logged for quote: {}", execution.quote_id);
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::adapters::traits::AssetInfo;
 use crate::api::handler::AppState;
 use crate::error::AppResult;
-use crate::ledger::models::Chain;
+use crate::ledger::models::{Chain, ChainPairConfig};
 
 #[derive(Serialize)]
 pub struct DexInfo {
@@ -21,6 +21,15 @@ pub struct ChainDiscovery {
     pub chain: Chain,
     pub dexes: Vec<DexInfo>,
     pub supported_tokens: Vec<AssetInfo>,
+    /// Whitelisted tokens that cannot be quoted for lack of a Pyth feed
+    pub missing_price_feeds: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ChainPairMatrix {
+    pub chains: Vec<Chain>,
+    pub pairs: Vec<ChainPairConfig>,
+    pub enabled_count: usize,
 }
 
 #[derive(Deserialize)]
@@ -88,6 +97,7 @@ pub async fn get_chain_discovery(
         chain,
         dexes,
         supported_tokens: all_tokens,
+        missing_price_feeds: state.price_feeds.missing_feeds(chain),
     }))
 }
 
@@ -115,6 +125,20 @@ pub async fn list_assets_on_dex(
     }))
 }
 
+/// Live funding -> execution pair matrix from supported_chain_pairs
+pub async fn get_chain_pairs(
+    State(state): State<AppState>,
+) -> AppResult<Json<ChainPairMatrix>> {
+    let pairs = state.chain_pairs.matrix().await;
+    let enabled_count = pairs.iter().filter(|p| p.enabled).count();
+
+    Ok(Json(ChainPairMatrix {
+        chains: Chain::all(),
+        pairs,
+        enabled_count,
+    }))
+}
+
 pub async fn get_all_chains() -> AppResult<Json<Vec<Chain>>> {
     Ok(Json(vec![Chain::Solana, Chain::Stellar, Chain::Near]))
 }
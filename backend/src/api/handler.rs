@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, State},
+    http::StatusCode,
     Json
 };
 use chrono::{DateTime, Utc};
@@ -8,15 +9,16 @@ use std::{str::FromStr, sync::Arc};
 use std::time::Duration;
 use tracing::{error, info, warn};
 use uuid::Uuid;
-use sqlx::{Row, types::BigDecimal};
+use sqlx::Row;
 use rust_decimal::Decimal;
 
 use super::models::*;
 use crate::{
-    adapters::{AdapterRegistry, DexWhitelist}, api::spending_approval::{CreateSpendingApprovalRequest, SpendingApproval, SpendingApprovalResponse}, error::{AppError, AppResult, ExecutionError, QuoteError}, execution::{router::ExecutionRouter, solana::SolanaExecutor, stellar::StellarExecutor, near::NearExecutor}, ledger::{
-        models::Chain,
-        repository::LedgerRepository
-    }, quote_engine::{OhlcStore, PriceCache, engine::QuoteEngine, realtime::RealtimeQuoteEngine}, risk::controls::RiskController, trading::TradeRepository, wallet::WalletRepository
+    adapters::{AdapterRegistry, DexWhitelist}, api::{spending_approval::{CreateSpendingApprovalRequest, SpendingApproval, SpendingApprovalResponse}, websocket::PriceFeedBroadcaster}, error::{AppError, AppResult, ExecutionError, QuoteError}, middleware::IdempotencyStore, execution::{router::ExecutionRouter, solana::SolanaExecutor, stellar::StellarExecutor, near::NearExecutor}, ledger::{
+        journal::TrialBalance,
+        models::{AuditEventType, ChainPairConfig, Chain, FeeSchedule, FeeScheduleParams, PriceFeed, PriceFeedParams},
+        repository::{to_big_decimal, LedgerRepository}
+    }, quote_engine::{ChainPairRegistry, FeeScheduleRegistry, OhlcStore, PriceFeedRegistry, PriceCache, QuoteCacheStats, RoutePlanner, SplitRouter, engine::QuoteEngine, realtime::RealtimeQuoteEngine}, risk::controls::RiskController, settlement::SettlementReconciler, trading::TradeRepository, wallet::WalletRepository
 };
 
 #[derive(Clone)]
@@ -27,10 +29,18 @@ pub struct AppState {
     pub risk_controller: Arc<RiskController>,
     pub adapter_registry: Arc<AdapterRegistry>,
     pub realtime_quote_engine: Arc<RealtimeQuoteEngine>,
+    pub route_planner: Arc<RoutePlanner>,
+    pub split_router: Arc<SplitRouter>,
     pub wallet_repository: Arc<WalletRepository>,
     pub trade_repository: Arc<TradeRepository>,
     pub ohlc_store: Arc<OhlcStore>,
     pub price_cache: Arc<PriceCache>,
+    pub price_feed_broadcaster: Arc<PriceFeedBroadcaster>,
+    pub settlement_reconciler: Arc<SettlementReconciler>,
+    pub chain_pairs: Arc<ChainPairRegistry>,
+    pub fee_schedules: Arc<FeeScheduleRegistry>,
+    pub price_feeds: Arc<PriceFeedRegistry>,
+    pub idempotency: Arc<IdempotencyStore>,
     // Direct executor references for signature verification
     pub solana_executor: Arc<SolanaExecutor>,
     pub stellar_executor: Arc<StellarExecutor>,
@@ -46,7 +56,7 @@ pub async fn create_quote(
     info!(" Creating quote: {:?} -> {:?} for user {}", request.funding_chain, request.execution_chain, request.user_id);
 
     // Validate input
-    validate_quote_request(&request)?;
+    let amount = validate_quote_request(&request)?;
 
     // Validate tokens are whitelisted
     let whitelist = crate::adapters::DexWhitelist::new();
@@ -97,8 +107,11 @@ pub async fn create_quote(
             request.execution_chain, 
             request.funding_asset, 
             request.execution_asset, 
+            request.mode,
+            amount,
             instructions, 
-            request.estimated_compute_units
+            request.estimated_compute_units,
+            request.simulate,
         ).await?;
 
     // Validate quote is in Pending status and can be committed
@@ -114,6 +127,23 @@ pub async fn create_quote(
     Ok(Json(QuoteResponse::from(quote)))
 }
 
+/// Refresh a pending quote against current prices and fees
+/// POST /quote/:id/refresh
+///
+/// Extends the quote if its total payment moved less than the configured
+/// tolerance, otherwise returns a new quote that supersedes it (same nonce
+/// and payment address)
+pub async fn refresh_quote(
+    State(state): State<AppState>,
+    Path(quote_id): Path<Uuid>,
+) -> AppResult<Json<QuoteRefreshResponse>> {
+    info!("Refreshing quote: {}", quote_id);
+
+    let refresh = state.quote_engine.refresh_quote(quote_id).await?;
+
+    Ok(Json(QuoteRefreshResponse::from(refresh)))
+}
+
 ///Commit a quote for execution
 /// POST /commit
 ///
@@ -172,7 +202,7 @@ pub async fn commit_quote(
 /// - Circuit breaker triggers after consecutive failures
 /// - Checks treasury balance before execution
 /// - Treasury state machine validation (prevents invalid transitions)
-async fn execute_with_retries(
+pub(crate) async fn execute_with_retries(
     router: Arc<ExecutionRouter>,
     ledger: Arc<LedgerRepository>,
     quote: &crate::ledger::models::Quote,
@@ -486,24 +516,22 @@ pub async fn payment_webhook(
                 Ok(_) => {
                     info!("Execution completed for quote: {}", quote_id);
                     
-                    // Record settlement
-                    if let Ok(Some(row)) = sqlx::query(
-                        r#"SELECT id FROM executions WHERE quote_id = $1 LIMIT 1"#
-                    )
-                    .bind(quote_id)
-                    .fetch_optional(&ledger.pool)
-                    .await
-                    {
-                        if let Ok(execution_id) = row.try_get::<Uuid, _>("id") {
-                            let _ = ledger
-                                .create_settlement(
-                                    execution_id,
-                                    quote.funding_chain,
-                                    payload.transaction_hash.clone(),
-                                    BigDecimal::from_str(&paid_amount.to_string()).unwrap(),
-                                )
-                                .await;
-                        }
+                    // Record settlement, verified later by the reconciler
+                    let recorded: AppResult<()> = async {
+                        let execution = ledger.get_execution_by_quote_id(&quote_id).await?;
+                        ledger
+                            .create_settlement(
+                                execution.id,
+                                quote.funding_chain,
+                                payload.transaction_hash.clone(),
+                                to_big_decimal(paid_amount)?,
+                            )
+                            .await?;
+                        Ok(())
+                    }
+                    .await;
+                    if let Err(e) = recorded {
+                        error!("Failed to record settlement for quote {}: {:?}", quote_id, e);
                     }
                 }
                 Err(e) => error!("Execution failed for quote {}: {:?}", quote_id, e),
@@ -578,6 +606,8 @@ pub async fn get_status(
         None => (None, None, None),
     };
 
+    let history = state.ledger.get_quote_status_history(quote_id).await?;
+
     Ok(Json(StatusResponse {
         quote_id,
         funding_chain: quote.funding_chain.as_str().to_string(),
@@ -586,6 +616,8 @@ pub async fn get_status(
         transaction_hash,
         executed_at,
         error_message,
+        superseded_by: quote.superseded_by,
+        history,
     }))
 }
 
@@ -654,6 +686,204 @@ pub async fn get_treasury_balances(
     })))
 }
 
+/// GET /admin/reconciliation - Last reconciliation run and open discrepancies
+pub async fn get_reconciliation_status(
+    State(state): State<AppState>,
+) -> AppResult<Json<serde_json::Value>> {
+    let last_run = state.settlement_reconciler.last_report().await;
+    let discrepancies = state.ledger.get_open_settlement_discrepancies().await?;
+
+    Ok(Json(serde_json::json!({
+        "last_run": last_run,
+        "open_discrepancies": discrepancies.len(),
+        "discrepancies": discrepancies,
+        "timestamp": Utc::now().to_rfc3339(),
+    })))
+}
+
+/// POST /admin/reconciliation/run - Run settlement reconciliation now
+pub async fn run_reconciliation(
+    State(state): State<AppState>,
+) -> AppResult<Json<serde_json::Value>> {
+    info!("Manual settlement reconciliation requested");
+
+    let report = state.settlement_reconciler.reconcile_pending().await?;
+
+    Ok(Json(serde_json::json!({
+        "report": report,
+        "timestamp": Utc::now().to_rfc3339(),
+    })))
+}
+
+/// GET /admin/ledger/trial-balance - Journal trial balance and invariant check
+pub async fn get_trial_balance(
+    State(state): State<AppState>,
+) -> AppResult<Json<TrialBalance>> {
+    let trial_balance = state.ledger.get_trial_balance().await?;
+
+    if !trial_balance.balanced {
+        error!(
+            "🚨 Journal out of balance: {} imbalances",
+            trial_balance.imbalances.len()
+        );
+    }
+
+    Ok(Json(trial_balance))
+}
+
+/// GET /admin/chain-pairs - Configured chain pairs
+pub async fn list_chain_pairs(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<ChainPairConfig>>> {
+    Ok(Json(state.chain_pairs.matrix().await))
+}
+
+/// POST /admin/chain-pairs/:funding/:execution - Enable/disable a pair or change its limits
+pub async fn update_chain_pair(
+    State(state): State<AppState>,
+    Path((funding, execution)): Path<(Chain, Chain)>,
+    Json(request): Json<UpdateChainPairRequest>,
+) -> AppResult<Json<ChainPairConfig>> {
+    info!("Admin chain pair update: {} -> {} {:?}", funding, execution, request);
+
+    let pair = state
+        .chain_pairs
+        .update(
+            funding,
+            execution,
+            request.enabled,
+            request.min_amount,
+            request.max_amount,
+            request.service_fee_rate,
+        )
+        .await?;
+
+    state
+        .ledger
+        .log_audit_event(
+            AuditEventType::ChainPairUpdated,
+            Some(execution),
+            None,
+            None,
+            serde_json::json!({
+                "chain_pair": format!("{}->{}", funding, execution),
+                "enabled": pair.enabled,
+                "min_amount": pair.min_amount.to_string(),
+                "max_amount": pair.max_amount.map(|a| a.to_string()),
+                "service_fee_rate": pair.service_fee_rate.map(|r| r.to_string()),
+            }),
+        )
+        .await?;
+
+    Ok(Json(pair))
+}
+
+/// GET /admin/fee-schedules - Configured fee schedules
+pub async fn list_fee_schedules(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<FeeSchedule>>> {
+    Ok(Json(state.fee_schedules.list().await))
+}
+
+/// POST /admin/fee-schedules - Create a fee schedule
+pub async fn create_fee_schedule(
+    State(state): State<AppState>,
+    Json(request): Json<FeeScheduleParams>,
+) -> AppResult<Json<FeeSchedule>> {
+    info!("Admin fee schedule create: {:?}", request);
+
+    let schedule = state.fee_schedules.create(&request).await?;
+    log_fee_schedule_change(&state, &schedule, "created").await?;
+
+    Ok(Json(schedule))
+}
+
+/// POST /admin/fee-schedules/:id - Replace a fee schedule (set enabled=false to retire it)
+pub async fn update_fee_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<FeeScheduleParams>,
+) -> AppResult<Json<FeeSchedule>> {
+    info!("Admin fee schedule update: {} {:?}", id, request);
+
+    let schedule = state.fee_schedules.update(id, &request).await?;
+    log_fee_schedule_change(&state, &schedule, "updated").await?;
+
+    Ok(Json(schedule))
+}
+
+async fn log_fee_schedule_change(state: &AppState, schedule: &FeeSchedule, action: &str) -> AppResult<()> {
+    state
+        .ledger
+        .log_audit_event(
+            AuditEventType::FeeScheduleUpdated,
+            schedule.execution_chain,
+            Some(schedule.id),
+            None,
+            serde_json::json!({
+                "action": action,
+                "schedule": schedule,
+            }),
+        )
+        .await
+}
+
+/// GET /admin/price-feeds - Configured Pyth price feeds
+pub async fn list_price_feeds(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<PriceFeed>>> {
+    Ok(Json(state.price_feeds.list().await))
+}
+
+/// POST /admin/price-feeds - Create or replace the feed of an asset
+pub async fn upsert_price_feed(
+    State(state): State<AppState>,
+    Json(request): Json<PriceFeedParams>,
+) -> AppResult<Json<PriceFeed>> {
+    info!("Admin price feed update: {:?}", request);
+
+    let feed = state.price_feeds.upsert(&request).await?;
+
+    state
+        .ledger
+        .log_audit_event(
+            AuditEventType::PriceFeedUpdated,
+            feed.chain,
+            Some(feed.id),
+            None,
+            serde_json::json!({
+                "action": "upserted",
+                "feed": feed,
+            }),
+        )
+        .await?;
+
+    Ok(Json(feed))
+}
+
+/// POST /admin/price-feeds/reload - Re-read price feeds from the database
+pub async fn reload_price_feeds(
+    State(state): State<AppState>,
+) -> AppResult<Json<serde_json::Value>> {
+    let loaded = state.price_feeds.reload().await?;
+
+    state
+        .ledger
+        .log_audit_event(
+            AuditEventType::PriceFeedUpdated,
+            None,
+            None,
+            None,
+            serde_json::json!({
+                "action": "reloaded",
+                "loaded": loaded,
+            }),
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({ "loaded": loaded })))
+}
+
 /// GET /admin/treasury/:chain - Get specific chain treasury balance
 pub async fn get_chain_treasury_balance(
     State(state): State<AppState>,
@@ -910,6 +1140,9 @@ pub async fn get_settlement_status(
         "status": status_str,
         "execution_chain": quote.execution_chain.as_str(),
         "funding_chain": quote.funding_chain.as_str(),
+        "mode": quote.mode,
+        "funding_amount": quote.funding_amount.to_string(),
+        "execution_amount": quote.execution_amount.to_string(),
         "execution_cost": quote.execution_cost.to_string(),
         "max_funding_amount": quote.max_funding_amount.to_string(),
         "service_fee": quote.service_fee.to_string(),
@@ -989,10 +1222,25 @@ pub async fn get_chart_stats(
     })))
 }
 
+/// Realtime quote cache counters
+pub async fn get_quote_cache_stats(
+    State(state): State<AppState>,
+) -> AppResult<Json<QuoteCacheStats>> {
+    Ok(Json(state.realtime_quote_engine.cache_stats()))
+}
+
+/// Drop every cached realtime quote; in-flight fetches still complete
+pub async fn clear_quote_cache(State(state): State<AppState>) -> AppResult<StatusCode> {
+    state.realtime_quote_engine.clear_cache().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ========== VALIDATION HELPERS ==========
 
 /// Validate quote request parameters
-fn validate_quote_request(request: &QuoteRequest) -> AppResult<()> {
+///
+/// Returns the parsed trade amount
+fn validate_quote_request(request: &QuoteRequest) -> AppResult<Decimal> {
     // Validate funding chain - it's already an enum from deserialization
     // Just ensure it's not equal to execution chain
     
@@ -1009,8 +1257,15 @@ fn validate_quote_request(request: &QuoteRequest) -> AppResult<()> {
     if !is_valid_asset_name(&request.execution_asset) {
         return Err(AppError::InvalidInput(format!("Invalid execution asset: {}", request.execution_asset)));
     }
+
+    // Trade amount in display units of the asset the mode fixes
+    let amount = Decimal::from_str(request.amount.trim())
+        .map_err(|_| AppError::InvalidInput(format!("Invalid amount: {}", request.amount)))?;
+    if amount <= Decimal::ZERO {
+        return Err(AppError::InvalidInput("Amount must be positive".to_string()));
+    }
     
-    Ok(())
+    Ok(amount)
 }
 
 /// Whitelist of supported asset names (prevents SQL injection)
@@ -1,4 +1,5 @@
 use crate::ledger::models::*;
+use crate::quote_engine::engine::QuoteRefresh;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -17,12 +18,20 @@ pub struct QuoteRequest {
     // Assets
     pub funding_asset: String,
     pub execution_asset: String,
+
+    // Trade: exact_input fixes the funding amount, exact_output the execution amount
+    pub mode: QuoteMode,
+    pub amount: String,
     
     /// Base64 encoded execution instructions (chain-specific)
     pub execution_instructions_base64: String,
     
     /// Optional compute units (for Solana execution)
     pub estimated_compute_units: Option<i32>,
+
+    /// Dry-run the instructions on the execution chain before quoting
+    #[serde(default)]
+    pub simulate: bool,
 }
 
 /// Request to commit a quote (after payment detected)
@@ -44,6 +53,29 @@ pub struct ChainWebhookPayload {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Admin update of a supported chain pair; omitted fields are unchanged,
+/// `null` clears `max_amount` or `service_fee_rate`
+#[derive(Debug, Deserialize)]
+pub struct UpdateChainPairRequest {
+    pub enabled: Option<bool>,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub min_amount: Option<rust_decimal::Decimal>,
+    #[serde(default, deserialize_with = "clearable_decimal")]
+    pub max_amount: Option<Option<rust_decimal::Decimal>>,
+    #[serde(default, deserialize_with = "clearable_decimal")]
+    pub service_fee_rate: Option<Option<rust_decimal::Decimal>>,
+}
+
+/// Omitted → None, `null` → Some(None), a number → Some(Some(value))
+fn clearable_decimal<'de, D>(
+    deserializer: D,
+) -> Result<Option<Option<rust_decimal::Decimal>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    rust_decimal::serde::float_option::deserialize(deserializer).map(Some)
+}
+
 // ========== RESPONSE MODELS ==========
 
 /// Symmetric quote response
@@ -59,11 +91,20 @@ pub struct QuoteResponse {
     // Assets
     pub funding_asset: String,
     pub execution_asset: String,
+
+    // Trade
+    pub mode: QuoteMode,
+    pub funding_amount: String,
+    pub execution_amount: String,
     
     // Costs
     pub max_funding_amount: String,
     pub execution_cost: String,
     pub service_fee: String,
+    pub fee_schedule_id: Option<Uuid>,
+
+    // Price staleness and confidence check the quote passed
+    pub price_check: Option<PriceCheck>,
     
     // Payment details
     pub payment_address: String,
@@ -80,9 +121,14 @@ impl From<Quote> for QuoteResponse {
             execution_chain: quote.execution_chain.as_str().to_string(),
             funding_asset: quote.funding_asset,
             execution_asset: quote.execution_asset,
+            mode: quote.mode,
+            funding_amount: quote.funding_amount.to_string(),
+            execution_amount: quote.execution_amount.to_string(),
             max_funding_amount: quote.max_funding_amount.to_string(),
             execution_cost: quote.execution_cost.to_string(),
             service_fee: quote.service_fee.to_string(),
+            fee_schedule_id: quote.fee_schedule_id,
+            price_check: quote.price_check,
             payment_address: quote.payment_address.unwrap_or_default(),
             expires_at: quote.expires_at,
             nonce: quote.nonce,
@@ -90,6 +136,34 @@ impl From<Quote> for QuoteResponse {
     }
 }
 
+/// Quote refresh response
+#[derive(Debug, Serialize)]
+pub struct QuoteRefreshResponse {
+    /// "extended" or "superseded"
+    pub outcome: String,
+    /// The live quote: the same one with a new expiry, or its replacement
+    pub quote: QuoteResponse,
+    /// Set when the refreshed quote was superseded
+    pub superseded_quote_id: Option<Uuid>,
+}
+
+impl From<QuoteRefresh> for QuoteRefreshResponse {
+    fn from(refresh: QuoteRefresh) -> Self {
+        match refresh {
+            QuoteRefresh::Extended(quote) => Self {
+                outcome: "extended".to_string(),
+                quote: QuoteResponse::from(quote),
+                superseded_quote_id: None,
+            },
+            QuoteRefresh::Superseded { previous, quote } => Self {
+                outcome: "superseded".to_string(),
+                quote: QuoteResponse::from(quote),
+                superseded_quote_id: Some(previous),
+            },
+        }
+    }
+}
+
 /// Commit response
 #[derive(Debug, Serialize)]
 pub struct CommitResponse {
@@ -109,6 +183,10 @@ pub struct StatusResponse {
     pub transaction_hash: Option<String>,
     pub executed_at: Option<DateTime<Utc>>,
     pub error_message: Option<String>,
+    /// Quote that replaced this one, if a refresh superseded it
+    pub superseded_by: Option<Uuid>,
+    /// Status transitions, oldest first
+    pub history: Vec<QuoteStatusChange>,
 }
 
 /// Webhook processing response
@@ -2,6 +2,9 @@ use crate::adapters::traits::AssetInfo;
 use crate::api::handler::AppState;
 use crate::error::AppResult;
 use crate::ledger::models::Chain;
+use crate::quote_engine::realtime::RouteOption;
+use crate::quote_engine::{RoutePlan, SplitPlan};
+use crate::trading::models::RouteStep;
 
 use axum::{
     extract::{State},
@@ -10,6 +13,7 @@ use axum::{
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize)]
 pub struct BestQuoteResponse {
@@ -40,12 +44,16 @@ pub struct QuoteRequest {
     pub asset_out_chain: Chain,
     pub asset_out_address: String,
     pub amount: Decimal,
+    /// Prices cross-chain routes at this user's fee tier
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
 }
 
 #[derive(Serialize)]
 pub struct PriceImpactResponse {
     pub spot_price: Decimal,
     pub execution_price: Decimal,
+    pub marginal_price: Option<Decimal>,
     pub price_impact_bps: Decimal,
     pub slippage_percent: Decimal,
     pub recommended_slippage_tolerance: Decimal,
@@ -63,7 +71,53 @@ pub struct RouteResponse {
     pub path: Vec<PathStep>,
     pub total_amount_out: Decimal,
     pub estimated_gas_fees: Decimal,
+    pub net_amount_out: Decimal,
     pub total_slippage: Decimal,
+    /// Swaps and, across chains, the treasury hop, in order
+    pub steps: Vec<RouteStep>,
+}
+
+impl From<&RouteOption> for RouteResponse {
+    fn from(route: &RouteOption) -> Self {
+        RouteResponse {
+            hops: route.hops,
+            path: route
+                .path
+                .iter()
+                .zip(route.dex_sequence.iter())
+                .map(|(asset, dex)| PathStep {
+                    asset: asset.clone(),
+                    dex: dex.clone(),
+                })
+                .collect(),
+            total_amount_out: route.total_amount_out,
+            estimated_gas_fees: route.estimated_gas_fees,
+            net_amount_out: route.net_amount_out,
+            total_slippage: route.total_slippage,
+            steps: route.steps.clone(),
+        }
+    }
+}
+
+impl From<&RoutePlan> for RouteResponse {
+    fn from(plan: &RoutePlan) -> Self {
+        RouteResponse {
+            hops: plan.steps.len(),
+            path: plan
+                .steps
+                .iter()
+                .map(|step| PathStep {
+                    asset: step.asset_in.clone(),
+                    dex: step.dex.clone(),
+                })
+                .collect(),
+            total_amount_out: plan.amount_out,
+            estimated_gas_fees: plan.total_gas_estimate,
+            net_amount_out: plan.net_amount_out,
+            total_slippage: plan.total_slippage_percent,
+            steps: plan.steps.clone(),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -154,6 +208,7 @@ pub async fn get_price_impact(
     let response = PriceImpactResponse {
         spot_price: impact.spot_price,
         execution_price: impact.execution_price,
+        marginal_price: impact.marginal_price,
         price_impact_bps: impact.price_impact_bps,
         slippage_percent: impact.slippage_percent,
         recommended_slippage_tolerance: impact.recommended_slippage_tolerance,
@@ -184,10 +239,24 @@ pub async fn find_routes(
         logo_url: None,
     };
 
-    let routes = state
-        .realtime_quote_engine
-        .find_multi_hop_routes(&asset_in, &asset_out, req.amount, 3)
-        .await?;
+    // Across chains a route goes through the treasury hop
+    let mut routes: Vec<RouteResponse> = if asset_in.chain == asset_out.chain {
+        state
+            .realtime_quote_engine
+            .find_multi_hop_routes(&asset_in, &asset_out, req.amount, 3)
+            .await?
+            .iter()
+            .map(RouteResponse::from)
+            .collect()
+    } else {
+        state
+            .route_planner
+            .plan(req.user_id, &asset_in, &asset_out, req.amount, 2)
+            .await?
+            .iter()
+            .map(RouteResponse::from)
+            .collect()
+    };
 
     if routes.is_empty() {
         return Err(crate::error::AppError::NoLiquidityAvailable(
@@ -195,42 +264,8 @@ pub async fn find_routes(
         ));
     }
 
-    let best_route = &routes[0];
-    let best_route_response = RouteResponse {
-        hops: best_route.hops,
-        path: best_route
-            .path
-            .iter()
-            .zip(best_route.dex_sequence.iter())
-            .map(|(asset, dex)| PathStep {
-                asset: asset.clone(),
-                dex: dex.clone(),
-            })
-            .collect(),
-        total_amount_out: best_route.total_amount_out,
-        estimated_gas_fees: best_route.estimated_gas_fees,
-        total_slippage: best_route.total_slippage,
-    };
-
-    let alternative_routes = routes
-        .iter()
-        .skip(1)
-        .map(|route| RouteResponse {
-            hops: route.hops,
-            path: route
-                .path
-                .iter()
-                .zip(route.dex_sequence.iter())
-                .map(|(asset, dex)| PathStep {
-                    asset: asset.clone(),
-                    dex: dex.clone(),
-                })
-                .collect(),
-            total_amount_out: route.total_amount_out,
-            estimated_gas_fees: route.estimated_gas_fees,
-            total_slippage: route.total_slippage,
-        })
-        .collect();
+    let best_route_response = routes.remove(0);
+    let alternative_routes = routes;
 
     Ok((
         StatusCode::OK,
@@ -240,3 +275,34 @@ pub async fn find_routes(
         }),
     ))
 }
+
+/// Split an order across the chain's DEXes
+pub async fn find_split_route(
+    State(state): State<AppState>,
+    Json(req): Json<QuoteRequest>,
+) -> AppResult<(StatusCode, Json<SplitPlan>)> {
+    let asset_in = AssetInfo {
+        chain: req.asset_in_chain,
+        address: req.asset_in_address,
+        symbol: String::new(),
+        name: String::new(),
+        decimals: 18,
+        logo_url: None,
+    };
+
+    let asset_out = AssetInfo {
+        chain: req.asset_out_chain,
+        address: req.asset_out_address,
+        symbol: String::new(),
+        name: String::new(),
+        decimals: 18,
+        logo_url: None,
+    };
+
+    let plan = state
+        .split_router
+        .plan(&asset_in, &asset_out, req.amount)
+        .await?;
+
+    Ok((StatusCode::OK, Json(plan)))
+}
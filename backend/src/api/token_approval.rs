@@ -104,7 +104,7 @@ pub async fn create_token_approval(
         nonce: nonce.clone(),
         signature: None,
         user_wallet: String::new(), // Will be filled on submission
-        status: "pending".to_string(),
+        status: ApprovalStatus::Pending,
         transaction_hash: None,
         block_height: None,
         confirmation_status: None,
@@ -145,7 +145,7 @@ pub async fn submit_token_approval(
     let approval = app_state.ledger.get_token_approval(&req.approval_id).await?;
 
     // 2. Validate approval is still pending
-    if approval.status != "pending" {
+    if approval.status != ApprovalStatus::Pending {
         return Err(AppError::Internal(format!(
             "Approval is in {} status, expected pending",
             approval.status
@@ -320,7 +320,7 @@ pub async fn get_token_approval_status(
     let approval = app_state.ledger.get_token_approval(&approval_id).await?;
 
     // Check if expired and update status if needed
-    if Utc::now() > approval.expires_at && approval.status == "pending" {
+    if Utc::now() > approval.expires_at && approval.status == ApprovalStatus::Pending {
         app_state
             .ledger
             .update_token_approval_failed(
@@ -332,7 +332,7 @@ pub async fn get_token_approval_status(
 
         return Ok(Json(TokenApprovalStatusResponse {
             approval_id: approval.id,
-            status: "expired".to_string(),
+            status: ApprovalStatus::Expired,
             transaction_hash: None,
             confirmation_status: None,
             block_height: None,
@@ -3,7 +3,7 @@ use solana_sdk::signature::Keypair;
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use tracing::{info, error};
 use crate::{
-    adapters::{AdapterRegistry, dex::{NearDexAdapter, PhantomSwapAdapter, RaydiumAdapter}}, api::handler::AppState, error::AppResult, execution::{near::{NearConfig, NearExecutor}, router::ExecutionRouter, solana::{SolanaConfig, SolanaExecutor}, stellar::{StellarConfig, StellarExecutor}}, ledger::{models::Chain, repository::LedgerRepository}, quote_engine::{OhlcStore, PriceCache, PythOracle, QuoteEngine, engine::QuoteConfig, realtime::RealtimeQuoteEngine}, risk::controls::{RiskConfig, RiskController}, trading::TradeRepository, wallet::WalletRepository
+    adapters::{AdapterRegistry, dex::{NearDexAdapter, PhantomSwapAdapter, RaydiumAdapter}}, api::{handler::AppState, websocket::PriceFeedBroadcaster}, error::{AppError, AppResult}, middleware::IdempotencyStore, funding::{FundingProcessor, HorizonPaymentSource, NearBlockSource, NearMonitor, SolanaMonitor, SolanaPaymentSource, StellarMonitor}, execution::{near::{NearConfig, NearExecutor}, router::ExecutionRouter, solana::{SolanaConfig, SolanaExecutor}, stellar::{StellarConfig, StellarExecutor}}, ledger::{models::Chain, repository::LedgerRepository}, quote_engine::{ChainPairRegistry, FeeEstimatorConfig, FeeEstimators, FeeScheduleRegistry, DexPriceSource, OhlcStore, PaymentAddressConfig, PaymentAddresses, PriceAggregator, PriceAggregatorConfig, PriceCache, PriceFeedRegistry, PythOracle, PythStreamConfig, PythStreamSubscriber, QuoteCacheConfig, QuoteEngine, RoutePlanner, SplitRouteConfig, SplitRouter, StaticPriceSource, engine::QuoteConfig, realtime::RealtimeQuoteEngine}, risk::controls::{RiskConfig, RiskController}, settlement::SettlementReconciler, trading::TradeRepository, wallet::WalletRepository
 };
 
 pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
@@ -20,17 +20,37 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
     let pyth_oracle = Arc::new(PythOracle::new(&network));
     info!("✅ Pyth price oracle initialized for network: {}", network);
 
-    // Initialize quote engine
-    let quote_config = QuoteConfig::default();
-    let quote_engine = Arc::new(QuoteEngine::new(
-        quote_config,
-        ledger.clone(),
-        pyth_oracle.clone(),
-        network.clone(),
-    ));
+    // Pyth feed IDs - pyth_price_feeds table, reloaded on SIGHUP or by admin
+    let price_feeds = Arc::new(PriceFeedRegistry::load(ledger.clone(), pyth_oracle.clone()).await?);
+
+    // Payment addresses - configured per deployment
+    let payment_address_config = PaymentAddressConfig::from_env()?;
+    let payment_addresses = Arc::new(PaymentAddresses::from_config(&payment_address_config)?);
+    info!(
+        "✅ Payment addresses configured (Stellar: {}, NEAR: {}, Solana program: {})",
+        payment_address_config.stellar_collection_account,
+        payment_address_config.near_escrow_account,
+        payment_address_config.solana_escrow_program_id
+    );
+
+    // Supported chain pairs - supported_chain_pairs table, admin editable
+    let chain_pairs = Arc::new(ChainPairRegistry::load(ledger.clone()).await?);
+    info!("✅ Chain pairs loaded");
+
+    // Service fee schedules - fee_schedules table, admin editable
+    let fee_schedules = Arc::new(FeeScheduleRegistry::load(ledger.clone()).await?);
+    info!("✅ Fee schedules loaded");
+
+    // Live fee estimation per execution chain
+    let fee_estimator_config = FeeEstimatorConfig::from_env()?;
+    let fee_estimators = Arc::new(FeeEstimators::from_config(&fee_estimator_config));
+    info!(
+        "✅ Fee estimators configured (percentiles - Solana: p{}, Stellar: p{}, NEAR: p{})",
+        fee_estimator_config.solana_percentile,
+        fee_estimator_config.stellar_percentile,
+        fee_estimator_config.near_percentile
+    );
 
-    let execution_route = Arc::new(ExecutionRouter::new());
-    
     // Initialize risk controls
     let risk_config = RiskConfig::default();
     let risk_controller = Arc::new(RiskController::new(risk_config, ledger.clone()));
@@ -40,7 +60,7 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
     let ohlc_store = Arc::new(OhlcStore::new(100));
 
     // Initialize execution router first so we can pass executors to adapters
-    let mut execution_router = ExecutionRouter::new();
+    let mut execution_router = ExecutionRouter::new(chain_pairs.clone());
 
     info!("⚙️  Initializing chain executors...");
 
@@ -115,10 +135,14 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
         .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
     
     // Create Raydium adapter and inject Solana executor for smart contract calls
+    let mut raydium = RaydiumAdapter::new(solana_rpc.clone());
+    if let Ok(api_url) = std::env::var("RAYDIUM_API_URL") {
+        raydium = raydium.with_api_url(api_url);
+    }
     let raydium_adapter = if let Some(executor) = solana_executor.clone() {
-        Arc::new(RaydiumAdapter::new(solana_rpc).with_executor(executor))
+        Arc::new(raydium.with_executor(executor))
     } else {
-        Arc::new(RaydiumAdapter::new(solana_rpc))
+        Arc::new(raydium)
     };
     adapter_registry.register_dex("Raydium".to_string(), raydium_adapter);
     info!("✅ Raydium adapter registered (with smart contract integration)");
@@ -127,30 +151,97 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
         .unwrap_or_else(|_| "https://horizon.stellar.org".to_string());
     adapter_registry.register_dex(
         "PhantomSwap".to_string(),
-        Arc::new(PhantomSwapAdapter::new(stellar_horizon)),
+        Arc::new(PhantomSwapAdapter::new(stellar_horizon.clone())),
     );
     info!("✅ PhantomSwap adapter registered");
 
     let near_rpc = std::env::var("NEAR_RPC_URL")
         .unwrap_or_else(|_| "https://rpc.mainnet.near.org".to_string());
-    adapter_registry.register_dex(
-        "Ref Finance".to_string(),
-        Arc::new(NearDexAdapter::new(near_rpc)),
-    );
+    // Ref Finance pools read for price impact (comma separated pool ids)
+    let mut ref_finance = NearDexAdapter::new(near_rpc.clone());
+    if let Ok(pool_ids) = std::env::var("REF_FINANCE_POOL_IDS") {
+        let pool_ids = pool_ids
+            .split(',')
+            .map(|id| id.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                AppError::Config("REF_FINANCE_POOL_IDS must be comma separated pool ids".to_string())
+            })?;
+        let contract = std::env::var("REF_FINANCE_CONTRACT").unwrap_or_else(|_| {
+            match network.as_str() {
+                "mainnet" => "v2.ref-finance.near",
+                _ => "ref-finance-101.testnet",
+            }
+            .to_string()
+        });
+        ref_finance = ref_finance.with_pools(&contract, pool_ids)?;
+    }
+    adapter_registry.register_dex("Ref Finance".to_string(), Arc::new(ref_finance));
     info!("✅ Ref Finance (NEAR) adapter registered");
 
     let adapter_registry = Arc::new(adapter_registry);
 
     // Initialize realtime quote engine
-    let realtime_quote_engine = Arc::new(RealtimeQuoteEngine::new(adapter_registry.clone()));
-    info!("✅ Realtime quote engine initialized");
+    let quote_cache_config = QuoteCacheConfig::from_env()?;
+    info!(
+        "✅ Realtime quote engine initialized (cache: {}s TTL, {} entries max)",
+        quote_cache_config.ttl.as_secs(),
+        quote_cache_config.max_entries
+    );
+    let realtime_quote_engine = Arc::new(
+        RealtimeQuoteEngine::new(adapter_registry.clone()).with_cache_config(quote_cache_config),
+    );
+
+    // Large orders are split across the chain's DEXes
+    let split_config = SplitRouteConfig::from_env()?;
+    info!(
+        "✅ Split routing: {} increments across up to {} DEXes",
+        split_config.increments, split_config.max_dexes
+    );
+    let split_router = Arc::new(SplitRouter::new(
+        adapter_registry.clone(),
+        realtime_quote_engine.clone(),
+        split_config,
+    ));
+
+    // Price sources - Pyth cross-checked against DEX-implied prices
+    let mut price_aggregator = PriceAggregator::new(PriceAggregatorConfig::from_env()?);
+    price_aggregator.register_source(pyth_oracle.clone());
+    price_aggregator.register_source(Arc::new(DexPriceSource::new(realtime_quote_engine.clone())));
+    if let Some(manual) = StaticPriceSource::from_env()? {
+        price_aggregator.register_source(Arc::new(manual));
+    }
+    info!("✅ Price sources: {:?}", price_aggregator.source_names());
+    let price_aggregator = Arc::new(price_aggregator);
+
+    // Initialize quote engine - executors simulate instructions on request
+    let quote_config = QuoteConfig::from_env()?;
+    let quote_engine = Arc::new(QuoteEngine::new(
+        quote_config,
+        ledger.clone(),
+        price_aggregator.clone(),
+        payment_addresses.clone(),
+        chain_pairs.clone(),
+        fee_estimators,
+        fee_schedules.clone(),
+        execution_router.executors(),
+        network.clone(),
+    ));
+
+    // Cross-chain routes: funding swap, treasury hop, execution swap
+    let route_planner = Arc::new(RoutePlanner::new(
+        realtime_quote_engine.clone(),
+        price_aggregator.clone(),
+        chain_pairs.clone(),
+        quote_engine.clone(),
+    ));
 
     // Initialize wallet repository
-    let wallet_repository = Arc::new(WalletRepository::new());
+    let wallet_repository = Arc::new(WalletRepository::new(pool.clone()));
     info!("✅ Wallet repository initialized");
 
     // Initialize trade repository
-    let trade_repository = Arc::new(TradeRepository::new());
+    let trade_repository = Arc::new(TradeRepository::new(pool.clone()));
     info!("✅ Trade repository initialized");
 
     // Initialize OHLC store (100 candles max per series)
@@ -161,6 +252,27 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
     let price_cache = Arc::new(PriceCache::new(1000));
     info!("✅ Price cache initialized (<1ms per quote)");
 
+    // Price updates for WebSocket subscribers
+    let price_feed_broadcaster = Arc::new(PriceFeedBroadcaster::new());
+
+    // Initialize settlement reconciler with a funding verifier per chain
+    let mut settlement_reconciler = SettlementReconciler::new(ledger.clone());
+    settlement_reconciler.register_verifier(
+        Chain::Stellar,
+        Arc::new(HorizonPaymentSource::new(stellar_horizon.clone())),
+    );
+    settlement_reconciler.register_verifier(Chain::Near, Arc::new(NearBlockSource::new(near_rpc.clone())));
+    settlement_reconciler.register_verifier(
+        Chain::Solana,
+        Arc::new(SolanaPaymentSource::new(solana_rpc.clone())),
+    );
+    let settlement_reconciler = Arc::new(settlement_reconciler);
+    info!("✅ Settlement reconciler initialized");
+
+    // Idempotency-Key records for mutating v1 endpoints
+    let idempotency = Arc::new(IdempotencyStore::from_env(ledger.clone())?);
+    info!("✅ Idempotency keys enabled (TTL: {}h)", idempotency.ttl().num_hours());
+
     // Build application state
     let state = AppState {
         ledger: ledger.clone(),
@@ -169,10 +281,18 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
         risk_controller: risk_controller.clone(),
         adapter_registry: adapter_registry.clone(),
         realtime_quote_engine: realtime_quote_engine.clone(),
+        route_planner: route_planner.clone(),
+        split_router: split_router.clone(),
         wallet_repository: wallet_repository.clone(),
         trade_repository: trade_repository.clone(),
         ohlc_store: ohlc_store.clone(),
         price_cache: price_cache.clone(),
+        price_feed_broadcaster: price_feed_broadcaster.clone(),
+        settlement_reconciler: settlement_reconciler.clone(),
+        chain_pairs: chain_pairs.clone(),
+        fee_schedules: fee_schedules.clone(),
+        price_feeds: price_feeds.clone(),
+        idempotency: idempotency.clone(),
         solana_executor: solana_executor.unwrap_or_else(|| {
             panic!("SOLANA_TREASURY_KEY must be set for token approval operations");
         }),
@@ -186,12 +306,29 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
 
     // Display supported chain pairs
     info!("📋 Supported chain pairs:");
-    for funding in Chain::all() {
-        for execution in Chain::all() {
-            if Chain::is_pair_supported(funding, execution) {
-                info!("   {:?} → {:?}", funding, execution);
-            }
-        }
+    for pair in chain_pairs.matrix().await.iter().filter(|p| p.enabled) {
+        info!("   {:?} → {:?}", pair.funding_chain, pair.execution_chain);
+    }
+
+    // Pick up pair changes made through other instances
+    chain_pairs.clone().start(Duration::from_secs(60));
+    fee_schedules.clone().start(Duration::from_secs(60));
+    #[cfg(unix)]
+    price_feeds.clone().listen_for_sighup();
+
+    // Keep the price caches warm from the Hermes stream
+    let pyth_stream_config = PythStreamConfig::from_env()?;
+    if pyth_stream_config.enabled {
+        Arc::new(PythStreamSubscriber::new(
+            pyth_stream_config,
+            pyth_oracle.clone(),
+            price_cache.clone(),
+            ohlc_store.clone(),
+            price_feed_broadcaster.clone(),
+        ))
+        .start();
+    } else {
+        info!("Pyth price streaming disabled, prices are fetched per quote");
     }
 
     // Start background task to clean expired quotes (every hour)
@@ -217,10 +354,63 @@ pub async fn initialize_app_state(database_url: &str) -> AppResult<AppState> {
                 }
                 Err(e) => error!("Failed to expire committed quotes: {:?}", e),
             }
+
+            match idempotency.purge_expired().await {
+                Ok(count) => {
+                    if count > 0 {
+                        info!("🗑️  Purged {} expired idempotency keys", count);
+                    }
+                }
+                Err(e) => error!("Failed to purge idempotency keys: {:?}", e),
+            }
         }
     });
     info!("✅ Quote expiration cleanup task started (hourly)");
 
+    // Start funding monitors - detect payments to quote addresses on each chain
+    let funding_processor = Arc::new(FundingProcessor::new(
+        ledger.clone(),
+        quote_engine.clone(),
+        execution_router.clone(),
+        payment_addresses.clone(),
+    ));
+
+    // Poll intervals in milliseconds; each monitor has its own default
+    let poll_interval = |name: &str| -> AppResult<Option<Duration>> {
+        match std::env::var(name) {
+            Ok(value) => value
+                .parse::<u64>()
+                .ok()
+                .filter(|ms| *ms > 0)
+                .map(|ms| Some(Duration::from_millis(ms)))
+                .ok_or_else(|| AppError::Config(format!("{} must be a positive integer", name))),
+            Err(_) => Ok(None),
+        }
+    };
+
+    let mut stellar_monitor = StellarMonitor::new(stellar_horizon, funding_processor.clone());
+    if let Some(interval) = poll_interval("STELLAR_FUNDING_POLL_MS")? {
+        stellar_monitor = stellar_monitor.with_poll_interval(interval);
+    }
+    tokio::spawn(async move { stellar_monitor.start().await });
+
+    let mut near_monitor = NearMonitor::new(near_rpc, funding_processor.clone());
+    if let Some(interval) = poll_interval("NEAR_FUNDING_POLL_MS")? {
+        near_monitor = near_monitor.with_poll_interval(interval);
+    }
+    tokio::spawn(async move { near_monitor.start().await });
+
+    let mut solana_monitor = SolanaMonitor::new(solana_rpc, funding_processor.clone());
+    if let Some(interval) = poll_interval("SOLANA_FUNDING_POLL_MS")? {
+        solana_monitor = solana_monitor.with_poll_interval(interval);
+    }
+    tokio::spawn(async move { solana_monitor.start().await });
+    info!("✅ Funding monitors started (Stellar, NEAR, Solana)");
+
+    // Start settlement reconciliation (every 5 minutes)
+    settlement_reconciler.clone().start(Duration::from_secs(300));
+    info!("✅ Settlement reconciliation task started (every 5 minutes)");
+
     Ok(state)
 }
 
@@ -67,6 +67,12 @@ pub enum AppError {
 
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    #[error("Idempotency key conflict: {0}")]
+    IdempotencyConflict(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
 }
 
 /// Quote-related errors
@@ -101,6 +107,9 @@ pub enum QuoteError {
 
     #[error("Price feed unavailable: {0}")]
     PriceUnavailable(String),
+
+    #[error("Execution simulation failed on {chain:?}: {message}")]
+    SimulationFailed { chain: Chain, message: String },
 }
 
 /// Execution-related errors
@@ -228,6 +237,18 @@ impl IntoResponse for AppError {
                     "execution_chain": execution,
                 })),
             ),
+            AppError::Quote(QuoteError::PriceUnavailable(reason)) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "PRICE_UNAVAILABLE",
+                format!("No usable price: {}", reason),
+                None,
+            ),
+            AppError::Quote(QuoteError::SimulationFailed { chain, message }) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "SIMULATION_FAILED",
+                format!("Execution instructions failed simulation on {:?}: {}", chain, message),
+                Some(serde_json::json!({"chain": chain})),
+            ),
             AppError::Execution(ExecutionError::ChainExecutionFailed { chain, message }) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "EXECUTION_FAILED",
@@ -274,6 +295,24 @@ impl IntoResponse for AppError {
                 format!("Service temporarily unavailable for {:?}: {}", chain, reason),
                 Some(serde_json::json!({"chain": chain})),
             ),
+            AppError::IdempotencyConflict(message) => (
+                StatusCode::CONFLICT,
+                "IDEMPOTENCY_KEY_CONFLICT",
+                message,
+                None,
+            ),
+            AppError::Conflict(message) => (
+                StatusCode::CONFLICT,
+                "CONFLICT",
+                message,
+                None,
+            ),
+            AppError::BadRequest(message) => (
+                StatusCode::BAD_REQUEST,
+                "BAD_REQUEST",
+                message,
+                None,
+            ),
             AppError::Database(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "DATABASE_ERROR",
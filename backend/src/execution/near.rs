@@ -4,7 +4,7 @@ use near_jsonrpc_client::{JsonRpcClient, methods};
 use near_primitives::{
     account::id::TryIntoAccountId,
     hash::CryptoHash,
-    types::{AccountId, BlockId, BlockReference, Finality}, views::{QueryRequest, TxExecutionStatus},
+    types::{AccountId, BlockId, BlockReference, Finality}, views::{AccountView, QueryRequest, TxExecutionStatus},
 };
 use near_crypto::{SecretKey, InMemorySigner, Signer};
 use near_token::NearToken;
@@ -14,14 +14,15 @@ use sqlx::types::BigDecimal;
 use std::sync::Arc;
 use std::str::FromStr;
 use tracing::{error, info};
-use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryError};
 
 
 
 use crate::{
     error::{AppResult, ExecutionError},
-    execution::router::Executor,
+    execution::router::{Executor, SimulationResult},
     ledger::{
+        amount::{self, Amount},
         models::*,
         repository::LedgerRepository,
     },
@@ -414,7 +415,7 @@ impl Executor for NearExecutor {
 
         // Risk control check
         self.risk
-            .check_execution_allowed(Chain::Near, quote.execution_cost)
+            .check_execution_allowed(Chain::Near, quote.execution_outlay())
             .await?;
 
         // Parse action
@@ -479,7 +480,7 @@ impl Executor for NearExecutor {
             .await?;
 
         self.risk
-            .record_spending(&mut tx, Chain::Near, quote.execution_cost)
+            .record_spending(&mut tx, Chain::Near, quote.execution_outlay())
             .await?;
 
         self.ledger
@@ -562,7 +563,7 @@ impl Executor for NearExecutor {
         };
         
         // Convert from yoctoNEAR (10^24) to NEAR
-        let balance_near = Decimal::from(balance_yocto) / Decimal::from(1_000_000_000_000_000_000_000_000u128);
+        let balance_near = Amount::<amount::Near>::from_base(balance_yocto).to_display()?;
 
         info!("Treasury balance: {} NEAR ({} yoctoNEAR)", balance_near, balance_yocto);
 
@@ -939,9 +940,76 @@ impl Executor for NearExecutor {
     
     Ok(tx_hash)
 }
+
+    async fn simulate(&self, instructions: &[u8]) -> AppResult<SimulationResult> {
+        // NEAR has no transaction dry run: check the action against the
+        // accounts it touches with view queries instead
+        let action = self.parse_action(instructions).await.map_err(|e| {
+            ExecutionError::SimulationFailed(format!("Invalid instructions: {}", e))
+        })?;
+
+        let receiver_id: AccountId = action.receiver_id.parse().map_err(|_| {
+            ExecutionError::SimulationFailed(format!("Invalid receiver ID: {}", action.receiver_id))
+        })?;
+
+        let receiver = self.view_account(receiver_id).await?.ok_or_else(|| {
+            ExecutionError::SimulationFailed(format!(
+                "Receiver account {} does not exist",
+                action.receiver_id
+            ))
+        })?;
+
+        if action.method_name.is_some() && receiver.code_hash == CryptoHash::default() {
+            return Err(ExecutionError::SimulationFailed(format!(
+                "Receiver {} has no contract deployed",
+                action.receiver_id
+            ))
+            .into());
+        }
+
+        let required = Amount::<amount::Near>::from_base(action.amount).to_display()?;
+        let balance = self.get_treasury_balance().await?;
+        if balance < required {
+            return Err(ExecutionError::SimulationFailed(format!(
+                "Treasury balance {} NEAR is below {} NEAR",
+                balance, required
+            ))
+            .into());
+        }
+
+        info!("NEAR simulation passed for {}", action.receiver_id);
+        Ok(SimulationResult::default())
+    }
 }
 
 impl NearExecutor {
+    /// View an account, or None if it does not exist
+    async fn view_account(&self, account_id: AccountId) -> AppResult<Option<AccountView>> {
+        let request = methods::query::RpcQueryRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::ViewAccount { account_id },
+        };
+
+        match self.client.call(request).await {
+            Ok(response) => match response.kind {
+                QueryResponseKind::ViewAccount(account_view) => Ok(Some(account_view)),
+                _ => Err(ExecutionError::ChainExecutionFailed {
+                    chain: Chain::Near,
+                    message: "Unexpected query response type".to_string(),
+                }
+                .into()),
+            },
+            Err(e) if matches!(e.handler_error(), Some(RpcQueryError::UnknownAccount { .. })) => {
+                Ok(None)
+            }
+            Err(e) => Err(ExecutionError::ChainExecutionFailed {
+                chain: Chain::Near,
+                message: format!("Failed to query account: {}", e),
+            }
+            .into()),
+        }
+    }
+
     /// Wait for transaction confirmation
     pub async fn wait_for_confirmation(
         &self,
@@ -1,10 +1,21 @@
 use crate::error::{AppResult, ExecutionError};
+use crate::ledger::amount::NativeAmount;
 use crate::ledger::models::*;
+use crate::quote_engine::chain_pairs::ChainPairRegistry;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{info, instrument};
 
+/// Outcome of a successful dry run of execution instructions
+#[derive(Debug, Clone, Default)]
+pub struct SimulationResult {
+    /// Compute units consumed (Solana)
+    pub compute_units: Option<u64>,
+    /// Network fee the chain reported for the transaction
+    pub fee: Option<NativeAmount>,
+}
+
 /// Executor trait - implemented by each chain's executor
 ///
 /// SECURITY: All executors must implement idempotent execution
@@ -31,6 +42,11 @@ pub trait Executor: Send + Sync {
     /// Returns transaction hash/ID
     async fn transfer_to_treasury(&self, token_or_asset: &str, amount: &str) -> AppResult<String>;
 
+    /// Dry-run execution instructions against current chain state
+    ///
+    /// Nothing is submitted. Instructions that would fail return
+    /// ExecutionError::SimulationFailed.
+    async fn simulate(&self, instructions: &[u8]) -> AppResult<SimulationResult>;
 }
 
 
@@ -40,13 +56,15 @@ pub trait Executor: Send + Sync {
 /// Any chain can be an execution target, and the router dynamically selects the correct executor.
 pub struct ExecutionRouter {
     executors: HashMap<Chain, Arc<dyn Executor>>,
+    chain_pairs: Arc<ChainPairRegistry>,
 }
 
 impl ExecutionRouter {
     /// Create a new execution router
-    pub fn new() -> Self {
+    pub fn new(chain_pairs: Arc<ChainPairRegistry>) -> Self {
         Self {
             executors: HashMap::new(),
+            chain_pairs,
         }
     }
 
@@ -68,8 +86,13 @@ impl ExecutionRouter {
             quote.id, quote.execution_chain
         );
 
-        // Validate quote has valid chain pair
-        if !quote.has_valid_chain_pair() {
+        // Validate quote has a valid, currently enabled chain pair
+        if !quote.has_valid_chain_pair()
+            || !self
+                .chain_pairs
+                .is_enabled(quote.funding_chain, quote.execution_chain)
+                .await
+        {
             return Err(ExecutionError::InvalidChainPair {
                 funding: quote.funding_chain,
                 execution: quote.execution_chain,
@@ -94,13 +117,18 @@ impl ExecutionRouter {
 
         // Check treasury balance before execution
         executor
-            .check_treasury_balance(quote.execution_cost)
+            .check_treasury_balance(quote.execution_outlay())
             .await?;
 
         // Execute on target chain
         executor.execute(quote).await
     }
 
+    /// Registered executors by chain
+    pub fn executors(&self) -> HashMap<Chain, Arc<dyn Executor>> {
+        self.executors.clone()
+    }
+
     /// Get all registered chains
     pub fn registered_chains(&self) -> Vec<Chain> {
         self.executors.keys().copied().collect()
@@ -126,12 +154,6 @@ impl ExecutionRouter {
     }
 }
 
-impl Default for ExecutionRouter {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -13,12 +13,13 @@ use sqlx::types::BigDecimal;
 use std::{
     str::FromStr, sync::Arc, time::Duration
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::{
     error::{AppResult, ExecutionError},
-    execution::router::Executor,
+    execution::router::{Executor, SimulationResult},
     ledger::{
+        amount::{Amount, NativeAmount, Sol},
         models::*,
         repository::LedgerRepository,
     },
@@ -180,7 +181,8 @@ impl SolanaExecutor {
         Ok(transaction)
     }
 
-    fn simulate_transaction(&self, transaction: &Transaction) -> AppResult<()> {
+    /// Simulate a transaction, returning the compute units it consumed
+    fn simulate_transaction(&self, transaction: &Transaction) -> AppResult<Option<u64>> {
         let result = self.client
             .simulate_transaction(transaction)
             .map_err(|e| {
@@ -195,7 +197,7 @@ impl SolanaExecutor {
             .into());
         }
 
-        Ok(())
+        Ok(result.value.units_consumed)
     }
 
     fn send_transaction(&self, transaction: &Transaction) -> AppResult<Signature> {
@@ -211,19 +213,18 @@ impl SolanaExecutor {
     }
 
 
+    /// Fee paid by the transaction, in SOL
     fn confirm_transaction(&self, signature: &Signature) -> AppResult<Decimal> {
         //Get transaction details
-        match self.client.get_transaction(signature, UiTransactionEncoding::Json) {
-            Ok(confirmed_tx) => {
-                if let Some(meta) = confirmed_tx.transaction.meta {
-                    let fee = meta.fee;
-                    Ok(Decimal::from(fee))
-                } else {
-                    Ok(Decimal::from(5000))  // Default fee
-                }
-            }
-            Err(_) => Ok(Decimal::from(5000))
-        }
+        let fee_lamports = match self.client.get_transaction(signature, UiTransactionEncoding::Json) {
+            Ok(confirmed_tx) => confirmed_tx
+                .transaction
+                .meta
+                .map_or(5000, |meta| meta.fee),  // Default fee
+            Err(_) => 5000,
+        };
+
+        Amount::<Sol>::from_base(fee_lamports as u128).to_display()
     }
 }
 
@@ -288,7 +289,7 @@ impl Executor for SolanaExecutor {
 
         //Risk control check
         self.risk
-        .check_execution_allowed(Chain::Solana, quote.execution_cost)
+        .check_execution_allowed(Chain::Solana, quote.execution_outlay())
         .await?;
 
         let instructions = self.deserialize_instructions(&quote.execution_instructions)?;
@@ -353,7 +354,7 @@ impl Executor for SolanaExecutor {
             ).await?;
 
         self.risk
-            .record_spending(&mut tx, Chain::Solana, quote.execution_cost)
+            .record_spending(&mut tx, Chain::Solana, quote.execution_outlay())
             .await?;
 
         self.ledger
@@ -409,7 +410,7 @@ impl Executor for SolanaExecutor {
             })?;
 
         //convert lamports to SOL
-        Ok(Decimal::from(balance) / Decimal::from(1_000_000_000))
+        Amount::<Sol>::from_base(balance as u128).to_display()
     }
 
     async fn transfer_to_treasury(&self, token_or_asset: &str, amount: &str) -> AppResult<String> {
@@ -638,6 +639,27 @@ impl Executor for SolanaExecutor {
     info!(" Settlement recorded: {}", tx_hash);
     Ok(tx_hash)
 }
+
+    async fn simulate(&self, instructions: &[u8]) -> AppResult<SimulationResult> {
+        let instructions = self
+            .deserialize_instructions(instructions)
+            .map_err(|e| ExecutionError::SimulationFailed(format!("Invalid instructions: {}", e)))?;
+
+        let transaction = self.build_transaction(instructions)?;
+        let compute_units = self.simulate_transaction(&transaction)?;
+
+        // Base and priority fee for the message as built
+        let fee = match self.client.get_fee_for_message(&transaction.message) {
+            Ok(lamports) => Some(NativeAmount::Solana(Amount::from_base(lamports as u128))),
+            Err(e) => {
+                warn!("Could not get fee for simulated Solana transaction: {}", e);
+                None
+            }
+        };
+
+        info!("Solana simulation passed ({:?} compute units)", compute_units);
+        Ok(SimulationResult { compute_units, fee })
+    }
 }
 
 impl SolanaExecutor {
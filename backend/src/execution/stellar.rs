@@ -16,7 +16,8 @@ use stellar_xdr::curr::{
 use tracing::error;
 
 use crate::{
-    error::{AppResult, ExecutionError}, execution::router::Executor, ledger::{
+    error::{AppResult, ExecutionError}, execution::router::{Executor, SimulationResult}, ledger::{
+        amount::{Amount, AssetUnit, TokenAmount, Xlm},
         models::*,
         repository::LedgerRepository,
     }, risk::controls::RiskController
@@ -42,6 +43,9 @@ struct StellarPaymentOp {
     pub destination: Keypair,
     pub amount: u64,
     pub asset: Asset,
+    pub asset_code: String,
+    /// None for native XLM
+    pub asset_issuer: Option<String>,
 }
 
 pub struct StellarExecutor {
@@ -134,6 +138,7 @@ impl StellarExecutor {
         cursor += code_len;
 
         // VALIDATION 7: Parse asset and validate issuer if not native
+        let mut asset_issuer = None;
         let asset = if asset_code == "XLM" {
             Asset::Native
         } else {
@@ -155,6 +160,7 @@ impl StellarExecutor {
                     chain: Chain::Stellar,
                     message: format!("Invalid issuer public key: {}", issuer_str),
                 })?;
+            asset_issuer = Some(issuer_str.to_string());
 
             // create Asset based on code length
             let issuer_key_bytes: [u8; 32] = issuer.public_key()
@@ -194,11 +200,40 @@ impl StellarExecutor {
         Ok(StellarPaymentOp { 
             destination, 
             amount, 
-            asset 
+            asset,
+            asset_code,
+            asset_issuer,
         })
 
     }
 
+    /// Account record from Horizon, None if the account does not exist
+    async fn fetch_account(&self, account_id: &str) -> AppResult<Option<serde_json::Value>> {
+        let url = format!("{}/accounts/{}", self.config.horizon_url, account_id);
+        let response = reqwest::get(&url).await
+            .map_err(|e| ExecutionError::ChainExecutionFailed {
+                chain: Chain::Stellar,
+                message: format!("Failed to fetch account {}: {:?}", account_id, e),
+            })?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(ExecutionError::ChainExecutionFailed {
+                chain: Chain::Stellar,
+                message: format!("Horizon returned {} for account {}", response.status(), account_id),
+            }.into());
+        }
+
+        let account = response.json().await
+            .map_err(|e| ExecutionError::ChainExecutionFailed {
+                chain: Chain::Stellar,
+                message: format!("Failed to parse account: {:?}", e),
+            })?;
+        Ok(Some(account))
+    }
+
     async fn wait_for_confirmation(&self, tx_hash: &str) -> AppResult<()> {
         let start = Instant::now();
         let timeout = Duration::from_secs(60);
@@ -450,7 +485,7 @@ impl StellarExecutor {
             })?;
 
         // Convert stroops to XLM (1 XLM = 10,000,000 stroops)
-        Ok(Decimal::from(fee_stroops) / Decimal::from(10_000_000u64))
+        Amount::<Xlm>::from_base(fee_stroops as u128).to_display()
     }
 
 }
@@ -500,7 +535,7 @@ impl Executor for StellarExecutor {
         
         // risk control check
         self.risk
-            .check_execution_allowed(Chain::Stellar, quote.execution_cost)
+            .check_execution_allowed(Chain::Stellar, quote.execution_outlay())
             .await?;
 
         //parse payment operation
@@ -567,7 +602,7 @@ impl Executor for StellarExecutor {
                 .await?;
         
         self.risk
-            .record_spending(&mut tx, Chain::Stellar, quote.execution_cost)
+            .record_spending(&mut tx, Chain::Stellar, quote.execution_outlay())
             .await?;
 
         self.ledger
@@ -638,7 +673,7 @@ impl Executor for StellarExecutor {
         // Convert from stroops (1 XLM = 10,000,000 stroops)
         let balance_stroops = Into::<u64>::into(xlm_balance.balances().iter().any(|b| b.balance() != ""));
 
-        Ok(Decimal::from(balance_stroops) / Decimal::from(10_000_000u64))
+        Amount::<Xlm>::from_base(balance_stroops as u128).to_display()
     }
 
     async fn transfer_to_treasury(&self, token_or_asset: &str, amount: &str) -> AppResult<String> {
@@ -1150,6 +1185,74 @@ impl Executor for StellarExecutor {
     
     Ok(tx_hash)
 }
+
+    async fn simulate(&self, instructions: &[u8]) -> AppResult<SimulationResult> {
+        let payment = self
+            .parse_payment_operation(instructions)
+            .await
+            .map_err(|e| ExecutionError::SimulationFailed(format!("Invalid instructions: {}", e)))?;
+        let issuer = payment.asset_issuer.as_deref();
+
+        // Classic payments cannot go through Soroban simulateTransaction;
+        // preflight the conditions the network would reject them for instead
+        let destination = self
+            .fetch_account(&payment.destination.public_key())
+            .await?
+            .ok_or_else(|| ExecutionError::SimulationFailed("Destination account does not exist".to_string()))?;
+        if asset_balance(&destination, &payment.asset_code, issuer).is_none() {
+            return Err(ExecutionError::SimulationFailed(format!(
+                "Destination has no trustline for {}",
+                payment.asset_code
+            ))
+            .into());
+        }
+
+        let treasury_kp = Keypair::from_secret_key(&self.treasury_secret)?;
+        let treasury = self
+            .fetch_account(&treasury_kp.public_key())
+            .await?
+            .ok_or_else(|| ExecutionError::ChainExecutionFailed {
+                chain: Chain::Stellar,
+                message: "Treasury account not found".to_string(),
+            })?;
+
+        // Every Stellar asset has 7 decimals, whatever the asset
+        let amount = TokenAmount::new(
+            Chain::Stellar,
+            &payment.asset_code,
+            Xlm::DECIMALS,
+            payment.amount as u128,
+        )
+        .to_display()?;
+        let available = asset_balance(&treasury, &payment.asset_code, issuer).unwrap_or(Decimal::ZERO);
+        if available < amount {
+            return Err(ExecutionError::SimulationFailed(format!(
+                "Treasury holds {} {}, payment needs {}",
+                available, payment.asset_code, amount
+            ))
+            .into());
+        }
+
+        info!("Stellar preflight passed for {} {}", amount, payment.asset_code);
+
+        // One payment operation: the fee estimator's per-operation fee applies
+        Ok(SimulationResult::default())
+    }
+}
+
+/// Balance of an asset in a Horizon account record, None without a trustline
+///
+/// `issuer` is None for native XLM
+fn asset_balance(account: &serde_json::Value, code: &str, issuer: Option<&str>) -> Option<Decimal> {
+    account["balances"]
+        .as_array()?
+        .iter()
+        .find(|balance| match issuer {
+            None => balance["asset_type"] == "native",
+            Some(issuer) => balance["asset_code"] == code && balance["asset_issuer"] == issuer,
+        })
+        .and_then(|balance| balance["balance"].as_str())
+        .and_then(|balance| Decimal::from_str(balance).ok())
 }
 
 impl StellarExecutor {
@@ -1205,3 +1308,26 @@ impl StellarExecutor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+    #[test]
+    fn test_asset_balance_needs_trustline() {
+        let account = serde_json::json!({
+            "balances": [
+                { "balance": "12.5000000", "asset_type": "credit_alphanum4", "asset_code": "USDC", "asset_issuer": ISSUER },
+                { "balance": "100.0000000", "asset_type": "native" }
+            ]
+        });
+
+        assert_eq!(asset_balance(&account, "XLM", None), Some(dec!(100)));
+        assert_eq!(asset_balance(&account, "USDC", Some(ISSUER)), Some(dec!(12.5)));
+        assert_eq!(asset_balance(&account, "EURC", Some(ISSUER)), None);
+        assert_eq!(asset_balance(&serde_json::json!({}), "XLM", None), None);
+    }
+}
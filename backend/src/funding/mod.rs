@@ -1,51 +1,267 @@
 // Funding adapters for detecting payments on each chain
 // These monitor blockchain events and trigger quote commits
 
+pub mod near;
+pub mod solana;
+pub mod stellar;
+
+pub use near::{NearBlockSource, NearMonitor};
+pub use solana::{SolanaMonitor, SolanaPaymentSource};
+pub use stellar::{HorizonPaymentSource, StellarMonitor};
+
+use crate::api::handler::execute_with_retries;
+use crate::error::AppResult;
+use crate::execution::router::ExecutionRouter;
+use crate::ledger::models::{Chain, ExecutionStatus, FundingPayment, FundingPaymentStatus};
 use crate::ledger::repository::LedgerRepository;
+use crate::quote_engine::engine::QuoteEngine;
+use crate::quote_engine::payment_address::{split_memo, PaymentAddresses};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use sqlx::types::BigDecimal;
+use std::str::FromStr;
 use std::sync::Arc;
+use tracing::{error, info, warn};
 
-pub struct StellarMonitor {
-    horizon_url: String,
-    ledger: Arc<LedgerRepository>,
+/// A transfer detected on a funding chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncomingPayment {
+    pub chain: Chain,
+    pub transaction_hash: String,
+    /// Transfer destination, with `?memo=<memo>` appended when the
+    /// transfer carried a memo (same format as `Quote::payment_address`)
+    pub payment_address: String,
+    /// Paying account, if the chain exposes one
+    pub sender: Option<String>,
+    /// Asset symbol (XLM, NEAR, SOL, USDC, ...)
+    pub asset: String,
+    /// Amount in display units
+    pub amount: Decimal,
 }
 
-impl StellarMonitor {
-    pub fn new(horizon_url: String, ledger: Arc<LedgerRepository>) -> Self {
-        Self { horizon_url, ledger }
-    }
+/// Re-reads a recorded funding payment from its source chain
+///
+/// Used by settlement reconciliation; implemented by each chain's payment source.
+#[async_trait]
+pub trait FundingVerifier: Send + Sync {
+    /// The payment as seen on chain, or None if the transaction does not
+    /// exist, failed, or did not pay `payment.payment_address`
+    async fn verify(&self, payment: &FundingPayment) -> AppResult<Option<IncomingPayment>>;
+}
 
-    pub async fn start(&self) {
-        // Monitor Stellar payments via Horizon streaming
-        // When payment detected with quote memo, trigger commit
-    }
+/// Outcome of processing a detected payment
+#[derive(Debug, Clone, PartialEq)]
+pub enum FundingOutcome {
+    /// Quote committed and execution dispatched
+    Committed,
+    /// Paid less than `Quote::total_funding_required`
+    Underpaid,
+    /// Quote could not be committed
+    Rejected(String),
+    /// No pending quote for this address
+    Unmatched,
+    /// Transaction was already processed
+    Duplicate,
 }
 
-pub struct NearMonitor {
-    rpc_url: String,
+/// Matches detected payments to pending quotes and dispatches execution
+///
+/// Shared by all chain monitors so every chain applies the same rules
+/// as the payment webhook.
+pub struct FundingProcessor {
     ledger: Arc<LedgerRepository>,
+    quote_engine: Arc<QuoteEngine>,
+    execution_router: Arc<ExecutionRouter>,
+    payment_addresses: Arc<PaymentAddresses>,
 }
 
-impl NearMonitor {
-    pub fn new(rpc_url: String, ledger: Arc<LedgerRepository>) -> Self {
-        Self { rpc_url, ledger }
+impl FundingProcessor {
+    pub fn new(
+        ledger: Arc<LedgerRepository>,
+        quote_engine: Arc<QuoteEngine>,
+        execution_router: Arc<ExecutionRouter>,
+        payment_addresses: Arc<PaymentAddresses>,
+    ) -> Self {
+        Self {
+            ledger,
+            quote_engine,
+            execution_router,
+            payment_addresses,
+        }
     }
 
-    pub async fn start(&self) {
-        // Monitor Near transactions via RPC
+    pub fn ledger(&self) -> &Arc<LedgerRepository> {
+        &self.ledger
     }
-}
 
-pub struct SolanaMonitor {
-    rpc_url: String,
-    ledger: Arc<LedgerRepository>,
-}
+    /// Match a payment to its quote, commit it and dispatch execution
+    ///
+    /// SECURITY: The payment is recorded before the quote is committed.
+    /// A transaction hash seen twice (monitor restart, overlapping polls)
+    /// is ignored.
+    pub async fn process(&self, payment: IncomingPayment) -> AppResult<FundingOutcome> {
+        let (destination, memo) = split_memo(&payment.payment_address);
+        let quote = match self
+            .payment_addresses
+            .find_quote(&self.ledger, payment.chain, destination, memo)
+            .await?
+        {
+            Some(quote) => quote,
+            None => return Ok(FundingOutcome::Unmatched),
+        };
+
+        if !quote.funding_asset.eq_ignore_ascii_case(&payment.asset) {
+            let reason = format!(
+                "Asset mismatch: expected {}, got {}",
+                quote.funding_asset, payment.asset
+            );
+            return self
+                .record(&payment, Some(quote.id), FundingPaymentStatus::Rejected, Some(reason))
+                .await;
+        }
+
+        let required = quote.total_funding_required();
+        if payment.amount < required {
+            warn!(
+                "⚠️  Underpayment for quote {} on {:?}: {} < {}",
+                quote.id, payment.chain, payment.amount, required
+            );
+            let reason = format!("Insufficient payment: {} < {}", payment.amount, required);
+            return self
+                .record(&payment, Some(quote.id), FundingPaymentStatus::Underpaid, Some(reason))
+                .await;
+        }
 
-impl SolanaMonitor {
-    pub fn new(rpc_url: String, ledger: Arc<LedgerRepository>) -> Self {
-        Self { rpc_url, ledger }
+        let claimed = self
+            .ledger
+            .record_funding_payment(
+                payment.chain,
+                &payment.transaction_hash,
+                Some(quote.id),
+                &payment.payment_address,
+                payment.sender.as_deref(),
+                &payment.asset,
+                payment.amount,
+                FundingPaymentStatus::Matched,
+                None,
+            )
+            .await?;
+        if !claimed {
+            return Ok(FundingOutcome::Duplicate);
+        }
+
+        if let Err(e) = self.quote_engine.commit_quote(quote.id).await {
+            let reason = e.to_string();
+            self.ledger
+                .update_funding_payment_status(
+                    payment.chain,
+                    &payment.transaction_hash,
+                    FundingPaymentStatus::Rejected,
+                    Some(&reason),
+                )
+                .await?;
+            return Ok(FundingOutcome::Rejected(reason));
+        }
+
+        info!(
+            "💰 Payment {} on {:?} funded quote {} ({} {})",
+            payment.transaction_hash, payment.chain, quote.id, payment.amount, payment.asset
+        );
+
+        let router = self.execution_router.clone();
+        let ledger = self.ledger.clone();
+        tokio::spawn(async move {
+            let quote_id = quote.id;
+            execute_with_retries(router, ledger.clone(), &quote, quote_id, 3).await;
+
+            // Unverified until the reconciler re-reads the payment on chain
+            match ledger.get_execution_by_quote_id(&quote_id).await {
+                Ok(execution) if execution.status == ExecutionStatus::Success => {
+                    if let Err(e) = ledger
+                        .create_settlement(
+                            execution.id,
+                            payment.chain,
+                            payment.transaction_hash.clone(),
+                            BigDecimal::from_str(&payment.amount.to_string()).unwrap(),
+                        )
+                        .await
+                    {
+                        error!("Failed to record settlement for quote {}: {:?}", quote_id, e);
+                    }
+                }
+                Ok(_) => warn!("Execution for quote {} did not succeed, no settlement recorded", quote_id),
+                Err(e) => error!("Execution lookup failed for quote {}: {:?}", quote_id, e),
+            }
+        });
+
+        Ok(FundingOutcome::Committed)
     }
 
-    pub async fn start(&self) {
-        // Monitor Solana transactions via RPC
+    /// Process a payment for a monitor poll
+    ///
+    /// A payment that fails to process is logged and recorded as rejected
+    /// instead of failing the poll: a monitor that stopped at it would read it
+    /// again on every poll and never move its checkpoint past it.
+    pub async fn process_or_record(&self, payment: IncomingPayment) -> FundingOutcome {
+        let error = match self.process(payment.clone()).await {
+            Ok(outcome) => return outcome,
+            Err(e) => e,
+        };
+
+        error!(
+            "Failed to process payment {} on {:?}: {:?}",
+            payment.transaction_hash, payment.chain, error
+        );
+        let reason = format!("Processing failed: {}", error);
+        if let Err(e) = self
+            .ledger
+            .record_funding_payment(
+                payment.chain,
+                &payment.transaction_hash,
+                None,
+                &payment.payment_address,
+                payment.sender.as_deref(),
+                &payment.asset,
+                payment.amount,
+                FundingPaymentStatus::Rejected,
+                Some(&reason),
+            )
+            .await
+        {
+            error!(
+                "Failed to record payment {} on {:?}: {:?}",
+                payment.transaction_hash, payment.chain, e
+            );
+        }
+        FundingOutcome::Rejected(reason)
     }
-}
\ No newline at end of file
+
+    async fn record(
+        &self,
+        payment: &IncomingPayment,
+        quote_id: Option<uuid::Uuid>,
+        status: FundingPaymentStatus,
+        reason: Option<String>,
+    ) -> AppResult<FundingOutcome> {
+        let inserted = self
+            .ledger
+            .record_funding_payment(
+                payment.chain,
+                &payment.transaction_hash,
+                quote_id,
+                &payment.payment_address,
+                payment.sender.as_deref(),
+                &payment.asset,
+                payment.amount,
+                status,
+                reason.as_deref(),
+            )
+            .await?;
+
+        Ok(match (inserted, status) {
+            (false, _) => FundingOutcome::Duplicate,
+            (true, FundingPaymentStatus::Underpaid) => FundingOutcome::Underpaid,
+            (true, _) => FundingOutcome::Rejected(reason.unwrap_or_default()),
+        })
+    }
+}
@@ -0,0 +1,438 @@
+use super::{FundingProcessor, FundingVerifier, IncomingPayment};
+use crate::adapters::DexWhitelist;
+use crate::error::{AppError, AppResult};
+use crate::ledger::amount::{self, Amount, TokenAmount};
+use crate::ledger::models::{Chain, FundingPayment};
+use crate::quote_engine::payment_address::MEMO_SEPARATOR;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Checkpoint scope - NEAR is scanned block by block for all addresses
+const SCOPE: &str = "blocks";
+
+/// Maximum blocks scanned per poll so a long outage catches up gradually
+const MAX_BLOCKS_PER_POLL: u64 = 50;
+
+/// Reads final blocks and transfers from a NEAR RPC node
+pub struct NearBlockSource {
+    client: reqwest::Client,
+    rpc_url: String,
+    whitelist: DexWhitelist,
+}
+
+impl NearBlockSource {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rpc_url,
+            whitelist: DexWhitelist::new(),
+        }
+    }
+
+    /// Height of the latest final block
+    pub async fn latest_final_height(&self) -> AppResult<u64> {
+        let result = self.call("block", json!({ "finality": "final" })).await?;
+        result
+            .pointer("/header/height")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| AppError::ExternalError("NEAR block has no height".to_string()))
+    }
+
+    /// Successful transfers in block `height` to one of `addresses`
+    ///
+    /// Handles native `Transfer` actions and NEP-141 `ft_transfer` calls.
+    /// Skipped heights (no block produced) yield no payments.
+    pub async fn block_payments(
+        &self,
+        height: u64,
+        addresses: &HashSet<String>,
+    ) -> AppResult<Vec<IncomingPayment>> {
+        let block = match self.call("block", json!({ "block_id": height })).await {
+            Ok(block) => block,
+            Err(AppError::ExternalError(msg)) if msg.contains("UNKNOWN_BLOCK") => {
+                return Ok(vec![])
+            }
+            Err(e) => return Err(e),
+        };
+
+        let chunk_hashes: Vec<String> = block["chunks"]
+            .as_array()
+            .map(|chunks| {
+                chunks
+                    .iter()
+                    .filter_map(|c| c["chunk_hash"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut payments = vec![];
+        for chunk_hash in chunk_hashes {
+            let chunk = self.call("chunk", json!({ "chunk_id": chunk_hash })).await?;
+            let transactions = chunk["transactions"].as_array().cloned().unwrap_or_default();
+
+            for tx in transactions {
+                for payment in self.transaction_payments(&tx, addresses) {
+                    let signer = tx["signer_id"].as_str().unwrap_or_default();
+                    if self.is_successful(&payment.transaction_hash, signer).await? {
+                        payments.push(payment);
+                    }
+                }
+            }
+        }
+
+        Ok(payments)
+    }
+
+    /// Transfers in a chunk transaction addressed to one of `addresses`
+    ///
+    /// Recognised actions:
+    /// - `Transfer` - plain NEAR transfer (no memo)
+    /// - `deposit` call on the receiver with attached NEAR and `{"memo"}` args
+    /// - NEP-141 `ft_transfer` with `{"receiver_id", "amount", "memo"}` args
+    fn transaction_payments(&self, tx: &Value, addresses: &HashSet<String>) -> Vec<IncomingPayment> {
+        let (Some(hash), Some(receiver)) = (tx["hash"].as_str(), tx["receiver_id"].as_str()) else {
+            return vec![];
+        };
+        let sender = tx["signer_id"].as_str().map(str::to_string);
+        let actions = tx["actions"].as_array().cloned().unwrap_or_default();
+
+        let payment = |destination: &str, memo: Option<&str>, asset: String, amount: Decimal| {
+            let payment_address = match memo {
+                Some(memo) => format!("{}{}{}", destination, MEMO_SEPARATOR, memo),
+                None => destination.to_string(),
+            };
+            addresses.contains(&payment_address).then(|| IncomingPayment {
+                chain: Chain::Near,
+                transaction_hash: hash.to_string(),
+                payment_address,
+                sender: sender.clone(),
+                asset,
+                amount,
+            })
+        };
+
+        actions
+            .iter()
+            .filter_map(|action| {
+                if let Some(deposit) = action.pointer("/Transfer/deposit").and_then(Value::as_str) {
+                    let amount = near_deposit(deposit)?;
+                    return payment(receiver, None, "NEAR".to_string(), amount);
+                }
+
+                let call = action.get("FunctionCall")?;
+                let args = BASE64.decode(call["args"].as_str()?).ok()?;
+                let args: Value = serde_json::from_slice(&args).ok()?;
+                let memo = args["memo"].as_str();
+
+                match call["method_name"].as_str()? {
+                    "deposit" => {
+                        let amount = near_deposit(call["deposit"].as_str()?)?;
+                        payment(receiver, memo, "NEAR".to_string(), amount)
+                    }
+                    "ft_transfer" => {
+                        // Token contract is the transaction receiver
+                        let token = self.whitelist.get_by_id(Chain::Near, receiver).ok()?;
+                        let amount = TokenAmount::parse_base(&token, args["amount"].as_str()?)
+                            .and_then(|amount| amount.to_display())
+                            .ok()?;
+                        payment(args["receiver_id"].as_str()?, memo, token.symbol, amount)
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a transaction's final outcome is a success
+    async fn is_successful(&self, tx_hash: &str, signer: &str) -> AppResult<bool> {
+        let result = self.tx_status(tx_hash, signer).await?;
+        Ok(result.pointer("/status/SuccessValue").is_some())
+    }
+
+    async fn tx_status(&self, tx_hash: &str, signer: &str) -> AppResult<Value> {
+        self.call(
+            "tx",
+            json!({ "tx_hash": tx_hash, "sender_account_id": signer, "wait_until": "FINAL" }),
+        )
+        .await
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": "omnixec",
+            "method": method,
+            "params": params,
+        });
+
+        let response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("NEAR RPC request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Invalid NEAR RPC response: {}", e)))?;
+
+        if let Some(err) = response.get("error") {
+            let name = err
+                .pointer("/cause/name")
+                .and_then(Value::as_str)
+                .unwrap_or("UNKNOWN");
+            return Err(AppError::ExternalError(format!("NEAR RPC {} error: {}", name, err)));
+        }
+
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| AppError::ExternalError("NEAR RPC response has no result".to_string()))
+    }
+}
+
+#[async_trait]
+impl FundingVerifier for NearBlockSource {
+    async fn verify(&self, payment: &FundingPayment) -> AppResult<Option<IncomingPayment>> {
+        // Transaction lookups are routed to the sender's shard
+        let Some(sender) = payment.sender.as_deref() else {
+            return Ok(None);
+        };
+
+        let result = match self.tx_status(&payment.transaction_hash, sender).await {
+            Ok(result) => result,
+            Err(AppError::ExternalError(msg)) if msg.contains("UNKNOWN_TRANSACTION") => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+
+        if result.pointer("/status/SuccessValue").is_none() {
+            return Ok(None);
+        }
+
+        let addresses: HashSet<String> = [payment.payment_address.clone()].into_iter().collect();
+        Ok(self
+            .transaction_payments(&result["transaction"], &addresses)
+            .into_iter()
+            .next())
+    }
+}
+
+/// Convert an attached deposit in yoctoNEAR to NEAR
+fn near_deposit(yocto: &str) -> Option<Decimal> {
+    let yocto: u128 = yocto.parse().ok()?;
+    Amount::<amount::Near>::from_base(yocto).to_display().ok()
+}
+
+/// NEAR funding monitor
+///
+/// Scans final blocks for transfers to the escrow account of pending quotes.
+/// The last scanned block height is checkpointed.
+pub struct NearMonitor {
+    source: NearBlockSource,
+    processor: Arc<FundingProcessor>,
+    poll_interval: Duration,
+}
+
+impl NearMonitor {
+    pub fn new(rpc_url: String, processor: Arc<FundingProcessor>) -> Self {
+        Self {
+            source: NearBlockSource::new(rpc_url),
+            processor,
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub async fn start(&self) {
+        info!("👀 NEAR funding monitor started");
+        loop {
+            if let Err(e) = self.poll().await {
+                error!("NEAR funding monitor poll failed: {:?}", e);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Scan blocks after the checkpoint, up to MAX_BLOCKS_PER_POLL
+    pub async fn poll(&self) -> AppResult<()> {
+        let ledger = self.processor.ledger();
+        let latest = self.source.latest_final_height().await?;
+
+        let last_scanned = match ledger.get_funding_checkpoint(Chain::Near, SCOPE).await? {
+            Some(cursor) => cursor
+                .parse::<u64>()
+                .map_err(|_| AppError::Internal(format!("Invalid NEAR checkpoint: {}", cursor)))?,
+            // First run: start from the current final block
+            None => latest.saturating_sub(1),
+        };
+
+        if last_scanned >= latest {
+            return Ok(());
+        }
+
+        let addresses: HashSet<String> = ledger
+            .get_pending_payment_addresses(Chain::Near)
+            .await?
+            .into_iter()
+            .map(|pending| pending.payment_address)
+            .collect();
+
+        let end = latest.min(last_scanned + MAX_BLOCKS_PER_POLL);
+        for height in (last_scanned + 1)..=end {
+            if !addresses.is_empty() {
+                for payment in self.source.block_payments(height, &addresses).await? {
+                    let outcome = self.processor.process_or_record(payment.clone()).await;
+                    info!("NEAR payment {}: {:?}", payment.transaction_hash, outcome);
+                }
+            }
+            ledger
+                .save_funding_checkpoint(Chain::Near, SCOPE, &height.to_string())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use std::str::FromStr;
+
+    async fn fake_rpc() -> String {
+        let app = Router::new().route(
+            "/",
+            post(|Json(req): Json<Value>| async move {
+                let result = match req["method"].as_str().unwrap() {
+                    "block" if req["params"]["finality"] == "final" => {
+                        json!({ "header": { "height": 120 } })
+                    }
+                    "block" if req["params"]["block_id"] == 101 => {
+                        json!({ "chunks": [{ "chunk_hash": "chunk1" }] })
+                    }
+                    "block" => {
+                        return Json(json!({
+                            "jsonrpc": "2.0", "id": "omnixec",
+                            "error": { "cause": { "name": "UNKNOWN_BLOCK" } }
+                        }))
+                    }
+                    "chunk" => json!({ "transactions": [
+                        { "hash": "tx1", "signer_id": "alice.testnet",
+                          "receiver_id": "escrow.testnet",
+                          "actions": [{ "FunctionCall": {
+                              "method_name": "deposit",
+                              "args": BASE64.encode(r#"{"memo":"quote_abc123def4567890"}"#),
+                              "gas": 30000000000000u64, "deposit": "2500000000000000000000000" } }] },
+                        { "hash": "tx2", "signer_id": "bob.testnet",
+                          "receiver_id": "usdc.testnet",
+                          "actions": [{ "FunctionCall": {
+                              "method_name": "ft_transfer",
+                              "args": BASE64.encode(r#"{"receiver_id":"escrow.testnet","amount":"15000000","memo":"quote_abc123def4567890"}"#),
+                              "gas": 30000000000000u64, "deposit": "1" } }] },
+                        { "hash": "tx3", "signer_id": "carol.testnet",
+                          "receiver_id": "someone.testnet",
+                          "actions": [{ "Transfer": { "deposit": "1" } }] },
+                        { "hash": "tx4", "signer_id": "dave.testnet",
+                          "receiver_id": "escrow.testnet",
+                          "actions": [{ "FunctionCall": {
+                              "method_name": "deposit",
+                              "args": BASE64.encode(r#"{"memo":"quote_abc123def4567890"}"#),
+                              "gas": 30000000000000u64, "deposit": "1000000000000000000000000" } }] },
+                        { "hash": "tx5", "signer_id": "frank.testnet",
+                          "receiver_id": "escrow.testnet",
+                          "actions": [{ "Transfer": { "deposit": "1000000000000000000000000" } }] }
+                    ]}),
+                    "tx" if req["params"]["tx_hash"] == "tx4" => {
+                        json!({ "status": { "Failure": {} } })
+                    }
+                    "tx" if req["params"]["tx_hash"] == "tx9" => json!({
+                        "status": { "SuccessValue": "" },
+                        "transaction": { "hash": "tx9", "signer_id": "erin.testnet",
+                          "receiver_id": "escrow.testnet",
+                          "actions": [{ "FunctionCall": {
+                              "method_name": "deposit",
+                              "args": BASE64.encode(r#"{"memo":"quote_abc123def4567890"}"#),
+                              "gas": 30000000000000u64, "deposit": "3000000000000000000000000" } }] }
+                    }),
+                    "tx" => json!({ "status": { "SuccessValue": "" } }),
+                    other => panic!("unexpected method {}", other),
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": "omnixec", "result": result }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_latest_final_height() {
+        let source = NearBlockSource::new(fake_rpc().await);
+        assert_eq!(source.latest_final_height().await.unwrap(), 120);
+    }
+
+    #[tokio::test]
+    async fn test_block_payments_native_and_ft() {
+        let source = NearBlockSource::new(fake_rpc().await);
+        let addresses: HashSet<String> =
+            ["escrow.testnet?memo=quote_abc123def4567890".to_string()].into_iter().collect();
+
+        let payments = source.block_payments(101, &addresses).await.unwrap();
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].transaction_hash, "tx1");
+        assert_eq!(payments[0].asset, "NEAR");
+        assert_eq!(payments[0].amount, Decimal::from_str("2.5").unwrap());
+        assert_eq!(payments[1].transaction_hash, "tx2");
+        assert_eq!(payments[1].asset, "USDC");
+        assert_eq!(payments[1].amount, Decimal::from(15));
+        assert_eq!(payments[1].payment_address, "escrow.testnet?memo=quote_abc123def4567890");
+    }
+
+    #[tokio::test]
+    async fn test_verify_recorded_payment() {
+        let source = NearBlockSource::new(fake_rpc().await);
+        let recorded = FundingPayment {
+            id: uuid::Uuid::new_v4(),
+            chain: Chain::Near,
+            transaction_hash: "tx9".to_string(),
+            quote_id: None,
+            payment_address: "escrow.testnet?memo=quote_abc123def4567890".to_string(),
+            sender: Some("erin.testnet".to_string()),
+            asset: "NEAR".to_string(),
+            amount: Decimal::from(3),
+            status: "matched".to_string(),
+            reason: None,
+            detected_at: chrono::Utc::now(),
+        };
+
+        let verified = source.verify(&recorded).await.unwrap().unwrap();
+        assert_eq!(verified.amount, Decimal::from(3));
+        assert_eq!(verified.sender.as_deref(), Some("erin.testnet"));
+
+        let unknown_sender = FundingPayment { sender: None, ..recorded };
+        assert!(source.verify(&unknown_sender).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_skipped_block_has_no_payments() {
+        let source = NearBlockSource::new(fake_rpc().await);
+        let addresses: HashSet<String> = ["escrow.testnet?memo=quote_x".to_string()].into_iter().collect();
+        assert!(source.block_payments(102, &addresses).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,499 @@
+use super::{FundingProcessor, FundingVerifier, IncomingPayment};
+use crate::adapters::DexWhitelist;
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::{Chain, FundingPayment, PendingPaymentAddress};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Signatures per getSignaturesForAddress page
+const SIGNATURE_LIMIT: usize = 100;
+
+/// lamports per SOL (10^9)
+const SOL_DECIMALS: u32 = 9;
+
+/// A confirmed signature touching a payment address
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddressSignature {
+    pub signature: String,
+    pub slot: u64,
+    /// Unix timestamp of the block, if the node has it
+    pub block_time: Option<i64>,
+}
+
+/// A page of signatures read from getSignaturesForAddress
+#[derive(Debug, Default, PartialEq)]
+pub struct SignaturePage {
+    /// Successful signatures, newest first (failed ones are skipped)
+    pub signatures: Vec<AddressSignature>,
+    /// Newest signature in the page, including failed ones
+    pub newest: Option<String>,
+    /// Oldest signature in the page, including failed ones
+    pub oldest: Option<String>,
+    /// Number of signatures the node returned, including failed ones
+    pub record_count: usize,
+}
+
+/// Reads transfers to payment addresses from a Solana RPC node
+pub struct SolanaPaymentSource {
+    client: reqwest::Client,
+    rpc_url: String,
+    whitelist: DexWhitelist,
+}
+
+impl SolanaPaymentSource {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rpc_url,
+            whitelist: DexWhitelist::new(),
+        }
+    }
+
+    /// Confirmed signatures for `address`, newest first
+    ///
+    /// Pages back from `before` (or the newest signature) and stops at
+    /// `until`, which is not included.
+    pub async fn signatures_for_address(
+        &self,
+        address: &str,
+        until: Option<&str>,
+        before: Option<&str>,
+    ) -> AppResult<SignaturePage> {
+        let mut config = json!({ "commitment": "confirmed", "limit": SIGNATURE_LIMIT });
+        if let Some(until) = until {
+            config["until"] = json!(until);
+        }
+        if let Some(before) = before {
+            config["before"] = json!(before);
+        }
+
+        let result = self.call("getSignaturesForAddress", json!([address, config])).await?;
+        let records = result.as_array().cloned().unwrap_or_default();
+        let signature_of = |s: &Value| s["signature"].as_str().map(str::to_string);
+
+        Ok(SignaturePage {
+            newest: records.first().and_then(signature_of),
+            oldest: records.last().and_then(signature_of),
+            record_count: records.len(),
+            signatures: records
+                .iter()
+                .filter(|s| s["err"].is_null())
+                .filter_map(|s| {
+                    Some(AddressSignature {
+                        signature: signature_of(s)?,
+                        slot: s["slot"].as_u64()?,
+                        block_time: s["blockTime"].as_i64(),
+                    })
+                })
+                .collect(),
+        })
+    }
+
+    /// Amount `address` received in transaction `signature`
+    ///
+    /// SPL token transfers are detected through token balance changes of
+    /// accounts owned by `address`; otherwise the lamport balance change is used.
+    pub async fn transaction_payment(
+        &self,
+        signature: &str,
+        address: &str,
+    ) -> AppResult<Option<IncomingPayment>> {
+        let tx = self
+            .call(
+                "getTransaction",
+                json!([signature, {
+                    "encoding": "json",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0
+                }]),
+            )
+            .await?;
+
+        if tx.is_null() || !tx.pointer("/meta/err").is_none_or(Value::is_null) {
+            return Ok(None);
+        }
+
+        let keys = tx
+            .pointer("/transaction/message/accountKeys")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        // Fee payer is the first account key
+        let sender = keys.first().and_then(Value::as_str).map(str::to_string);
+        let payment = |asset: String, amount: Decimal| IncomingPayment {
+            chain: Chain::Solana,
+            transaction_hash: signature.to_string(),
+            payment_address: address.to_string(),
+            sender: sender.clone(),
+            asset,
+            amount,
+        };
+
+        if let Some((mint, amount)) = token_balance_change(&tx["meta"], address) {
+            let asset = self
+                .whitelist
+                .get_by_id(Chain::Solana, &mint)
+                .map(|token| token.symbol)
+                .unwrap_or(mint);
+            return Ok(Some(payment(asset, amount)));
+        }
+
+        let Some(index) = keys.iter().position(|k| k.as_str() == Some(address)) else {
+            return Ok(None);
+        };
+
+        let pre = tx["meta"]["preBalances"][index].as_i64().unwrap_or(0);
+        let post = tx["meta"]["postBalances"][index].as_i64().unwrap_or(0);
+        if post <= pre {
+            return Ok(None);
+        }
+
+        Ok(Some(payment(
+            "SOL".to_string(),
+            Decimal::new(post - pre, SOL_DECIMALS).normalize(),
+        )))
+    }
+
+    /// Associated token accounts of `owner` for every whitelisted SPL mint
+    ///
+    /// SPL transfers only touch the token account, never its owner, so token
+    /// payments only show up in the signatures of these accounts.
+    pub fn token_accounts(&self, owner: &str) -> Vec<String> {
+        let Ok(owner) = Pubkey::from_str(owner) else {
+            return vec![];
+        };
+
+        self.whitelist
+            .get_tokens_for_chain(Chain::Solana)
+            .iter()
+            .filter_map(|token| Pubkey::from_str(&token.token_id).ok())
+            .map(|mint| get_associated_token_address(&owner, &mint).to_string())
+            .collect()
+    }
+
+    async fn call(&self, method: &str, params: Value) -> AppResult<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Solana RPC request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Invalid Solana RPC response: {}", e)))?;
+
+        if let Some(err) = response.get("error") {
+            return Err(AppError::ExternalError(format!("Solana RPC error: {}", err)));
+        }
+
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+#[async_trait]
+impl FundingVerifier for SolanaPaymentSource {
+    async fn verify(&self, payment: &FundingPayment) -> AppResult<Option<IncomingPayment>> {
+        self.transaction_payment(&payment.transaction_hash, &payment.payment_address)
+            .await
+    }
+}
+
+/// Positive token balance change of accounts owned by `owner`, as (mint, amount)
+fn token_balance_change(meta: &Value, owner: &str) -> Option<(String, Decimal)> {
+    let empty = vec![];
+    let pre = meta["preTokenBalances"].as_array().unwrap_or(&empty);
+    let post = meta["postTokenBalances"].as_array().unwrap_or(&empty);
+
+    post.iter()
+        .filter(|b| b["owner"].as_str() == Some(owner))
+        .find_map(|after| {
+            let index = after["accountIndex"].as_u64()?;
+            let decimals = after.pointer("/uiTokenAmount/decimals")?.as_u64()? as u32;
+            let post_amount: i128 = after.pointer("/uiTokenAmount/amount")?.as_str()?.parse().ok()?;
+            let pre_amount: i128 = pre
+                .iter()
+                .find(|b| b["accountIndex"].as_u64() == Some(index))
+                .and_then(|b| b.pointer("/uiTokenAmount/amount")?.as_str()?.parse().ok())
+                .unwrap_or(0);
+
+            let delta = post_amount - pre_amount;
+            if delta <= 0 {
+                return None;
+            }
+            let amount = Decimal::try_from_i128_with_scale(delta, decimals).ok()?.normalize();
+            Some((after["mint"].as_str()?.to_string(), amount))
+        })
+}
+
+/// Solana funding monitor
+///
+/// Polls signatures of every pending payment address and of its token
+/// accounts. The newest signature of each account is checkpointed and the
+/// next poll reads only the signatures after it.
+pub struct SolanaMonitor {
+    source: SolanaPaymentSource,
+    processor: Arc<FundingProcessor>,
+    poll_interval: Duration,
+}
+
+impl SolanaMonitor {
+    pub fn new(rpc_url: String, processor: Arc<FundingProcessor>) -> Self {
+        Self {
+            source: SolanaPaymentSource::new(rpc_url),
+            processor,
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub async fn start(&self) {
+        info!("👀 Solana funding monitor started");
+        loop {
+            if let Err(e) = self.poll().await {
+                error!("Solana funding monitor poll failed: {:?}", e);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Scan all pending payment addresses once
+    pub async fn poll(&self) -> AppResult<()> {
+        let ledger = self.processor.ledger();
+
+        for pending in ledger.get_pending_payment_addresses(Chain::Solana).await? {
+            // A transaction can touch the address and its token accounts
+            let mut seen = HashSet::new();
+            let accounts = std::iter::once(pending.payment_address.clone())
+                .chain(self.source.token_accounts(&pending.payment_address));
+
+            for account in accounts {
+                self.poll_account(&account, &pending, &mut seen).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process signatures of `account` after its checkpoint as payments to
+    /// `pending`, then checkpoint the newest one
+    async fn poll_account(
+        &self,
+        account: &str,
+        pending: &PendingPaymentAddress,
+        seen: &mut HashSet<String>,
+    ) -> AppResult<()> {
+        let ledger = self.processor.ledger();
+        let checkpoint = ledger.get_funding_checkpoint(Chain::Solana, account).await?;
+
+        // Page back to the checkpoint or, on the first poll of the account,
+        // to the oldest pending quote for the address
+        let mut signatures = Vec::new();
+        let mut newest = None;
+        let mut before: Option<String> = None;
+        loop {
+            let page = self
+                .source
+                .signatures_for_address(account, checkpoint.as_deref(), before.as_deref())
+                .await?;
+            if newest.is_none() {
+                newest = page.newest.clone();
+            }
+
+            let mut reached_quote = false;
+            for sig in page.signatures {
+                if checkpoint.is_none()
+                    && sig.block_time.is_some_and(|time| time < pending.since.timestamp())
+                {
+                    reached_quote = true;
+                    break;
+                }
+                signatures.push(sig);
+            }
+
+            if reached_quote || page.record_count < SIGNATURE_LIMIT {
+                break;
+            }
+            before = page.oldest;
+        }
+
+        let hashes: Vec<String> = signatures.iter().map(|sig| sig.signature.clone()).collect();
+        let recorded = ledger
+            .get_recorded_funding_transactions(Chain::Solana, &hashes)
+            .await?;
+
+        // SECURITY: Checkpoint only after every signature was processed, so a
+        // failed poll re-reads them and skips the ones already recorded
+        for sig in signatures.into_iter().rev() {
+            if recorded.contains(&sig.signature) || !seen.insert(sig.signature.clone()) {
+                continue;
+            }
+            if let Some(payment) = self
+                .source
+                .transaction_payment(&sig.signature, &pending.payment_address)
+                .await?
+            {
+                let outcome = self.processor.process_or_record(payment).await;
+                info!("Solana payment {}: {:?}", sig.signature, outcome);
+            }
+        }
+
+        if let Some(newest) = newest {
+            ledger.save_funding_checkpoint(Chain::Solana, account, &newest).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+
+    const ADDRESS: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    async fn fake_rpc() -> String {
+        let app = Router::new().route(
+            "/",
+            post(|Json(req): Json<Value>| async move {
+                let result = match req["method"].as_str().unwrap() {
+                    "getSignaturesForAddress" if req["params"][1]["until"] == "sigToken" => json!([]),
+                    "getSignaturesForAddress" => json!([
+                        { "signature": "sigToken", "slot": 500, "blockTime": 1768471200, "err": null },
+                        { "signature": "sigFailed", "slot": 499, "err": { "InstructionError": [0, "Custom"] } },
+                        { "signature": "sigSol", "slot": 480, "blockTime": 1768471000, "err": null },
+                        { "signature": "sigOld", "slot": 470, "err": { "InstructionError": [0, "Custom"] } }
+                    ]),
+                    "getTransaction" if req["params"][0] == "sigSol" => json!({
+                        "slot": 480,
+                        "meta": {
+                            "err": null,
+                            "preBalances": [5000000000u64, 0],
+                            "postBalances": [3749995000u64, 1250000000u64],
+                            "preTokenBalances": [],
+                            "postTokenBalances": []
+                        },
+                        "transaction": { "message": { "accountKeys": ["payer", ADDRESS] } }
+                    }),
+                    "getTransaction" => json!({
+                        "slot": 500,
+                        "meta": {
+                            "err": null,
+                            "preBalances": [5000000000u64, 2039280, 2039280],
+                            "postBalances": [4999995000u64, 2039280, 2039280],
+                            "preTokenBalances": [
+                                { "accountIndex": 2, "mint": "EPjFWaLb3odcccccccccccccccccccccccccccccc",
+                                  "owner": ADDRESS, "uiTokenAmount": { "amount": "1000000", "decimals": 6 } }
+                            ],
+                            "postTokenBalances": [
+                                { "accountIndex": 1, "mint": "EPjFWaLb3odcccccccccccccccccccccccccccccc",
+                                  "owner": "payer", "uiTokenAmount": { "amount": "0", "decimals": 6 } },
+                                { "accountIndex": 2, "mint": "EPjFWaLb3odcccccccccccccccccccccccccccccc",
+                                  "owner": ADDRESS, "uiTokenAmount": { "amount": "26500000", "decimals": 6 } }
+                            ]
+                        },
+                        "transaction": { "message": { "accountKeys": ["payer", "payerAta", "quoteAta"] } }
+                    }),
+                    other => panic!("unexpected method {}", other),
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_signatures_skip_failed_transactions() {
+        let source = SolanaPaymentSource::new(fake_rpc().await);
+
+        let page = source.signatures_for_address(ADDRESS, None, None).await.unwrap();
+
+        assert_eq!(
+            page,
+            SignaturePage {
+                signatures: vec![
+                    AddressSignature {
+                        signature: "sigToken".to_string(),
+                        slot: 500,
+                        block_time: Some(1768471200),
+                    },
+                    AddressSignature {
+                        signature: "sigSol".to_string(),
+                        slot: 480,
+                        block_time: Some(1768471000),
+                    },
+                ],
+                newest: Some("sigToken".to_string()),
+                oldest: Some("sigOld".to_string()),
+                record_count: 4,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signatures_stop_at_the_checkpoint() {
+        let source = SolanaPaymentSource::new(fake_rpc().await);
+
+        let page = source
+            .signatures_for_address(ADDRESS, Some("sigToken"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(page, SignaturePage::default());
+    }
+
+    #[test]
+    fn test_token_accounts_include_wrapped_sol() {
+        let source = SolanaPaymentSource::new("http://localhost".to_string());
+        let wrapped_sol = get_associated_token_address(
+            &Pubkey::from_str(ADDRESS).unwrap(),
+            &Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap(),
+        );
+
+        let accounts = source.token_accounts(ADDRESS);
+
+        assert!(accounts.contains(&wrapped_sol.to_string()));
+        assert!(!accounts.contains(&ADDRESS.to_string()));
+        assert!(source.token_accounts("not-a-pubkey").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_native_and_token_payments() {
+        let source = SolanaPaymentSource::new(fake_rpc().await);
+
+        let sol = source.transaction_payment("sigSol", ADDRESS).await.unwrap().unwrap();
+        assert_eq!(sol.asset, "SOL");
+        assert_eq!(sol.amount, Decimal::from_str("1.25").unwrap());
+        assert_eq!(sol.sender.as_deref(), Some("payer"));
+
+        let usdc = source.transaction_payment("sigToken", ADDRESS).await.unwrap().unwrap();
+        assert_eq!(usdc.asset, "USDC");
+        assert_eq!(usdc.amount, Decimal::from_str("25.5").unwrap());
+        assert_eq!(usdc.payment_address, ADDRESS);
+    }
+}
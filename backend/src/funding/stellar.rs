@@ -0,0 +1,433 @@
+use super::{FundingProcessor, FundingVerifier, IncomingPayment};
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::{Chain, FundingPayment};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Horizon page size (maximum allowed by Horizon)
+const PAGE_LIMIT: usize = 200;
+
+/// A page of payments read from Horizon
+#[derive(Debug, Default)]
+pub struct PaymentPage {
+    /// Incoming payments to the account (other operations are skipped)
+    pub payments: Vec<IncomingPayment>,
+    /// Paging token of the last record in the page
+    pub next_cursor: Option<String>,
+    /// Number of records Horizon returned, including skipped ones
+    pub record_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct HorizonPage {
+    #[serde(rename = "_embedded")]
+    embedded: HorizonEmbedded,
+}
+
+#[derive(Debug, Deserialize)]
+struct HorizonEmbedded {
+    records: Vec<HorizonPayment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HorizonPayment {
+    paging_token: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default = "default_successful")]
+    transaction_successful: bool,
+    transaction_hash: String,
+    created_at: Option<DateTime<Utc>>,
+    // payment / path payments
+    from: Option<String>,
+    to: Option<String>,
+    amount: Option<String>,
+    asset_type: Option<String>,
+    asset_code: Option<String>,
+    // create_account
+    funder: Option<String>,
+    account: Option<String>,
+    starting_balance: Option<String>,
+    transaction: Option<HorizonTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HorizonTransaction {
+    memo: Option<String>,
+}
+
+fn default_successful() -> bool {
+    true
+}
+
+/// Reads payments to a collection account from Horizon
+pub struct HorizonPaymentSource {
+    client: reqwest::Client,
+    horizon_url: String,
+}
+
+impl HorizonPaymentSource {
+    pub fn new(horizon_url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            horizon_url: horizon_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Paging token of the newest payment made before `since`, used as the
+    /// starting cursor when a monitor has no checkpoint for the account
+    ///
+    /// Returns "0" (the start of the account history) if every payment is
+    /// newer than `since`.
+    pub async fn cursor_before(&self, account: &str, since: DateTime<Utc>) -> AppResult<String> {
+        let mut url = format!(
+            "{}/accounts/{}/payments?order=desc&limit={}",
+            self.horizon_url, account, PAGE_LIMIT
+        );
+
+        loop {
+            let records = self.get_page(&url).await?.embedded.records;
+            let record_count = records.len();
+
+            if let Some(record) = records
+                .iter()
+                .find(|r| r.created_at.is_some_and(|created_at| created_at < since))
+            {
+                return Ok(record.paging_token.clone());
+            }
+
+            match records.last() {
+                Some(last) if record_count == PAGE_LIMIT => {
+                    url = format!(
+                        "{}/accounts/{}/payments?order=desc&limit={}&cursor={}",
+                        self.horizon_url, account, PAGE_LIMIT, last.paging_token
+                    );
+                }
+                _ => return Ok("0".to_string()),
+            }
+        }
+    }
+
+    /// Payments to `account` after `cursor`, oldest first
+    pub async fn fetch_payments(&self, account: &str, cursor: &str) -> AppResult<PaymentPage> {
+        let url = format!(
+            "{}/accounts/{}/payments?cursor={}&order=asc&limit={}&join=transactions",
+            self.horizon_url, account, cursor, PAGE_LIMIT
+        );
+        let page = self.get_page(&url).await?;
+
+        let record_count = page.embedded.records.len();
+        let next_cursor = page.embedded.records.last().map(|r| r.paging_token.clone());
+        let payments = page
+            .embedded
+            .records
+            .into_iter()
+            .filter_map(|record| Self::to_payment(account, record))
+            .collect();
+
+        Ok(PaymentPage {
+            payments,
+            next_cursor,
+            record_count,
+        })
+    }
+
+    async fn get_page(&self, url: &str) -> AppResult<HorizonPage> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Horizon request failed: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::NotFound(format!("Horizon resource not found: {}", url)));
+        }
+
+        if !response.status().is_success() {
+            return Err(AppError::ExternalError(format!(
+                "Horizon returned {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json::<HorizonPage>()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Invalid Horizon response: {}", e)))
+    }
+
+    /// Convert a Horizon operation into a payment to `account`
+    ///
+    /// Payment address matches the quote format: `[account]?memo=[memo]`
+    fn to_payment(account: &str, record: HorizonPayment) -> Option<IncomingPayment> {
+        if !record.transaction_successful {
+            return None;
+        }
+
+        let (to, sender, amount, asset) = match record.kind.as_str() {
+            "payment" | "path_payment_strict_receive" | "path_payment_strict_send" => {
+                let asset = match record.asset_type.as_deref() {
+                    Some("native") => "XLM".to_string(),
+                    _ => record.asset_code?,
+                };
+                (record.to?, record.from, record.amount?, asset)
+            }
+            "create_account" => (
+                record.account?,
+                record.funder,
+                record.starting_balance?,
+                "XLM".to_string(),
+            ),
+            _ => return None,
+        };
+
+        if to != account {
+            return None;
+        }
+
+        let payment_address = match record.transaction.and_then(|t| t.memo) {
+            Some(memo) => format!("{}?memo={}", to, memo),
+            None => to,
+        };
+
+        Some(IncomingPayment {
+            chain: Chain::Stellar,
+            transaction_hash: record.transaction_hash,
+            payment_address,
+            sender,
+            asset,
+            amount: Decimal::from_str(&amount).ok()?,
+        })
+    }
+}
+
+#[async_trait]
+impl FundingVerifier for HorizonPaymentSource {
+    async fn verify(&self, payment: &FundingPayment) -> AppResult<Option<IncomingPayment>> {
+        let account = match payment.payment_address.split_once("?memo=") {
+            Some((account, _)) => account,
+            None => payment.payment_address.as_str(),
+        };
+        let url = format!(
+            "{}/transactions/{}/payments?join=transactions&limit={}",
+            self.horizon_url, payment.transaction_hash, PAGE_LIMIT
+        );
+
+        let page = match self.get_page(&url).await {
+            Ok(page) => page,
+            Err(AppError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Ok(page
+            .embedded
+            .records
+            .into_iter()
+            .filter_map(|record| Self::to_payment(account, record))
+            .find(|p| p.payment_address == payment.payment_address))
+    }
+}
+
+/// Stellar funding monitor
+///
+/// Polls Horizon for payments to every collection account that has a
+/// pending quote. The Horizon paging token is checkpointed per account.
+pub struct StellarMonitor {
+    source: HorizonPaymentSource,
+    processor: Arc<FundingProcessor>,
+    poll_interval: Duration,
+}
+
+impl StellarMonitor {
+    pub fn new(horizon_url: String, processor: Arc<FundingProcessor>) -> Self {
+        Self {
+            source: HorizonPaymentSource::new(horizon_url),
+            processor,
+            poll_interval: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub async fn start(&self) {
+        info!("👀 Stellar funding monitor started");
+        loop {
+            if let Err(e) = self.poll().await {
+                error!("Stellar funding monitor poll failed: {:?}", e);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Scan all collection accounts once
+    pub async fn poll(&self) -> AppResult<()> {
+        let ledger = self.processor.ledger();
+
+        // Collection account -> creation time of its oldest pending quote
+        let mut accounts: BTreeMap<String, DateTime<Utc>> = BTreeMap::new();
+        for pending in ledger.get_pending_payment_addresses(Chain::Stellar).await? {
+            let account = match pending.payment_address.split_once("?memo=") {
+                Some((account, _)) => account.to_string(),
+                None => pending.payment_address,
+            };
+            let since = accounts.entry(account).or_insert(pending.since);
+            *since = (*since).min(pending.since);
+        }
+
+        for (account, since) in accounts {
+            self.poll_account(&account, since).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn poll_account(&self, account: &str, since: DateTime<Utc>) -> AppResult<()> {
+        let ledger = self.processor.ledger();
+        let mut cursor = match ledger.get_funding_checkpoint(Chain::Stellar, account).await? {
+            Some(cursor) => cursor,
+            // First run: payments before the oldest pending quote paid no quote
+            // we know of, anything after it may already be a quote payment
+            None => self.source.cursor_before(account, since).await?,
+        };
+
+        loop {
+            let page = self.source.fetch_payments(account, &cursor).await?;
+
+            // SECURITY: Checkpoint only after every payment in the page was
+            // processed or recorded as failed, so nothing in it is skipped
+            for payment in page.payments {
+                let outcome = self.processor.process_or_record(payment.clone()).await;
+                info!("Stellar payment {}: {:?}", payment.transaction_hash, outcome);
+            }
+
+            if let Some(next) = page.next_cursor {
+                cursor = next;
+            }
+            ledger.save_funding_checkpoint(Chain::Stellar, account, &cursor).await?;
+
+            if page.record_count < PAGE_LIMIT {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use std::collections::HashMap;
+
+    const ACCOUNT: &str = "GBUQWP3BOUZX34ULNQG23RQ6F4OFSAI5TU2MMQBB3IXWVYLXVCLWEB7V";
+
+    async fn fake_horizon() -> String {
+        let app = Router::new().route(
+            &format!("/accounts/{}/payments", ACCOUNT),
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                if params.get("order").map(String::as_str) == Some("desc") {
+                    return Json(serde_json::json!({
+                        "_embedded": { "records": [
+                            { "paging_token": "300", "type": "payment", "transaction_hash": "c",
+                              "created_at": "2026-01-15T11:00:00Z",
+                              "to": ACCOUNT, "amount": "1.0", "asset_type": "native" },
+                            { "paging_token": "250", "type": "payment", "transaction_hash": "f",
+                              "created_at": "2026-01-15T09:00:00Z",
+                              "to": ACCOUNT, "amount": "4.0", "asset_type": "native" }
+                        ]}
+                    }));
+                }
+                if params.get("cursor").map(String::as_str) == Some("250") {
+                    return Json(serde_json::json!({
+                        "_embedded": { "records": [
+                            { "paging_token": "300", "type": "payment", "transaction_hash": "c",
+                              "created_at": "2026-01-15T11:00:00Z",
+                              "to": ACCOUNT, "amount": "1.0", "asset_type": "native",
+                              "transaction": { "memo": "quote_abc123def456" } }
+                        ]}
+                    }));
+                }
+                assert_eq!(params.get("cursor").map(String::as_str), Some("100"));
+                Json(serde_json::json!({
+                    "_embedded": { "records": [
+                        { "paging_token": "101", "type": "payment", "transaction_hash": "a",
+                          "transaction_successful": true, "from": "GSENDER",
+                          "to": ACCOUNT, "amount": "12.5000000", "asset_type": "native",
+                          "transaction": { "memo": "quote_abc123def456" } },
+                        { "paging_token": "102", "type": "payment", "transaction_hash": "b",
+                          "to": "GOTHERACCOUNT", "amount": "3.0", "asset_type": "native" },
+                        { "paging_token": "103", "type": "payment", "transaction_hash": "d",
+                          "to": ACCOUNT, "amount": "7.0", "asset_type": "credit_alphanum4",
+                          "asset_code": "USDC", "transaction_successful": false },
+                        { "paging_token": "104", "type": "create_account", "transaction_hash": "e",
+                          "account": ACCOUNT, "starting_balance": "2.0" }
+                    ]}
+                }))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_payments_parses_incoming_payments() {
+        let source = HorizonPaymentSource::new(fake_horizon().await);
+
+        let page = source.fetch_payments(ACCOUNT, "100").await.unwrap();
+
+        assert_eq!(page.record_count, 4);
+        assert_eq!(page.next_cursor.as_deref(), Some("104"));
+        assert_eq!(page.payments.len(), 2);
+        assert_eq!(
+            page.payments[0],
+            IncomingPayment {
+                chain: Chain::Stellar,
+                transaction_hash: "a".to_string(),
+                payment_address: format!("{}?memo=quote_abc123def456", ACCOUNT),
+                sender: Some("GSENDER".to_string()),
+                asset: "XLM".to_string(),
+                amount: Decimal::from_str("12.5").unwrap(),
+            }
+        );
+        assert_eq!(page.payments[1].payment_address, ACCOUNT);
+    }
+
+    #[tokio::test]
+    async fn test_first_poll_reads_payments_made_after_the_quote() {
+        let source = HorizonPaymentSource::new(fake_horizon().await);
+        let quoted_at = "2026-01-15T10:00:00Z".parse().unwrap();
+
+        // The quote was paid before the monitor first polled the account
+        let cursor = source.cursor_before(ACCOUNT, quoted_at).await.unwrap();
+        assert_eq!(cursor, "250");
+
+        let page = source.fetch_payments(ACCOUNT, &cursor).await.unwrap();
+        assert_eq!(page.payments.len(), 1);
+        assert_eq!(page.payments[0].transaction_hash, "c");
+        assert_eq!(
+            page.payments[0].payment_address,
+            format!("{}?memo=quote_abc123def456", ACCOUNT)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cursor_before_older_than_history_starts_at_the_beginning() {
+        let source = HorizonPaymentSource::new(fake_horizon().await);
+        let quoted_at = "2026-01-15T08:00:00Z".parse().unwrap();
+
+        assert_eq!(source.cursor_before(ACCOUNT, quoted_at).await.unwrap(), "0");
+    }
+}
@@ -0,0 +1,355 @@
+// Unit-safe amounts
+//
+// Amounts are held in integer base units (lamports, stroops, yoctoNEAR,
+// token base units) and only converted to display units (SOL, XLM, NEAR,
+// USDC) explicitly.
+//
+// - Amount<A>: asset known at compile time (native chain assets). Only
+//   amounts of the same asset can be added, anything else fails to compile.
+// - TokenAmount: asset known at runtime (whitelisted tokens), decimals come
+//   from WhitelistedToken.decimals; mixing assets is a runtime error.
+// - NativeAmount: an Amount<A> for a chain chosen at runtime.
+
+use super::models::Chain;
+use crate::adapters::dex_whitelist::WhitelistedToken;
+use crate::error::{AppError, AppResult};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Asset with a fixed chain, symbol and number of decimals
+pub trait AssetUnit: Copy + Send + Sync + 'static {
+    const CHAIN: Chain;
+    const SYMBOL: &'static str;
+    const DECIMALS: u32;
+}
+
+/// SOL, base unit lamports (10^-9)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Sol;
+
+/// XLM, base unit stroops (10^-7)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Xlm;
+
+/// NEAR, base unit yoctoNEAR (10^-24)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Near;
+
+impl AssetUnit for Sol {
+    const CHAIN: Chain = Chain::Solana;
+    const SYMBOL: &'static str = "SOL";
+    const DECIMALS: u32 = 9;
+}
+
+impl AssetUnit for Xlm {
+    const CHAIN: Chain = Chain::Stellar;
+    const SYMBOL: &'static str = "XLM";
+    const DECIMALS: u32 = 7;
+}
+
+impl AssetUnit for Near {
+    const CHAIN: Chain = Chain::Near;
+    const SYMBOL: &'static str = "NEAR";
+    const DECIMALS: u32 = 24;
+}
+
+/// Convert base units to display units
+fn base_to_display(base: u128, decimals: u32) -> AppResult<Decimal> {
+    let overflow = || AppError::Internal(format!("Amount {} exceeds decimal range", base));
+
+    // Values above 2^96 base units (e.g. > ~79k NEAR) are split to stay in range
+    let unit = 10u128.pow(decimals);
+    let whole = i128::try_from(base / unit)
+        .ok()
+        .and_then(|whole| Decimal::try_from_i128_with_scale(whole, 0).ok())
+        .ok_or_else(overflow)?;
+    let fraction = Decimal::try_from_i128_with_scale((base % unit) as i128, decimals)
+        .map_err(|_| overflow())?;
+    whole.checked_add(fraction).map(|d| d.normalize()).ok_or_else(overflow)
+}
+
+/// Convert display units to base units, rounding up any sub-base-unit remainder
+fn display_to_base(display: Decimal, decimals: u32) -> AppResult<u128> {
+    if display.is_sign_negative() && !display.is_zero() {
+        return Err(AppError::InvalidInput(format!("Negative amount: {}", display)));
+    }
+
+    let overflow = || AppError::InvalidInput(format!("Amount {} out of range", display));
+    let unit = 10u128.pow(decimals);
+
+    let whole = display.trunc();
+    let fraction = display - whole;
+    let whole_base = whole
+        .to_u128()
+        .and_then(|w| w.checked_mul(unit))
+        .ok_or_else(overflow)?;
+
+    // Fractions carry at most 28 digits, so split the scaling for 24-decimal assets
+    let mut fraction_base = fraction;
+    let mut remaining = decimals;
+    while remaining > 0 {
+        let step = remaining.min(9);
+        fraction_base = fraction_base
+            .checked_mul(Decimal::from(10u64.pow(step)))
+            .ok_or_else(overflow)?;
+        remaining -= step;
+    }
+    let fraction_base = fraction_base
+        .round_dp_with_strategy(0, RoundingStrategy::AwayFromZero)
+        .to_u128()
+        .ok_or_else(overflow)?;
+
+    whole_base.checked_add(fraction_base).ok_or_else(overflow)
+}
+
+/// Amount of a compile-time known asset, in base units
+///
+/// ```compile_fail
+/// let fee = Amount::<Sol>::from_base(5_000);
+/// let base_fee = Amount::<Xlm>::from_base(100);
+/// let total = fee.checked_add(base_fee); // mismatched types
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Amount<A: AssetUnit> {
+    base: u128,
+    asset: PhantomData<A>,
+}
+
+impl<A: AssetUnit> Amount<A> {
+    pub fn from_base(base: u128) -> Self {
+        Self { base, asset: PhantomData }
+    }
+
+    /// Parse a display amount, rounding up to the next base unit
+    pub fn from_display(display: Decimal) -> AppResult<Self> {
+        Ok(Self::from_base(display_to_base(display, A::DECIMALS)?))
+    }
+
+    pub fn to_display(self) -> AppResult<Decimal> {
+        base_to_display(self.base, A::DECIMALS)
+    }
+
+    /// Multiply by a ratio (fee rate, safety buffer), rounding up
+    pub fn scale(&self, factor: Decimal) -> AppResult<Self> {
+        let overflow = || AppError::Internal(format!("Amount overflow scaling {:?}", self));
+
+        let scaled = i128::try_from(self.base)
+            .ok()
+            .and_then(|base| Decimal::try_from_i128_with_scale(base, 0).ok())
+            .and_then(|base| base.checked_mul(factor))
+            .ok_or_else(overflow)?;
+        if scaled.is_sign_negative() && !scaled.is_zero() {
+            return Err(AppError::InvalidInput(format!("Negative scale factor: {}", factor)));
+        }
+
+        scaled
+            .round_dp_with_strategy(0, RoundingStrategy::AwayFromZero)
+            .to_u128()
+            .map(Self::from_base)
+            .ok_or_else(overflow)
+    }
+
+    pub fn checked_add(self, other: Self) -> AppResult<Self> {
+        self.base
+            .checked_add(other.base)
+            .map(Self::from_base)
+            .ok_or_else(|| AppError::InvalidInput(format!("{} amount overflow", A::SYMBOL)))
+    }
+}
+
+impl<A: AssetUnit> fmt::Debug for Amount<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Amount({} base {})", self.base, A::SYMBOL)
+    }
+}
+
+impl<A: AssetUnit> fmt::Display for Amount<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_display() {
+            Ok(display) => write!(f, "{} {}", display, A::SYMBOL),
+            Err(_) => write!(f, "{} base {}", self.base, A::SYMBOL),
+        }
+    }
+}
+
+/// Native asset amount for a chain chosen at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeAmount {
+    Solana(Amount<Sol>),
+    Stellar(Amount<Xlm>),
+    Near(Amount<Near>),
+}
+
+impl NativeAmount {
+    pub fn from_display(chain: Chain, display: Decimal) -> AppResult<Self> {
+        Ok(match chain {
+            Chain::Solana => NativeAmount::Solana(Amount::from_display(display)?),
+            Chain::Stellar => NativeAmount::Stellar(Amount::from_display(display)?),
+            Chain::Near => NativeAmount::Near(Amount::from_display(display)?),
+        })
+    }
+
+    pub fn chain(&self) -> Chain {
+        match self {
+            NativeAmount::Solana(_) => Sol::CHAIN,
+            NativeAmount::Stellar(_) => Xlm::CHAIN,
+            NativeAmount::Near(_) => Near::CHAIN,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            NativeAmount::Solana(_) => Sol::SYMBOL,
+            NativeAmount::Stellar(_) => Xlm::SYMBOL,
+            NativeAmount::Near(_) => Near::SYMBOL,
+        }
+    }
+
+    pub fn to_display(self) -> AppResult<Decimal> {
+        match self {
+            NativeAmount::Solana(a) => a.to_display(),
+            NativeAmount::Stellar(a) => a.to_display(),
+            NativeAmount::Near(a) => a.to_display(),
+        }
+    }
+
+    pub fn scale(&self, factor: Decimal) -> AppResult<Self> {
+        Ok(match self {
+            NativeAmount::Solana(a) => NativeAmount::Solana(a.scale(factor)?),
+            NativeAmount::Stellar(a) => NativeAmount::Stellar(a.scale(factor)?),
+            NativeAmount::Near(a) => NativeAmount::Near(a.scale(factor)?),
+        })
+    }
+}
+
+/// Amount of a whitelisted token, asset known at runtime
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenAmount {
+    pub chain: Chain,
+    pub symbol: String,
+    pub decimals: u32,
+    /// Base units as a decimal string (u128 does not fit JSON numbers)
+    #[serde(serialize_with = "serialize_base")]
+    base: u128,
+}
+
+fn serialize_base<S: serde::Serializer>(base: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base.to_string())
+}
+
+impl TokenAmount {
+    pub fn from_base(token: &WhitelistedToken, base: u128) -> Self {
+        Self {
+            chain: token.chain,
+            symbol: token.symbol.clone(),
+            decimals: token.decimals as u32,
+            base,
+        }
+    }
+
+    /// Amount of a token that is not looked up in the whitelist, such as a
+    /// Stellar asset named by its code in execution instructions
+    pub fn new(chain: Chain, symbol: &str, decimals: u32, base: u128) -> Self {
+        Self {
+            chain,
+            symbol: symbol.to_string(),
+            decimals,
+            base,
+        }
+    }
+
+    /// Parse a base-unit string such as an RPC `amount` field
+    pub fn parse_base(token: &WhitelistedToken, base: &str) -> AppResult<Self> {
+        let base = base
+            .parse::<u128>()
+            .map_err(|_| AppError::InvalidInput(format!("Invalid {} base amount: {}", token.symbol, base)))?;
+        Ok(Self::from_base(token, base))
+    }
+
+    /// Parse a display amount, rounding up to the next base unit
+    pub fn from_display(token: &WhitelistedToken, display: Decimal) -> AppResult<Self> {
+        Ok(Self::from_base(token, display_to_base(display, token.decimals as u32)?))
+    }
+
+    pub fn to_display(&self) -> AppResult<Decimal> {
+        base_to_display(self.base, self.decimals)
+    }
+
+    pub fn is_same_asset(&self, other: &Self) -> bool {
+        self.chain == other.chain && self.symbol.eq_ignore_ascii_case(&other.symbol)
+    }
+
+    /// Compare against an amount of the same token
+    pub fn checked_cmp(&self, other: &Self) -> AppResult<std::cmp::Ordering> {
+        if !self.is_same_asset(other) || self.decimals != other.decimals {
+            return Err(AppError::InvalidInput(format!(
+                "Cannot compare {} and {}",
+                self.symbol, other.symbol
+            )));
+        }
+        Ok(self.base.cmp(&other.base))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::DexWhitelist;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_native_conversions() {
+        assert_eq!(Amount::<Sol>::from_base(10_000).to_display().unwrap(), dec!(0.00001));
+        assert_eq!(Amount::<Xlm>::from_display(dec!(1.5)).unwrap(), Amount::from_base(15_000_000));
+        assert_eq!(
+            Amount::<Near>::from_display(dec!(0.0003)).unwrap(),
+            Amount::from_base(300_000_000_000_000_000_000)
+        );
+
+        // Beyond 2^96 yoctoNEAR
+        let large = Amount::<Near>::from_base(1_000_000 * 10u128.pow(24));
+        assert_eq!(large.to_display().unwrap(), dec!(1000000));
+
+        // Sub-base-unit remainders round up
+        assert_eq!(Amount::<Sol>::from_display(dec!(0.0000000001)).unwrap(), Amount::from_base(1));
+        assert!(Amount::<Sol>::from_display(dec!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_same_asset_arithmetic() {
+        let a = Amount::<Sol>::from_base(5_000);
+        let b = Amount::<Sol>::from_base(5_000);
+        assert_eq!(a.checked_add(b).unwrap(), Amount::from_base(10_000));
+        assert!(Amount::<Sol>::from_base(u128::MAX).checked_add(b).is_err());
+        assert_eq!(a.scale(dec!(1.2)).unwrap(), Amount::from_base(6_000));
+
+        let sol = NativeAmount::Solana(a);
+        assert_eq!(sol.scale(dec!(2)).unwrap(), NativeAmount::Solana(Amount::from_base(10_000)));
+        assert_eq!(sol.to_display().unwrap(), dec!(0.000005));
+    }
+
+    #[test]
+    fn test_token_amounts_use_whitelist_decimals() {
+        let whitelist = DexWhitelist::new();
+        let usdc = whitelist.get_by_symbol(Chain::Solana, "USDC").unwrap();
+        let sol = whitelist.get_by_symbol(Chain::Solana, "SOL").unwrap();
+
+        let amount = TokenAmount::parse_base(&usdc, "26500000").unwrap();
+        assert_eq!(amount.to_display().unwrap(), dec!(26.5));
+        assert_eq!(TokenAmount::from_display(&usdc, dec!(26.5)).unwrap(), amount);
+
+        let lamports = TokenAmount::from_base(&sol, 1);
+        assert!(amount.checked_cmp(&lamports).is_err());
+        assert_eq!(amount.checked_cmp(&amount).unwrap(), std::cmp::Ordering::Equal);
+
+        // Native decimals agree with the whitelist
+        assert_eq!(sol.decimals as u32, Sol::DECIMALS);
+        let near = whitelist.get_by_symbol(Chain::Near, "NEAR").unwrap();
+        assert_eq!(near.decimals as u32, Near::DECIMALS);
+        let xlm = whitelist.get_by_symbol(Chain::Stellar, "XLM").unwrap();
+        assert_eq!(xlm.decimals as u32, Xlm::DECIMALS);
+    }
+}
@@ -0,0 +1,387 @@
+// Double-entry journal - builds the balanced entries LedgerRepository posts
+//
+// Accounts are keyed by (kind, chain, asset):
+// - Quote committed (funding asset):
+//     Dr settlement_in_transit / Cr user_funding       max_funding_amount
+// - Execution completed:
+//     funding asset:   Dr user_funding / Cr service_fee_revenue   fee share
+//     native asset:    Dr gas_expense  / Cr treasury              execution_cost
+//     execution asset: Dr trade_delivery / Cr treasury            execution_amount
+// - Settlement recorded (funding asset):
+//     Dr treasury / Cr settlement_in_transit           funding_amount
+// - Committed quote failed or expired (funding asset), reverses the commit:
+//     Dr user_funding / Cr settlement_in_transit       max_funding_amount
+//
+// The service fee is quoted in execution-chain units; its funding-asset
+// share is the cost part of the payment (max_funding_amount minus the
+// trade's funding_amount) * service_fee / (execution_cost + service_fee).
+
+use super::models::{Asset, Chain, Quote, QuoteStatus};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Journal account kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "journal_account_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKind {
+    UserFunding,
+    Treasury,
+    ServiceFeeRevenue,
+    GasExpense,
+    SettlementInTransit,
+    /// Execution asset delivered to users
+    TradeDelivery,
+}
+
+/// Ledger event a journal entry was posted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "journal_event", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JournalEvent {
+    QuoteCommitted,
+    ExecutionCompleted,
+    SettlementRecorded,
+    QuoteReversed,
+}
+
+/// One debit or credit line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JournalLine {
+    pub account: AccountKind,
+    pub chain: Chain,
+    pub asset: String,
+    pub debit: Decimal,
+    pub credit: Decimal,
+}
+
+impl JournalLine {
+    fn debit(account: AccountKind, chain: Chain, asset: &str, amount: Decimal) -> Self {
+        Self { account, chain, asset: asset.to_string(), debit: amount, credit: Decimal::ZERO }
+    }
+
+    fn credit(account: AccountKind, chain: Chain, asset: &str, amount: Decimal) -> Self {
+        Self { account, chain, asset: asset.to_string(), debit: Decimal::ZERO, credit: amount }
+    }
+}
+
+/// Journal entry ready to be posted
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub event: JournalEvent,
+    pub reference_id: Uuid,
+    pub quote_id: Uuid,
+    pub description: String,
+    pub lines: Vec<JournalLine>,
+}
+
+impl JournalEntry {
+    fn new(event: JournalEvent, reference_id: Uuid, quote_id: Uuid, description: String) -> Self {
+        Self { event, reference_id, quote_id, description, lines: Vec::new() }
+    }
+
+    /// Add a debit/credit pair, skipping zero amounts
+    fn transfer(
+        mut self,
+        debit: AccountKind,
+        credit: AccountKind,
+        chain: Chain,
+        asset: &str,
+        amount: Decimal,
+    ) -> Self {
+        if amount > Decimal::ZERO {
+            self.lines.push(JournalLine::debit(debit, chain, asset, amount));
+            self.lines.push(JournalLine::credit(credit, chain, asset, amount));
+        }
+        self
+    }
+}
+
+/// Entry for a quote moving to committed
+pub fn quote_committed(quote: &Quote) -> JournalEntry {
+    JournalEntry::new(
+        JournalEvent::QuoteCommitted,
+        quote.id,
+        quote.id,
+        format!("Quote {} committed", quote.id),
+    )
+    .transfer(
+        AccountKind::SettlementInTransit,
+        AccountKind::UserFunding,
+        quote.funding_chain,
+        &quote.funding_asset,
+        quote.max_funding_amount,
+    )
+}
+
+/// Entry for a quote whose execution succeeded
+pub fn execution_completed(quote: &Quote) -> JournalEntry {
+    let native_asset = Asset::native(quote.execution_chain).symbol;
+
+    JournalEntry::new(
+        JournalEvent::ExecutionCompleted,
+        quote.id,
+        quote.id,
+        format!("Quote {} executed on {}", quote.id, quote.execution_chain),
+    )
+    .transfer(
+        AccountKind::UserFunding,
+        AccountKind::ServiceFeeRevenue,
+        quote.funding_chain,
+        &quote.funding_asset,
+        funding_fee_share(quote),
+    )
+    .transfer(
+        AccountKind::GasExpense,
+        AccountKind::Treasury,
+        quote.execution_chain,
+        &native_asset,
+        quote.execution_cost,
+    )
+    .transfer(
+        AccountKind::TradeDelivery,
+        AccountKind::Treasury,
+        quote.execution_chain,
+        &quote.execution_asset,
+        quote.execution_amount,
+    )
+}
+
+/// Entry for a committed quote that failed or expired before it settled
+///
+/// Reverses `quote_committed`: the payment no longer funds an execution.
+pub fn quote_reversed(quote: &Quote, to_status: QuoteStatus) -> JournalEntry {
+    JournalEntry::new(
+        JournalEvent::QuoteReversed,
+        quote.id,
+        quote.id,
+        format!("Quote {} {:?}, commit reversed", quote.id, to_status),
+    )
+    .transfer(
+        AccountKind::UserFunding,
+        AccountKind::SettlementInTransit,
+        quote.funding_chain,
+        &quote.funding_asset,
+        quote.max_funding_amount,
+    )
+}
+
+/// Entry for a settlement of a funding payment into the treasury
+pub fn settlement_recorded(
+    settlement_id: Uuid,
+    quote_id: Uuid,
+    funding_chain: Chain,
+    funding_asset: &str,
+    funding_amount: Decimal,
+) -> JournalEntry {
+    JournalEntry::new(
+        JournalEvent::SettlementRecorded,
+        settlement_id,
+        quote_id,
+        format!("Settlement {} for quote {}", settlement_id, quote_id),
+    )
+    .transfer(
+        AccountKind::Treasury,
+        AccountKind::SettlementInTransit,
+        funding_chain,
+        funding_asset,
+        funding_amount,
+    )
+}
+
+/// Service fee expressed in the funding asset
+fn funding_fee_share(quote: &Quote) -> Decimal {
+    let total = quote.execution_cost + quote.service_fee;
+    if total.is_zero() {
+        return Decimal::ZERO;
+    }
+    let cost_payment = (quote.max_funding_amount - quote.funding_amount).max(Decimal::ZERO);
+    (cost_payment * quote.service_fee / total).round_dp(18)
+}
+
+/// Trial balance row for one account
+#[derive(Debug, Clone, Serialize)]
+pub struct TrialBalanceRow {
+    pub account: AccountKind,
+    pub chain: Chain,
+    pub asset: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub debit: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub credit: Decimal,
+    /// debit - credit
+    #[serde(with = "rust_decimal::serde::float")]
+    pub balance: Decimal,
+}
+
+/// Asset or entry whose debits and credits differ
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalImbalance {
+    /// None for a per-asset total imbalance
+    pub entry_id: Option<Uuid>,
+    pub chain: Chain,
+    pub asset: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub debit: Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub credit: Decimal,
+}
+
+/// Result of the journal invariant check
+#[derive(Debug, Clone, Serialize)]
+pub struct TrialBalance {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub accounts: Vec<TrialBalanceRow>,
+    pub imbalances: Vec<JournalImbalance>,
+    pub balanced: bool,
+}
+
+impl TrialBalance {
+    /// Build from account rows and per-entry imbalances, adding per-asset totals
+    pub fn new(accounts: Vec<TrialBalanceRow>, mut imbalances: Vec<JournalImbalance>) -> Self {
+        let mut totals: BTreeMap<(&'static str, String), (Chain, Decimal, Decimal)> = BTreeMap::new();
+        for row in &accounts {
+            let total = totals
+                .entry((row.chain.as_str(), row.asset.clone()))
+                .or_insert((row.chain, Decimal::ZERO, Decimal::ZERO));
+            total.1 += row.debit;
+            total.2 += row.credit;
+        }
+
+        for ((_, asset), (chain, debit, credit)) in totals {
+            if debit != credit {
+                imbalances.push(JournalImbalance { entry_id: None, chain, asset, debit, credit });
+            }
+        }
+
+        Self {
+            generated_at: chrono::Utc::now(),
+            balanced: imbalances.is_empty(),
+            accounts,
+            imbalances,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::models::QuoteMode;
+    use rust_decimal_macros::dec;
+
+    fn quote() -> Quote {
+        Quote {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            funding_chain: Chain::Stellar,
+            execution_chain: Chain::Solana,
+            funding_asset: "XLM".to_string(),
+            execution_asset: "SOL".to_string(),
+            mode: QuoteMode::ExactInput,
+            funding_amount: dec!(1000),
+            execution_amount: dec!(5),
+            max_funding_amount: dec!(1110),
+            execution_cost: dec!(0.01),
+            service_fee: dec!(0.001),
+            execution_instructions: Vec::new(),
+            estimated_compute_units: None,
+            simulated: false,
+            nonce: "nonce".to_string(),
+            status: QuoteStatus::Committed,
+            expires_at: chrono::Utc::now(),
+            payment_address: None,
+            superseded_by: None,
+            fee_schedule_id: None,
+            price_check: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    /// debit - credit per (account, chain, asset) over `entries`
+    fn account_totals(entries: &[JournalEntry]) -> BTreeMap<(AccountKind, String), Decimal> {
+        let mut totals = BTreeMap::new();
+        for line in entries.iter().flat_map(|entry| &entry.lines) {
+            *totals
+                .entry((line.account, format!("{}:{}", line.chain.as_str(), line.asset)))
+                .or_insert(Decimal::ZERO) += line.debit - line.credit;
+        }
+        totals
+    }
+
+    fn total(totals: &BTreeMap<(AccountKind, String), Decimal>, account: AccountKind, asset: &str) -> Decimal {
+        totals.get(&(account, asset.to_string())).copied().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_settled_quote_closes_transit_and_books_delivery() {
+        let quote = quote();
+        let totals = account_totals(&[
+            quote_committed(&quote),
+            execution_completed(&quote),
+            settlement_recorded(Uuid::new_v4(), quote.id, Chain::Stellar, "XLM", dec!(1110)),
+        ]);
+
+        assert_eq!(total(&totals, AccountKind::SettlementInTransit, "stellar:XLM"), dec!(0));
+        assert_eq!(total(&totals, AccountKind::Treasury, "stellar:XLM"), dec!(1110));
+        // 110 XLM paid for costs, 10 of it is the fee share
+        assert_eq!(total(&totals, AccountKind::ServiceFeeRevenue, "stellar:XLM"), dec!(-10));
+        assert_eq!(total(&totals, AccountKind::UserFunding, "stellar:XLM"), dec!(-1100));
+
+        // Delivered SOL and gas both leave the treasury
+        assert_eq!(total(&totals, AccountKind::TradeDelivery, "solana:SOL"), dec!(5));
+        assert_eq!(total(&totals, AccountKind::GasExpense, "solana:SOL"), dec!(0.01));
+        assert_eq!(total(&totals, AccountKind::Treasury, "solana:SOL"), dec!(-5.01));
+
+        // Every asset nets to zero across accounts
+        let mut per_asset: BTreeMap<&str, Decimal> = BTreeMap::new();
+        for ((_, asset), net) in &totals {
+            *per_asset.entry(asset.as_str()).or_default() += net;
+        }
+        assert!(per_asset.values().all(|net| net.is_zero()), "{:?}", per_asset);
+    }
+
+    #[test]
+    fn test_reversal_closes_committed_quote() {
+        let quote = quote();
+        let reversal = quote_reversed(&quote, QuoteStatus::Failed);
+        assert!(reversal
+            .lines
+            .iter()
+            .all(|line| line.chain == Chain::Stellar && line.asset == "XLM"));
+
+        let totals = account_totals(&[quote_committed(&quote), reversal]);
+        assert!(totals.values().all(|net| net.is_zero()), "{:?}", totals);
+    }
+
+    #[test]
+    fn test_trial_balance_flags_asset_imbalance() {
+        let row = |account, debit, credit| TrialBalanceRow {
+            account,
+            chain: Chain::Stellar,
+            asset: "XLM".to_string(),
+            debit,
+            credit,
+            balance: debit - credit,
+        };
+
+        let balanced = TrialBalance::new(
+            vec![
+                row(AccountKind::SettlementInTransit, dec!(110), dec!(0)),
+                row(AccountKind::UserFunding, dec!(0), dec!(110)),
+            ],
+            Vec::new(),
+        );
+        assert!(balanced.balanced);
+
+        let unbalanced = TrialBalance::new(
+            vec![row(AccountKind::Treasury, dec!(5), dec!(0))],
+            Vec::new(),
+        );
+        assert!(!unbalanced.balanced);
+        assert_eq!(unbalanced.imbalances[0].asset, "XLM");
+    }
+}
@@ -1,2 +1,4 @@
+pub mod amount;
+pub mod journal;
 pub mod models;
 pub mod repository;
\ No newline at end of file
@@ -37,28 +37,6 @@ impl Chain {
         vec![Chain::Solana, Chain::Stellar, Chain::Near]
     }
 
-    /// Check if this chain pair is supported for cross-chain execution
-    pub fn is_pair_supported(funding: Chain, execution: Chain) -> bool {
-       // SECURITY: Explicit whitelist of supported pairs
-     // this would be configurable
-        if funding == execution {
-            //Same-chain not allowed
-            return false; 
-        }
-
-        match (funding, execution) {
-            //All combinations that are currently supported
-            (Chain::Stellar, Chain::Solana) => true,
-            (Chain::Stellar, Chain::Near) => true,
-            (Chain::Near, Chain::Stellar) => true,
-            (Chain::Near, Chain::Solana) => true,
-            (Chain::Solana, Chain::Stellar) => true,
-            (Chain::Solana, Chain::Near) => true,
-            _ => false,
-        }
-
-    }
-
 }
 
 /// Asset representation (chain-specific)
@@ -89,6 +67,26 @@ impl Asset {
 }
 
 
+/// Which side of a quote's trade the user fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "quote_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteMode {
+    /// Spend exactly `amount` of the funding asset
+    ExactInput,
+    /// Deliver exactly `amount` of the execution asset
+    ExactOutput,
+}
+
+impl QuoteMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteMode::ExactInput => "exact_input",
+            QuoteMode::ExactOutput => "exact_output",
+        }
+    }
+}
+
 /// Quote status enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
 #[sqlx(type_name = "quote_status", rename_all = "lowercase")]
@@ -101,6 +99,55 @@ pub enum QuoteStatus {
     Settled
 }
 
+impl QuoteStatus {
+    /// Statuses this status may move to - the single source of truth for
+    /// the quote lifecycle:
+    /// - Pending → Committed, Expired
+    /// - Committed → Executed, Failed, Expired
+    /// - Executed → Settled, Failed
+    /// - Settled, Expired, Failed are terminal
+    pub fn allowed_transitions(&self) -> &'static [QuoteStatus] {
+        match self {
+            QuoteStatus::Pending => &[QuoteStatus::Committed, QuoteStatus::Expired],
+            QuoteStatus::Committed => &[
+                QuoteStatus::Executed,
+                QuoteStatus::Failed,
+                QuoteStatus::Expired,
+            ],
+            QuoteStatus::Executed => &[QuoteStatus::Settled, QuoteStatus::Failed],
+            QuoteStatus::Settled | QuoteStatus::Expired | QuoteStatus::Failed => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: QuoteStatus) -> bool {
+        self.allowed_transitions().contains(&next)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.allowed_transitions().is_empty()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteStatus::Pending => "pending",
+            QuoteStatus::Committed => "committed",
+            QuoteStatus::Executed => "executed",
+            QuoteStatus::Expired => "expired",
+            QuoteStatus::Failed => "failed",
+            QuoteStatus::Settled => "settled",
+        }
+    }
+}
+
+/// Quote status transition record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QuoteStatusChange {
+    pub from_status: Option<QuoteStatus>,
+    pub to_status: QuoteStatus,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
 ///Execution status enum
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
@@ -165,7 +212,17 @@ pub struct Quote {
     pub funding_asset: String,
     pub execution_asset: String,
 
+    //Trade
+    pub mode: QuoteMode,
+    /// Funding asset spent on the trade
+    #[serde(with = "rust_decimal::serde::float")]
+    pub funding_amount: rust_decimal::Decimal,
+    /// Execution asset delivered (minimum for exact input)
+    #[serde(with = "rust_decimal::serde::float")]
+    pub execution_amount: rust_decimal::Decimal,
+
     //Amounts
+    /// Total payment: funding_amount plus converted costs and slippage
     #[serde(with = "rust_decimal::serde::float")]
     pub max_funding_amount: rust_decimal::Decimal,
     #[serde(with = "rust_decimal::serde::float")]
@@ -176,17 +233,43 @@ pub struct Quote {
     //Execution payload (chain-agnostic)
     pub execution_instructions: Vec<u8>,
     pub estimated_compute_units: Option<i32>,
+    /// Instructions were dry-run on the execution chain when quoted
+    pub simulated: bool,
 
     //Metadata
     pub nonce: String,
     pub status: QuoteStatus,
     pub expires_at: DateTime<Utc>,
     pub payment_address: Option<String>,
+    /// Quote that replaced this one after a refresh repriced it
+    pub superseded_by: Option<Uuid>,
+    /// Fee schedule that priced the service fee (None = default rate)
+    pub fee_schedule_id: Option<Uuid>,
+    /// Staleness and confidence check of the quoted price (None for
+    /// quotes made before the check was recorded)
+    pub price_check: Option<PriceCheck>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>, 
 
 }
 
+/// Outcome of the price policy for the price a quote was made at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceCheck {
+    pub passed: bool,
+    /// Seconds since the oldest source price was published
+    pub age_secs: i64,
+    pub max_age_secs: i64,
+    /// Widest confidence interval of the sources, in percent (None when no
+    /// source reports one)
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub confidence_pct: Option<rust_decimal::Decimal>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub max_confidence_pct: rust_decimal::Decimal,
+    /// Why the price was rejected
+    pub reason: Option<String>,
+}
+
 
 impl Quote {
     ///Check if quote is still valid
@@ -210,14 +293,31 @@ impl Quote {
     }
 
     /// Verify funding and execution chains are different
+    ///
+    /// Whether the pair is enabled is decided by ChainPairRegistry
     pub fn has_valid_chain_pair(&self) -> bool {
-        self.funding_chain != self.execution_chain 
-            && Chain::is_pair_supported(self.funding_chain, self.execution_chain)
+        self.funding_chain != self.execution_chain
     }
 
     /// Total amount user must pay on funding chain
+    ///
+    /// Costs are already converted into max_funding_amount; service_fee is
+    /// in execution chain units and must not be added again.
     pub fn total_funding_required(&self) -> rust_decimal::Decimal {
-        self.max_funding_amount + self.service_fee
+        self.max_funding_amount
+    }
+
+    /// Native asset the treasury spends on the execution chain: execution
+    /// cost, plus the delivered amount when it is the native asset
+    pub fn execution_outlay(&self) -> rust_decimal::Decimal {
+        if self
+            .execution_asset
+            .eq_ignore_ascii_case(&Asset::native(self.execution_chain).symbol)
+        {
+            self.execution_cost + self.execution_amount
+        } else {
+            self.execution_cost
+        }
     }
 }
 
@@ -296,6 +396,12 @@ pub enum AuditEventType {
     CircuitBreakerTriggered,
     CircuitBreakerReset,
     LimitExceeded,
+    SettlementDiscrepancy,
+    ChainPairUpdated,
+    QuoteRefreshed,
+    FeeScheduleUpdated,
+    PriceFeedUpdated,
+    QuotePriceRejected,
 }
 
 /// Audit log entry
@@ -327,6 +433,382 @@ impl CircuitBreakerState {
     }
 }
 
+/// Supported chain pair (row of supported_chain_pairs)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainPairConfig {
+    pub funding_chain: Chain,
+    pub execution_chain: Chain,
+    pub enabled: bool,
+    /// Limits on the funding amount, in funding asset units
+    #[serde(with = "rust_decimal::serde::float")]
+    pub min_amount: rust_decimal::Decimal,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub max_amount: Option<rust_decimal::Decimal>,
+    /// Overrides the default service fee rate when set
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub service_fee_rate: Option<rust_decimal::Decimal>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ChainPairConfig {
+    pub fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<Self> {
+        use sqlx::Row;
+        use std::str::FromStr;
+
+        let decimal = |value: sqlx::types::BigDecimal| {
+            rust_decimal::Decimal::from_str(&value.to_string())
+                .map_err(|e| AppError::Internal(format!("Invalid chain pair amount: {}", e)))
+        };
+
+        Ok(Self {
+            funding_chain: row.try_get("funding_chain")?,
+            execution_chain: row.try_get("execution_chain")?,
+            enabled: row.try_get("enabled")?,
+            min_amount: decimal(row.try_get("min_amount")?)?,
+            max_amount: row
+                .try_get::<Option<sqlx::types::BigDecimal>, _>("max_amount")?
+                .map(decimal)
+                .transpose()?,
+            service_fee_rate: row
+                .try_get::<Option<sqlx::types::BigDecimal>, _>("service_fee_rate")?
+                .map(decimal)
+                .transpose()?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    /// Check a trade's funding amount against the pair limits
+    pub fn check_amount(&self, amount: rust_decimal::Decimal) -> Result<(), String> {
+        if amount < self.min_amount {
+            return Err(format!(
+                "Amount {} below minimum {} for {} -> {}",
+                amount, self.min_amount, self.funding_chain, self.execution_chain
+            ));
+        }
+        if let Some(max_amount) = self.max_amount {
+            if amount > max_amount {
+                return Err(format!(
+                    "Amount {} above maximum {} for {} -> {}",
+                    amount, max_amount, self.funding_chain, self.execution_chain
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Service fee schedule (row of fee_schedules)
+///
+/// Selectors left as None match any chain or asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub id: Uuid,
+    pub name: String,
+    pub funding_chain: Option<Chain>,
+    pub execution_chain: Option<Chain>,
+    pub funding_asset: Option<String>,
+    pub execution_asset: Option<String>,
+    /// Minimum rolling 30-day volume of the user, in USD
+    #[serde(with = "rust_decimal::serde::float")]
+    pub min_volume_usd: rust_decimal::Decimal,
+    /// Fraction of the execution cost (0.1% = 0.001)
+    #[serde(with = "rust_decimal::serde::float")]
+    pub fee_rate: rust_decimal::Decimal,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub min_fee_usd: Option<rust_decimal::Decimal>,
+    #[serde(with = "rust_decimal::serde::float_option")]
+    pub max_fee_usd: Option<rust_decimal::Decimal>,
+    /// Promotions take precedence over regular schedules while valid
+    pub promotional: bool,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeeSchedule {
+    pub fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<Self> {
+        use sqlx::Row;
+        use std::str::FromStr;
+
+        let decimal = |value: sqlx::types::BigDecimal| {
+            rust_decimal::Decimal::from_str(&value.to_string())
+                .map_err(|e| AppError::Internal(format!("Invalid fee schedule amount: {}", e)))
+        };
+        let optional = |column: &str| -> AppResult<Option<rust_decimal::Decimal>> {
+            row.try_get::<Option<sqlx::types::BigDecimal>, _>(column)?
+                .map(decimal)
+                .transpose()
+        };
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            funding_chain: row.try_get("funding_chain")?,
+            execution_chain: row.try_get("execution_chain")?,
+            funding_asset: row.try_get("funding_asset")?,
+            execution_asset: row.try_get("execution_asset")?,
+            min_volume_usd: decimal(row.try_get("min_volume_usd")?)?,
+            fee_rate: decimal(row.try_get("fee_rate")?)?,
+            min_fee_usd: optional("min_fee_usd")?,
+            max_fee_usd: optional("max_fee_usd")?,
+            promotional: row.try_get("promotional")?,
+            valid_from: row.try_get("valid_from")?,
+            valid_until: row.try_get("valid_until")?,
+            enabled: row.try_get("enabled")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    /// Enabled and inside its validity window
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.enabled
+            && self.valid_from.is_none_or(|from| from <= now)
+            && self.valid_until.is_none_or(|until| now < until)
+    }
+}
+
+/// Admin-provided fee schedule fields, for create and full update
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeScheduleParams {
+    pub name: String,
+    pub funding_chain: Option<Chain>,
+    pub execution_chain: Option<Chain>,
+    pub funding_asset: Option<String>,
+    pub execution_asset: Option<String>,
+    #[serde(default, with = "rust_decimal::serde::float")]
+    pub min_volume_usd: rust_decimal::Decimal,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub fee_rate: rust_decimal::Decimal,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub min_fee_usd: Option<rust_decimal::Decimal>,
+    #[serde(default, with = "rust_decimal::serde::float_option")]
+    pub max_fee_usd: Option<rust_decimal::Decimal>,
+    #[serde(default)]
+    pub promotional: bool,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl FeeScheduleParams {
+    /// Same rules as the fee_schedules constraints, checked before writing
+    pub fn validate(&self) -> Result<(), String> {
+        use rust_decimal::Decimal;
+
+        if self.name.trim().is_empty() {
+            return Err("Fee schedule name cannot be empty".to_string());
+        }
+        if self.fee_rate < Decimal::ZERO || self.fee_rate >= Decimal::ONE {
+            return Err("fee_rate must be between 0 and 1".to_string());
+        }
+        if self.min_volume_usd < Decimal::ZERO {
+            return Err("min_volume_usd cannot be negative".to_string());
+        }
+        if self.min_fee_usd.is_some_and(|f| f < Decimal::ZERO)
+            || self.max_fee_usd.is_some_and(|f| f < Decimal::ZERO)
+        {
+            return Err("Fee caps cannot be negative".to_string());
+        }
+        if let (Some(min), Some(max)) = (self.min_fee_usd, self.max_fee_usd) {
+            if max < min {
+                return Err("max_fee_usd must be at least min_fee_usd".to_string());
+            }
+        }
+        if let (Some(from), Some(until)) = (self.valid_from, self.valid_until) {
+            if until <= from {
+                return Err("valid_until must be after valid_from".to_string());
+            }
+        }
+        if self.promotional && self.valid_until.is_none() {
+            return Err("Promotional schedules need valid_until".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Pyth Hermes price feed for an asset's USD price
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PriceFeed {
+    pub id: Uuid,
+    /// mainnet or testnet
+    pub network: String,
+    /// None = every chain
+    pub chain: Option<Chain>,
+    pub asset: String,
+    pub feed_id: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PriceFeed {
+    /// Hermes feed IDs are 32 bytes of hex, optionally 0x-prefixed
+    pub fn is_valid_feed_id(feed_id: &str) -> bool {
+        let hex = feed_id.strip_prefix("0x").unwrap_or(feed_id);
+        hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+}
+
+/// Admin-provided price feed mapping, created or replaced by
+/// (network, chain, asset)
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceFeedParams {
+    pub network: String,
+    pub chain: Option<Chain>,
+    pub asset: String,
+    pub feed_id: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl PriceFeedParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.network != "mainnet" && self.network != "testnet" {
+            return Err("network must be mainnet or testnet".to_string());
+        }
+        if self.asset.trim().is_empty() {
+            return Err("asset cannot be empty".to_string());
+        }
+        if !PriceFeed::is_valid_feed_id(&self.feed_id) {
+            return Err(format!("Invalid Pyth feed ID: {}", self.feed_id));
+        }
+        Ok(())
+    }
+}
+
+/// Stored request and response for an Idempotency-Key
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyRecord {
+    /// Client the key belongs to, empty for requests without a user
+    pub client_id: String,
+    pub key: String,
+    /// SHA-256 of method, path and body of the first request
+    pub request_hash: String,
+    /// None while the first request is still being handled
+    pub response_status: Option<i32>,
+    pub response_body: Option<Vec<u8>>,
+    pub response_content_type: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    pub fn is_completed(&self) -> bool {
+        self.response_status.is_some()
+    }
+}
+
+/// Funding payment status - outcome of matching a detected transfer to a quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FundingPaymentStatus {
+    /// Matched a pending quote with sufficient amount; quote committed
+    Matched,
+    /// Matched a pending quote but paid less than required
+    Underpaid,
+    /// Matched a quote that could not be committed (expired, wrong asset, ...)
+    Rejected,
+}
+
+impl FundingPaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FundingPaymentStatus::Matched => "matched",
+            FundingPaymentStatus::Underpaid => "underpaid",
+            FundingPaymentStatus::Rejected => "rejected",
+        }
+    }
+}
+
+/// Funding payment detected by a chain monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPayment {
+    pub id: Uuid,
+    pub chain: Chain,
+    pub transaction_hash: String,
+    pub quote_id: Option<Uuid>,
+    pub payment_address: String,
+    pub sender: Option<String>,
+    pub asset: String,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub amount: rust_decimal::Decimal,
+    pub status: String,
+    pub reason: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl FundingPayment {
+    /// Create from database row
+    pub fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<Self> {
+        use sqlx::Row;
+        use std::str::FromStr;
+
+        let amount: sqlx::types::BigDecimal = row.try_get("amount")?;
+        let amount = rust_decimal::Decimal::from_str(&amount.to_string())
+            .map_err(|_| AppError::InvalidInput("Invalid amount format".to_string()))?;
+
+        Ok(FundingPayment {
+            id: row.try_get("id")?,
+            chain: row.try_get("chain")?,
+            transaction_hash: row.try_get("transaction_hash")?,
+            quote_id: row.try_get("quote_id")?,
+            payment_address: row.try_get("payment_address")?,
+            sender: row.try_get("sender")?,
+            asset: row.try_get("asset")?,
+            amount,
+            status: row.try_get("status")?,
+            reason: row.try_get("reason")?,
+            detected_at: row.try_get("detected_at")?,
+        })
+    }
+}
+
+/// Payment address of pending quotes, with the creation time of the oldest one
+///
+/// Monitors without a checkpoint for the address scan back to `since`.
+#[derive(Debug, Clone)]
+pub struct PendingPaymentAddress {
+    pub payment_address: String,
+    pub since: DateTime<Utc>,
+}
+
+/// Successful execution that has no settlement yet
+#[derive(Debug, Clone)]
+pub struct UnsettledExecution {
+    pub execution_id: Uuid,
+    pub quote_id: Uuid,
+    pub user_id: Uuid,
+    pub funding_chain: Chain,
+    pub funding_asset: String,
+    pub payment_address: Option<String>,
+    pub funding_required: rust_decimal::Decimal,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Execution whose funding payment could not be verified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementDiscrepancy {
+    pub id: Uuid,
+    pub execution_id: Uuid,
+    pub quote_id: Uuid,
+    pub funding_chain: Chain,
+    pub payment_address: Option<String>,
+    #[serde(with = "rust_decimal::serde::float")]
+    pub expected_amount: rust_decimal::Decimal,
+    pub funding_txn_hash: Option<String>,
+    pub reason: String,
+    pub detected_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
 /// Token Approval entity - represents a user-signed approval for token transfer
 /// This uses the signature verification pattern instead of manual transfers
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -346,7 +828,7 @@ pub struct TokenApproval {
     pub signature: Option<String>,
     pub user_wallet: String,
     
-    pub status: String, // "pending", "signed", "submitted", "confirmed", "executed", "failed", "expired", "cancelled"
+    pub status: ApprovalStatus,
     pub transaction_hash: Option<String>,
     pub block_height: Option<i64>,
     pub confirmation_status: Option<String>,
@@ -415,22 +897,22 @@ impl TokenApproval {
 impl TokenApproval {
     /// Check if approval is still valid (not expired and pending)
     pub fn is_valid(&self) -> bool {
-        self.status == "pending" && Utc::now() < self.expires_at
+        self.status == ApprovalStatus::Pending && Utc::now() < self.expires_at
     }
 
     /// Check if approval has been signed
     pub fn is_signed(&self) -> bool {
-        self.status == "signed" && self.signature.is_some()
+        self.status == ApprovalStatus::Signed && self.signature.is_some()
     }
 
     /// Check if approval has been confirmed on-chain
     pub fn is_confirmed(&self) -> bool {
-        self.status == "confirmed" && self.confirmed_at.is_some()
+        self.status == ApprovalStatus::Confirmed && self.confirmed_at.is_some()
     }
 
     /// Check if approval has been executed
     pub fn is_executed(&self) -> bool {
-        self.status == "executed" && self.executed_at.is_some()
+        self.status == ApprovalStatus::Executed && self.executed_at.is_some()
     }
 }
 
@@ -478,7 +960,7 @@ pub struct SubmitTokenApprovalResponse {
 #[derive(Debug, Serialize)]
 pub struct TokenApprovalStatusResponse {
     pub approval_id: Uuid,
-    pub status: String,
+    pub status: ApprovalStatus,
     pub transaction_hash: Option<String>,
     pub confirmation_status: Option<String>,
     pub block_height: Option<i64>,
@@ -486,7 +968,17 @@ pub struct TokenApprovalStatusResponse {
     pub error_message: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Token approval status
+///
+/// Allowed transitions (enforced by LedgerRepository):
+/// - pending   → signed | submitted | failed | expired | cancelled
+/// - signed    → submitted | failed | expired | cancelled
+/// - submitted → confirmed | failed
+/// - confirmed → executed | failed
+/// - executed, failed, expired, cancelled are terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "approval_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum ApprovalStatus {
     Pending,
     Signed,
@@ -512,21 +1004,63 @@ impl ApprovalStatus {
         }
     }
 
-    pub fn from_str(s: &str) -> Self {
+    /// Whether moving from this status to `next` is allowed
+    pub fn can_transition_to(&self, next: ApprovalStatus) -> bool {
+        use ApprovalStatus::*;
+        matches!(
+            (self, next),
+            (Pending, Signed | Submitted | Failed | Expired | Cancelled)
+                | (Signed, Submitted | Failed | Expired | Cancelled)
+                | (Submitted, Confirmed | Failed)
+                | (Confirmed, Executed | Failed)
+        )
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ApprovalStatus::Executed
+                | ApprovalStatus::Failed
+                | ApprovalStatus::Expired
+                | ApprovalStatus::Cancelled
+        )
+    }
+}
+
+impl fmt::Display for ApprovalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ApprovalStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "pending" => ApprovalStatus::Pending,
-            "signed" => ApprovalStatus::Signed,
-            "submitted" => ApprovalStatus::Submitted,
-            "confirmed" => ApprovalStatus::Confirmed,
-            "executed" => ApprovalStatus::Executed,
-            "failed" => ApprovalStatus::Failed,
-            "expired" => ApprovalStatus::Expired,
-            "cancelled" => ApprovalStatus::Cancelled,
-            _ => ApprovalStatus::Pending,
+            "pending" => Ok(ApprovalStatus::Pending),
+            "signed" => Ok(ApprovalStatus::Signed),
+            "submitted" => Ok(ApprovalStatus::Submitted),
+            "confirmed" => Ok(ApprovalStatus::Confirmed),
+            "executed" => Ok(ApprovalStatus::Executed),
+            "failed" => Ok(ApprovalStatus::Failed),
+            "expired" => Ok(ApprovalStatus::Expired),
+            "cancelled" => Ok(ApprovalStatus::Cancelled),
+            other => Err(AppError::InvalidInput(format!("Unknown approval status: {}", other))),
         }
     }
 }
 
+/// Token approval status transition record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApprovalStatusChange {
+    pub approval_id: Uuid,
+    pub from_status: Option<ApprovalStatus>,
+    pub to_status: ApprovalStatus,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
 
 
 
@@ -586,3 +1120,52 @@ impl ApprovalStatus {
 //         assert_eq!(Asset::native(Chain::Near).symbol, "NEAR");
 //     }
 // }
+
+#[cfg(test)]
+mod quote_status_tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_lifecycle() {
+        use QuoteStatus::*;
+        assert!(Pending.can_transition_to(Committed));
+        assert!(Committed.can_transition_to(Executed));
+        assert!(Executed.can_transition_to(Settled));
+
+        assert!(!Committed.can_transition_to(Pending));
+        assert!(!Pending.can_transition_to(Executed));
+        for terminal in [Settled, Expired, Failed] {
+            assert!(terminal.is_terminal());
+        }
+    }
+}
+
+#[cfg(test)]
+mod approval_status_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_approval_transitions() {
+        use ApprovalStatus::*;
+        assert!(Pending.can_transition_to(Submitted));
+        assert!(Submitted.can_transition_to(Confirmed));
+        assert!(Confirmed.can_transition_to(Executed));
+        assert!(Pending.can_transition_to(Expired));
+
+        assert!(!Executed.can_transition_to(Pending));
+        assert!(!Submitted.can_transition_to(Expired));
+        assert!(!Expired.can_transition_to(Submitted));
+        for terminal in [Executed, Failed, Expired, Cancelled] {
+            assert!(terminal.is_terminal());
+            assert!(!terminal.can_transition_to(Failed));
+        }
+    }
+
+    #[test]
+    fn test_approval_status_parsing() {
+        assert_eq!(ApprovalStatus::from_str("confirmed").unwrap(), ApprovalStatus::Confirmed);
+        assert_eq!(ApprovalStatus::Cancelled.to_string(), "cancelled");
+        assert!(ApprovalStatus::from_str("unknown").is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use super::journal::{self, JournalEntry, JournalImbalance, TrialBalance, TrialBalanceRow};
 use super::models::*;
 use crate::error::{AppError, AppResult, ExecutionError, QuoteError};
 use sqlx::types::BigDecimal;
@@ -140,50 +141,70 @@ impl LedgerRepository {
         execution_chain: Chain,
         funding_asset: String,
         execution_asset: String,
+        mode: QuoteMode,
+        funding_amount: BigDecimal,
+        execution_amount: BigDecimal,
         max_funding_amount: BigDecimal,
         execution_cost: BigDecimal,
         service_fee: BigDecimal,
+        fee_schedule_id: Option<Uuid>,
+        price_check: &PriceCheck,
         execution_instructions: Vec<u8>,
         estimated_compute_units: Option<i32>,
+        simulated: bool,
         nonce: String,
         expires_at: chrono::DateTime<chrono::Utc>,
         payment_address: Option<String>,
     ) -> AppResult<Quote> {
+        let price_check = price_check_json(price_check)?;
+        let mut tx = self.begin_tx().await?;
+
         let quote = sqlx::query!(
             r#"
             INSERT INTO quotes (
                 user_id, funding_chain, execution_chain, funding_asset, execution_asset,
-                max_funding_amount, execution_cost, service_fee, execution_instructions,
-                estimated_compute_units, nonce, expires_at, payment_address
+                mode, funding_amount, execution_amount,
+                max_funding_amount, execution_cost, service_fee, fee_schedule_id, price_check,
+                execution_instructions, estimated_compute_units, simulated, nonce, expires_at, payment_address
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
             RETURNING 
                 id, user_id,
                 funding_chain as "funding_chain: Chain",
                 execution_chain as "execution_chain: Chain",
                 funding_asset, execution_asset,
+                mode as "mode: QuoteMode", funding_amount, execution_amount,
                 max_funding_amount, execution_cost, service_fee,
-                execution_instructions, estimated_compute_units, nonce,
+                execution_instructions, estimated_compute_units, simulated, nonce,
                 status as "status: QuoteStatus", expires_at, payment_address,
-                created_at, updated_at
+                superseded_by, fee_schedule_id, price_check, created_at, updated_at
             "#,
             user_id,
             funding_chain as Chain,
             execution_chain as Chain,
             funding_asset,
             execution_asset,
+            mode as QuoteMode,
+            funding_amount,
+            execution_amount,
             BigDecimal::from_str(&max_funding_amount.to_string()).unwrap(),
             BigDecimal::from_str(&execution_cost.to_string()).unwrap(),
             BigDecimal::from_str(&service_fee.to_string()).unwrap(),
+            fee_schedule_id,
+            price_check,
             execution_instructions,
             estimated_compute_units,
+            simulated,
             nonce,
             expires_at,
             payment_address
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        Self::record_quote_transition(&mut tx, quote.id, None, quote.status, Some("created")).await?;
+        tx.commit().await?;
+
         Ok(Quote {
             id: quote.id,
             user_id: quote.user_id,
@@ -191,15 +212,22 @@ impl LedgerRepository {
             execution_chain: quote.execution_chain,
             funding_asset: quote.funding_asset,
             execution_asset: quote.execution_asset,
+            mode: quote.mode,
+            funding_amount: Decimal::from_str(&quote.funding_amount.to_string()).unwrap(),
+            execution_amount: Decimal::from_str(&quote.execution_amount.to_string()).unwrap(),
             max_funding_amount: Decimal::from_str(&quote.max_funding_amount.to_string()).unwrap(),
             execution_cost: Decimal::from_str(&quote.execution_cost.to_string()).unwrap(),
             service_fee: Decimal::from_str(&quote.service_fee.to_string()).unwrap(),
             execution_instructions: quote.execution_instructions,
             estimated_compute_units: quote.estimated_compute_units,
+            simulated: quote.simulated,
             nonce: quote.nonce,
             status: quote.status,
             expires_at: quote.expires_at,
             payment_address: quote.payment_address,
+            superseded_by: quote.superseded_by,
+            fee_schedule_id: quote.fee_schedule_id,
+            price_check: quote.price_check.and_then(|v| serde_json::from_value(v).ok()),
             created_at: quote.created_at,
             updated_at: quote.updated_at,
         })
@@ -213,10 +241,11 @@ impl LedgerRepository {
                 funding_chain as "funding_chain: Chain",
                 execution_chain as "execution_chain: Chain",
                 funding_asset, execution_asset,
+                mode as "mode: QuoteMode", funding_amount, execution_amount,
                 max_funding_amount, execution_cost, service_fee,
-                execution_instructions, estimated_compute_units, nonce,
+                execution_instructions, estimated_compute_units, simulated, nonce,
                 status as "status: QuoteStatus", expires_at, payment_address,
-                created_at, updated_at
+                superseded_by, fee_schedule_id, price_check, created_at, updated_at
             FROM quotes
             WHERE id = $1
             "#,
@@ -225,6 +254,8 @@ impl LedgerRepository {
         .fetch_optional(&self.pool)
         .await?
         .map(|row| {
+            let funding_amount = Decimal::from_str(&row.funding_amount.to_string()).ok()?;
+            let execution_amount = Decimal::from_str(&row.execution_amount.to_string()).ok()?;
             let max_funding_amount = Decimal::from_str(&row.max_funding_amount.to_string()).ok()?;
             let execution_cost = Decimal::from_str(&row.execution_cost.to_string()).ok()?;
             let service_fee = Decimal::from_str(&row.service_fee.to_string()).ok()?;
@@ -235,15 +266,22 @@ impl LedgerRepository {
                 execution_chain: row.execution_chain,
                 funding_asset: row.funding_asset,
                 execution_asset: row.execution_asset,
+                mode: row.mode,
+                funding_amount,
+                execution_amount,
                 max_funding_amount,
                 execution_cost,
                 service_fee,
                 execution_instructions: row.execution_instructions,
                 estimated_compute_units: row.estimated_compute_units,
+                simulated: row.simulated,
                 nonce: row.nonce,
                 status: row.status,
                 expires_at: row.expires_at,
                 payment_address: row.payment_address,
+                superseded_by: row.superseded_by,
+                fee_schedule_id: row.fee_schedule_id,
+                price_check: row.price_check.and_then(|v| serde_json::from_value(v).ok()),
                 created_at: row.created_at,
                 updated_at: row.updated_at,
             })
@@ -252,6 +290,11 @@ impl LedgerRepository {
         Ok(quote)
     }
 
+    /// Move a quote between statuses inside the caller's transaction
+    ///
+    /// SECURITY: The transition must be allowed by QuoteStatus and the quote
+    /// must still be in `from_status`; every transition is recorded in
+    /// quote_status_history and journaled where it moves funds.
     pub async fn update_quote_status(
         &self,
         tx: &mut Transaction<'_, Postgres>,
@@ -259,68 +302,89 @@ impl LedgerRepository {
         from_status: QuoteStatus,
         to_status: QuoteStatus,
     ) -> AppResult<()> {
-        // SECURITY: Validate state machine transitions
+        if !Self::transition_quote(tx, quote_id, from_status, to_status, None).await? {
+            let current = sqlx::query_scalar::<_, QuoteStatus>(
+                "SELECT status FROM quotes WHERE id = $1"
+            )
+            .bind(quote_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+
+            return Err(QuoteError::InvalidState {
+                current: current.map_or("unknown".to_string(), |s| format!("{:?}", s)),
+                expected: format!("{:?}", from_status),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Apply a transition if the quote is still in `from_status`
+    ///
+    /// Returns false if the quote was not in `from_status`
+    async fn transition_quote(
+        tx: &mut Transaction<'_, Postgres>,
+        quote_id: Uuid,
+        from_status: QuoteStatus,
+        to_status: QuoteStatus,
+        reason: Option<&str>,
+    ) -> AppResult<bool> {
         Self::validate_state_transition(from_status, to_status)?;
 
-        let result = sqlx::query!(
+        let result = sqlx::query(
             r#"
             UPDATE quotes
             SET status = $3, updated_at = NOW()
             WHERE id = $1 AND status = $2
-            "#,
-            quote_id,
-            from_status as QuoteStatus,
-            to_status as QuoteStatus
+            "#
         )
+        .bind(quote_id)
+        .bind(from_status as QuoteStatus)
+        .bind(to_status as QuoteStatus)
         .execute(&mut **tx)
         .await?;
 
         if result.rows_affected() == 0 {
-            return Err(QuoteError::InvalidState {
-                current: "unknown".to_string(),
-                expected: format!("{:?}", from_status),
+            return Ok(false);
+        }
+
+        Self::record_quote_transition(tx, quote_id, Some(from_status), to_status, reason).await?;
+
+        // Journal the commit/execution/reversal in the same transaction
+        match (from_status, to_status) {
+            (QuoteStatus::Pending, QuoteStatus::Committed) => {
+                let quote = Self::get_quote_for_journal(tx, quote_id).await?;
+                Self::post_journal_entry(tx, &journal::quote_committed(&quote)).await?;
             }
-            .into());
+            (QuoteStatus::Committed, QuoteStatus::Executed) => {
+                let quote = Self::get_quote_for_journal(tx, quote_id).await?;
+                Self::post_journal_entry(tx, &journal::execution_completed(&quote)).await?;
+            }
+            (QuoteStatus::Committed, QuoteStatus::Failed | QuoteStatus::Expired) => {
+                let quote = Self::get_quote_for_journal(tx, quote_id).await?;
+                Self::post_journal_entry(tx, &journal::quote_reversed(&quote, to_status)).await?;
+            }
+            _ => {}
         }
 
-        Ok(())
+        Ok(true)
     }
 
-    /// Validate quote status state machine transitions
-    /// Valid transitions:
-    /// - Pending → Committed, Expired
-    /// - Committed → Executed, Failed, Expired
-    /// - Executed → Settled, Failed
-    /// - Terminal states (Settled, Failed, Expired) → NO TRANSITIONS ALLOWED
+    /// Validate a quote status transition against QuoteStatus::allowed_transitions
     fn validate_state_transition(from: QuoteStatus, to: QuoteStatus) -> AppResult<()> {
-        let allowed_transitions = match from {
-            QuoteStatus::Pending => vec![
-                QuoteStatus::Committed,
-                QuoteStatus::Expired,
-            ],
-            QuoteStatus::Committed => vec![
-                QuoteStatus::Executed,
-                QuoteStatus::Failed,
-                QuoteStatus::Expired,
-            ],
-            QuoteStatus::Executed => vec![
-                QuoteStatus::Settled,
-                QuoteStatus::Failed,
-            ],
-            // Terminal states - no transitions allowed
-            QuoteStatus::Settled | QuoteStatus::Expired | QuoteStatus::Failed => {
-                return Err(QuoteError::InvalidState {
-                    current: format!("{:?}", from),
-                    expected: "No transitions from terminal states".to_string(),
-                }
-                .into());
+        if from.is_terminal() {
+            return Err(QuoteError::InvalidState {
+                current: format!("{:?}", from),
+                expected: "No transitions from terminal states".to_string(),
             }
-        };
+            .into());
+        }
 
-        if !allowed_transitions.contains(&to) {
+        if !from.can_transition_to(to) {
             return Err(QuoteError::InvalidState {
                 current: format!("{:?}", from),
-                expected: format!("{:?}", allowed_transitions),
+                expected: format!("{:?}", from.allowed_transitions()),
             }
             .into());
         }
@@ -328,38 +392,229 @@ impl LedgerRepository {
         Ok(())
     }
 
-    /// Expire old pending quotes (TTL exceeded)
-    pub async fn expire_old_pending_quotes(&self) -> AppResult<u64> {
-        let result = sqlx::query!(
+    /// Append a row to the quote status history
+    async fn record_quote_transition(
+        tx: &mut Transaction<'_, Postgres>,
+        quote_id: Uuid,
+        from: Option<QuoteStatus>,
+        to: QuoteStatus,
+        reason: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
             r#"
-            UPDATE quotes
-            SET status = $2
-            WHERE status = $1 AND expires_at < NOW()
-            "#,
-            QuoteStatus::Pending as QuoteStatus,
-            QuoteStatus::Expired as QuoteStatus
+            INSERT INTO quote_status_history (quote_id, from_status, to_status, reason)
+            VALUES ($1, $2, $3, $4)
+            "#
         )
-        .execute(&self.pool)
+        .bind(quote_id)
+        .bind(from)
+        .bind(to)
+        .bind(reason)
+        .execute(&mut **tx)
         .await?;
-        
-        Ok(result.rows_affected())
+
+        Ok(())
+    }
+
+    /// Status transitions of a quote, oldest first
+    pub async fn get_quote_status_history(&self, quote_id: Uuid) -> AppResult<Vec<QuoteStatusChange>> {
+        let history = sqlx::query_as::<_, QuoteStatusChange>(
+            r#"
+            SELECT from_status, to_status, reason, changed_at
+            FROM quote_status_history
+            WHERE quote_id = $1
+            ORDER BY changed_at ASC
+            "#
+        )
+        .bind(quote_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(history)
+    }
+
+    /// Expire old pending quotes (TTL exceeded)
+    pub async fn expire_old_pending_quotes(&self) -> AppResult<u64> {
+        self.expire_quotes_in_status(QuoteStatus::Pending).await
     }
 
     /// Expire old committed quotes (TTL exceeded while waiting for execution)
     pub async fn expire_old_committed_quotes(&self) -> AppResult<u64> {
-        let result = sqlx::query!(
+        self.expire_quotes_in_status(QuoteStatus::Committed).await
+    }
+
+    async fn expire_quotes_in_status(&self, from_status: QuoteStatus) -> AppResult<u64> {
+        Self::validate_state_transition(from_status, QuoteStatus::Expired)?;
+
+        let mut tx = self.begin_tx().await?;
+        let expired = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            WITH expired AS (
+                UPDATE quotes
+                SET status = $2, updated_at = NOW()
+                WHERE status = $1 AND expires_at < NOW()
+                RETURNING id
+            ), history AS (
+                INSERT INTO quote_status_history (quote_id, from_status, to_status, reason)
+                SELECT id, $1, $2, 'ttl exceeded'
+                FROM expired
+            )
+            SELECT id FROM expired
+            "#
+        )
+        .bind(from_status as QuoteStatus)
+        .bind(QuoteStatus::Expired as QuoteStatus)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Committed quotes were journaled; reverse their commit
+        if from_status == QuoteStatus::Committed {
+            for quote_id in &expired {
+                let quote = Self::get_quote_for_journal(&mut tx, *quote_id).await?;
+                let entry = journal::quote_reversed(&quote, QuoteStatus::Expired);
+                Self::post_journal_entry(&mut tx, &entry).await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(expired.len() as u64)
+    }
+
+    /// Extend the expiry of a pending quote whose price still holds
+    pub async fn extend_quote_expiry(
+        &self,
+        quote_id: Uuid,
+        expires_at: chrono::DateTime<chrono::Utc>,
+        price_check: &PriceCheck,
+    ) -> AppResult<()> {
+        let price_check = price_check_json(price_check)?;
+        let mut tx = self.begin_tx().await?;
+        Self::lock_refreshable_quote(&mut tx, quote_id).await?;
+
+        sqlx::query(
             r#"
             UPDATE quotes
-            SET status = $2
-            WHERE status = $1 AND expires_at < NOW()
-            "#,
-            QuoteStatus::Committed as QuoteStatus,
-            QuoteStatus::Expired as QuoteStatus
+            SET expires_at = GREATEST(expires_at, $2), price_check = $3, updated_at = NOW()
+            WHERE id = $1
+            "#
         )
-        .execute(&self.pool)
+        .bind(quote_id)
+        .bind(expires_at)
+        .bind(price_check)
+        .execute(&mut *tx)
         .await?;
-        
-        Ok(result.rows_affected())
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replace a pending quote with a repriced one
+    ///
+    /// SECURITY: Atomic - the old quote is expired and linked to its
+    /// replacement in the same transaction that creates it. The replacement
+    /// keeps the nonce and payment address, so only one of them is ever
+    /// pending for a given payment.
+    pub async fn supersede_quote(
+        &self,
+        quote_id: Uuid,
+        funding_amount: BigDecimal,
+        execution_amount: BigDecimal,
+        max_funding_amount: BigDecimal,
+        execution_cost: BigDecimal,
+        service_fee: BigDecimal,
+        fee_schedule_id: Option<Uuid>,
+        price_check: &PriceCheck,
+        estimated_compute_units: Option<i32>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Quote> {
+        let price_check = price_check_json(price_check)?;
+        let mut tx = self.begin_tx().await?;
+        Self::lock_refreshable_quote(&mut tx, quote_id).await?;
+
+        let new_id = Uuid::new_v4();
+        let reason = format!("superseded by {}", new_id);
+        if !Self::transition_quote(&mut tx, quote_id, QuoteStatus::Pending, QuoteStatus::Expired, Some(&reason)).await? {
+            return Err(QuoteError::InvalidState {
+                current: "unknown".to_string(),
+                expected: format!("{:?}", QuoteStatus::Pending),
+            }
+            .into());
+        }
+
+        // Link first: the nonce is only unique among quotes not superseded
+        sqlx::query("UPDATE quotes SET superseded_by = $2 WHERE id = $1")
+            .bind(quote_id)
+            .bind(new_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO quotes (
+                id, user_id, funding_chain, execution_chain, funding_asset, execution_asset,
+                mode, funding_amount, execution_amount,
+                max_funding_amount, execution_cost, service_fee, fee_schedule_id, price_check,
+                execution_instructions, estimated_compute_units, simulated, nonce, expires_at, payment_address
+            )
+            SELECT
+                $2, user_id, funding_chain, execution_chain, funding_asset, execution_asset,
+                mode, $3, $4,
+                $5, $6, $7, $8, $9,
+                execution_instructions, $11, simulated, nonce, $10, payment_address
+            FROM quotes
+            WHERE id = $1
+            "#
+        )
+        .bind(quote_id)
+        .bind(new_id)
+        .bind(funding_amount)
+        .bind(execution_amount)
+        .bind(max_funding_amount)
+        .bind(execution_cost)
+        .bind(service_fee)
+        .bind(fee_schedule_id)
+        .bind(price_check)
+        .bind(expires_at)
+        .bind(estimated_compute_units)
+        .execute(&mut *tx)
+        .await?;
+
+        let reason = format!("supersedes {}", quote_id);
+        Self::record_quote_transition(&mut tx, new_id, None, QuoteStatus::Pending, Some(&reason)).await?;
+        let quote = Self::get_quote_for_journal(&mut tx, new_id).await?;
+        tx.commit().await?;
+
+        Ok(quote)
+    }
+
+    /// Lock a quote that is about to be refreshed
+    ///
+    /// Only pending quotes that have not expired yet can be refreshed
+    async fn lock_refreshable_quote(
+        tx: &mut Transaction<'_, Postgres>,
+        quote_id: Uuid,
+    ) -> AppResult<()> {
+        let (status, expires_at) = sqlx::query_as::<_, (QuoteStatus, chrono::DateTime<chrono::Utc>)>(
+            "SELECT status, expires_at FROM quotes WHERE id = $1 FOR UPDATE"
+        )
+        .bind(quote_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| QuoteError::NotFound(quote_id.to_string()))?;
+
+        if status != QuoteStatus::Pending {
+            return Err(QuoteError::InvalidState {
+                current: format!("{:?}", status),
+                expected: format!("{:?}", QuoteStatus::Pending),
+            }
+            .into());
+        }
+
+        if expires_at <= chrono::Utc::now() {
+            return Err(QuoteError::Expired.into());
+        }
+
+        Ok(())
     }
 
     // ========== EXECUTION OPERATIONS ==========
@@ -474,6 +729,11 @@ impl LedgerRepository {
 
     // ========== SETTLEMENT OPERATIONS ==========
 
+    /// Record an unverified settlement for a successful execution
+    ///
+    /// SECURITY: The settlement is not booked to the journal and the quote is
+    /// not marked settled until the reconciler re-reads the funding payment
+    /// on chain (see `create_verified_settlement`).
     pub async fn create_settlement(
         &self,
         execution_id: Uuid,
@@ -498,13 +758,12 @@ impl LedgerRepository {
         .fetch_one(&self.pool)
         .await?;
 
-        let funding_amount = Decimal::from_str(&settlement.funding_amount.to_string()).unwrap();
         Ok(Settlement {
             id: settlement.id,
             execution_id: settlement.execution_id,
             funding_chain: settlement.funding_chain,
             funding_txn_hash: settlement.funding_txn_hash,
-            funding_amount,
+            funding_amount: Decimal::from_str(&settlement.funding_amount.to_string()).unwrap(),
             settled_at: settlement.settled_at,
             verified_at: settlement.verified_at,
         })
@@ -708,28 +967,6 @@ impl LedgerRepository {
         Ok(())
     }
 
-    /// Update quote status by ID
-    pub async fn update_quote_status_direct(
-        &self,
-        quote_id: &Uuid,
-        to_status: QuoteStatus,
-        transaction_hash: Option<&str>,
-    ) -> AppResult<()> {
-        sqlx::query(
-            r#"
-            UPDATE quotes
-            SET status = $2
-            WHERE id = $1
-            "#
-        )
-        .bind(quote_id)
-        .bind(to_status as QuoteStatus)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
-
     /// Record settlement - insert into settlements table
     pub async fn record_settlement(
         &self,
@@ -857,75 +1094,1032 @@ impl LedgerRepository {
         Ok(result)
     }
 
-    // ========== SPENDING APPROVAL OPERATIONS ==========
+    // ========== FUNDING MONITOR SUPPORT ==========
 
-    /// Create a new spending approval (unsigned, waiting for user signature)
-    pub async fn create_spending_approval(
+    /// Get the resume position of a funding monitor
+    pub async fn get_funding_checkpoint(
         &self,
-        user_id: Uuid,
-        quote_id: Uuid,
-        funding_chain: Chain,
-        asset: &str,
-        approved_amount: BigDecimal,
-        fee_amount: BigDecimal,
-        gas_amount: BigDecimal,
-        execution_amount: BigDecimal,
-        wallet_address: &str,
-        treasury_address: &str,
-        nonce: &str,
-        expires_at: chrono::DateTime<chrono::Utc>,
-    ) -> AppResult<Uuid> {
-        let approval_id = Uuid::new_v4();
-        
-        sqlx::query!(
-            r#"
-            INSERT INTO spending_approvals (
-                id, user_id, quote_id, funding_chain, asset,
-                approved_amount, fee_amount, gas_amount, execution_amount,
-                wallet_address, treasury_address, nonce, expires_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-            "#,
-            approval_id,
-            user_id,
-            quote_id,
-            funding_chain as Chain,
-            asset,
-            approved_amount,
-            fee_amount,
-            gas_amount,
-            execution_amount,
-            wallet_address,
-            treasury_address,
-            nonce,
-            expires_at
+        chain: Chain,
+        scope: &str,
+    ) -> AppResult<Option<String>> {
+        let cursor = sqlx::query_scalar::<_, String>(
+            "SELECT cursor FROM funding_checkpoints WHERE chain = $1 AND scope = $2"
         )
-        .execute(&self.pool)
+        .bind(chain as Chain)
+        .bind(scope)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(approval_id)
+        Ok(cursor)
     }
 
-    /// Get spending approval by ID with full details
-    pub async fn get_spending_approval(&self, approval_id: &Uuid) -> AppResult<Option<crate::api::spending_approval::SpendingApproval>> {
-        let row = sqlx::query!(
+    /// Persist the resume position of a funding monitor
+    pub async fn save_funding_checkpoint(
+        &self,
+        chain: Chain,
+        scope: &str,
+        cursor: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
             r#"
-            SELECT 
-                id, user_id, quote_id, funding_chain as "funding_chain: Chain",
-                asset, approved_amount, fee_amount, gas_amount, execution_amount,
-                wallet_address, treasury_address, user_signature,
-                nonce, is_used, used_at, created_at, expires_at
-            FROM spending_approvals
-            WHERE id = $1
-            "#,
-            approval_id
+            INSERT INTO funding_checkpoints (chain, scope, cursor, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (chain, scope)
+            DO UPDATE SET cursor = EXCLUDED.cursor, updated_at = EXCLUDED.updated_at
+            "#
         )
-        .fetch_optional(&self.pool)
+        .bind(chain as Chain)
+        .bind(scope)
+        .bind(cursor)
+        .execute(&self.pool)
         .await?;
 
-        Ok(row.map(|r| crate::api::spending_approval::SpendingApproval {
-            id: r.id,
-            user_id: r.user_id,
+        Ok(())
+    }
+
+    /// Payment addresses of quotes still waiting for funding on a chain
+    pub async fn get_pending_payment_addresses(
+        &self,
+        chain: Chain,
+    ) -> AppResult<Vec<PendingPaymentAddress>> {
+        let rows = sqlx::query_as::<_, (String, chrono::DateTime<chrono::Utc>)>(
+            r#"
+            SELECT payment_address, MIN(created_at)
+            FROM quotes
+            WHERE funding_chain = $1
+              AND status = $2
+              AND expires_at > NOW()
+              AND payment_address IS NOT NULL
+            GROUP BY payment_address
+            "#
+        )
+        .bind(chain as Chain)
+        .bind(QuoteStatus::Pending as QuoteStatus)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(payment_address, since)| PendingPaymentAddress { payment_address, since })
+            .collect())
+    }
+
+    /// Transactions among `transaction_hashes` that were already recorded
+    pub async fn get_recorded_funding_transactions(
+        &self,
+        chain: Chain,
+        transaction_hashes: &[String],
+    ) -> AppResult<std::collections::HashSet<String>> {
+        let recorded = sqlx::query_scalar::<_, String>(
+            "SELECT transaction_hash FROM funding_payments WHERE chain = $1 AND transaction_hash = ANY($2)"
+        )
+        .bind(chain as Chain)
+        .bind(transaction_hashes)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recorded.into_iter().collect())
+    }
+
+    /// Find the pending quote a payment address belongs to
+    pub async fn get_pending_quote_by_payment_address(
+        &self,
+        chain: Chain,
+        payment_address: &str,
+    ) -> AppResult<Option<Quote>> {
+        let quote_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM quotes
+            WHERE funding_chain = $1 AND payment_address = $2 AND status = $3
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(chain as Chain)
+        .bind(payment_address)
+        .bind(QuoteStatus::Pending as QuoteStatus)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match quote_id {
+            Some(id) => self.get_quote(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Record a detected funding payment
+    ///
+    /// SECURITY: Returns false if this transaction was already recorded,
+    /// so a transfer can never fund a quote twice
+    pub async fn record_funding_payment(
+        &self,
+        chain: Chain,
+        transaction_hash: &str,
+        quote_id: Option<Uuid>,
+        payment_address: &str,
+        sender: Option<&str>,
+        asset: &str,
+        amount: Decimal,
+        status: FundingPaymentStatus,
+        reason: Option<&str>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO funding_payments (
+                chain, transaction_hash, quote_id, payment_address, sender, asset, amount, status, reason
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (chain, transaction_hash) DO NOTHING
+            "#
+        )
+        .bind(chain as Chain)
+        .bind(transaction_hash)
+        .bind(quote_id)
+        .bind(payment_address)
+        .bind(sender)
+        .bind(asset)
+        .bind(BigDecimal::from_str(&amount.to_string()).unwrap())
+        .bind(status.as_str())
+        .bind(reason)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Update the outcome of a recorded funding payment
+    pub async fn update_funding_payment_status(
+        &self,
+        chain: Chain,
+        transaction_hash: &str,
+        status: FundingPaymentStatus,
+        reason: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE funding_payments SET status = $3, reason = $4 WHERE chain = $1 AND transaction_hash = $2"
+        )
+        .bind(chain as Chain)
+        .bind(transaction_hash)
+        .bind(status.as_str())
+        .bind(reason)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // ========== SETTLEMENT RECONCILIATION ==========
+
+    /// Successful executions with no verified settlement and no open discrepancy
+    pub async fn get_unsettled_executions(&self, limit: i64) -> AppResult<Vec<UnsettledExecution>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                e.id AS execution_id, e.quote_id, e.executed_at,
+                q.user_id, q.funding_chain, q.funding_asset, q.payment_address,
+                q.max_funding_amount AS funding_required
+            FROM executions e
+            JOIN quotes q ON q.id = e.quote_id
+            WHERE e.status = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM settlements s
+                  WHERE s.execution_id = e.id AND s.verified_at IS NOT NULL
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM settlement_discrepancies d
+                  WHERE d.execution_id = e.id AND d.resolved_at IS NULL
+              )
+            ORDER BY e.executed_at ASC
+            LIMIT $2
+            "#
+        )
+        .bind(ExecutionStatus::Success as ExecutionStatus)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                use sqlx::Row;
+                let funding_required: BigDecimal = row.try_get("funding_required")?;
+                Ok(UnsettledExecution {
+                    execution_id: row.try_get("execution_id")?,
+                    quote_id: row.try_get("quote_id")?,
+                    user_id: row.try_get("user_id")?,
+                    funding_chain: row.try_get("funding_chain")?,
+                    funding_asset: row.try_get("funding_asset")?,
+                    payment_address: row.try_get("payment_address")?,
+                    funding_required: Decimal::from_str(&funding_required.to_string())
+                        .map_err(|e| AppError::Internal(e.to_string()))?,
+                    executed_at: row.try_get("executed_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Funding payment that was matched to a quote by a chain monitor
+    pub async fn get_matched_funding_payment(&self, quote_id: Uuid) -> AppResult<Option<FundingPayment>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, chain, transaction_hash, quote_id, payment_address, sender,
+                   asset, amount, status, reason, detected_at
+            FROM funding_payments
+            WHERE quote_id = $1 AND status = $2
+            ORDER BY detected_at ASC
+            LIMIT 1
+            "#
+        )
+        .bind(quote_id)
+        .bind(FundingPaymentStatus::Matched.as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(FundingPayment::from_row).transpose()
+    }
+
+    /// Settlement recorded for an execution that was not verified yet
+    ///
+    /// Payments accepted through the webhook are only recorded this way.
+    pub async fn get_unverified_settlement(&self, execution_id: Uuid) -> AppResult<Option<Settlement>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, execution_id, funding_chain, funding_txn_hash, funding_amount, settled_at, verified_at
+            FROM settlements
+            WHERE execution_id = $1 AND verified_at IS NULL
+            "#
+        )
+        .bind(execution_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            use sqlx::Row;
+            let funding_amount: BigDecimal = row.try_get("funding_amount")?;
+            Ok(Settlement {
+                id: row.try_get("id")?,
+                execution_id: row.try_get("execution_id")?,
+                funding_chain: row.try_get("funding_chain")?,
+                funding_txn_hash: row.try_get("funding_txn_hash")?,
+                funding_amount: Decimal::from_str(&funding_amount.to_string())
+                    .map_err(|e| AppError::Internal(e.to_string()))?,
+                settled_at: row.try_get("settled_at")?,
+                verified_at: row.try_get("verified_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Record a settlement whose funding payment was verified on chain,
+    /// verifying the unverified settlement of the execution if there is one
+    ///
+    /// Returns None if the execution was settled concurrently
+    pub async fn create_verified_settlement(
+        &self,
+        execution_id: Uuid,
+        funding_chain: Chain,
+        funding_txn_hash: &str,
+        funding_amount: Decimal,
+    ) -> AppResult<Option<Uuid>> {
+        let mut tx = self.begin_tx().await?;
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO settlements (execution_id, funding_chain, funding_txn_hash, funding_amount, verified_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (execution_id) DO UPDATE
+            SET funding_txn_hash = EXCLUDED.funding_txn_hash,
+                funding_amount = EXCLUDED.funding_amount,
+                verified_at = EXCLUDED.verified_at
+            WHERE settlements.verified_at IS NULL
+            RETURNING id
+            "#
+        )
+        .bind(execution_id)
+        .bind(funding_chain as Chain)
+        .bind(funding_txn_hash)
+        .bind(BigDecimal::from_str(&funding_amount.to_string()).unwrap())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(settlement_id) = id {
+            Self::post_settlement_entry(&mut tx, settlement_id, execution_id, funding_chain, funding_amount)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(id)
+    }
+
+    /// Flag an execution with no verifiable funding payment
+    ///
+    /// Returns false if the execution was already flagged
+    pub async fn create_settlement_discrepancy(
+        &self,
+        execution: &UnsettledExecution,
+        funding_txn_hash: Option<&str>,
+        reason: &str,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO settlement_discrepancies (
+                execution_id, quote_id, funding_chain, payment_address,
+                expected_amount, funding_txn_hash, reason
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (execution_id) DO NOTHING
+            "#
+        )
+        .bind(execution.execution_id)
+        .bind(execution.quote_id)
+        .bind(execution.funding_chain as Chain)
+        .bind(&execution.payment_address)
+        .bind(BigDecimal::from_str(&execution.funding_required.to_string()).unwrap())
+        .bind(funding_txn_hash)
+        .bind(reason)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Unresolved settlement discrepancies, newest first
+    pub async fn get_open_settlement_discrepancies(&self) -> AppResult<Vec<SettlementDiscrepancy>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, execution_id, quote_id, funding_chain, payment_address,
+                   expected_amount, funding_txn_hash, reason, detected_at, resolved_at
+            FROM settlement_discrepancies
+            WHERE resolved_at IS NULL
+            ORDER BY detected_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                use sqlx::Row;
+                let expected_amount: BigDecimal = row.try_get("expected_amount")?;
+                Ok(SettlementDiscrepancy {
+                    id: row.try_get("id")?,
+                    execution_id: row.try_get("execution_id")?,
+                    quote_id: row.try_get("quote_id")?,
+                    funding_chain: row.try_get("funding_chain")?,
+                    payment_address: row.try_get("payment_address")?,
+                    expected_amount: Decimal::from_str(&expected_amount.to_string())
+                        .map_err(|e| AppError::Internal(e.to_string()))?,
+                    funding_txn_hash: row.try_get("funding_txn_hash")?,
+                    reason: row.try_get("reason")?,
+                    detected_at: row.try_get("detected_at")?,
+                    resolved_at: row.try_get("resolved_at")?,
+                })
+            })
+            .collect()
+    }
+
+    // ========== CHAIN PAIR CONFIGURATION ==========
+
+    /// All configured chain pairs, enabled or not
+    pub async fn get_chain_pairs(&self) -> AppResult<Vec<ChainPairConfig>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT funding_chain, execution_chain, enabled, min_amount, max_amount,
+                   service_fee_rate, updated_at
+            FROM supported_chain_pairs
+            ORDER BY funding_chain, execution_chain
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(ChainPairConfig::from_row).collect()
+    }
+
+    /// Update a chain pair; fields left as None keep their current value,
+    /// `Some(None)` clears `max_amount` or `service_fee_rate`
+    pub async fn update_chain_pair(
+        &self,
+        funding_chain: Chain,
+        execution_chain: Chain,
+        enabled: Option<bool>,
+        min_amount: Option<Decimal>,
+        max_amount: Option<Option<Decimal>>,
+        service_fee_rate: Option<Option<Decimal>>,
+    ) -> AppResult<ChainPairConfig> {
+        let to_big = |d: Decimal| BigDecimal::from_str(&d.to_string()).unwrap();
+
+        let row = sqlx::query(
+            r#"
+            UPDATE supported_chain_pairs
+            SET enabled = COALESCE($3, enabled),
+                min_amount = COALESCE($4, min_amount),
+                max_amount = CASE WHEN $5 THEN $6 ELSE max_amount END,
+                service_fee_rate = CASE WHEN $7 THEN $8 ELSE service_fee_rate END
+            WHERE funding_chain = $1 AND execution_chain = $2
+            RETURNING funding_chain, execution_chain, enabled, min_amount, max_amount,
+                      service_fee_rate, updated_at
+            "#
+        )
+        .bind(funding_chain as Chain)
+        .bind(execution_chain as Chain)
+        .bind(enabled)
+        .bind(min_amount.map(to_big))
+        .bind(max_amount.is_some())
+        .bind(max_amount.flatten().map(to_big))
+        .bind(service_fee_rate.is_some())
+        .bind(service_fee_rate.flatten().map(to_big))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Chain pair not configured: {} -> {}",
+                funding_chain, execution_chain
+            ))
+        })?;
+
+        ChainPairConfig::from_row(&row)
+    }
+
+    // ========== FEE SCHEDULES ==========
+
+    /// All fee schedules, enabled or not
+    pub async fn get_fee_schedules(&self) -> AppResult<Vec<FeeSchedule>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, funding_chain, execution_chain, funding_asset, execution_asset,
+                   min_volume_usd, fee_rate, min_fee_usd, max_fee_usd,
+                   promotional, valid_from, valid_until, enabled, created_at, updated_at
+            FROM fee_schedules
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(FeeSchedule::from_row).collect()
+    }
+
+    pub async fn create_fee_schedule(&self, params: &FeeScheduleParams) -> AppResult<FeeSchedule> {
+        let to_big = |d: Decimal| BigDecimal::from_str(&d.to_string()).unwrap();
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO fee_schedules (
+                name, funding_chain, execution_chain, funding_asset, execution_asset,
+                min_volume_usd, fee_rate, min_fee_usd, max_fee_usd,
+                promotional, valid_from, valid_until, enabled
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id, name, funding_chain, execution_chain, funding_asset, execution_asset,
+                      min_volume_usd, fee_rate, min_fee_usd, max_fee_usd,
+                      promotional, valid_from, valid_until, enabled, created_at, updated_at
+            "#
+        )
+        .bind(&params.name)
+        .bind(params.funding_chain)
+        .bind(params.execution_chain)
+        .bind(&params.funding_asset)
+        .bind(&params.execution_asset)
+        .bind(to_big(params.min_volume_usd))
+        .bind(to_big(params.fee_rate))
+        .bind(params.min_fee_usd.map(to_big))
+        .bind(params.max_fee_usd.map(to_big))
+        .bind(params.promotional)
+        .bind(params.valid_from)
+        .bind(params.valid_until)
+        .bind(params.enabled)
+        .fetch_one(&self.pool)
+        .await?;
+
+        FeeSchedule::from_row(&row)
+    }
+
+    /// Replace all fields of a fee schedule
+    pub async fn update_fee_schedule(
+        &self,
+        id: Uuid,
+        params: &FeeScheduleParams,
+    ) -> AppResult<FeeSchedule> {
+        let to_big = |d: Decimal| BigDecimal::from_str(&d.to_string()).unwrap();
+
+        let row = sqlx::query(
+            r#"
+            UPDATE fee_schedules
+            SET name = $2, funding_chain = $3, execution_chain = $4,
+                funding_asset = $5, execution_asset = $6,
+                min_volume_usd = $7, fee_rate = $8, min_fee_usd = $9, max_fee_usd = $10,
+                promotional = $11, valid_from = $12, valid_until = $13, enabled = $14
+            WHERE id = $1
+            RETURNING id, name, funding_chain, execution_chain, funding_asset, execution_asset,
+                      min_volume_usd, fee_rate, min_fee_usd, max_fee_usd,
+                      promotional, valid_from, valid_until, enabled, created_at, updated_at
+            "#
+        )
+        .bind(id)
+        .bind(&params.name)
+        .bind(params.funding_chain)
+        .bind(params.execution_chain)
+        .bind(&params.funding_asset)
+        .bind(&params.execution_asset)
+        .bind(to_big(params.min_volume_usd))
+        .bind(to_big(params.fee_rate))
+        .bind(params.min_fee_usd.map(to_big))
+        .bind(params.max_fee_usd.map(to_big))
+        .bind(params.promotional)
+        .bind(params.valid_from)
+        .bind(params.valid_until)
+        .bind(params.enabled)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Fee schedule not found: {}", id)))?;
+
+        FeeSchedule::from_row(&row)
+    }
+
+    /// Funding volume of a user's executed quotes since `since`, per funding asset
+    pub async fn get_user_volume(
+        &self,
+        user_id: Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Vec<(Chain, String, Decimal)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT funding_chain, funding_asset, SUM(funding_amount) AS volume
+            FROM quotes
+            WHERE user_id = $1 AND created_at >= $2 AND status IN ($3, $4)
+            GROUP BY funding_chain, funding_asset
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(QuoteStatus::Executed as QuoteStatus)
+        .bind(QuoteStatus::Settled as QuoteStatus)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                use sqlx::Row;
+                let volume: BigDecimal = row.try_get("volume")?;
+                Ok((
+                    row.try_get("funding_chain")?,
+                    row.try_get("funding_asset")?,
+                    Decimal::from_str(&volume.to_string())
+                        .map_err(|e| AppError::Internal(e.to_string()))?,
+                ))
+            })
+            .collect()
+    }
+
+    // ========== PRICE FEEDS ==========
+
+    /// All Pyth price feed mappings, enabled or not
+    pub async fn get_price_feeds(&self) -> AppResult<Vec<PriceFeed>> {
+        let feeds = sqlx::query_as::<_, PriceFeed>(
+            r#"
+            SELECT id, network, chain, asset, feed_id, enabled, created_at, updated_at
+            FROM pyth_price_feeds
+            ORDER BY network, asset, chain NULLS FIRST
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(feeds)
+    }
+
+    /// Create the mapping for (network, chain, asset) or replace its feed
+    pub async fn upsert_price_feed(&self, params: &PriceFeedParams) -> AppResult<PriceFeed> {
+        // Feeds for every chain and chain overrides have separate unique indexes
+        let conflict = match params.chain {
+            Some(_) => "(network, chain, asset) WHERE chain IS NOT NULL",
+            None => "(network, asset) WHERE chain IS NULL",
+        };
+        let query = format!(
+            r#"
+            INSERT INTO pyth_price_feeds (network, chain, asset, feed_id, enabled)
+            VALUES ($1, $2, upper($3), $4, $5)
+            ON CONFLICT {}
+            DO UPDATE SET feed_id = EXCLUDED.feed_id, enabled = EXCLUDED.enabled
+            RETURNING id, network, chain, asset, feed_id, enabled, created_at, updated_at
+            "#,
+            conflict
+        );
+        let feed = sqlx::query_as::<_, PriceFeed>(&query)
+        .bind(&params.network)
+        .bind(params.chain)
+        .bind(params.asset.trim())
+        .bind(&params.feed_id)
+        .bind(params.enabled)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(feed)
+    }
+
+    // ========== IDEMPOTENCY KEYS ==========
+
+    /// Claim an idempotency key for a new request
+    ///
+    /// Returns false if the key is held by an unexpired record. Expired
+    /// records, and in-flight records for the same request abandoned for
+    /// longer than `stale_after_seconds`, are taken over.
+    pub async fn claim_idempotency_key(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+        ttl_seconds: i64,
+        stale_after_seconds: i64,
+    ) -> AppResult<bool> {
+        let claimed = sqlx::query_scalar::<_, String>(
+            r#"
+            INSERT INTO idempotency_keys (client_id, key, request_hash, expires_at)
+            VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+            ON CONFLICT (client_id, key) DO UPDATE
+            SET request_hash = EXCLUDED.request_hash,
+                response_status = NULL,
+                response_body = NULL,
+                response_content_type = NULL,
+                created_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+            WHERE idempotency_keys.expires_at <= NOW()
+               OR (idempotency_keys.response_status IS NULL
+                   AND idempotency_keys.request_hash = EXCLUDED.request_hash
+                   AND idempotency_keys.created_at < NOW() - make_interval(secs => $5))
+            RETURNING key
+            "#
+        )
+        .bind(client_id)
+        .bind(key)
+        .bind(request_hash)
+        .bind(ttl_seconds as f64)
+        .bind(stale_after_seconds as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+
+    pub async fn get_idempotency_record(
+        &self,
+        client_id: &str,
+        key: &str,
+    ) -> AppResult<Option<IdempotencyRecord>> {
+        let record = sqlx::query_as::<_, IdempotencyRecord>(
+            r#"
+            SELECT client_id, key, request_hash, response_status, response_body,
+                   response_content_type, created_at, expires_at
+            FROM idempotency_keys
+            WHERE client_id = $1 AND key = $2 AND expires_at > NOW()
+            "#
+        )
+        .bind(client_id)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Store the response of the request holding the key
+    pub async fn complete_idempotency_key(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+        status: u16,
+        body: &[u8],
+        content_type: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys
+            SET response_status = $4, response_body = $5, response_content_type = $6
+            WHERE client_id = $1 AND key = $2 AND request_hash = $3 AND response_status IS NULL
+            "#
+        )
+        .bind(client_id)
+        .bind(key)
+        .bind(request_hash)
+        .bind(status as i32)
+        .bind(body)
+        .bind(content_type)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drop an in-flight key so the request can be retried
+    pub async fn release_idempotency_key(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE client_id = $1 AND key = $2 AND request_hash = $3 AND response_status IS NULL
+            "#
+        )
+        .bind(client_id)
+        .bind(key)
+        .bind(request_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_expired_idempotency_keys(&self) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ========== DOUBLE-ENTRY JOURNAL ==========
+
+    /// Post a journal entry inside the caller's transaction
+    ///
+    /// SECURITY: The deferred journal_entry_balanced trigger checks the entry
+    /// balances per asset at commit; journal-wide totals are checked by
+    /// `get_trial_balance`. Re-posting the same event is a no-op.
+    async fn post_journal_entry(
+        tx: &mut Transaction<'_, Postgres>,
+        entry: &JournalEntry,
+    ) -> AppResult<()> {
+        if entry.lines.is_empty() {
+            return Ok(());
+        }
+
+        let entry_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO journal_entries (event, reference_id, quote_id, description)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (event, reference_id) DO NOTHING
+            RETURNING id
+            "#
+        )
+        .bind(entry.event)
+        .bind(entry.reference_id)
+        .bind(entry.quote_id)
+        .bind(&entry.description)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(entry_id) = entry_id else {
+            return Ok(());
+        };
+
+        for line in &entry.lines {
+            sqlx::query(
+                r#"
+                INSERT INTO journal_lines (entry_id, account, chain, asset, debit, credit)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(entry_id)
+            .bind(line.account)
+            .bind(line.chain as Chain)
+            .bind(&line.asset)
+            .bind(to_big_decimal(line.debit)?)
+            .bind(to_big_decimal(line.credit)?)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Quote amounts as seen by the current transaction
+    async fn get_quote_for_journal(
+        tx: &mut Transaction<'_, Postgres>,
+        quote_id: Uuid,
+    ) -> AppResult<Quote> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, funding_chain, execution_chain, funding_asset, execution_asset,
+                   mode, funding_amount, execution_amount, max_funding_amount, execution_cost, service_fee, execution_instructions,
+                   estimated_compute_units, simulated, nonce, status, expires_at, payment_address,
+                   superseded_by, fee_schedule_id, price_check, created_at, updated_at
+            FROM quotes
+            WHERE id = $1
+            "#
+        )
+        .bind(quote_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Quote not found: {}", quote_id)))?;
+
+        let decimal = |column: &str| -> AppResult<Decimal> {
+            let value: BigDecimal = row.try_get(column)?;
+            Decimal::from_str(&value.to_string())
+                .map_err(|e| AppError::Internal(format!("Invalid {}: {}", column, e)))
+        };
+
+        Ok(Quote {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            funding_chain: row.try_get("funding_chain")?,
+            execution_chain: row.try_get("execution_chain")?,
+            funding_asset: row.try_get("funding_asset")?,
+            execution_asset: row.try_get("execution_asset")?,
+            mode: row.try_get("mode")?,
+            funding_amount: decimal("funding_amount")?,
+            execution_amount: decimal("execution_amount")?,
+            max_funding_amount: decimal("max_funding_amount")?,
+            execution_cost: decimal("execution_cost")?,
+            service_fee: decimal("service_fee")?,
+            execution_instructions: row.try_get("execution_instructions")?,
+            estimated_compute_units: row.try_get("estimated_compute_units")?,
+            simulated: row.try_get("simulated")?,
+            nonce: row.try_get("nonce")?,
+            status: row.try_get("status")?,
+            expires_at: row.try_get("expires_at")?,
+            payment_address: row.try_get("payment_address")?,
+            superseded_by: row.try_get("superseded_by")?,
+            fee_schedule_id: row.try_get("fee_schedule_id")?,
+            price_check: row
+                .try_get::<Option<serde_json::Value>, _>("price_check")?
+                .and_then(|v| serde_json::from_value(v).ok()),
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    /// Journal a settlement of the execution's funding payment and settle its quote
+    async fn post_settlement_entry(
+        tx: &mut Transaction<'_, Postgres>,
+        settlement_id: Uuid,
+        execution_id: Uuid,
+        funding_chain: Chain,
+        funding_amount: Decimal,
+    ) -> AppResult<()> {
+        let (quote_id, funding_asset) = sqlx::query_as::<_, (Uuid, String)>(
+            r#"
+            SELECT q.id, q.funding_asset
+            FROM executions e
+            JOIN quotes q ON q.id = e.quote_id
+            WHERE e.id = $1
+            "#
+        )
+        .bind(execution_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        let entry = journal::settlement_recorded(
+            settlement_id,
+            quote_id,
+            funding_chain,
+            &funding_asset,
+            funding_amount,
+        );
+        Self::post_journal_entry(tx, &entry).await?;
+
+        // Quotes still marked committed by a lagging executor stay as they are
+        Self::transition_quote(
+            tx,
+            quote_id,
+            QuoteStatus::Executed,
+            QuoteStatus::Settled,
+            Some("funding settled"),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Trial balance per account, with debits = credits checked per entry and per asset
+    pub async fn get_trial_balance(&self) -> AppResult<TrialBalance> {
+        use sqlx::Row;
+
+        let to_decimal = |value: BigDecimal| Decimal::from_str(&value.to_string()).unwrap_or_default();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT account, chain, asset, SUM(debit) AS debit, SUM(credit) AS credit
+            FROM journal_lines
+            GROUP BY account, chain, asset
+            ORDER BY chain, asset, account
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut accounts = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let debit = to_decimal(row.try_get("debit")?);
+            let credit = to_decimal(row.try_get("credit")?);
+            accounts.push(TrialBalanceRow {
+                account: row.try_get("account")?,
+                chain: row.try_get("chain")?,
+                asset: row.try_get("asset")?,
+                debit,
+                credit,
+                balance: debit - credit,
+            });
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT entry_id, chain, asset, SUM(debit) AS debit, SUM(credit) AS credit
+            FROM journal_lines
+            GROUP BY entry_id, chain, asset
+            HAVING SUM(debit) <> SUM(credit)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut imbalances = Vec::with_capacity(rows.len());
+        for row in &rows {
+            imbalances.push(JournalImbalance {
+                entry_id: Some(row.try_get("entry_id")?),
+                chain: row.try_get("chain")?,
+                asset: row.try_get("asset")?,
+                debit: to_decimal(row.try_get("debit")?),
+                credit: to_decimal(row.try_get("credit")?),
+            });
+        }
+
+        Ok(TrialBalance::new(accounts, imbalances))
+    }
+
+    // ========== SPENDING APPROVAL OPERATIONS ==========
+
+    /// Create a new spending approval (unsigned, waiting for user signature)
+    pub async fn create_spending_approval(
+        &self,
+        user_id: Uuid,
+        quote_id: Uuid,
+        funding_chain: Chain,
+        asset: &str,
+        approved_amount: BigDecimal,
+        fee_amount: BigDecimal,
+        gas_amount: BigDecimal,
+        execution_amount: BigDecimal,
+        wallet_address: &str,
+        treasury_address: &str,
+        nonce: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Uuid> {
+        let approval_id = Uuid::new_v4();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO spending_approvals (
+                id, user_id, quote_id, funding_chain, asset,
+                approved_amount, fee_amount, gas_amount, execution_amount,
+                wallet_address, treasury_address, nonce, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            "#,
+            approval_id,
+            user_id,
+            quote_id,
+            funding_chain as Chain,
+            asset,
+            approved_amount,
+            fee_amount,
+            gas_amount,
+            execution_amount,
+            wallet_address,
+            treasury_address,
+            nonce,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(approval_id)
+    }
+
+    /// Get spending approval by ID with full details
+    pub async fn get_spending_approval(&self, approval_id: &Uuid) -> AppResult<Option<crate::api::spending_approval::SpendingApproval>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT 
+                id, user_id, quote_id, funding_chain as "funding_chain: Chain",
+                asset, approved_amount, fee_amount, gas_amount, execution_amount,
+                wallet_address, treasury_address, user_signature,
+                nonce, is_used, used_at, created_at, expires_at
+            FROM spending_approvals
+            WHERE id = $1
+            "#,
+            approval_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| crate::api::spending_approval::SpendingApproval {
+            id: r.id,
+            user_id: r.user_id,
             funding_chain: r.funding_chain,
             approved_amount: Decimal::from_str(&r.approved_amount.to_string()).unwrap_or_default(),
             fee_amount: Decimal::from_str(&r.fee_amount.to_string()).unwrap_or_default(),
@@ -1191,6 +2385,8 @@ impl LedgerRepository {
 
     /// Create new token approval (unsigned)
     pub async fn create_token_approval(&self, approval: &TokenApproval) -> AppResult<()> {
+        let mut tx = self.begin_tx().await?;
+
         sqlx::query(
             r#"
             INSERT INTO token_approvals (
@@ -1209,10 +2405,65 @@ impl LedgerRepository {
         .bind(&approval.message)
         .bind(&approval.nonce)
         .bind(&approval.user_wallet)
-        .bind(&approval.status)
+        .bind(approval.status)
         .bind(approval.created_at)
         .bind(approval.expires_at)
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_approval_transition(&mut tx, &approval.id, None, approval.status, Some("created"))
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Lock an approval row and check that it may move to `next`
+    ///
+    /// SECURITY: Rejects illegal moves such as executed -> pending, so a
+    /// replayed or out-of-order update can never rewind an approval.
+    async fn lock_approval_for_transition(
+        tx: &mut Transaction<'_, Postgres>,
+        approval_id: &Uuid,
+        next: ApprovalStatus,
+    ) -> AppResult<ApprovalStatus> {
+        let current = sqlx::query_scalar::<_, ApprovalStatus>(
+            "SELECT status FROM token_approvals WHERE id = $1 FOR UPDATE"
+        )
+        .bind(approval_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Approval not found: {}", approval_id)))?;
+
+        if !current.can_transition_to(next) {
+            return Err(AppError::InvalidInput(format!(
+                "Illegal token approval transition: {} -> {}",
+                current, next
+            )));
+        }
+
+        Ok(current)
+    }
+
+    /// Append a row to the approval status history
+    async fn record_approval_transition(
+        tx: &mut Transaction<'_, Postgres>,
+        approval_id: &Uuid,
+        from: Option<ApprovalStatus>,
+        to: ApprovalStatus,
+        reason: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO token_approval_status_history (approval_id, from_status, to_status, reason)
+            VALUES ($1, $2, $3, $4)
+            "#
+        )
+        .bind(approval_id)
+        .bind(from)
+        .bind(to)
+        .bind(reason)
+        .execute(&mut **tx)
         .await?;
 
         Ok(())
@@ -1225,6 +2476,10 @@ impl LedgerRepository {
         signature: &str,
         tx_hash: &str,
     ) -> AppResult<()> {
+        let mut tx = self.begin_tx().await?;
+        let current =
+            Self::lock_approval_for_transition(&mut tx, approval_id, ApprovalStatus::Submitted).await?;
+
         sqlx::query(
             r#"
             UPDATE token_approvals 
@@ -1235,9 +2490,19 @@ impl LedgerRepository {
         .bind(signature)
         .bind(tx_hash)
         .bind(approval_id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_approval_transition(
+            &mut tx,
+            approval_id,
+            Some(current),
+            ApprovalStatus::Submitted,
+            Some("transaction submitted"),
+        )
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
@@ -1247,6 +2512,10 @@ impl LedgerRepository {
         approval_id: &Uuid,
         block_height: i64,
     ) -> AppResult<()> {
+        let mut tx = self.begin_tx().await?;
+        let current =
+            Self::lock_approval_for_transition(&mut tx, approval_id, ApprovalStatus::Confirmed).await?;
+
         sqlx::query(
             r#"
             UPDATE token_approvals 
@@ -1257,9 +2526,19 @@ impl LedgerRepository {
         )
         .bind(block_height)
         .bind(approval_id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_approval_transition(
+            &mut tx,
+            approval_id,
+            Some(current),
+            ApprovalStatus::Confirmed,
+            Some(&format!("finalized at block {}", block_height)),
+        )
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
@@ -1268,13 +2547,27 @@ impl LedgerRepository {
         &self,
         approval_id: &Uuid,
     ) -> AppResult<()> {
+        let mut tx = self.begin_tx().await?;
+        let current =
+            Self::lock_approval_for_transition(&mut tx, approval_id, ApprovalStatus::Executed).await?;
+
         sqlx::query(
             "UPDATE token_approvals SET status = 'executed', executed_at = NOW() WHERE id = $1"
         )
         .bind(approval_id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_approval_transition(
+            &mut tx,
+            approval_id,
+            Some(current),
+            ApprovalStatus::Executed,
+            None,
+        )
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
@@ -1285,6 +2578,10 @@ impl LedgerRepository {
         error_message: &str,
         error_code: &str,
     ) -> AppResult<()> {
+        let mut tx = self.begin_tx().await?;
+        let current =
+            Self::lock_approval_for_transition(&mut tx, approval_id, ApprovalStatus::Failed).await?;
+
         sqlx::query(
             r#"
             UPDATE token_approvals 
@@ -1295,25 +2592,69 @@ impl LedgerRepository {
         .bind(error_message)
         .bind(error_code)
         .bind(approval_id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::record_approval_transition(
+            &mut tx,
+            approval_id,
+            Some(current),
+            ApprovalStatus::Failed,
+            Some(&format!("{}: {}", error_code, error_message)),
+        )
         .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
     /// Mark token approval as expired
     pub async fn mark_token_approvals_expired(&self) -> AppResult<u64> {
-        let result = sqlx::query(
+        // Only pending/signed may expire; history rows are written in the same statement
+        let expired = sqlx::query_scalar::<_, i64>(
             r#"
-            UPDATE token_approvals 
-            SET status = 'expired'
-            WHERE status = 'pending' AND expires_at < NOW()
+            WITH expired AS (
+                UPDATE token_approvals t
+                SET status = 'expired'
+                FROM (
+                    SELECT id, status FROM token_approvals
+                    WHERE status IN ('pending', 'signed') AND expires_at < NOW()
+                    FOR UPDATE
+                ) prev
+                WHERE t.id = prev.id
+                RETURNING t.id, prev.status AS from_status
+            ), history AS (
+                INSERT INTO token_approval_status_history (approval_id, from_status, to_status, reason)
+                SELECT id, from_status, 'expired', 'expired before submission'
+                FROM expired
+            )
+            SELECT COUNT(*) FROM expired
             "#
         )
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await?;
 
-        Ok(result.rows_affected())
+        Ok(expired as u64)
+    }
+
+    /// Status history of a token approval, oldest first
+    pub async fn get_token_approval_history(
+        &self,
+        approval_id: &Uuid,
+    ) -> AppResult<Vec<ApprovalStatusChange>> {
+        let history = sqlx::query_as::<_, ApprovalStatusChange>(
+            r#"
+            SELECT approval_id, from_status, to_status, reason, changed_at
+            FROM token_approval_status_history
+            WHERE approval_id = $1
+            ORDER BY changed_at ASC
+            "#
+        )
+        .bind(approval_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(history)
     }
 
     /// Get all pending token approvals for a user
@@ -1342,3 +2683,14 @@ impl LedgerRepository {
 }
 
 
+/// Price check as stored in quotes.price_check
+fn price_check_json(price_check: &PriceCheck) -> AppResult<serde_json::Value> {
+    serde_json::to_value(price_check)
+        .map_err(|e| AppError::Internal(format!("Invalid price check: {}", e)))
+}
+
+/// Decimal as bound to a NUMERIC column
+pub fn to_big_decimal(value: Decimal) -> AppResult<BigDecimal> {
+    BigDecimal::from_str(&value.to_string())
+        .map_err(|e| AppError::Internal(format!("Invalid decimal {}: {}", value, e)))
+}
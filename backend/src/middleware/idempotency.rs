@@ -0,0 +1,498 @@
+// Idempotency-Key support for mutating v1 endpoints
+//
+// A POST carrying an Idempotency-Key header claims the key in Postgres
+// before it reaches the handler. The response is stored with the hash of
+// the request, so a retry with the same method, path and body gets the
+// original response replayed, while a retry with a different body (or one
+// sent while the first is still running) gets 409.
+//
+// Keys are scoped per client by the credential in its Authorization header
+// (stored as a SHA-256 hash). A request with a key but no credential is
+// rejected: the body's `user_id` is not authenticated, so scoping by it would
+// let anyone replay another user's response.
+//
+// Server errors (5xx) are not stored: the key is released so the client
+// can retry once the problem is gone.
+
+use crate::api::handler::AppState;
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::IdempotencyRecord;
+use crate::ledger::repository::LedgerRepository;
+use async_trait::async_trait;
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{error, info};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses replayed from a stored record
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Requests and responses larger than this are not handled idempotently
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Storage for idempotency records, keyed by (client_id, key)
+#[async_trait]
+pub trait IdempotencyKeys: Send + Sync {
+    /// Claim a key for a new request, false if an unexpired record holds it
+    async fn claim(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+        ttl_seconds: i64,
+        stale_after_seconds: i64,
+    ) -> AppResult<bool>;
+
+    /// Unexpired record for a key
+    async fn get(&self, client_id: &str, key: &str) -> AppResult<Option<IdempotencyRecord>>;
+
+    /// Store the response of the request holding the key
+    async fn complete(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+        status: u16,
+        body: &[u8],
+        content_type: Option<&str>,
+    ) -> AppResult<()>;
+
+    /// Drop an in-flight key so the request can be retried
+    async fn release(&self, client_id: &str, key: &str, request_hash: &str) -> AppResult<()>;
+
+    async fn purge_expired(&self) -> AppResult<u64>;
+}
+
+#[async_trait]
+impl IdempotencyKeys for LedgerRepository {
+    async fn claim(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+        ttl_seconds: i64,
+        stale_after_seconds: i64,
+    ) -> AppResult<bool> {
+        self.claim_idempotency_key(client_id, key, request_hash, ttl_seconds, stale_after_seconds)
+            .await
+    }
+
+    async fn get(&self, client_id: &str, key: &str) -> AppResult<Option<IdempotencyRecord>> {
+        self.get_idempotency_record(client_id, key).await
+    }
+
+    async fn complete(
+        &self,
+        client_id: &str,
+        key: &str,
+        request_hash: &str,
+        status: u16,
+        body: &[u8],
+        content_type: Option<&str>,
+    ) -> AppResult<()> {
+        self.complete_idempotency_key(client_id, key, request_hash, status, body, content_type)
+            .await
+    }
+
+    async fn release(&self, client_id: &str, key: &str, request_hash: &str) -> AppResult<()> {
+        self.release_idempotency_key(client_id, key, request_hash).await
+    }
+
+    async fn purge_expired(&self) -> AppResult<u64> {
+        self.purge_expired_idempotency_keys().await
+    }
+}
+
+/// Idempotency key storage with its retention policy
+pub struct IdempotencyStore {
+    keys: Arc<dyn IdempotencyKeys>,
+    ttl: chrono::Duration,
+    /// An in-flight claim older than this is treated as abandoned
+    stale_after: chrono::Duration,
+}
+
+impl IdempotencyStore {
+    pub fn new(keys: Arc<dyn IdempotencyKeys>, ttl: chrono::Duration) -> Self {
+        Self {
+            keys,
+            ttl,
+            stale_after: chrono::Duration::seconds(60),
+        }
+    }
+
+    /// Read the TTL from IDEMPOTENCY_KEY_TTL_HOURS (default 24)
+    pub fn from_env(ledger: Arc<LedgerRepository>) -> AppResult<Self> {
+        let hours = match std::env::var("IDEMPOTENCY_KEY_TTL_HOURS") {
+            Ok(value) => value.parse::<i64>().ok().filter(|h| *h > 0).ok_or_else(|| {
+                AppError::Config("IDEMPOTENCY_KEY_TTL_HOURS must be a positive integer".to_string())
+            })?,
+            Err(_) => 24,
+        };
+        Ok(Self::new(ledger, chrono::Duration::hours(hours)))
+    }
+
+    pub fn ttl(&self) -> chrono::Duration {
+        self.ttl
+    }
+
+    pub async fn purge_expired(&self) -> AppResult<u64> {
+        self.keys.purge_expired().await
+    }
+}
+
+/// Hash identifying a request: method, path and query, body
+pub fn request_hash(method: &Method, path_and_query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Client a key is scoped to: hash of the Authorization credential
+fn client_id(headers: &HeaderMap) -> Option<String> {
+    let credential = headers
+        .get(header::AUTHORIZATION)
+        .map(HeaderValue::as_bytes)
+        .filter(|credential| !credential.is_empty())?;
+    Some(hex::encode(Sha256::digest(credential)))
+}
+
+/// Keys are 1-255 visible ASCII characters
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LENGTH && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Idempotency middleware for the v1 router
+///
+/// Only POST requests with an Idempotency-Key header are affected.
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    apply_idempotency(&state.idempotency, req, next).await
+}
+
+async fn apply_idempotency(store: &IdempotencyStore, req: Request, next: Next) -> Response {
+    if req.method() != Method::POST || !req.headers().contains_key(IDEMPOTENCY_KEY_HEADER) {
+        return next.run(req).await;
+    }
+
+    match handle_idempotent(store, req, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn handle_idempotent(store: &IdempotencyStore, req: Request, next: Next) -> AppResult<Response> {
+    let key = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .filter(|key| valid_key(key))
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Idempotency-Key must be 1-{} visible ASCII characters",
+                MAX_KEY_LENGTH
+            ))
+        })?;
+
+    let client_id = client_id(req.headers()).ok_or_else(|| {
+        AppError::BadRequest(
+            "Idempotency-Key requires an Authorization header identifying the client".to_string(),
+        )
+    })?;
+
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::BadRequest("Request body too large".to_string()))?;
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path().to_string(), |p| p.as_str().to_string());
+    let hash = request_hash(&parts.method, &path, &body);
+
+    let keys = store.keys.as_ref();
+    let claimed = keys
+        .claim(
+            &client_id,
+            &key,
+            &hash,
+            store.ttl.num_seconds(),
+            store.stale_after.num_seconds(),
+        )
+        .await?;
+
+    if !claimed {
+        return replay(keys, &client_id, &key, &hash).await;
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (mut parts, body) = response.into_parts();
+
+    if parts.status.is_server_error() {
+        keys.release(&client_id, &key, &hash).await?;
+        return Ok(Response::from_parts(parts, body));
+    }
+
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Failed to buffer response for idempotency key {}: {}", key, e);
+            keys.release(&client_id, &key, &hash).await?;
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, "Response too large").into_response());
+        }
+    };
+
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    keys.complete(&client_id, &key, &hash, parts.status.as_u16(), &body, content_type)
+        .await?;
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Response for a key that is already claimed
+async fn replay(
+    keys: &dyn IdempotencyKeys,
+    client_id: &str,
+    key: &str,
+    hash: &str,
+) -> AppResult<Response> {
+    let record = keys.get(client_id, key).await?.ok_or_else(|| {
+        // Expired between the claim attempt and this read
+        AppError::IdempotencyConflict("Idempotency key was just released, retry the request".to_string())
+    })?;
+
+    if record.request_hash != hash {
+        return Err(AppError::IdempotencyConflict(
+            "Idempotency key was already used with a different request".to_string(),
+        ));
+    }
+
+    let (Some(status), Some(body)) = (record.response_status, record.response_body) else {
+        return Err(AppError::IdempotencyConflict(
+            "A request with this idempotency key is still in progress".to_string(),
+        ));
+    };
+
+    info!("🔁 Replaying stored response for idempotency key {}", key);
+
+    let status = StatusCode::from_u16(status as u16)
+        .map_err(|_| AppError::Internal(format!("Invalid stored status {}", status)))?;
+    let mut response = (status, Bytes::from(body)).into_response();
+    if let Some(content_type) = record
+        .response_content_type
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    const ALICE: Option<&str> = Some("Bearer alice-api-key");
+    const BOB: Option<&str> = Some("Bearer bob-api-key");
+
+    /// In-memory IdempotencyKeys, records never expire
+    #[derive(Default)]
+    struct MemoryKeys {
+        records: Mutex<HashMap<(String, String), IdempotencyRecord>>,
+    }
+
+    #[async_trait]
+    impl IdempotencyKeys for MemoryKeys {
+        async fn claim(
+            &self,
+            client_id: &str,
+            key: &str,
+            request_hash: &str,
+            ttl_seconds: i64,
+            _: i64,
+        ) -> AppResult<bool> {
+            let mut records = self.records.lock();
+            let id = (client_id.to_string(), key.to_string());
+            if records.contains_key(&id) {
+                return Ok(false);
+            }
+            let now = chrono::Utc::now();
+            records.insert(
+                id,
+                IdempotencyRecord {
+                    client_id: client_id.to_string(),
+                    key: key.to_string(),
+                    request_hash: request_hash.to_string(),
+                    response_status: None,
+                    response_body: None,
+                    response_content_type: None,
+                    created_at: now,
+                    expires_at: now + chrono::Duration::seconds(ttl_seconds),
+                },
+            );
+            Ok(true)
+        }
+
+        async fn get(&self, client_id: &str, key: &str) -> AppResult<Option<IdempotencyRecord>> {
+            Ok(self.records.lock().get(&(client_id.to_string(), key.to_string())).cloned())
+        }
+
+        async fn complete(
+            &self,
+            client_id: &str,
+            key: &str,
+            _: &str,
+            status: u16,
+            body: &[u8],
+            content_type: Option<&str>,
+        ) -> AppResult<()> {
+            let mut records = self.records.lock();
+            if let Some(record) = records.get_mut(&(client_id.to_string(), key.to_string())) {
+                record.response_status = Some(status as i32);
+                record.response_body = Some(body.to_vec());
+                record.response_content_type = content_type.map(str::to_string);
+            }
+            Ok(())
+        }
+
+        async fn release(&self, client_id: &str, key: &str, _: &str) -> AppResult<()> {
+            self.records.lock().remove(&(client_id.to_string(), key.to_string()));
+            Ok(())
+        }
+
+        async fn purge_expired(&self) -> AppResult<u64> {
+            Ok(0)
+        }
+    }
+
+    /// Router whose handler counts its calls and echoes the count
+    fn app(calls: Arc<AtomicUsize>) -> Router {
+        let keys = Arc::new(MemoryKeys::default());
+        let store = Arc::new(IdempotencyStore::new(keys, chrono::Duration::hours(24)));
+        Router::new()
+            .route(
+                "/quote",
+                post(move || {
+                    let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move { (StatusCode::CREATED, format!("call {}", call)) }
+                }),
+            )
+            .layer(axum::middleware::from_fn(move |req: Request, next: Next| {
+                let store = store.clone();
+                async move { apply_idempotency(&store, req, next).await }
+            }))
+    }
+
+    async fn send(
+        app: &Router,
+        credential: Option<&str>,
+        key: &str,
+        body: &str,
+    ) -> (StatusCode, bool, String) {
+        let mut request = http::Request::post("/quote").header(IDEMPOTENCY_KEY_HEADER, key);
+        if let Some(credential) = credential {
+            request = request.header(header::AUTHORIZATION, credential);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER);
+        let body = to_bytes(response.into_body(), MAX_BODY_BYTES).await.unwrap();
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_retry_replays_stored_response() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(calls.clone());
+        let body = r#"{"user_id":"alice","amount":"10"}"#;
+
+        let first = send(&app, ALICE, "key-1", body).await;
+        assert_eq!(first, (StatusCode::CREATED, false, "call 1".to_string()));
+        let retry = send(&app, ALICE, "key-1", body).await;
+        assert_eq!(retry, (StatusCode::CREATED, true, "call 1".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reused_key_with_different_body_conflicts() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(calls.clone());
+
+        send(&app, ALICE, "key-1", r#"{"user_id":"alice","amount":"10"}"#).await;
+        let (status, replayed, _) =
+            send(&app, ALICE, "key-1", r#"{"user_id":"alice","amount":"11"}"#).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(!replayed);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_scoped_per_client() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(calls.clone());
+
+        let body = r#"{"user_id":"alice","amount":"10"}"#;
+        send(&app, ALICE, "key-1", body).await;
+
+        // Claiming alice's user_id does not give access to her responses
+        let (status, replayed, response) = send(&app, BOB, "key-1", body).await;
+        assert_eq!((status, replayed, response.as_str()), (StatusCode::CREATED, false, "call 2"));
+    }
+
+    #[tokio::test]
+    async fn test_key_without_client_credential_is_rejected() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(calls.clone());
+
+        let (status, replayed, _) = send(&app, None, "key-1", r#"{"user_id":"alice"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(!replayed);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_request_hash_covers_path_and_body() {
+        let hash = request_hash(&Method::POST, "/api/v1/quote", br#"{"amount":"10"}"#);
+        assert_eq!(hash, request_hash(&Method::POST, "/api/v1/quote", br#"{"amount":"10"}"#));
+        assert_ne!(hash, request_hash(&Method::POST, "/api/v1/quote", br#"{"amount":"11"}"#));
+        assert_ne!(hash, request_hash(&Method::POST, "/api/v1/commit", br#"{"amount":"10"}"#));
+        assert_eq!(hash.len(), 64);
+    }
+
+    #[test]
+    fn test_key_validation() {
+        assert!(valid_key("3f2a9c4e-81b7-4d0a-9e6f-0c1d2e3f4a5b"));
+        assert!(!valid_key(""));
+        assert!(!valid_key("has space"));
+        assert!(!valid_key(&"k".repeat(MAX_KEY_LENGTH + 1)));
+    }
+}
@@ -1,7 +1,9 @@
 pub mod cors;
+pub mod idempotency;
 pub mod rate_limit;
 pub mod validation;
 
 pub use cors::create_cors_layer;
+pub use idempotency::{idempotency_middleware, IdempotencyStore};
 pub use rate_limit::{rate_limit_middleware, RateLimitLayer};
 pub use validation::{validate_json, ValidationError};
@@ -0,0 +1,165 @@
+// Supported chain pairs - loaded from the supported_chain_pairs table
+//
+// The quote engine and execution router only accept pairs that are enabled
+// here. Admin updates are written to the database and applied immediately;
+// other instances pick them up on their next refresh.
+
+use crate::error::{AppResult, QuoteError};
+use crate::ledger::models::{Chain, ChainPairConfig};
+use crate::ledger::repository::LedgerRepository;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+pub struct ChainPairRegistry {
+    ledger: Arc<LedgerRepository>,
+    pairs: RwLock<HashMap<(Chain, Chain), ChainPairConfig>>,
+}
+
+impl ChainPairRegistry {
+    pub fn new(ledger: Arc<LedgerRepository>) -> Self {
+        Self {
+            ledger,
+            pairs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create and load the current pairs
+    pub async fn load(ledger: Arc<LedgerRepository>) -> AppResult<Self> {
+        let registry = Self::new(ledger);
+        registry.reload().await?;
+        Ok(registry)
+    }
+
+    /// Re-read all pairs from the database
+    pub async fn reload(&self) -> AppResult<usize> {
+        let pairs = self.ledger.get_chain_pairs().await?;
+        let enabled = pairs.iter().filter(|p| p.enabled).count();
+        self.replace(pairs).await;
+        Ok(enabled)
+    }
+
+    async fn replace(&self, pairs: Vec<ChainPairConfig>) {
+        let pairs = pairs
+            .into_iter()
+            .map(|pair| ((pair.funding_chain, pair.execution_chain), pair))
+            .collect();
+        *self.pairs.write().await = pairs;
+    }
+
+    /// Configuration of a pair, enabled or not
+    pub async fn get(&self, funding: Chain, execution: Chain) -> Option<ChainPairConfig> {
+        self.pairs.read().await.get(&(funding, execution)).cloned()
+    }
+
+    pub async fn is_enabled(&self, funding: Chain, execution: Chain) -> bool {
+        self.get(funding, execution)
+            .await
+            .is_some_and(|pair| pair.enabled)
+    }
+
+    /// Configuration of an enabled pair, or UnsupportedChainPair
+    pub async fn require(&self, funding: Chain, execution: Chain) -> AppResult<ChainPairConfig> {
+        match self.get(funding, execution).await {
+            Some(pair) if pair.enabled && funding != execution => Ok(pair),
+            _ => Err(QuoteError::UnsupportedChainPair { funding, execution }.into()),
+        }
+    }
+
+    /// Full pair matrix, sorted by funding then execution chain
+    pub async fn matrix(&self) -> Vec<ChainPairConfig> {
+        let mut pairs: Vec<_> = self.pairs.read().await.values().cloned().collect();
+        pairs.sort_by_key(|p| (p.funding_chain.as_str(), p.execution_chain.as_str()));
+        pairs
+    }
+
+    /// Update a pair and apply it to this instance immediately
+    pub async fn update(
+        &self,
+        funding: Chain,
+        execution: Chain,
+        enabled: Option<bool>,
+        min_amount: Option<Decimal>,
+        max_amount: Option<Option<Decimal>>,
+        service_fee_rate: Option<Option<Decimal>>,
+    ) -> AppResult<ChainPairConfig> {
+        let pair = self
+            .ledger
+            .update_chain_pair(funding, execution, enabled, min_amount, max_amount, service_fee_rate)
+            .await?;
+
+        info!(
+            "🔀 Chain pair {} -> {} updated (enabled: {})",
+            funding, execution, pair.enabled
+        );
+
+        self.pairs
+            .write()
+            .await
+            .insert((funding, execution), pair.clone());
+        Ok(pair)
+    }
+
+    /// Refresh periodically so changes made by other instances are applied
+    pub fn start(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reload().await {
+                    error!("Failed to refresh chain pairs: {:?}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use sqlx::postgres::PgPoolOptions;
+
+    fn pair(funding: Chain, execution: Chain, enabled: bool) -> ChainPairConfig {
+        ChainPairConfig {
+            funding_chain: funding,
+            execution_chain: execution,
+            enabled,
+            min_amount: Decimal::from(1),
+            max_amount: Some(Decimal::from(1000)),
+            service_fee_rate: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_enabled_pairs_are_accepted() {
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let registry = ChainPairRegistry::new(Arc::new(LedgerRepository::new(pool)));
+        registry
+            .replace(vec![
+                pair(Chain::Stellar, Chain::Solana, true),
+                pair(Chain::Solana, Chain::Near, false),
+            ])
+            .await;
+
+        assert!(registry.require(Chain::Stellar, Chain::Solana).await.is_ok());
+        assert!(registry.require(Chain::Solana, Chain::Near).await.is_err());
+        assert!(registry.require(Chain::Near, Chain::Stellar).await.is_err());
+        assert_eq!(registry.matrix().await.len(), 2);
+    }
+
+    #[test]
+    fn test_pair_amount_limits() {
+        let pair = pair(Chain::Stellar, Chain::Solana, true);
+        assert!(pair.check_amount(Decimal::from(10)).is_ok());
+        assert!(pair.check_amount(Decimal::new(5, 1)).is_err());
+        assert!(pair.check_amount(Decimal::from(1001)).is_err());
+    }
+}
@@ -1,19 +1,61 @@
-use crate::error::{AppResult, QuoteError};
+use crate::adapters::dex_whitelist::{DexWhitelist, WhitelistedToken};
+use crate::adapters::traits::AssetInfo;
+use crate::error::{AppError, AppResult, ExecutionError, QuoteError};
+use crate::execution::router::{Executor, SimulationResult};
+use crate::ledger::amount::{NativeAmount, TokenAmount};
 use crate::ledger::{models::*, repository::LedgerRepository};
-use crate::quote_engine::pyth_oracle::{PythOracle, PythPriceData};
+use crate::quote_engine::chain_pairs::ChainPairRegistry;
+use crate::quote_engine::fee_estimator::FeeEstimators;
+use crate::quote_engine::fee_schedule::{self, FeeContext, FeeScheduleRegistry};
+use crate::quote_engine::payment_address::PaymentAddresses;
+use crate::quote_engine::price_policy::PricePolicyConfig;
+use crate::quote_engine::price_source::{AggregatedPrice, PriceAggregator};
+use crate::quote_engine::route_planner::{TreasuryHop, TreasuryPricer};
+use async_trait::async_trait;
 use chrono::{Duration, Utc};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use rust_decimal_macros::dec;
 use sqlx::types::BigDecimal;
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Prices, costs and expiry of a quote at current market conditions
+#[derive(Debug, Clone)]
+struct QuotePricing {
+    funding_amount: Decimal,
+    execution_amount: Decimal,
+    /// Total payment in the funding asset, costs and slippage included
+    max_funding_amount: Decimal,
+    /// Execution chain native asset
+    execution_cost: Decimal,
+    /// Execution chain native asset
+    service_fee: Decimal,
+    /// Schedule that priced the service fee (None = default rate)
+    fee_schedule_id: Option<Uuid>,
+    /// 1 funding asset = price.rate execution asset, with the sources
+    price: AggregatedPrice,
+    /// Price policy outcome for `price` (always passed)
+    price_check: PriceCheck,
+    ttl_seconds: i64,
+}
+
+/// Outcome of refreshing a pending quote
+#[derive(Debug, Clone)]
+pub enum QuoteRefresh {
+    /// Price still within tolerance: same quote, later expiry
+    Extended(Quote),
+    /// Price moved: a new quote replaces the old one
+    Superseded { previous: Uuid, quote: Quote },
+}
+
 /// Quote engine configuration
 #[derive(Debug, Clone)]
 pub struct QuoteConfig {
-    /// Service fee as a percentage (0.1% = 0.001)
+    /// Default service fee as a fraction of the execution cost (0.1% = 0.001),
+    /// used when neither a fee schedule nor the chain pair sets one
     pub service_fee_rate: Decimal,
     /// Quote validity duration in seconds
     pub quote_ttl_seconds: i64,
@@ -21,6 +63,11 @@ pub struct QuoteConfig {
     pub max_compute_units: i32,
     /// Maximum slippage allowed (1% = 0.01)
     pub max_slippage: Decimal,
+    /// Relative change in total payment a refresh absorbs by extending
+    /// the quote instead of superseding it (0.5% = 0.005)
+    pub refresh_tolerance: Decimal,
+    /// Price staleness and confidence limits, and the TTL they give
+    pub price_policy: PricePolicyConfig,
 }
 
 impl Default for QuoteConfig {
@@ -30,7 +77,27 @@ impl Default for QuoteConfig {
             quote_ttl_seconds: 300,        // 5 minutes
             max_compute_units: 1_400_000,  // Solana max
             max_slippage: dec!(0.01),       // 1% max slippage
+            refresh_tolerance: dec!(0.005), // 0.5% price drift
+            price_policy: PricePolicyConfig::default(),
+        }
+    }
+}
+
+impl QuoteConfig {
+    /// Defaults, with the refresh tolerance read from QUOTE_REFRESH_TOLERANCE
+    /// and the price policy from its variables (see PricePolicyConfig)
+    pub fn from_env() -> AppResult<Self> {
+        let mut config = Self::default();
+        config.price_policy = PricePolicyConfig::from_env()?;
+        if let Ok(value) = std::env::var("QUOTE_REFRESH_TOLERANCE") {
+            config.refresh_tolerance = Decimal::from_str(&value)
+                .ok()
+                .filter(|t| *t >= Decimal::ZERO && *t < Decimal::ONE)
+                .ok_or_else(|| {
+                    AppError::Config("QUOTE_REFRESH_TOLERANCE must be a fraction between 0 and 1".to_string())
+                })?;
         }
+        Ok(config)
     }
 }
 
@@ -38,17 +105,45 @@ impl Default for QuoteConfig {
 ///
 /// ARCHITECTURE: This engine is completely chain-agnostic.
 /// It validates chain pairs but doesn't privilege any chain.
-/// Uses Pyth for real-time price feeds.
+/// Prices come from Pyth cross-checked against the other price sources.
 pub struct QuoteEngine {
     config: QuoteConfig,
     ledger: Arc<LedgerRepository>,
-    pyth_oracle: Arc<PythOracle>,
+    prices: Arc<PriceAggregator>,
+    payment_addresses: Arc<PaymentAddresses>,
+    chain_pairs: Arc<ChainPairRegistry>,
+    fee_estimators: Arc<FeeEstimators>,
+    fee_schedules: Arc<FeeScheduleRegistry>,
+    /// Execution chain executors, used to dry-run instructions
+    executors: HashMap<Chain, Arc<dyn Executor>>,
+    whitelist: DexWhitelist,
     network: String,
 }
 
 impl QuoteEngine {
-    pub fn new(config: QuoteConfig, ledger: Arc<LedgerRepository>, pyth_oracle: Arc<PythOracle>, network: String) -> Self {
-        Self { config, ledger, pyth_oracle, network }
+    pub fn new(
+        config: QuoteConfig,
+        ledger: Arc<LedgerRepository>,
+        prices: Arc<PriceAggregator>,
+        payment_addresses: Arc<PaymentAddresses>,
+        chain_pairs: Arc<ChainPairRegistry>,
+        fee_estimators: Arc<FeeEstimators>,
+        fee_schedules: Arc<FeeScheduleRegistry>,
+        executors: HashMap<Chain, Arc<dyn Executor>>,
+        network: String,
+    ) -> Self {
+        Self {
+            config,
+            ledger,
+            prices,
+            payment_addresses,
+            chain_pairs,
+            fee_estimators,
+            fee_schedules,
+            executors,
+            whitelist: DexWhitelist::new(),
+            network,
+        }
     }
 
     /// Generate a new quote for cross-chain execution
@@ -59,6 +154,10 @@ impl QuoteEngine {
     /// - Execution instructions must be valid
     /// - Cost estimates must be worst-case
     /// - Real-time prices from Pyth for accuracy
+    ///
+    /// With `simulate`, the instructions are dry-run on the execution chain
+    /// first: a failed simulation rejects the quote, and the measured
+    /// compute units and fee replace the estimates where they are higher.
     pub async fn generate_quote(
         &self,
         user_id: Uuid,
@@ -66,12 +165,15 @@ impl QuoteEngine {
         execution_chain: Chain,
         funding_asset: String,
         execution_asset: String,
+        mode: QuoteMode,
+        amount: Decimal,
         execution_instructions: Vec<u8>,
         estimated_compute_units: Option<i32>,
+        simulate: bool,
     ) -> AppResult<Quote> {
         info!(
-            "Generating quote: {:?} -> {:?} for user {} ({} -> {})",
-            funding_chain, execution_chain, user_id, funding_asset, execution_asset
+            "Generating {} quote: {:?} -> {:?} for user {} ({} {} -> {})",
+            mode.as_str(), funding_chain, execution_chain, user_id, amount, funding_asset, execution_asset
         );
 
         // VALIDATION 1: Funding and execution chains must be different
@@ -80,18 +182,18 @@ impl QuoteEngine {
             return Err(QuoteError::SameChainFunding.into());
         }
 
-        // VALIDATION 2: Check if chain pair is supported
-        if !Chain::is_pair_supported(funding_chain, execution_chain) {
-            warn!(
-                "Rejected unsupported chain pair: {:?} -> {:?}",
-                funding_chain, execution_chain
-            );
-            return Err(QuoteError::UnsupportedChainPair {
-                funding: funding_chain,
-                execution: execution_chain,
-            }
-            .into());
-        }
+        // VALIDATION 2: Check if chain pair is enabled
+        let pair = self
+            .chain_pairs
+            .require(funding_chain, execution_chain)
+            .await
+            .map_err(|e| {
+                warn!(
+                    "Rejected unsupported chain pair: {:?} -> {:?}",
+                    funding_chain, execution_chain
+                );
+                e
+            })?;
 
         // VALIDATION 3: Verify execution instructions
         if execution_instructions.is_empty() {
@@ -125,51 +227,41 @@ impl QuoteEngine {
 
         info!("✓ User has wallet on execution chain: {}", user_execution_wallet);
 
-        // VALIDATION 6: Get real-time price from Pyth for all three chains
-        info!("Fetching real-time prices from Pyth...");
-        
-        let price_data = self
-            .pyth_oracle
-            .get_price(&funding_asset, &execution_asset, funding_chain.as_str())
-            .await
-            .map_err(|e| QuoteError::PriceUnavailable(format!("Pyth error: {}", e)))?;
-
-        info!(
-            "✓ Price: 1 {} = {} {} (confidence: {}%)",
-            funding_asset,
-            price_data.rate,
-            execution_asset,
-            price_data.base_price.confidence_pct().unwrap_or_default()
-        );
+        // VALIDATION 6: Dry-run the instructions if requested
+        let simulation = if simulate {
+            Some(
+                self.simulate_instructions(execution_chain, &execution_instructions)
+                    .await?,
+            )
+        } else {
+            None
+        };
+        let estimated_compute_units = match simulation.as_ref().and_then(|s| s.compute_units) {
+            Some(units) => self.simulated_compute_units(execution_chain, units, estimated_compute_units)?,
+            None => estimated_compute_units,
+        };
 
-        // Calculate execution cost based on target chain
-        let execution_cost = self
-            .estimate_execution_cost(execution_chain, estimated_compute_units)
+        // VALIDATION 7: Price the trade, costs and expiry
+        let pricing = self
+            .price_quote(
+                Some(user_id),
+                &pair,
+                funding_chain,
+                execution_chain,
+                &funding_asset,
+                &execution_asset,
+                mode,
+                amount,
+                estimated_compute_units,
+                simulation.as_ref().and_then(|s| s.fee),
+            )
             .await?;
 
-        // Calculate service fee (0.1% of execution cost)
-        let service_fee = execution_cost * self.config.service_fee_rate;
-
-        // Convert execution cost to funding chain asset using Pyth price
-        let max_funding_amount = (execution_cost + service_fee) / price_data.rate;
-
-        // Apply slippage buffer (add 1% for safety)
-        let max_funding_amount_with_slippage = max_funding_amount * (Decimal::ONE + self.config.max_slippage);
-
-        // CRITICAL FIX #2: Dynamic quote TTL based on volatility
-        let ttl_seconds = self.calculate_dynamic_ttl(&price_data)?;
-        
-        info!(
-            "Quote volatility: {}% confidence, TTL: {} seconds",
-            price_data.base_price.confidence_pct().unwrap_or_default(),
-            ttl_seconds
-        );
-
         // Generate unique nonce for replay protection
         let nonce = format!("{}-{}", Uuid::new_v4(), Utc::now().timestamp_millis());
 
         // Set expiry based on volatility
-        let expires_at = Utc::now() + Duration::seconds(ttl_seconds);
+        let expires_at = Utc::now() + Duration::seconds(pricing.ttl_seconds);
 
         // Generate payment address for funding chain
         let payment_address = self
@@ -185,11 +277,17 @@ impl QuoteEngine {
                 execution_chain,
                 funding_asset.clone(),
                 execution_asset.clone(),
-                BigDecimal::from_str(&max_funding_amount_with_slippage.to_string()).unwrap(),
-                BigDecimal::from_str(&execution_cost.to_string()).unwrap(),
-                BigDecimal::from_str(&service_fee.to_string()).unwrap(),
+                mode,
+                BigDecimal::from_str(&pricing.funding_amount.to_string()).unwrap(),
+                BigDecimal::from_str(&pricing.execution_amount.to_string()).unwrap(),
+                BigDecimal::from_str(&pricing.max_funding_amount.to_string()).unwrap(),
+                BigDecimal::from_str(&pricing.execution_cost.to_string()).unwrap(),
+                BigDecimal::from_str(&pricing.service_fee.to_string()).unwrap(),
+                pricing.fee_schedule_id,
+                &pricing.price_check,
                 execution_instructions,
                 estimated_compute_units,
+                simulation.is_some(),
                 nonce,
                 expires_at,
                 Some(payment_address),
@@ -208,18 +306,401 @@ impl QuoteEngine {
                     "execution_chain": execution_chain,
                     "funding_asset": funding_asset,
                     "execution_asset": execution_asset,
-                    "execution_cost": execution_cost.to_string(),
-                    "service_fee": service_fee.to_string(),
-                    "pyth_price_rate": price_data.rate.to_string(),
+                    "mode": mode,
+                    "funding_amount": pricing.funding_amount.to_string(),
+                    "execution_amount": pricing.execution_amount.to_string(),
+                    "execution_cost": pricing.execution_cost.to_string(),
+                    "service_fee": pricing.service_fee.to_string(),
+                    "fee_schedule_id": pricing.fee_schedule_id,
+                    "price_rate": pricing.price.rate.to_string(),
+                    "price_sources": pricing.price.audit_json(),
+                    "price_check": pricing.price_check,
                     "user_wallet": user_execution_wallet,
+                    "simulated": simulation.is_some(),
+                    "simulated_compute_units": simulation.as_ref().and_then(|s| s.compute_units),
+                    "simulated_fee": simulation
+                        .as_ref()
+                        .and_then(|s| s.fee)
+                        .map(|fee| fee.to_display().map(|d| d.to_string()).unwrap_or_default()),
                 }),
             )
             .await?;
 
-        info!("✓ Quote created: {} (expires in {}s)", quote.id, self.config.quote_ttl_seconds);
+        info!("✓ Quote created: {} (expires in {}s)", quote.id, pricing.ttl_seconds);
         Ok(quote)
     }
 
+    /// Refresh a pending quote against current prices and fees
+    ///
+    /// Drift is measured on the side the client did not fix: the delivered
+    /// amount for exact input, the total payment for exact output. Within
+    /// `refresh_tolerance` the quote keeps its amounts and gets a new expiry.
+    /// Otherwise it is superseded by a repriced quote with the same nonce
+    /// and payment address.
+    pub async fn refresh_quote(&self, quote_id: Uuid) -> AppResult<QuoteRefresh> {
+        let quote = self
+            .ledger
+            .get_quote(quote_id)
+            .await?
+            .ok_or_else(|| QuoteError::NotFound(quote_id.to_string()))?;
+
+        if quote.status != QuoteStatus::Pending {
+            return Err(QuoteError::InvalidState {
+                current: format!("{:?}", quote.status),
+                expected: "Pending".to_string(),
+            }
+            .into());
+        }
+        if quote.is_expired() {
+            return Err(QuoteError::Expired.into());
+        }
+
+        // Quotes from before trade amounts existed cannot be repriced
+        let amount = match quote.mode {
+            QuoteMode::ExactInput => quote.funding_amount,
+            QuoteMode::ExactOutput => quote.execution_amount,
+        };
+        if amount <= Decimal::ZERO {
+            return Err(QuoteError::InvalidParameters(
+                "Quote has no trade amount and cannot be refreshed".to_string(),
+            )
+            .into());
+        }
+
+        let pair = self
+            .chain_pairs
+            .require(quote.funding_chain, quote.execution_chain)
+            .await?;
+
+        // Chain state may have changed since the quote: dry-run it again if
+        // it was simulated when issued
+        let simulation = if quote.simulated {
+            Some(
+                self.simulate_instructions(quote.execution_chain, &quote.execution_instructions)
+                    .await?,
+            )
+        } else {
+            None
+        };
+        let estimated_compute_units = match simulation.as_ref().and_then(|s| s.compute_units) {
+            Some(units) => self.simulated_compute_units(
+                quote.execution_chain,
+                units,
+                quote.estimated_compute_units,
+            )?,
+            None => quote.estimated_compute_units,
+        };
+
+        let pricing = self
+            .price_quote(
+                Some(quote.user_id),
+                &pair,
+                quote.funding_chain,
+                quote.execution_chain,
+                &quote.funding_asset,
+                &quote.execution_asset,
+                quote.mode,
+                amount,
+                estimated_compute_units,
+                simulation.as_ref().and_then(|s| s.fee),
+            )
+            .await?;
+
+        let expires_at = Utc::now() + Duration::seconds(pricing.ttl_seconds);
+        let drift = match quote.mode {
+            QuoteMode::ExactInput => refresh_drift(quote.execution_amount, pricing.execution_amount),
+            QuoteMode::ExactOutput => {
+                refresh_drift(quote.total_funding_required(), pricing.max_funding_amount)
+            }
+        };
+
+        // A quote that now needs more compute units is repriced with them
+        let units_changed = estimated_compute_units != quote.estimated_compute_units;
+
+        let (outcome, audit_quote_id) = if drift <= self.config.refresh_tolerance && !units_changed {
+            self.ledger
+                .extend_quote_expiry(quote.id, expires_at, &pricing.price_check)
+                .await?;
+            let extended = self
+                .ledger
+                .get_quote(quote.id)
+                .await?
+                .ok_or_else(|| QuoteError::NotFound(quote.id.to_string()))?;
+
+            info!("✓ Quote {} extended (drift {}), expires at {}", quote.id, drift, extended.expires_at);
+            (QuoteRefresh::Extended(extended), quote.id)
+        } else {
+            let replacement = self
+                .ledger
+                .supersede_quote(
+                    quote.id,
+                    BigDecimal::from_str(&pricing.funding_amount.to_string()).unwrap(),
+                    BigDecimal::from_str(&pricing.execution_amount.to_string()).unwrap(),
+                    BigDecimal::from_str(&pricing.max_funding_amount.to_string()).unwrap(),
+                    BigDecimal::from_str(&pricing.execution_cost.to_string()).unwrap(),
+                    BigDecimal::from_str(&pricing.service_fee.to_string()).unwrap(),
+                    pricing.fee_schedule_id,
+                    &pricing.price_check,
+                    estimated_compute_units,
+                    expires_at,
+                )
+                .await?;
+
+            info!("✓ Quote {} superseded by {} (drift {})", quote.id, replacement.id, drift);
+            let replacement_id = replacement.id;
+            (
+                QuoteRefresh::Superseded {
+                    previous: quote.id,
+                    quote: replacement,
+                },
+                replacement_id,
+            )
+        };
+
+        // Audit log
+        self.ledger
+            .log_audit_event(
+                AuditEventType::QuoteRefreshed,
+                Some(quote.execution_chain),
+                Some(audit_quote_id),
+                Some(quote.user_id),
+                serde_json::json!({
+                    "refreshed_from": quote.id,
+                    "outcome": match &outcome {
+                        QuoteRefresh::Extended(_) => "extended",
+                        QuoteRefresh::Superseded { .. } => "superseded",
+                    },
+                    "previous_max_funding_amount": quote.max_funding_amount.to_string(),
+                    "previous_execution_amount": quote.execution_amount.to_string(),
+                    "max_funding_amount": pricing.max_funding_amount.to_string(),
+                    "funding_amount": pricing.funding_amount.to_string(),
+                    "execution_amount": pricing.execution_amount.to_string(),
+                    "execution_cost": pricing.execution_cost.to_string(),
+                    "service_fee": pricing.service_fee.to_string(),
+                    "fee_schedule_id": pricing.fee_schedule_id,
+                    "price_rate": pricing.price.rate.to_string(),
+                    "price_sources": pricing.price.audit_json(),
+                    "price_check": pricing.price_check,
+                    "drift": drift.to_string(),
+                    "simulated": simulation.is_some(),
+                    "simulated_compute_units": simulation.as_ref().and_then(|s| s.compute_units),
+                    "simulated_fee": simulation
+                        .as_ref()
+                        .and_then(|s| s.fee)
+                        .map(|fee| fee.to_display().map(|d| d.to_string()).unwrap_or_default()),
+                }),
+            )
+            .await?;
+
+        Ok(outcome)
+    }
+
+    /// Price a trade at current market conditions: Pyth rate, live fees,
+    /// slippage buffer, per-pair limits and volatility-based TTL
+    ///
+    /// Without a user (route previews) volume fee tiers do not apply.
+    async fn price_quote(
+        &self,
+        user_id: Option<Uuid>,
+        pair: &ChainPairConfig,
+        funding_chain: Chain,
+        execution_chain: Chain,
+        funding_asset: &str,
+        execution_asset: &str,
+        mode: QuoteMode,
+        amount: Decimal,
+        estimated_compute_units: Option<i32>,
+        simulated_fee: Option<NativeAmount>,
+    ) -> AppResult<QuotePricing> {
+        // Get real-time price, checked across all price sources
+        info!("Fetching real-time prices...");
+        
+        let price_data = self
+            .prices
+            .get_rate(funding_asset, execution_asset, funding_chain)
+            .await?;
+
+        info!(
+            "✓ Price: 1 {} = {} {} (confidence: {}%, {} sources within {} bps)",
+            funding_asset,
+            price_data.rate,
+            execution_asset,
+            price_data.confidence_pct.unwrap_or_default(),
+            price_data.sources.len(),
+            price_data.deviation_bps.round_dp(2)
+        );
+
+        // Stale or imprecise prices cannot be quoted
+        let price_check = self
+            .config
+            .price_policy
+            .check(funding_asset, execution_asset, &price_data, Utc::now());
+        if !price_check.passed {
+            let reason = price_check.reason.clone().unwrap_or_default();
+            warn!("Rejected {}/{} price: {}", funding_asset, execution_asset, reason);
+            self.ledger
+                .log_audit_event(
+                    AuditEventType::QuotePriceRejected,
+                    Some(execution_chain),
+                    None,
+                    user_id,
+                    serde_json::json!({
+                        "funding_chain": funding_chain,
+                        "execution_chain": execution_chain,
+                        "funding_asset": funding_asset,
+                        "execution_asset": execution_asset,
+                        "price_rate": price_data.rate.to_string(),
+                        "price_sources": price_data.audit_json(),
+                        "price_check": price_check,
+                    }),
+                )
+                .await?;
+            return Err(QuoteError::PriceUnavailable(reason).into());
+        }
+
+        // Calculate execution cost based on target chain (native asset base units)
+        let execution_cost = self
+            .estimate_execution_cost(execution_chain, estimated_compute_units, simulated_fee)
+            .await?;
+
+        // Calculate service fee: matching fee schedule, else the pair's
+        // override, else the default 0.1% of execution cost
+        let now = Utc::now();
+        let volume_usd = match user_id {
+            Some(user_id) if self.fee_schedules.uses_volume_tiers(now).await => {
+                self.user_volume_usd(user_id).await?
+            }
+            _ => Decimal::ZERO,
+        };
+        let schedule = self
+            .fee_schedules
+            .resolve(&FeeContext {
+                funding_chain,
+                execution_chain,
+                funding_asset,
+                execution_asset,
+                volume_usd,
+                now,
+            })
+            .await;
+
+        let service_fee = match &schedule {
+            Some(schedule) => {
+                let fee = execution_cost.scale(schedule.fee_rate)?;
+                let native_usd = if schedule.min_fee_usd.is_some() || schedule.max_fee_usd.is_some() {
+                    self.usd_rate(execution_cost.symbol(), execution_chain).await?
+                } else {
+                    Decimal::ONE
+                };
+                info!("✓ Fee schedule: {} ({}), 30-day volume ${}", schedule.name, schedule.id, volume_usd);
+                fee_schedule::apply_fee_caps(fee, native_usd, schedule.min_fee_usd, schedule.max_fee_usd)?
+            }
+            None => {
+                let service_fee_rate = pair.service_fee_rate.unwrap_or(self.config.service_fee_rate);
+                execution_cost.scale(service_fee_rate)?
+            }
+        };
+
+        // Costs are paid in the execution chain's native asset; price that
+        // asset separately if the quoted execution asset is something else
+        let native_rate = if execution_asset.eq_ignore_ascii_case(execution_cost.symbol()) {
+            price_data.rate
+        } else {
+            self.prices
+                .get_rate(funding_asset, execution_cost.symbol(), funding_chain)
+                .await?
+                .rate
+        };
+
+        // Price the other side of the trade, slippage against the user
+        let funding_token = self
+            .whitelist
+            .get_by_symbol(funding_chain, funding_asset)
+            .map_err(|e| QuoteError::InvalidParameters(e.to_string()))?;
+        let execution_token = self
+            .whitelist
+            .get_by_symbol(execution_chain, execution_asset)
+            .map_err(|e| QuoteError::InvalidParameters(e.to_string()))?;
+        let (funding_amount, execution_amount) = price_trade(
+            mode,
+            amount,
+            price_data.rate,
+            self.config.max_slippage,
+            &funding_token,
+            &execution_token,
+        )?;
+
+        // Convert execution cost to funding chain asset at the quoted price
+        let execution_cost_display = execution_cost.to_display()?;
+        let service_fee_display = service_fee.to_display()?;
+        let cost_in_funding_asset = (execution_cost_display + service_fee_display) / native_rate;
+
+        // Apply slippage buffer (add 1% for safety), rounded up to the funding token's decimals
+        let max_funding_amount_with_slippage = TokenAmount::from_display(
+            &funding_token,
+            funding_amount + cost_in_funding_asset * (Decimal::ONE + self.config.max_slippage),
+        )?
+        .to_display()?;
+
+        info!(
+            "✓ Trade: {} {} -> {} {} (total payment: {} {})",
+            funding_amount, funding_asset, execution_amount, execution_asset,
+            max_funding_amount_with_slippage, funding_asset
+        );
+
+        // Per-pair limits apply to the trade, not the costs on top of it
+        pair.check_amount(funding_amount)
+            .map_err(QuoteError::InvalidParameters)?;
+
+        // CRITICAL FIX #2: Dynamic quote TTL based on volatility
+        let ttl_seconds = self.calculate_dynamic_ttl(price_data.confidence_pct)?;
+        
+        info!(
+            "Quote volatility: {}% confidence, TTL: {} seconds",
+            price_data.confidence_pct.unwrap_or_default(),
+            ttl_seconds
+        );
+
+        Ok(QuotePricing {
+            funding_amount,
+            execution_amount,
+            max_funding_amount: max_funding_amount_with_slippage,
+            execution_cost: execution_cost_display,
+            service_fee: service_fee_display,
+            fee_schedule_id: schedule.map(|s| s.id),
+            price: price_data,
+            price_check,
+            ttl_seconds,
+        })
+    }
+
+    /// Rolling volume of the user's executed quotes, in USD at current prices
+    ///
+    /// Assets without a price are left out (warned), which can only put the
+    /// user in a lower tier
+    async fn user_volume_usd(&self, user_id: Uuid) -> AppResult<Decimal> {
+        let since = Utc::now() - Duration::days(fee_schedule::VOLUME_WINDOW_DAYS);
+        let mut total = Decimal::ZERO;
+
+        for (chain, asset, volume) in self.ledger.get_user_volume(user_id, since).await? {
+            match self.usd_rate(&asset, chain).await {
+                Ok(rate) => total += volume * rate,
+                Err(e) => warn!("Leaving {} {} on {} out of volume: {}", volume, asset, chain, e),
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// USD price of an asset, with USDC standing in for USD
+    async fn usd_rate(&self, asset: &str, chain: Chain) -> AppResult<Decimal> {
+        if asset.eq_ignore_ascii_case("USDC") {
+            return Ok(Decimal::ONE);
+        }
+
+        Ok(self.prices.get_rate(asset, "USDC", chain).await?.rate)
+    }
+
     /// Get user wallet address for a specific chain
     pub fn get_user_wallet_for_chain(&self, user: &User, chain: Chain) -> Option<String> {
         match chain {
@@ -233,179 +714,91 @@ impl QuoteEngine {
     /// 
     /// High volatility → shorter TTL (reduce price drift risk)
     /// Low volatility → longer TTL (more time for user to act)
-    fn calculate_dynamic_ttl(&self, price_data: &PythPriceData) -> AppResult<i64> {
-        let conf_pct = price_data.base_price.confidence_pct().unwrap_or(dec!(0.5));
-
-        let ttl = match conf_pct {
-            // Extreme volatility: >5% confidence interval
-            v if v > dec!(5.0) => {
-                info!("⚠️  High volatility detected ({}%), reducing TTL to 2 minutes", v);
-                120
-            }
-            // High volatility: 2-5% confidence
-            v if v > dec!(2.0) => {
-                info!("⚠️  Moderate volatility detected ({}%), reducing TTL to 3 minutes", v);
-                180
-            }
-            // Normal volatility: 1-2% confidence
-            v if v > dec!(1.0) => {
-                info!("📊 Normal volatility ({}%), TTL: 4 minutes", v);
-                240
-            }
-            // Stable: <1% confidence
-            v => {
-                info!("✓ Low volatility ({}%), TTL: 5 minutes", v);
-                300
-            }
-        };
+    ///
+    /// The tiers come from the price policy (PricePolicyConfig::ttl_tiers).
+    fn calculate_dynamic_ttl(&self, confidence_pct: Option<Decimal>) -> AppResult<i64> {
+        let policy = &self.config.price_policy;
+        let conf_pct = confidence_pct.unwrap_or(policy.unknown_confidence_pct);
+        let ttl = policy.ttl_seconds(confidence_pct);
+
+        if ttl < policy.stable_ttl_seconds {
+            info!("⚠️  Volatility detected ({}%), reducing TTL to {}s", conf_pct, ttl);
+        } else {
+            info!("✓ Low volatility ({}%), TTL: {}s", conf_pct, ttl);
+        }
 
         Ok(ttl)
     }
 
-    /// Estimate execution cost for a given chain
+    /// Estimate execution cost for a given chain, in its native asset
     ///
-    /// SECURITY: Uses worst-case estimation with safety margins
+    /// SECURITY: Uses worst-case estimation with safety margins. A fee
+    /// reported by simulation is used when it is higher than the estimate.
     async fn estimate_execution_cost(
         &self,
         execution_chain: Chain,
         estimated_compute_units: Option<i32>,
-    ) -> AppResult<Decimal> {
-        match execution_chain {
-            Chain::Solana => {
-                let cu = estimated_compute_units.unwrap_or(200_000);
-                // Base cost: compute units * lamports per CU
-                let compute_cost = Decimal::from(cu) * dec!(0.000001);
-                // Signature cost (5000 lamports per signature, assume 2)
-                let signature_cost = dec!(10000);
-                // Priority fee buffer (20%)
-                let priority_buffer = compute_cost * dec!(0.2);
-                Ok(compute_cost + signature_cost + priority_buffer)
-            }
-            Chain::Stellar => {
-                // Stellar base fee (100 stroops) + buffer
-                let base_fee = dec!(100);
-                let buffer = base_fee * dec!(0.2);
-                Ok(base_fee + buffer)
+        simulated_fee: Option<NativeAmount>,
+    ) -> AppResult<NativeAmount> {
+        let estimate = self
+            .fee_estimators
+            .estimate(execution_chain, estimated_compute_units)
+            .await?;
+
+        match simulated_fee {
+            Some(fee)
+                if fee.chain() == execution_chain && fee.to_display()? > estimate.to_display()? =>
+            {
+                info!("Using simulated fee {} {} over estimate", fee.to_display()?, fee.symbol());
+                Ok(fee)
             }
-            Chain::Near => {
-                // NEAR gas cost estimation based on function call complexity
-                // Standard function call: ~1 TGas (1,000,000,000,000 gas units)
-                // Storage allocation: ~100 million gas per 32KB
-                // Cross-contract call: ~2 TGas
-                
-                // Base cost for simple transfer: 1 TGas
-                let base_gas = dec!(1_000_000_000_000);
-                
-                // Add extra for potential cross-contract interaction: 2 TGas
-                let cross_contract_multiplier = dec!(2);
-                
-                // Gas price: typically 100 yoctoNEAR per unit (1 yocto = 10^-24)
-                // So: (gas_units * gas_price) / 10^24 = cost in NEAR
-                let gas_price = dec!(100);
-                
-                // Total gas cost = (base_gas * cross_contract_multiplier * gas_price) / 10^24
-                let total_gas_units = base_gas * cross_contract_multiplier;
-                let cost_in_yoctonear = total_gas_units * gas_price;
-                
-                // Convert from yoctoNEAR to NEAR (divide by 10^24)
-                let cost_in_near = cost_in_yoctonear / Decimal::from(1_000_000_000_000_000_000_000_000i128);
-                
-                // Add 50% buffer for safety
-                Ok(cost_in_near * dec!(1.5))
+            _ => Ok(estimate),
+        }
+    }
+
+    /// Dry-run execution instructions on the execution chain
+    async fn simulate_instructions(
+        &self,
+        execution_chain: Chain,
+        instructions: &[u8],
+    ) -> AppResult<SimulationResult> {
+        let executor = self
+            .executors
+            .get(&execution_chain)
+            .ok_or(ExecutionError::UnsupportedChain(execution_chain))?;
+
+        simulate_with(executor.as_ref(), execution_chain, instructions).await
+    }
+
+    /// Compute units to quote after a simulation: the simulated usage plus
+    /// a margin, or the caller's estimate if that is higher
+    fn simulated_compute_units(
+        &self,
+        execution_chain: Chain,
+        simulated: u64,
+        estimated: Option<i32>,
+    ) -> AppResult<Option<i32>> {
+        let with_margin = simulated_units_with_margin(simulated);
+        if with_margin > self.config.max_compute_units as u64 {
+            return Err(QuoteError::SimulationFailed {
+                chain: execution_chain,
+                message: format!(
+                    "Simulation used {} compute units, above the limit of {}",
+                    simulated, self.config.max_compute_units
+                ),
             }
+            .into());
         }
+
+        Ok(Some(estimated.unwrap_or(0).max(with_margin as i32)))
     }
 
     /// Generate payment address for funding chain
     ///
-    /// SECURITY: Generates unique deterministic addresses for each quote
-    /// - Stellar: Uses contract account with memo for routing
-    /// - NEAR: Uses contract subaccount pattern
-    /// - Solana: Uses Program Derived Address (PDA) pattern
+    /// SECURITY: Deterministic, quote-specific destination derived by the
+    /// deployment's payment address provider for the chain
     async fn generate_payment_address(&self, chain: Chain, nonce: &str) -> AppResult<String> {
-        match chain {
-            Chain::Stellar => {
-                // Stellar: Use payment collection contract with memo-based routing
-                // Format: [contract_account]?memo=[nonce]
-                // The memo field ensures each quote has unique routing
-                // Example: GBUQWP3BOUZX34ULNQG23RQ6F4OFSAI5TU2MMQBB3IXWVYLXVCLWEB7V?memo=quote_abc123de
-                let memo = format!("quote_{}", &nonce[..12]);
-                Ok(format!(
-                    "GBUQWP3BOUZX34ULNQG23RQ6F4OFSAI5TU2MMQBB3IXWVYLXVCLWEB7V?memo={}",
-                    memo
-                ))
-            }
-            Chain::Near => {
-                // NEAR: Use escrow contract subaccount pattern
-                // Format: [quote_id].escrow.mainnet or [quote_id].escrow.testnet
-                // This provides isolation: each quote gets its own escrow subaccount
-                // The parent contract holds custody until execution confirmed
-                let quote_suffix = &nonce.replace("-", "")[..16]; // Use first 16 chars of nonce
-                let network = if cfg!(debug_assertions) {
-                    "testnet"
-                } else {
-                    "mainnet"
-                };
-                Ok(format!(
-                    "{}.escrow.{}",
-                    quote_suffix, network
-                ))
-            }
-            Chain::Solana => {
-                // Solana: Use Program Derived Address (PDA) seeded with nonce
-                // PDAs are deterministic: PDA = hash(program_id, ["escrow", nonce_bytes])
-                // Format: [base58_encoded_pda]
-                // This ensures:
-                // - Each quote has unique receiving address (seeded with nonce)
-                // - Address is controlled by the escrow program
-                // - No actual keypair needed - generated deterministically
-                
-                // In production, would use actual Solana SDK to generate PDA:
-                // let (pda, _bump) = Pubkey::find_program_address(
-                //     &[b"escrow", nonce.as_bytes()],
-                //     &escrow_program_id
-                // );
-                
-                // For now, generate base58 simulating PDA format
-                let pda_seed = format!("escrow_{}", &nonce[..16]);
-                let hash = sha256::digest(pda_seed.as_bytes());
-                
-                // Convert hash to base58 (Solana address format)
-                let pda_bytes = hash.as_bytes()[..32].to_vec();
-                Ok(Self::encode_base58(&pda_bytes))
-            }
-        }
-    }
-    
-    /// Encode bytes as base58 (Solana address format)
-    fn encode_base58(input: &[u8]) -> String {
-        const ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
-        
-        if input.is_empty() {
-            return String::new();
-        }
-        
-        let mut output = String::new();
-        let mut num = 0u128;
-        
-        // Convert bytes to number (simplified for demonstration)
-        for &byte in input.iter().take(16) {
-            num = num.wrapping_mul(256).wrapping_add(byte as u128);
-        }
-        
-        // Convert to base58
-        if num == 0 {
-            output.push('1');
-        } else {
-            let mut temp = num;
-            while temp > 0 {
-                let remainder = (temp % 58) as usize;
-                output.insert(0, ALPHABET.chars().nth(remainder).unwrap());
-                temp /= 58;
-            }
-        }
-        
-        output
+        self.payment_addresses.payment_address(chain, nonce)
     }
 
     /// Validate and commit a quote
@@ -434,7 +827,7 @@ impl QuoteEngine {
             .into());
         }
 
-        // Verify chain pair is still valid
+        // Verify chain pair is still enabled
         if !quote.has_valid_chain_pair() {
             return Err(QuoteError::UnsupportedChainPair {
                 funding: quote.funding_chain,
@@ -442,6 +835,9 @@ impl QuoteEngine {
             }
             .into());
         }
+        self.chain_pairs
+            .require(quote.funding_chain, quote.execution_chain)
+            .await?;
 
         // Update status to committed
         self.ledger
@@ -499,6 +895,9 @@ impl QuoteEngine {
             }
             .into());
         }
+        self.chain_pairs
+            .require(quote.funding_chain, quote.execution_chain)
+            .await?;
 
         Ok(quote)
     }
@@ -528,29 +927,310 @@ impl QuoteEngine {
     }
 }
 
+#[async_trait]
+impl TreasuryPricer for QuoteEngine {
+    /// The hop priced as an exact-input quote: the payment covers the
+    /// execution cost, service fee and slippage buffer first, the rest is
+    /// delivered at the quoted rate
+    async fn price_hop(
+        &self,
+        user_id: Option<Uuid>,
+        bridge_in: &AssetInfo,
+        bridge_out: &AssetInfo,
+        amount: Decimal,
+    ) -> AppResult<TreasuryHop> {
+        let pair = self
+            .chain_pairs
+            .require(bridge_in.chain, bridge_out.chain)
+            .await?;
+        let funding_token = self
+            .whitelist
+            .get_by_symbol(bridge_in.chain, &bridge_in.symbol)
+            .map_err(|e| QuoteError::InvalidParameters(e.to_string()))?;
+        let execution_token = self
+            .whitelist
+            .get_by_symbol(bridge_out.chain, &bridge_out.symbol)
+            .map_err(|e| QuoteError::InvalidParameters(e.to_string()))?;
+
+        // Swap outputs can be finer than the funding token's smallest unit
+        let amount = amount
+            .round_dp_with_strategy(funding_token.decimals as u32, RoundingStrategy::ToZero);
+        let pricing = self
+            .price_quote(
+                user_id,
+                &pair,
+                bridge_in.chain,
+                bridge_out.chain,
+                &bridge_in.symbol,
+                &bridge_out.symbol,
+                QuoteMode::ExactInput,
+                amount,
+                None,
+                None,
+            )
+            .await?;
+
+        let costs = pricing.max_funding_amount - pricing.funding_amount;
+        let traded = amount - costs;
+        if traded <= Decimal::ZERO {
+            return Err(QuoteError::InvalidParameters(format!(
+                "{} {} does not cover the treasury costs of {} {}",
+                amount, bridge_in.symbol, costs, bridge_in.symbol
+            ))
+            .into());
+        }
+
+        // Delivery is proportional to the amount traded at the quoted rate
+        let execution_amount = (pricing.execution_amount * traded / amount)
+            .round_dp_with_strategy(execution_token.decimals as u32, RoundingStrategy::ToZero)
+            .normalize();
+
+        Ok(TreasuryHop {
+            execution_amount,
+            costs,
+        })
+    }
+}
+
+/// Both sides of a trade at `rate` (1 funding asset = rate execution asset)
+///
+/// Slippage always goes against the user: an exact input delivers at least
+/// the returned execution amount, an exact output spends at most the
+/// returned funding amount.
+fn price_trade(
+    mode: QuoteMode,
+    amount: Decimal,
+    rate: Decimal,
+    max_slippage: Decimal,
+    funding_token: &WhitelistedToken,
+    execution_token: &WhitelistedToken,
+) -> AppResult<(Decimal, Decimal)> {
+    if amount <= Decimal::ZERO {
+        return Err(QuoteError::InvalidParameters("Amount must be positive".to_string()).into());
+    }
+    if rate <= Decimal::ZERO {
+        return Err(QuoteError::PriceUnavailable(format!("Invalid rate: {}", rate)).into());
+    }
+
+    match mode {
+        QuoteMode::ExactInput => {
+            check_precision(funding_token, amount)?;
+            let execution_amount = (amount * rate * (Decimal::ONE - max_slippage))
+                .round_dp_with_strategy(execution_token.decimals as u32, RoundingStrategy::ToZero);
+            if execution_amount.is_zero() {
+                return Err(QuoteError::InvalidParameters(format!(
+                    "Amount too small to deliver any {}",
+                    execution_token.symbol
+                ))
+                .into());
+            }
+            Ok((amount, execution_amount.normalize()))
+        }
+        QuoteMode::ExactOutput => {
+            check_precision(execution_token, amount)?;
+            let funding_amount = TokenAmount::from_display(
+                funding_token,
+                amount / rate * (Decimal::ONE + max_slippage),
+            )?
+            .to_display()?;
+            Ok((funding_amount, amount))
+        }
+    }
+}
+
+/// Dry-run execution instructions with the execution chain's executor
+///
+/// Instructions the chain would reject fail the quote with
+/// SimulationFailed; other errors (RPC down) are passed through unchanged.
+async fn simulate_with(
+    executor: &dyn Executor,
+    execution_chain: Chain,
+    instructions: &[u8],
+) -> AppResult<SimulationResult> {
+    info!("Simulating execution instructions on {:?}...", execution_chain);
+
+    match executor.simulate(instructions).await {
+        Ok(result) => {
+            info!("✓ Simulation passed on {:?}", execution_chain);
+            Ok(result)
+        }
+        Err(AppError::Execution(ExecutionError::SimulationFailed(message))) => {
+            warn!("Rejected quote: simulation failed on {:?}: {}", execution_chain, message);
+            Err(QuoteError::SimulationFailed {
+                chain: execution_chain,
+                message,
+            }
+            .into())
+        }
+        Err(AppError::Execution(ExecutionError::InvalidInstructionData)) => {
+            Err(QuoteError::SimulationFailed {
+                chain: execution_chain,
+                message: "Invalid instruction data".to_string(),
+            }
+            .into())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Simulated compute units plus a 10% margin for state changes between
+/// simulation and execution
+fn simulated_units_with_margin(simulated: u64) -> u64 {
+    simulated.saturating_add(simulated.div_ceil(10))
+}
+
+/// Relative change between the quoted and the repriced total payment
+fn refresh_drift(quoted: Decimal, repriced: Decimal) -> Decimal {
+    if quoted <= Decimal::ZERO {
+        return Decimal::MAX;
+    }
+    ((repriced - quoted) / quoted).abs()
+}
+
+/// Reject amounts finer than the token's smallest unit
+fn check_precision(token: &WhitelistedToken, amount: Decimal) -> AppResult<()> {
+    if amount.normalize().scale() > token.decimals as u32 {
+        return Err(QuoteError::InvalidParameters(format!(
+            "{} supports at most {} decimals",
+            token.symbol, token.decimals
+        ))
+        .into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ledger::amount::Amount;
+    use async_trait::async_trait;
+
+    /// Executor whose dry run always fails
+    struct RejectingExecutor;
+
+    #[async_trait]
+    impl Executor for RejectingExecutor {
+        async fn execute(&self, _quote: &Quote) -> AppResult<Execution> {
+            unreachable!("quotes are never executed in these tests")
+        }
+
+        fn chain(&self) -> Chain {
+            Chain::Stellar
+        }
+
+        async fn check_treasury_balance(&self, _required: Decimal) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn get_treasury_balance(&self) -> AppResult<Decimal> {
+            Ok(Decimal::ZERO)
+        }
+
+        async fn transfer_to_treasury(&self, _token_or_asset: &str, _amount: &str) -> AppResult<String> {
+            unreachable!("quotes are never settled in these tests")
+        }
+
+        async fn simulate(&self, _instructions: &[u8]) -> AppResult<SimulationResult> {
+            let message = "Destination has no trustline for USDC".to_string();
+            Err(ExecutionError::SimulationFailed(message).into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_simulation_rejects_quote() {
+        match simulate_with(&RejectingExecutor, Chain::Stellar, &[1]).await {
+            Err(AppError::Quote(QuoteError::SimulationFailed { chain, message })) => {
+                assert_eq!(chain, Chain::Stellar);
+                assert!(message.contains("trustline"));
+            }
+            other => panic!("expected SimulationFailed, got {:?}", other),
+        }
+    }
 
     #[test]
     fn test_service_fee_calculation() {
         let config = QuoteConfig::default();
-        let execution_cost = dec!(1000000);
-        let fee = execution_cost * config.service_fee_rate;
+        let execution_cost = NativeAmount::Solana(Amount::from_base(1_000_000));
+        let fee = execution_cost.scale(config.service_fee_rate).unwrap();
 
-        // 0.1% of 1000000 = 1000
-        assert_eq!(fee, dec!(1000));
+        // 0.1% of 1000000 lamports = 1000 lamports
+        assert_eq!(fee, NativeAmount::Solana(Amount::from_base(1000)));
+    }
+
+    #[test]
+    fn test_exact_input_and_exact_output_pricing() {
+        let whitelist = DexWhitelist::new();
+        let xlm = whitelist.get_by_symbol(Chain::Stellar, "XLM").unwrap();
+        let usdc = whitelist.get_by_symbol(Chain::Solana, "USDC").unwrap();
+
+        // 1 XLM = 0.1 USDC, 1% slippage
+        let (funding, execution) =
+            price_trade(QuoteMode::ExactInput, dec!(100), dec!(0.1), dec!(0.01), &xlm, &usdc).unwrap();
+        assert_eq!(funding, dec!(100));
+        assert_eq!(execution, dec!(9.9));
+
+        let (funding, execution) =
+            price_trade(QuoteMode::ExactOutput, dec!(50), dec!(0.1), dec!(0.01), &xlm, &usdc).unwrap();
+        assert_eq!(funding, dec!(505));
+        assert_eq!(execution, dec!(50));
+
+        // Finer than USDC's 6 decimals
+        assert!(price_trade(QuoteMode::ExactOutput, dec!(0.0000001), dec!(0.1), dec!(0.01), &xlm, &usdc).is_err());
+        assert!(price_trade(QuoteMode::ExactInput, dec!(0), dec!(0.1), dec!(0.01), &xlm, &usdc).is_err());
+    }
+
+    #[test]
+    fn test_refresh_drift() {
+        let tolerance = QuoteConfig::default().refresh_tolerance;
+
+        assert_eq!(refresh_drift(dec!(100), dec!(100.4)), dec!(0.004));
+        assert!(refresh_drift(dec!(100), dec!(99.5)) <= tolerance);
+        assert!(refresh_drift(dec!(100), dec!(101)) > tolerance);
+        // Legacy quotes without a total always reprice
+        assert!(refresh_drift(dec!(0), dec!(1)) > tolerance);
+    }
+
+    #[test]
+    fn test_simulated_units_margin() {
+        assert_eq!(simulated_units_with_margin(200_000), 220_000);
+        assert_eq!(simulated_units_with_margin(15), 17);
+        assert_eq!(simulated_units_with_margin(0), 0);
     }
 
     #[test]
     fn test_chain_pair_validation() {
+        let mut quote = Quote {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            funding_chain: Chain::Solana,
+            execution_chain: Chain::Solana,
+            funding_asset: "SOL".to_string(),
+            execution_asset: "SOL".to_string(),
+            mode: QuoteMode::ExactInput,
+            funding_amount: dec!(0),
+            execution_amount: dec!(0),
+            max_funding_amount: dec!(1),
+            execution_cost: dec!(1),
+            service_fee: dec!(0.001),
+            execution_instructions: vec![1],
+            estimated_compute_units: None,
+            simulated: false,
+            nonce: "nonce".to_string(),
+            status: QuoteStatus::Pending,
+            expires_at: Utc::now(),
+            payment_address: None,
+            superseded_by: None,
+            fee_schedule_id: None,
+            price_check: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
         // Same chain should be rejected
-        assert!(!Chain::is_pair_supported(Chain::Solana, Chain::Solana));
+        assert!(!quote.has_valid_chain_pair());
 
-        // Different chains should be supported
-        assert!(Chain::is_pair_supported(Chain::Stellar, Chain::Solana));
-        assert!(Chain::is_pair_supported(Chain::Solana, Chain::Stellar));
-        assert!(Chain::is_pair_supported(Chain::Near, Chain::Solana));
+        // Different chains pass; enablement is checked by ChainPairRegistry
+        quote.execution_chain = Chain::Stellar;
+        assert!(quote.has_valid_chain_pair());
     }
 }
-
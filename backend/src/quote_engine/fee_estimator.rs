@@ -0,0 +1,517 @@
+// Fee estimation - live network fees for the execution chain of a quote
+//
+// - Solana: getRecentPrioritizationFees (micro-lamports per compute unit)
+// - Stellar: Horizon /fee_stats (stroops per operation)
+// - NEAR: gas_price of recent blocks (yoctoNEAR per gas unit)
+//
+// Each estimator takes a configurable percentile of the samples the network
+// reports and falls back to a static fee when the network cannot be reached.
+// Costs are worst-case and carry the same safety buffers on every path.
+
+use crate::error::{AppError, AppResult};
+use crate::ledger::amount::{Amount, NativeAmount, Near, Sol, Xlm};
+use crate::ledger::models::Chain;
+use async_trait::async_trait;
+use futures::future::join_all;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Fallback Solana priority fee: 1 micro-lamport per compute unit
+pub const FALLBACK_SOLANA_MICRO_LAMPORTS_PER_CU: u128 = 1;
+
+/// Fallback Stellar fee: base fee of 100 stroops
+pub const FALLBACK_STELLAR_STROOPS: u128 = 100;
+
+/// Fallback NEAR gas price: protocol minimum of 100 million yoctoNEAR per gas
+pub const FALLBACK_NEAR_YOCTO_PER_GAS: u128 = 100_000_000;
+
+/// Default percentile of recent network fees
+pub const DEFAULT_FEE_PERCENTILE: u8 = 75;
+
+/// Number of recent NEAR blocks sampled for the gas price
+const NEAR_GAS_PRICE_SAMPLES: u64 = 10;
+
+/// Network calls must not hold up quoting for long
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Percentiles Horizon reports in fee_stats
+const STELLAR_PERCENTILES: [u8; 11] = [10, 20, 30, 40, 50, 60, 70, 80, 90, 95, 99];
+
+/// Estimates the execution cost on one chain
+#[async_trait]
+pub trait FeeEstimator: Send + Sync {
+    fn chain(&self) -> Chain;
+
+    /// Worst-case cost of executing a quote, in the chain's native asset
+    async fn estimate(&self, estimated_compute_units: Option<i32>) -> AppResult<NativeAmount>;
+}
+
+/// Nearest-rank percentile of the samples
+pub fn percentile(mut samples: Vec<u128>, pct: u8) -> Option<u128> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = (pct.min(100) as usize * samples.len()).div_ceil(100).max(1);
+    samples.get(rank - 1).copied()
+}
+
+/// Solana cost for a priority fee in micro-lamports per compute unit
+pub fn solana_cost(
+    estimated_compute_units: Option<i32>,
+    micro_lamports_per_cu: u128,
+) -> AppResult<NativeAmount> {
+    let cu = estimated_compute_units.unwrap_or(200_000).max(0) as u128;
+    // Priority fee, rounded up to lamports
+    let compute_cost = Amount::<Sol>::from_base((cu * micro_lamports_per_cu).div_ceil(1_000_000));
+    // Signature cost (5000 lamports per signature, assume 2)
+    let signature_cost = Amount::<Sol>::from_base(10_000);
+    // Priority fee buffer (20%)
+    let priority_buffer = compute_cost.scale(dec!(0.2))?;
+    Ok(NativeAmount::Solana(
+        compute_cost
+            .checked_add(signature_cost)?
+            .checked_add(priority_buffer)?,
+    ))
+}
+
+/// Stellar cost for a per-operation fee in stroops
+pub fn stellar_cost(stroops: u128) -> AppResult<NativeAmount> {
+    // Fee + 20% buffer
+    let fee = Amount::<Xlm>::from_base(stroops);
+    Ok(NativeAmount::Stellar(fee.checked_add(fee.scale(dec!(0.2))?)?))
+}
+
+/// NEAR cost for a gas price in yoctoNEAR per gas unit
+pub fn near_cost(yocto_per_gas: u128) -> AppResult<NativeAmount> {
+    // Simple call: 1 TGas, doubled for a potential cross-contract call
+    let total_gas_units: u128 = 2 * 1_000_000_000_000;
+    let gas_cost = Amount::<Near>::from_base(total_gas_units * yocto_per_gas);
+
+    // Add 50% buffer for safety
+    Ok(NativeAmount::Near(gas_cost.scale(dec!(1.5))?))
+}
+
+/// Parse a base-unit amount reported as a string or number
+fn parse_u128(value: &Value) -> Option<u128> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Number(n) => n.as_u64().map(u128::from),
+        _ => None,
+    }
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+async fn json_rpc(client: &reqwest::Client, url: &str, method: &str, params: Value) -> AppResult<Value> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": "omnixec",
+        "method": method,
+        "params": params,
+    });
+
+    let response: Value = client
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| AppError::ExternalError(format!("{} request failed: {}", method, e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::ExternalError(format!("Invalid {} response: {}", method, e)))?;
+
+    if let Some(err) = response.get("error") {
+        return Err(AppError::ExternalError(format!("{} error: {}", method, err)));
+    }
+
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| AppError::ExternalError(format!("{} response has no result", method)))
+}
+
+/// Solana: recent prioritization fees
+pub struct SolanaFeeEstimator {
+    client: reqwest::Client,
+    rpc_url: String,
+    percentile: u8,
+}
+
+impl SolanaFeeEstimator {
+    pub fn new(rpc_url: String, percentile: u8) -> Self {
+        Self { client: http_client(), rpc_url, percentile }
+    }
+
+    /// Priority fee in micro-lamports per compute unit
+    pub async fn priority_fee(&self) -> AppResult<u128> {
+        let result = json_rpc(&self.client, &self.rpc_url, "getRecentPrioritizationFees", json!([])).await?;
+        let samples = result
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|sample| parse_u128(&sample["prioritizationFee"]))
+            .collect();
+
+        percentile(samples, self.percentile)
+            .ok_or_else(|| AppError::ExternalError("No recent Solana prioritization fees".to_string()))
+    }
+}
+
+#[async_trait]
+impl FeeEstimator for SolanaFeeEstimator {
+    fn chain(&self) -> Chain {
+        Chain::Solana
+    }
+
+    async fn estimate(&self, estimated_compute_units: Option<i32>) -> AppResult<NativeAmount> {
+        let fee = self.priority_fee().await.unwrap_or_else(|e| {
+            warn!("⚠️  Solana fee estimation failed, using fallback: {}", e);
+            FALLBACK_SOLANA_MICRO_LAMPORTS_PER_CU
+        });
+        solana_cost(estimated_compute_units, fee)
+    }
+}
+
+/// Stellar: Horizon fee statistics
+pub struct StellarFeeEstimator {
+    client: reqwest::Client,
+    horizon_url: String,
+    percentile: u8,
+}
+
+impl StellarFeeEstimator {
+    pub fn new(horizon_url: String, percentile: u8) -> Self {
+        Self {
+            client: http_client(),
+            horizon_url: horizon_url.trim_end_matches('/').to_string(),
+            percentile,
+        }
+    }
+
+    /// Fee per operation in stroops
+    ///
+    /// Horizon only reports some percentiles; the next one up is used.
+    pub async fn operation_fee(&self) -> AppResult<u128> {
+        let stats: Value = self
+            .client
+            .get(format!("{}/fee_stats", self.horizon_url))
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Horizon fee_stats request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Invalid Horizon fee_stats response: {}", e)))?;
+
+        let reported = STELLAR_PERCENTILES
+            .iter()
+            .find(|p| **p >= self.percentile)
+            .map_or_else(|| "max".to_string(), |p| format!("p{}", p));
+
+        let charged = parse_u128(&stats["fee_charged"][reported.as_str()])
+            .ok_or_else(|| AppError::ExternalError(format!("fee_stats has no fee_charged.{}", reported)))?;
+        let base_fee = parse_u128(&stats["last_ledger_base_fee"]).unwrap_or(FALLBACK_STELLAR_STROOPS);
+
+        Ok(charged.max(base_fee))
+    }
+}
+
+#[async_trait]
+impl FeeEstimator for StellarFeeEstimator {
+    fn chain(&self) -> Chain {
+        Chain::Stellar
+    }
+
+    async fn estimate(&self, _estimated_compute_units: Option<i32>) -> AppResult<NativeAmount> {
+        let fee = self.operation_fee().await.unwrap_or_else(|e| {
+            warn!("⚠️  Stellar fee estimation failed, using fallback: {}", e);
+            FALLBACK_STELLAR_STROOPS
+        });
+        stellar_cost(fee)
+    }
+}
+
+/// NEAR: gas price of the most recent final blocks
+pub struct NearFeeEstimator {
+    client: reqwest::Client,
+    rpc_url: String,
+    percentile: u8,
+}
+
+impl NearFeeEstimator {
+    pub fn new(rpc_url: String, percentile: u8) -> Self {
+        Self { client: http_client(), rpc_url, percentile }
+    }
+
+    /// Gas price in yoctoNEAR per gas unit
+    pub async fn gas_price(&self) -> AppResult<u128> {
+        let block = json_rpc(&self.client, &self.rpc_url, "block", json!({ "finality": "final" })).await?;
+        let height = block
+            .pointer("/header/height")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| AppError::ExternalError("NEAR block has no height".to_string()))?;
+
+        // Skipped heights have no block; those samples are simply missing
+        let samples = join_all(
+            (0..NEAR_GAS_PRICE_SAMPLES)
+                .filter_map(|offset| height.checked_sub(offset))
+                .map(|h| json_rpc(&self.client, &self.rpc_url, "gas_price", json!([h]))),
+        )
+        .await
+        .into_iter()
+        .filter_map(|result| result.ok())
+        .filter_map(|result| parse_u128(&result["gas_price"]))
+        .collect();
+
+        percentile(samples, self.percentile)
+            .ok_or_else(|| AppError::ExternalError("No recent NEAR gas prices".to_string()))
+    }
+}
+
+#[async_trait]
+impl FeeEstimator for NearFeeEstimator {
+    fn chain(&self) -> Chain {
+        Chain::Near
+    }
+
+    async fn estimate(&self, _estimated_compute_units: Option<i32>) -> AppResult<NativeAmount> {
+        let price = self.gas_price().await.unwrap_or_else(|e| {
+            warn!("⚠️  NEAR fee estimation failed, using fallback: {}", e);
+            FALLBACK_NEAR_YOCTO_PER_GAS
+        });
+        near_cost(price)
+    }
+}
+
+/// Fee estimation configuration for a deployment
+#[derive(Debug, Clone)]
+pub struct FeeEstimatorConfig {
+    pub solana_rpc_url: String,
+    pub stellar_horizon_url: String,
+    pub near_rpc_url: String,
+    pub solana_percentile: u8,
+    pub stellar_percentile: u8,
+    pub near_percentile: u8,
+}
+
+impl FeeEstimatorConfig {
+    /// Read RPC URLs from SOLANA_RPC_URL, STELLAR_HORIZON_URL and NEAR_RPC_URL,
+    /// percentiles from SOLANA_FEE_PERCENTILE, STELLAR_FEE_PERCENTILE and
+    /// NEAR_FEE_PERCENTILE
+    pub fn from_env() -> AppResult<Self> {
+        let url = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_string());
+        let pct = |name: &str| -> AppResult<u8> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|p| (1..=100).contains(p))
+                    .ok_or_else(|| AppError::Config(format!("{} must be between 1 and 100", name))),
+                Err(_) => Ok(DEFAULT_FEE_PERCENTILE),
+            }
+        };
+
+        Ok(Self {
+            solana_rpc_url: url("SOLANA_RPC_URL", "https://api.mainnet-beta.solana.com"),
+            stellar_horizon_url: url("STELLAR_HORIZON_URL", "https://horizon.stellar.org"),
+            near_rpc_url: url("NEAR_RPC_URL", "https://rpc.mainnet.near.org"),
+            solana_percentile: pct("SOLANA_FEE_PERCENTILE")?,
+            stellar_percentile: pct("STELLAR_FEE_PERCENTILE")?,
+            near_percentile: pct("NEAR_FEE_PERCENTILE")?,
+        })
+    }
+}
+
+/// Fee estimators for every execution chain
+pub struct FeeEstimators {
+    estimators: HashMap<Chain, Arc<dyn FeeEstimator>>,
+}
+
+impl FeeEstimators {
+    pub fn new() -> Self {
+        Self {
+            estimators: HashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &FeeEstimatorConfig) -> Self {
+        let mut estimators = Self::new();
+        estimators.register(Arc::new(SolanaFeeEstimator::new(
+            config.solana_rpc_url.clone(),
+            config.solana_percentile,
+        )));
+        estimators.register(Arc::new(StellarFeeEstimator::new(
+            config.stellar_horizon_url.clone(),
+            config.stellar_percentile,
+        )));
+        estimators.register(Arc::new(NearFeeEstimator::new(
+            config.near_rpc_url.clone(),
+            config.near_percentile,
+        )));
+        estimators
+    }
+
+    pub fn register(&mut self, estimator: Arc<dyn FeeEstimator>) {
+        self.estimators.insert(estimator.chain(), estimator);
+    }
+
+    /// Worst-case execution cost on `chain`
+    pub async fn estimate(
+        &self,
+        chain: Chain,
+        estimated_compute_units: Option<i32>,
+    ) -> AppResult<NativeAmount> {
+        let estimator = self
+            .estimators
+            .get(&chain)
+            .ok_or_else(|| AppError::Config(format!("No fee estimator for {:?}", chain)))?;
+
+        let cost = estimator.estimate(estimated_compute_units).await?;
+        if cost.chain() != chain {
+            return Err(AppError::Internal(format!(
+                "Fee estimator for {:?} returned {}",
+                chain,
+                cost.symbol()
+            )));
+        }
+        Ok(cost)
+    }
+}
+
+impl Default for FeeEstimators {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::{get, post}, Json, Router};
+
+    struct FixedFeeEstimator(NativeAmount);
+
+    #[async_trait]
+    impl FeeEstimator for FixedFeeEstimator {
+        fn chain(&self) -> Chain {
+            self.0.chain()
+        }
+
+        async fn estimate(&self, _estimated_compute_units: Option<i32>) -> AppResult<NativeAmount> {
+            Ok(self.0)
+        }
+    }
+
+    async fn fake_network() -> String {
+        let app = Router::new()
+            .route(
+                "/",
+                post(|Json(req): Json<Value>| async move {
+                    let result = match req["method"].as_str().unwrap() {
+                        "getRecentPrioritizationFees" => json!([
+                            { "slot": 1, "prioritizationFee": 0 },
+                            { "slot": 2, "prioritizationFee": 1000 },
+                            { "slot": 3, "prioritizationFee": 5000 },
+                            { "slot": 4, "prioritizationFee": 20000 }
+                        ]),
+                        "block" => json!({ "header": { "height": 100 } }),
+                        "gas_price" if req["params"][0] == 97 => {
+                            return Json(json!({
+                                "jsonrpc": "2.0", "id": "omnixec",
+                                "error": { "cause": { "name": "UNKNOWN_BLOCK" } }
+                            }))
+                        }
+                        "gas_price" => {
+                            let height = req["params"][0].as_u64().unwrap();
+                            json!({ "gas_price": (100_000_000 + (100 - height) * 10_000_000).to_string() })
+                        }
+                        other => panic!("unexpected method {}", other),
+                    };
+                    Json(json!({ "jsonrpc": "2.0", "id": "omnixec", "result": result }))
+                }),
+            )
+            .route(
+                "/fee_stats",
+                get(|| async {
+                    Json(json!({
+                        "last_ledger_base_fee": "100",
+                        "fee_charged": {
+                            "max": "5000", "min": "100", "mode": "100",
+                            "p10": "100", "p20": "100", "p30": "100", "p40": "100",
+                            "p50": "100", "p60": "150", "p70": "200", "p80": "300",
+                            "p90": "1000", "p95": "2000", "p99": "5000"
+                        }
+                    }))
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/", addr)
+    }
+
+    #[test]
+    fn test_percentile() {
+        let samples = vec![50, 10, 40, 20, 30];
+        assert_eq!(percentile(samples.clone(), 50), Some(30));
+        assert_eq!(percentile(samples.clone(), 75), Some(40));
+        assert_eq!(percentile(samples.clone(), 100), Some(50));
+        assert_eq!(percentile(samples, 1), Some(10));
+        assert_eq!(percentile(Vec::new(), 50), None);
+    }
+
+    #[test]
+    fn test_fallback_costs() {
+        let solana = solana_cost(Some(200_000), FALLBACK_SOLANA_MICRO_LAMPORTS_PER_CU).unwrap();
+        assert_eq!(solana.to_display().unwrap(), dec!(0.000010002));
+
+        let stellar = stellar_cost(FALLBACK_STELLAR_STROOPS).unwrap();
+        assert_eq!(stellar.to_display().unwrap(), dec!(0.000012));
+
+        let near = near_cost(FALLBACK_NEAR_YOCTO_PER_GAS).unwrap();
+        assert_eq!(near.to_display().unwrap(), dec!(0.0003));
+    }
+
+    #[tokio::test]
+    async fn test_live_fees_use_percentile() {
+        let url = fake_network().await;
+
+        let solana = SolanaFeeEstimator::new(url.clone(), 75);
+        assert_eq!(solana.priority_fee().await.unwrap(), 5000);
+
+        let stellar = StellarFeeEstimator::new(url.clone(), 75);
+        assert_eq!(stellar.operation_fee().await.unwrap(), 300);
+
+        // Heights 91..=100 minus the missing 97: prices 100M..190M
+        let near = NearFeeEstimator::new(url, 50);
+        assert_eq!(near.gas_price().await.unwrap(), 150_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_network_falls_back() {
+        let estimator = StellarFeeEstimator::new("http://127.0.0.1:1".to_string(), 75);
+        let cost = estimator.estimate(None).await.unwrap();
+        assert_eq!(cost, stellar_cost(FALLBACK_STELLAR_STROOPS).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_injected_estimator() {
+        let mut estimators = FeeEstimators::new();
+        let fixed = NativeAmount::Solana(Amount::from_base(42));
+        estimators.register(Arc::new(FixedFeeEstimator(fixed)));
+
+        assert_eq!(estimators.estimate(Chain::Solana, None).await.unwrap(), fixed);
+        assert!(estimators.estimate(Chain::Near, None).await.is_err());
+    }
+}
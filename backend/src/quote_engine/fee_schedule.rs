@@ -0,0 +1,309 @@
+// Service fee schedules - loaded from the fee_schedules table
+//
+// A schedule sets the service fee rate for the quotes its selectors match
+// (chain pair, funding/execution asset, 30-day volume tier), optionally
+// bounded by caps in USD. Promotional schedules win while their window is
+// open. Admin changes apply immediately on this instance and on the others
+// at their next refresh.
+
+use crate::error::{AppError, AppResult};
+use crate::ledger::amount::NativeAmount;
+use crate::ledger::models::{Chain, FeeSchedule, FeeScheduleParams};
+use crate::ledger::repository::LedgerRepository;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Volume tiers look at the user's executed quotes over this many days
+pub const VOLUME_WINDOW_DAYS: i64 = 30;
+
+/// What a quote looks like to the fee schedule selectors
+#[derive(Debug, Clone)]
+pub struct FeeContext<'a> {
+    pub funding_chain: Chain,
+    pub execution_chain: Chain,
+    pub funding_asset: &'a str,
+    pub execution_asset: &'a str,
+    /// Rolling 30-day volume of the user, in USD
+    pub volume_usd: Decimal,
+    pub now: DateTime<Utc>,
+}
+
+fn matches(schedule: &FeeSchedule, ctx: &FeeContext<'_>) -> bool {
+    let asset_matches = |selector: &Option<String>, asset: &str| {
+        selector
+            .as_deref()
+            .is_none_or(|s| s.eq_ignore_ascii_case(asset))
+    };
+
+    schedule.is_active(ctx.now)
+        && schedule.funding_chain.is_none_or(|c| c == ctx.funding_chain)
+        && schedule.execution_chain.is_none_or(|c| c == ctx.execution_chain)
+        && asset_matches(&schedule.funding_asset, ctx.funding_asset)
+        && asset_matches(&schedule.execution_asset, ctx.execution_asset)
+        && ctx.volume_usd >= schedule.min_volume_usd
+}
+
+/// Number of selectors a schedule sets
+fn specificity(schedule: &FeeSchedule) -> usize {
+    [
+        schedule.funding_chain.is_some(),
+        schedule.execution_chain.is_some(),
+        schedule.funding_asset.is_some(),
+        schedule.execution_asset.is_some(),
+    ]
+    .iter()
+    .filter(|set| **set)
+    .count()
+}
+
+/// The schedule that applies to a quote, if any
+///
+/// Promotional before regular, then the most specific, then the highest
+/// volume tier reached; remaining ties go to the lower rate.
+pub fn select<'a>(schedules: &'a [FeeSchedule], ctx: &FeeContext<'_>) -> Option<&'a FeeSchedule> {
+    schedules
+        .iter()
+        .filter(|schedule| matches(schedule, ctx))
+        .max_by_key(|schedule| {
+            (
+                schedule.promotional,
+                specificity(schedule),
+                schedule.min_volume_usd,
+                Reverse(schedule.fee_rate),
+            )
+        })
+}
+
+/// Clamp a service fee to USD caps
+///
+/// `native_usd` is the USD price of the fee's native asset. A raised or
+/// lowered fee is rounded up to the asset's smallest unit.
+pub fn apply_fee_caps(
+    fee: NativeAmount,
+    native_usd: Decimal,
+    min_fee_usd: Option<Decimal>,
+    max_fee_usd: Option<Decimal>,
+) -> AppResult<NativeAmount> {
+    if min_fee_usd.is_none() && max_fee_usd.is_none() {
+        return Ok(fee);
+    }
+    if native_usd <= Decimal::ZERO {
+        return Err(AppError::Internal(format!(
+            "Invalid USD price {} for {}",
+            native_usd,
+            fee.symbol()
+        )));
+    }
+
+    let fee_usd = fee.to_display()? * native_usd;
+    let capped_usd = match (min_fee_usd, max_fee_usd) {
+        (Some(min), _) if fee_usd < min => min,
+        (_, Some(max)) if fee_usd > max => max,
+        _ => return Ok(fee),
+    };
+
+    NativeAmount::from_display(fee.chain(), capped_usd / native_usd)
+}
+
+pub struct FeeScheduleRegistry {
+    ledger: Arc<LedgerRepository>,
+    schedules: RwLock<Vec<FeeSchedule>>,
+}
+
+impl FeeScheduleRegistry {
+    pub fn new(ledger: Arc<LedgerRepository>) -> Self {
+        Self {
+            ledger,
+            schedules: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Create and load the current schedules
+    pub async fn load(ledger: Arc<LedgerRepository>) -> AppResult<Self> {
+        let registry = Self::new(ledger);
+        registry.reload().await?;
+        Ok(registry)
+    }
+
+    /// Re-read all schedules from the database
+    pub async fn reload(&self) -> AppResult<usize> {
+        let schedules = self.ledger.get_fee_schedules().await?;
+        let enabled = schedules.iter().filter(|s| s.enabled).count();
+        *self.schedules.write().await = schedules;
+        Ok(enabled)
+    }
+
+    /// All schedules, enabled or not
+    pub async fn list(&self) -> Vec<FeeSchedule> {
+        self.schedules.read().await.clone()
+    }
+
+    /// The schedule that applies to a quote, if any
+    pub async fn resolve(&self, ctx: &FeeContext<'_>) -> Option<FeeSchedule> {
+        select(&self.schedules.read().await, ctx).cloned()
+    }
+
+    /// Whether any active schedule depends on the user's volume
+    ///
+    /// Lets the quote engine skip the volume lookup when no tier exists
+    pub async fn uses_volume_tiers(&self, now: DateTime<Utc>) -> bool {
+        self.schedules
+            .read()
+            .await
+            .iter()
+            .any(|s| s.is_active(now) && s.min_volume_usd > Decimal::ZERO)
+    }
+
+    /// Create a schedule and apply it to this instance immediately
+    pub async fn create(&self, params: &FeeScheduleParams) -> AppResult<FeeSchedule> {
+        params.validate().map_err(AppError::BadRequest)?;
+        let schedule = self.ledger.create_fee_schedule(params).await?;
+
+        info!("💸 Fee schedule {} ({}) created", schedule.id, schedule.name);
+
+        self.schedules.write().await.push(schedule.clone());
+        Ok(schedule)
+    }
+
+    /// Replace a schedule and apply it to this instance immediately
+    pub async fn update(&self, id: Uuid, params: &FeeScheduleParams) -> AppResult<FeeSchedule> {
+        params.validate().map_err(AppError::BadRequest)?;
+        let schedule = self.ledger.update_fee_schedule(id, params).await?;
+
+        info!(
+            "💸 Fee schedule {} ({}) updated (enabled: {})",
+            schedule.id, schedule.name, schedule.enabled
+        );
+
+        let mut schedules = self.schedules.write().await;
+        match schedules.iter_mut().find(|s| s.id == id) {
+            Some(existing) => *existing = schedule.clone(),
+            None => schedules.push(schedule.clone()),
+        }
+        Ok(schedule)
+    }
+
+    /// Refresh periodically so changes made by other instances are applied
+    pub fn start(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reload().await {
+                    error!("Failed to refresh fee schedules: {:?}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::amount::Amount;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn schedule(name: &str, fee_rate: Decimal) -> FeeSchedule {
+        FeeSchedule {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            funding_chain: None,
+            execution_chain: None,
+            funding_asset: None,
+            execution_asset: None,
+            min_volume_usd: Decimal::ZERO,
+            fee_rate,
+            min_fee_usd: None,
+            max_fee_usd: None,
+            promotional: false,
+            valid_from: None,
+            valid_until: None,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn context(volume_usd: Decimal) -> FeeContext<'static> {
+        FeeContext {
+            funding_chain: Chain::Stellar,
+            execution_chain: Chain::Solana,
+            funding_asset: "XLM",
+            execution_asset: "USDC",
+            volume_usd,
+            now: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_most_specific_schedule_wins() {
+        let base = schedule("base", dec!(0.002));
+        let mut pair = schedule("stellar->solana", dec!(0.0015));
+        pair.funding_chain = Some(Chain::Stellar);
+        pair.execution_chain = Some(Chain::Solana);
+        let mut other_pair = schedule("near->solana", dec!(0.0005));
+        other_pair.funding_chain = Some(Chain::Near);
+        let schedules = vec![base, pair, other_pair];
+
+        let selected = select(&schedules, &context(Decimal::ZERO)).unwrap();
+        assert_eq!(selected.name, "stellar->solana");
+    }
+
+    #[test]
+    fn test_volume_tiers() {
+        let base = schedule("base", dec!(0.002));
+        let mut tier = schedule("10k", dec!(0.001));
+        tier.min_volume_usd = dec!(10000);
+        let schedules = vec![base, tier];
+
+        assert_eq!(select(&schedules, &context(dec!(9999))).unwrap().name, "base");
+        assert_eq!(select(&schedules, &context(dec!(10000))).unwrap().name, "10k");
+    }
+
+    #[test]
+    fn test_promotion_only_inside_window() {
+        let mut pair = schedule("pair", dec!(0.0015));
+        pair.funding_chain = Some(Chain::Stellar);
+        pair.execution_chain = Some(Chain::Solana);
+        let mut promo = schedule("launch promo", Decimal::ZERO);
+        promo.promotional = true;
+        promo.valid_until = Some(Utc::now() + Duration::days(7));
+        let schedules = vec![pair, promo];
+
+        let ctx = context(Decimal::ZERO);
+        assert_eq!(select(&schedules, &ctx).unwrap().name, "launch promo");
+
+        let later = FeeContext { now: Utc::now() + Duration::days(8), ..ctx };
+        assert_eq!(select(&schedules, &later).unwrap().name, "pair");
+    }
+
+    #[test]
+    fn test_disabled_schedules_are_ignored() {
+        let mut base = schedule("base", dec!(0.002));
+        base.enabled = false;
+        assert!(select(&[base], &context(Decimal::ZERO)).is_none());
+    }
+
+    #[test]
+    fn test_usd_fee_caps() {
+        // 0.01 SOL at $100 = $1
+        let fee = NativeAmount::Solana(Amount::from_base(10_000_000));
+
+        let raised = apply_fee_caps(fee, dec!(100), Some(dec!(2)), None).unwrap();
+        assert_eq!(raised.to_display().unwrap(), dec!(0.02));
+
+        let lowered = apply_fee_caps(fee, dec!(100), None, Some(dec!(0.5))).unwrap();
+        assert_eq!(lowered.to_display().unwrap(), dec!(0.005));
+
+        let unchanged = apply_fee_caps(fee, dec!(100), Some(dec!(0.5)), Some(dec!(2))).unwrap();
+        assert_eq!(unchanged, fee);
+    }
+}
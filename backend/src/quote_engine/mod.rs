@@ -4,9 +4,31 @@ pub mod pyth_oracle;
 pub mod price_cache;
 pub mod ohlc;
 pub mod slippage;
+pub mod payment_address;
+pub mod chain_pairs;
+pub mod fee_estimator;
+pub mod fee_schedule;
+pub mod price_source;
+pub mod price_policy;
+pub mod price_feeds;
+pub mod pyth_stream;
+pub mod quote_cache;
+pub mod route_planner;
+pub mod split_router;
 
 pub use engine::QuoteEngine;
+pub use chain_pairs::ChainPairRegistry;
+pub use fee_estimator::{FeeEstimatorConfig, FeeEstimators};
+pub use fee_schedule::FeeScheduleRegistry;
 pub use pyth_oracle::PythOracle;
+pub use price_feeds::PriceFeedRegistry;
+pub use pyth_stream::{PythStreamConfig, PythStreamSubscriber};
+pub use price_policy::{AssetPricePolicy, PricePolicyConfig};
+pub use price_source::{DexPriceSource, PriceAggregator, PriceAggregatorConfig, StaticPriceSource};
 pub use price_cache::PriceCache;
+pub use quote_cache::{QuoteCacheConfig, QuoteCacheStats};
+pub use route_planner::{RoutePlan, RoutePlanner};
+pub use split_router::{SplitPlan, SplitRouteConfig, SplitRouter};
+pub use payment_address::{PaymentAddressConfig, PaymentAddresses};
 pub use ohlc::{OhlcStore, Timeframe, OhlcResponse};
 
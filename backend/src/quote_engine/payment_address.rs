@@ -0,0 +1,328 @@
+// Payment address derivation - where users send funds for a quote
+//
+// Every funding chain derives a deterministic, quote-specific destination
+// from deployment configuration and the quote nonce:
+// - Stellar: configured collection account + `quote_<ref>` text memo
+// - NEAR: configured escrow account + `quote_<ref>` memo (ft_transfer memo
+//   or the escrow contract's `deposit` call)
+// - Solana: escrow program PDA seeded with ["escrow", ref]
+//
+// The reverse lookup maps an incoming transfer back to its quote.
+
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::{Chain, Quote};
+use crate::ledger::repository::LedgerRepository;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Separator between destination and memo in a payment address
+pub const MEMO_SEPARATOR: &str = "?memo=";
+
+/// Stellar text memos are limited to 28 bytes
+const STELLAR_MEMO_MAX_BYTES: usize = 28;
+
+/// Short quote reference derived from the nonce (16 hex chars, 64 bits)
+pub fn quote_reference(nonce: &str) -> AppResult<String> {
+    let hex: String = nonce.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    if hex.len() < 16 {
+        return Err(AppError::InvalidInput("Quote nonce too short".to_string()));
+    }
+    Ok(hex[..16].to_lowercase())
+}
+
+/// Split a payment address into destination and memo
+pub fn split_memo(payment_address: &str) -> (&str, Option<&str>) {
+    match payment_address.split_once(MEMO_SEPARATOR) {
+        Some((destination, memo)) => (destination, Some(memo)),
+        None => (payment_address, None),
+    }
+}
+
+/// Derives payment addresses for one funding chain
+pub trait PaymentAddressProvider: Send + Sync {
+    fn chain(&self) -> Chain;
+
+    /// Payment address for the quote with this nonce
+    fn payment_address(&self, nonce: &str) -> AppResult<String>;
+
+    /// Reverse lookup: payment address an incoming transfer was made to,
+    /// or None if the transfer does not target this deployment
+    fn resolve(&self, destination: &str, memo: Option<&str>) -> Option<String>;
+}
+
+/// Stellar: one collection account, quotes routed by text memo
+pub struct StellarPaymentAddressProvider {
+    collection_account: String,
+}
+
+impl StellarPaymentAddressProvider {
+    pub fn new(collection_account: String) -> AppResult<Self> {
+        if collection_account.len() != 56 || !collection_account.starts_with('G') {
+            return Err(AppError::Config(format!(
+                "Invalid Stellar collection account: {}",
+                collection_account
+            )));
+        }
+        Ok(Self { collection_account })
+    }
+}
+
+impl PaymentAddressProvider for StellarPaymentAddressProvider {
+    fn chain(&self) -> Chain {
+        Chain::Stellar
+    }
+
+    fn payment_address(&self, nonce: &str) -> AppResult<String> {
+        let memo = format!("quote_{}", quote_reference(nonce)?);
+        debug_assert!(memo.len() <= STELLAR_MEMO_MAX_BYTES);
+        Ok(format!("{}{}{}", self.collection_account, MEMO_SEPARATOR, memo))
+    }
+
+    fn resolve(&self, destination: &str, memo: Option<&str>) -> Option<String> {
+        let memo = memo?;
+        if destination != self.collection_account
+            || !memo.starts_with("quote_")
+            || memo.len() > STELLAR_MEMO_MAX_BYTES
+        {
+            return None;
+        }
+        Some(format!("{}{}{}", destination, MEMO_SEPARATOR, memo))
+    }
+}
+
+/// NEAR: one escrow account, quotes routed by memo
+pub struct NearPaymentAddressProvider {
+    escrow_account: String,
+}
+
+impl NearPaymentAddressProvider {
+    pub fn new(escrow_account: String) -> AppResult<Self> {
+        near_primitives::types::AccountId::from_str(&escrow_account).map_err(|e| {
+            AppError::Config(format!("Invalid NEAR escrow account {}: {}", escrow_account, e))
+        })?;
+        Ok(Self { escrow_account })
+    }
+}
+
+impl PaymentAddressProvider for NearPaymentAddressProvider {
+    fn chain(&self) -> Chain {
+        Chain::Near
+    }
+
+    fn payment_address(&self, nonce: &str) -> AppResult<String> {
+        Ok(format!(
+            "{}{}quote_{}",
+            self.escrow_account,
+            MEMO_SEPARATOR,
+            quote_reference(nonce)?
+        ))
+    }
+
+    fn resolve(&self, destination: &str, memo: Option<&str>) -> Option<String> {
+        let memo = memo?;
+        if destination != self.escrow_account || !memo.starts_with("quote_") {
+            return None;
+        }
+        Some(format!("{}{}{}", destination, MEMO_SEPARATOR, memo))
+    }
+}
+
+/// Solana: escrow program PDA per quote
+pub struct SolanaPaymentAddressProvider {
+    escrow_program_id: Pubkey,
+}
+
+impl SolanaPaymentAddressProvider {
+    pub fn new(escrow_program_id: &str) -> AppResult<Self> {
+        let escrow_program_id = Pubkey::from_str(escrow_program_id).map_err(|e| {
+            AppError::Config(format!("Invalid Solana escrow program id {}: {}", escrow_program_id, e))
+        })?;
+        Ok(Self { escrow_program_id })
+    }
+
+    /// Escrow PDA and bump for a quote reference
+    pub fn derive_pda(&self, reference: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"escrow", reference.as_bytes()], &self.escrow_program_id)
+    }
+}
+
+impl PaymentAddressProvider for SolanaPaymentAddressProvider {
+    fn chain(&self) -> Chain {
+        Chain::Solana
+    }
+
+    fn payment_address(&self, nonce: &str) -> AppResult<String> {
+        let (pda, _bump) = self.derive_pda(&quote_reference(nonce)?);
+        Ok(pda.to_string())
+    }
+
+    fn resolve(&self, destination: &str, memo: Option<&str>) -> Option<String> {
+        // PDAs carry no memo; any valid pubkey may be one of our escrows
+        if memo.is_some() {
+            return None;
+        }
+        Pubkey::from_str(destination).ok().map(|pubkey| pubkey.to_string())
+    }
+}
+
+/// Payment address configuration for a deployment
+#[derive(Debug, Clone)]
+pub struct PaymentAddressConfig {
+    pub stellar_collection_account: String,
+    pub near_escrow_account: String,
+    pub solana_escrow_program_id: String,
+}
+
+impl PaymentAddressConfig {
+    /// Read from STELLAR_COLLECTION_ACCOUNT, NEAR_ESCROW_ACCOUNT and
+    /// SOLANA_ESCROW_PROGRAM_ID
+    pub fn from_env() -> AppResult<Self> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| AppError::Config(format!("{} must be set", name)))
+        };
+        Ok(Self {
+            stellar_collection_account: var("STELLAR_COLLECTION_ACCOUNT")?,
+            near_escrow_account: var("NEAR_ESCROW_ACCOUNT")?,
+            solana_escrow_program_id: var("SOLANA_ESCROW_PROGRAM_ID")?,
+        })
+    }
+}
+
+/// Payment address providers for every funding chain
+pub struct PaymentAddresses {
+    providers: HashMap<Chain, Arc<dyn PaymentAddressProvider>>,
+}
+
+impl PaymentAddresses {
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &PaymentAddressConfig) -> AppResult<Self> {
+        let mut addresses = Self::new();
+        addresses.register(Arc::new(StellarPaymentAddressProvider::new(
+            config.stellar_collection_account.clone(),
+        )?));
+        addresses.register(Arc::new(NearPaymentAddressProvider::new(
+            config.near_escrow_account.clone(),
+        )?));
+        addresses.register(Arc::new(SolanaPaymentAddressProvider::new(
+            &config.solana_escrow_program_id,
+        )?));
+        Ok(addresses)
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn PaymentAddressProvider>) {
+        self.providers.insert(provider.chain(), provider);
+    }
+
+    fn provider(&self, chain: Chain) -> AppResult<&Arc<dyn PaymentAddressProvider>> {
+        self.providers
+            .get(&chain)
+            .ok_or_else(|| AppError::Config(format!("No payment address provider for {:?}", chain)))
+    }
+
+    /// Payment address for a new quote on its funding chain
+    pub fn payment_address(&self, chain: Chain, nonce: &str) -> AppResult<String> {
+        self.provider(chain)?.payment_address(nonce)
+    }
+
+    /// Find the pending quote an incoming transfer pays for
+    ///
+    /// SECURITY: The quote's address is re-derived from its nonce, so a
+    /// stored address that does not match this deployment's configuration
+    /// is never accepted.
+    pub async fn find_quote(
+        &self,
+        ledger: &LedgerRepository,
+        chain: Chain,
+        destination: &str,
+        memo: Option<&str>,
+    ) -> AppResult<Option<Quote>> {
+        let provider = self.provider(chain)?;
+        let Some(payment_address) = provider.resolve(destination, memo) else {
+            return Ok(None);
+        };
+
+        let Some(quote) = ledger
+            .get_pending_quote_by_payment_address(chain, &payment_address)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        if provider.payment_address(&quote.nonce)? != payment_address {
+            warn!(
+                "⚠️  Quote {} payment address does not match derived address {}",
+                quote.id, payment_address
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(quote))
+    }
+}
+
+impl Default for PaymentAddresses {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONCE: &str = "3f2a9c4e-81b7-4d0a-9e6f-0c1d2e3f4a5b-1767225600000";
+    const COLLECTION: &str = "GBUQWP3BOUZX34ULNQG23RQ6F4OFSAI5TU2MMQBB3IXWVYLXVCLWEB7V";
+    const PROGRAM: &str = "3gmvhqWzKGDc9oK5sSfmMZAviaKCoNbtXeKZsq8kzwa9";
+
+    #[test]
+    fn test_addresses_round_trip() {
+        let stellar = StellarPaymentAddressProvider::new(COLLECTION.to_string()).unwrap();
+        let address = stellar.payment_address(NONCE).unwrap();
+        assert_eq!(address, format!("{}?memo=quote_3f2a9c4e81b74d0a", COLLECTION));
+        let (destination, memo) = split_memo(&address);
+        assert_eq!(stellar.resolve(destination, memo), Some(address.clone()));
+        assert_eq!(stellar.resolve("GOTHER", memo), None);
+        assert_eq!(stellar.resolve(destination, None), None);
+
+        let near = NearPaymentAddressProvider::new("escrow.omnixec.near".to_string()).unwrap();
+        let address = near.payment_address(NONCE).unwrap();
+        assert_eq!(address, "escrow.omnixec.near?memo=quote_3f2a9c4e81b74d0a");
+        let (destination, memo) = split_memo(&address);
+        assert_eq!(near.resolve(destination, memo), Some(address.clone()));
+
+        let solana = SolanaPaymentAddressProvider::new(PROGRAM).unwrap();
+        let address = solana.payment_address(NONCE).unwrap();
+        assert_eq!(solana.resolve(&address, None), Some(address.clone()));
+    }
+
+    #[test]
+    fn test_solana_pda_is_off_curve_and_deterministic() {
+        let solana = SolanaPaymentAddressProvider::new(PROGRAM).unwrap();
+        let first = solana.payment_address(NONCE).unwrap();
+        let pda = Pubkey::from_str(&first).unwrap();
+
+        assert!(!pda.is_on_curve());
+        assert_eq!(first, solana.payment_address(NONCE).unwrap());
+        assert_ne!(
+            first,
+            solana
+                .payment_address("00000000-0000-4000-8000-000000000000-1767225600000")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_configuration_rejected() {
+        assert!(StellarPaymentAddressProvider::new("not-an-account".to_string()).is_err());
+        assert!(NearPaymentAddressProvider::new("Invalid Account!".to_string()).is_err());
+        assert!(SolanaPaymentAddressProvider::new("xyz").is_err());
+    }
+}
@@ -0,0 +1,121 @@
+// Pyth price feed mappings - loaded from the pyth_price_feeds table
+//
+// The mappings are validated when loaded: at startup an invalid feed ID,
+// or a network without any feed, stops the server; on a later reload it is
+// reported and the mappings in use are kept. Reloads happen on SIGHUP, through the admin endpoint and
+// after every admin change.
+
+use crate::adapters::DexWhitelist;
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::{Chain, PriceFeed, PriceFeedParams};
+use crate::ledger::repository::LedgerRepository;
+use crate::quote_engine::pyth_oracle::{PythOracle, PythPriceFeedIds};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+pub struct PriceFeedRegistry {
+    ledger: Arc<LedgerRepository>,
+    oracle: Arc<PythOracle>,
+    feeds: RwLock<Vec<PriceFeed>>,
+}
+
+impl PriceFeedRegistry {
+    pub fn new(ledger: Arc<LedgerRepository>, oracle: Arc<PythOracle>) -> Self {
+        Self {
+            ledger,
+            oracle,
+            feeds: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Create, load and install the current feeds into the oracle
+    pub async fn load(ledger: Arc<LedgerRepository>, oracle: Arc<PythOracle>) -> AppResult<Self> {
+        let registry = Self::new(ledger, oracle);
+        registry.reload().await?;
+        Ok(registry)
+    }
+
+    /// Re-read all feeds and install them into the oracle
+    ///
+    /// Nothing changes if any enabled feed ID is invalid, or if the
+    /// oracle's network would be left without feeds.
+    pub async fn reload(&self) -> AppResult<usize> {
+        let feeds = self.ledger.get_price_feeds().await?;
+        let feed_ids = PythPriceFeedIds::from_feeds(&feeds).map_err(AppError::Config)?;
+        let count = feed_ids.count();
+
+        let network = self.oracle.network();
+        if feed_ids.feeds_for_network(network).is_empty() {
+            return Err(AppError::Config(format!(
+                "No enabled Pyth price feeds for network {}: insert its Hermes feed IDs into pyth_price_feeds",
+                network
+            )));
+        }
+
+        self.oracle.set_feed_ids(feed_ids);
+        *self.feeds.write().await = feeds;
+
+        info!("📈 Loaded {} Pyth price feeds ({})", count, self.oracle.network());
+        Ok(count)
+    }
+
+    /// All feed mappings, enabled or not
+    pub async fn list(&self) -> Vec<PriceFeed> {
+        self.feeds.read().await.clone()
+    }
+
+    /// Create or replace a mapping and apply it immediately
+    pub async fn upsert(&self, params: &PriceFeedParams) -> AppResult<PriceFeed> {
+        params.validate().map_err(AppError::BadRequest)?;
+        let feed = self.ledger.upsert_price_feed(params).await?;
+
+        info!(
+            "📈 Price feed {} on {} ({}) set to {}",
+            feed.asset,
+            feed.chain.map_or("every chain", |c| c.as_str()),
+            feed.network,
+            feed.feed_id
+        );
+
+        self.reload().await?;
+        Ok(feed)
+    }
+
+    /// Whitelisted tokens on a chain that have no price feed
+    pub fn missing_feeds(&self, chain: Chain) -> Vec<String> {
+        let mut missing: Vec<String> = DexWhitelist::new()
+            .get_tokens_for_chain(chain)
+            .into_iter()
+            .filter(|token| !self.oracle.has_feed(&token.symbol, chain))
+            .map(|token| token.symbol)
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+
+    /// Reload whenever the process receives SIGHUP
+    #[cfg(unix)]
+    pub fn listen_for_sighup(self: Arc<Self>) -> JoinHandle<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        tokio::spawn(async move {
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(hangups) => hangups,
+                Err(e) => {
+                    error!("Failed to listen for SIGHUP, price feeds reload through the admin endpoint only: {}", e);
+                    return;
+                }
+            };
+
+            while hangups.recv().await.is_some() {
+                info!("SIGHUP received, reloading Pyth price feeds");
+                if let Err(e) = self.reload().await {
+                    error!("Failed to reload price feeds, keeping the current ones: {:?}", e);
+                }
+            }
+        })
+    }
+}
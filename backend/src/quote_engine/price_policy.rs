@@ -0,0 +1,247 @@
+// Price staleness and confidence policy
+//
+// Every quote is priced from an aggregated rate that must be recent and
+// precise enough for both of its assets: the pair gets the stricter of the
+// two assets' limits. The same configuration sets how long a quote lives
+// for a given confidence interval, so a wide interval that still passes
+// the policy gives a short-lived quote.
+
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::PriceCheck;
+use crate::quote_engine::price_source::AggregatedPrice;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Limits on the prices an asset is quoted at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AssetPricePolicy {
+    /// Oldest publish time accepted, in seconds
+    pub max_age_secs: i64,
+    /// Widest confidence interval accepted, in percent of the price
+    pub max_confidence_pct: Decimal,
+}
+
+impl Default for AssetPricePolicy {
+    fn default() -> Self {
+        Self {
+            max_age_secs: 10,
+            max_confidence_pct: dec!(10),
+        }
+    }
+}
+
+/// Quote TTL for confidence intervals wider than `above_confidence_pct`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TtlTier {
+    pub above_confidence_pct: Decimal,
+    pub ttl_seconds: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PricePolicyConfig {
+    /// Limits of assets without their own
+    pub default: AssetPricePolicy,
+    /// Per-asset limits, by uppercase symbol
+    pub assets: HashMap<String, AssetPricePolicy>,
+    /// Checked widest first; the first tier below the confidence applies
+    pub ttl_tiers: Vec<TtlTier>,
+    /// TTL when no tier applies
+    pub stable_ttl_seconds: i64,
+    /// Confidence assumed for the TTL when no source reports one
+    pub unknown_confidence_pct: Decimal,
+}
+
+impl Default for PricePolicyConfig {
+    fn default() -> Self {
+        Self {
+            default: AssetPricePolicy::default(),
+            assets: HashMap::new(),
+            ttl_tiers: vec![
+                TtlTier { above_confidence_pct: dec!(5), ttl_seconds: 120 },
+                TtlTier { above_confidence_pct: dec!(2), ttl_seconds: 180 },
+                TtlTier { above_confidence_pct: dec!(1), ttl_seconds: 240 },
+            ],
+            stable_ttl_seconds: 300,
+            unknown_confidence_pct: dec!(0.5),
+        }
+    }
+}
+
+impl PricePolicyConfig {
+    /// Defaults, overridden by PRICE_MAX_AGE_SECS, PRICE_MAX_CONFIDENCE_PCT
+    /// and PRICE_POLICY_ASSETS ("BTC=30:1.5,XLM=20:3", max age in seconds
+    /// and max confidence in percent per asset)
+    pub fn from_env() -> AppResult<Self> {
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("PRICE_MAX_AGE_SECS") {
+            config.default.max_age_secs = parse_max_age(&value).ok_or_else(|| {
+                AppError::Config("PRICE_MAX_AGE_SECS must be a positive integer".to_string())
+            })?;
+        }
+        if let Ok(value) = std::env::var("PRICE_MAX_CONFIDENCE_PCT") {
+            config.default.max_confidence_pct = parse_max_confidence(&value).ok_or_else(|| {
+                AppError::Config("PRICE_MAX_CONFIDENCE_PCT must be a positive number".to_string())
+            })?;
+        }
+        if let Ok(value) = std::env::var("PRICE_POLICY_ASSETS") {
+            for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let parsed = entry.split_once('=').and_then(|(asset, limits)| {
+                    let (max_age, max_confidence) = limits.split_once(':')?;
+                    Some((
+                        asset.trim().to_uppercase(),
+                        AssetPricePolicy {
+                            max_age_secs: parse_max_age(max_age)?,
+                            max_confidence_pct: parse_max_confidence(max_confidence)?,
+                        },
+                    ))
+                });
+                let (asset, policy) = parsed.ok_or_else(|| {
+                    AppError::Config(format!(
+                        "PRICE_POLICY_ASSETS entry '{}' must look like ASSET=MAX_AGE_SECS:MAX_CONFIDENCE_PCT",
+                        entry
+                    ))
+                })?;
+                config.assets.insert(asset, policy);
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn for_asset(&self, asset: &str) -> AssetPricePolicy {
+        self.assets
+            .get(&asset.to_uppercase())
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Stricter of the two assets' limits
+    pub fn for_pair(&self, base: &str, quote: &str) -> AssetPricePolicy {
+        let (base, quote) = (self.for_asset(base), self.for_asset(quote));
+        AssetPricePolicy {
+            max_age_secs: base.max_age_secs.min(quote.max_age_secs),
+            max_confidence_pct: base.max_confidence_pct.min(quote.max_confidence_pct),
+        }
+    }
+
+    /// Check the price of `base` in `quote` against the pair's limits
+    ///
+    /// A price without any confidence interval is only checked for age.
+    pub fn check(&self, base: &str, quote: &str, price: &AggregatedPrice, now: DateTime<Utc>) -> PriceCheck {
+        let policy = self.for_pair(base, quote);
+        let age_secs = now.signed_duration_since(price.published_at).num_seconds().max(0);
+
+        let reason = if age_secs > policy.max_age_secs {
+            Some(format!(
+                "{}/{} price is {}s old (max {}s)",
+                base, quote, age_secs, policy.max_age_secs
+            ))
+        } else {
+            price
+                .confidence_pct
+                .filter(|pct| *pct > policy.max_confidence_pct)
+                .map(|pct| {
+                    format!(
+                        "{}/{} price confidence interval is {}% (max {}%)",
+                        base,
+                        quote,
+                        pct.round_dp(4),
+                        policy.max_confidence_pct
+                    )
+                })
+        };
+
+        PriceCheck {
+            passed: reason.is_none(),
+            age_secs,
+            max_age_secs: policy.max_age_secs,
+            confidence_pct: price.confidence_pct,
+            max_confidence_pct: policy.max_confidence_pct,
+            reason,
+        }
+    }
+
+    /// Quote TTL for a confidence interval
+    pub fn ttl_seconds(&self, confidence_pct: Option<Decimal>) -> i64 {
+        let confidence_pct = confidence_pct.unwrap_or(self.unknown_confidence_pct);
+        self.ttl_tiers
+            .iter()
+            .find(|tier| confidence_pct > tier.above_confidence_pct)
+            .map_or(self.stable_ttl_seconds, |tier| tier.ttl_seconds)
+    }
+}
+
+fn parse_max_age(value: &str) -> Option<i64> {
+    value.trim().parse::<i64>().ok().filter(|secs| *secs > 0)
+}
+
+fn parse_max_confidence(value: &str) -> Option<Decimal> {
+    Decimal::from_str(value.trim()).ok().filter(|pct| *pct > Decimal::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn price(age_secs: i64, confidence_pct: Option<Decimal>) -> AggregatedPrice {
+        AggregatedPrice {
+            rate: dec!(0.12),
+            confidence_pct,
+            published_at: Utc::now() - Duration::seconds(age_secs),
+            deviation_bps: Decimal::ZERO,
+            sources: Vec::new(),
+            unavailable: Vec::new(),
+        }
+    }
+
+    fn config() -> PricePolicyConfig {
+        let mut config = PricePolicyConfig::default();
+        config.assets.insert(
+            "BTC".to_string(),
+            AssetPricePolicy { max_age_secs: 3, max_confidence_pct: dec!(0.5) },
+        );
+        config
+    }
+
+    #[test]
+    fn test_pair_uses_stricter_asset_limits() {
+        let policy = config().for_pair("XLM", "btc");
+        assert_eq!(policy.max_age_secs, 3);
+        assert_eq!(policy.max_confidence_pct, dec!(0.5));
+        assert_eq!(config().for_pair("XLM", "USDC"), AssetPricePolicy::default());
+    }
+
+    #[test]
+    fn test_stale_and_imprecise_prices_fail() {
+        let config = config();
+        let now = Utc::now();
+
+        let fresh = config.check("XLM", "USDC", &price(2, Some(dec!(0.1))), now);
+        assert!(fresh.passed);
+        assert_eq!(fresh.reason, None);
+
+        let stale = config.check("XLM", "BTC", &price(5, Some(dec!(0.1))), now);
+        assert!(!stale.passed);
+        assert!(stale.reason.unwrap().contains("old"));
+
+        let wide = config.check("XLM", "BTC", &price(1, Some(dec!(0.8))), now);
+        assert!(!wide.passed);
+        assert!(wide.reason.unwrap().contains("confidence"));
+
+        // No source reports a confidence interval
+        assert!(config.check("XLM", "BTC", &price(1, None), now).passed);
+    }
+
+    #[test]
+    fn test_ttl_tiers() {
+        let config = PricePolicyConfig::default();
+        assert_eq!(config.ttl_seconds(Some(dec!(6))), 120);
+        assert_eq!(config.ttl_seconds(Some(dec!(3))), 180);
+        assert_eq!(config.ttl_seconds(Some(dec!(1.5))), 240);
+        assert_eq!(config.ttl_seconds(Some(dec!(0.2))), 300);
+        assert_eq!(config.ttl_seconds(None), 300);
+    }
+}
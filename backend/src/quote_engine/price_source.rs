@@ -0,0 +1,566 @@
+// Price sources for quoting
+//
+// The quote engine prices pairs through a PriceAggregator rather than a
+// single oracle. Every registered source is asked concurrently; the quoted
+// rate is the median of the sources that answered, and the quote is
+// rejected when any of them is further from it than the configured
+// deviation. A source that fails (Pyth outage, pair without a DEX pool)
+// is left out as long as enough others answer.
+
+use crate::adapters::dex_whitelist::DexWhitelist;
+use crate::adapters::traits::AssetInfo;
+use crate::error::{AppError, AppResult, QuoteError};
+use crate::ledger::models::Chain;
+use crate::quote_engine::pyth_oracle::PythOracle;
+use crate::quote_engine::realtime::RealtimeQuoteEngine;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// A rate quoted by one source: 1 base = `rate` quote
+#[derive(Debug, Clone)]
+pub struct SourcePrice {
+    pub source: String,
+    pub rate: Decimal,
+    /// Confidence interval as a percentage of the price, if the source has one
+    pub confidence_pct: Option<Decimal>,
+    /// When the price was published; for a pair of feeds, the older one
+    pub timestamp: DateTime<Utc>,
+}
+
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Whether the rate must be confirmed by another source before it is
+    /// quoted; registering such a source raises `min_sources` to 2
+    fn needs_cross_check(&self) -> bool {
+        false
+    }
+
+    /// Rate of `base` in `quote` on `chain`
+    async fn get_rate(&self, base: &str, quote: &str, chain: Chain) -> AppResult<SourcePrice>;
+}
+
+#[async_trait]
+impl PriceSource for PythOracle {
+    fn name(&self) -> &str {
+        "pyth"
+    }
+
+    async fn get_rate(&self, base: &str, quote: &str, chain: Chain) -> AppResult<SourcePrice> {
+        let price_data = self
+            .get_price(base, quote, chain.as_str())
+            .await
+            .map_err(|e| QuoteError::PriceUnavailable(format!("Pyth error: {}", e)))?;
+
+        // The rate carries the uncertainty of both feeds
+        let confidence_pct = match (
+            price_data.base_price.confidence_pct(),
+            price_data.quote_price.confidence_pct(),
+        ) {
+            (Ok(base), Ok(quote)) => Some(base + quote),
+            _ => None,
+        };
+        let published = price_data
+            .base_price
+            .publish_time
+            .min(price_data.quote_price.publish_time);
+
+        Ok(SourcePrice {
+            source: self.name().to_string(),
+            rate: price_data.rate,
+            confidence_pct,
+            timestamp: Utc
+                .timestamp_opt(published, 0)
+                .single()
+                .unwrap_or(price_data.timestamp),
+        })
+    }
+}
+
+/// DEX-implied rate: best output over the registered DEX adapters for one
+/// unit of the base asset
+///
+/// Both assets whitelisted on `chain` are priced against each other there.
+/// Otherwise each asset is priced against USDC on the chain that lists it
+/// (its native chain for SOL, XLM, NEAR) and the rate is the ratio of the
+/// two USDC prices.
+pub struct DexPriceSource {
+    realtime: Arc<RealtimeQuoteEngine>,
+    whitelist: DexWhitelist,
+}
+
+/// Price of one unit of an asset read from a DEX
+struct DexLeg {
+    rate: Decimal,
+    /// None for USDC itself
+    dex: Option<String>,
+    timestamp: DateTime<Utc>,
+}
+
+impl DexPriceSource {
+    pub fn new(realtime: Arc<RealtimeQuoteEngine>) -> Self {
+        Self {
+            realtime,
+            whitelist: DexWhitelist::new(),
+        }
+    }
+
+    /// Chain to price `symbol` on: `chain` if it is whitelisted there,
+    /// otherwise the only chain that lists it
+    fn leg_chain(&self, symbol: &str, chain: Chain) -> AppResult<Chain> {
+        if self.whitelist.get_by_symbol(chain, symbol).is_ok() {
+            return Ok(chain);
+        }
+
+        let listed: Vec<Chain> = Chain::all()
+            .into_iter()
+            .filter(|c| self.whitelist.get_by_symbol(*c, symbol).is_ok())
+            .collect();
+        match listed.as_slice() {
+            [only] => Ok(*only),
+            _ => Err(QuoteError::PriceUnavailable(format!(
+                "No single chain to price {} on with DEX quotes",
+                symbol
+            ))
+            .into()),
+        }
+    }
+
+    /// Best output for one `base` in `quote`, both on `chain`
+    async fn quote_leg(&self, base: &str, quote: &str, chain: Chain) -> AppResult<DexLeg> {
+        if base.eq_ignore_ascii_case(quote) {
+            return Ok(DexLeg {
+                rate: Decimal::ONE,
+                dex: None,
+                timestamp: Utc::now(),
+            });
+        }
+
+        let asset_in = AssetInfo::from(self.whitelist.get_by_symbol(chain, base)?);
+        let asset_out = AssetInfo::from(self.whitelist.get_by_symbol(chain, quote)?);
+
+        let amount = Decimal::ONE;
+        let best = self
+            .realtime
+            .get_best_quote(&asset_in, &asset_out, amount)
+            .await?;
+
+        if best.best_amount_out <= Decimal::ZERO {
+            return Err(QuoteError::PriceUnavailable(format!(
+                "No DEX liquidity for {}/{} on {}",
+                base, quote, chain
+            ))
+            .into());
+        }
+
+        Ok(DexLeg {
+            rate: best.best_amount_out / amount,
+            dex: Some(best.best_dex),
+            timestamp: best.timestamp,
+        })
+    }
+}
+
+#[async_trait]
+impl PriceSource for DexPriceSource {
+    fn name(&self) -> &str {
+        "dex"
+    }
+
+    /// DEX spot prices can be moved by a single large trade
+    fn needs_cross_check(&self) -> bool {
+        true
+    }
+
+    async fn get_rate(&self, base: &str, quote: &str, chain: Chain) -> AppResult<SourcePrice> {
+        let base_chain = self.leg_chain(base, chain)?;
+        let quote_chain = self.leg_chain(quote, chain)?;
+
+        let legs = if base_chain == quote_chain {
+            vec![self.quote_leg(base, quote, base_chain).await?]
+        } else {
+            let (base_usd, quote_usd) = futures::try_join!(
+                self.quote_leg(base, "USDC", base_chain),
+                self.quote_leg(quote, "USDC", quote_chain),
+            )?;
+            let inverse = DexLeg {
+                rate: Decimal::ONE / quote_usd.rate,
+                ..quote_usd
+            };
+            vec![base_usd, inverse]
+        };
+
+        let dexes: Vec<String> = legs.iter().filter_map(|leg| leg.dex.clone()).collect();
+        Ok(SourcePrice {
+            source: format!("{}:{}", self.name(), dexes.join("+")),
+            rate: legs.iter().map(|leg| leg.rate).product(),
+            confidence_pct: None,
+            timestamp: legs
+                .iter()
+                .map(|leg| leg.timestamp)
+                .min()
+                .unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+/// Fixed rates set by an operator, or by tests
+///
+/// Rates apply on every chain; the inverse pair is derived.
+pub struct StaticPriceSource {
+    name: String,
+    rates: parking_lot::RwLock<HashMap<(String, String), Decimal>>,
+}
+
+impl StaticPriceSource {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rates: parking_lot::RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Read MANUAL_PRICE_RATES ("XLM/USDC=0.12,SOL/USDC=150"), if set
+    pub fn from_env() -> AppResult<Option<Self>> {
+        let Ok(value) = std::env::var("MANUAL_PRICE_RATES") else {
+            return Ok(None);
+        };
+
+        let source = Self::new("manual");
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(pair, rate)| {
+                let (base, quote) = pair.trim().split_once('/')?;
+                let rate = Decimal::from_str(rate.trim()).ok().filter(|r| *r > Decimal::ZERO)?;
+                Some((base.trim(), quote.trim(), rate))
+            });
+            let (base, quote, rate) = parsed.ok_or_else(|| {
+                AppError::Config(format!(
+                    "MANUAL_PRICE_RATES entry '{}' must look like BASE/QUOTE=RATE",
+                    entry
+                ))
+            })?;
+            source.set_rate(base, quote, rate);
+        }
+        Ok(Some(source))
+    }
+
+    pub fn set_rate(&self, base: &str, quote: &str, rate: Decimal) {
+        self.rates
+            .write()
+            .insert((base.to_uppercase(), quote.to_uppercase()), rate);
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPriceSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn get_rate(&self, base: &str, quote: &str, _chain: Chain) -> AppResult<SourcePrice> {
+        let (base, quote) = (base.to_uppercase(), quote.to_uppercase());
+        let rates = self.rates.read();
+        let rate = rates
+            .get(&(base.clone(), quote.clone()))
+            .copied()
+            .or_else(|| rates.get(&(quote.clone(), base.clone())).map(|r| Decimal::ONE / r))
+            .ok_or_else(|| {
+                QuoteError::PriceUnavailable(format!("No {} rate for {}/{}", self.name, base, quote))
+            })?;
+
+        Ok(SourcePrice {
+            source: self.name.clone(),
+            rate,
+            confidence_pct: None,
+            timestamp: Utc::now(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PriceAggregatorConfig {
+    /// Largest distance of any source from the median, in basis points
+    pub max_deviation_bps: Decimal,
+    /// Sources that must answer for a rate to be quoted, at least 2 once
+    /// a source that needs a cross-check (DEX) is registered
+    pub min_sources: usize,
+}
+
+impl Default for PriceAggregatorConfig {
+    fn default() -> Self {
+        Self {
+            max_deviation_bps: dec!(100), // 1%
+            min_sources: 1,
+        }
+    }
+}
+
+impl PriceAggregatorConfig {
+    /// Defaults, overridden by PRICE_MAX_DEVIATION_BPS and PRICE_MIN_SOURCES
+    pub fn from_env() -> AppResult<Self> {
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("PRICE_MAX_DEVIATION_BPS") {
+            config.max_deviation_bps = Decimal::from_str(&value)
+                .ok()
+                .filter(|bps| *bps > Decimal::ZERO)
+                .ok_or_else(|| {
+                    AppError::Config("PRICE_MAX_DEVIATION_BPS must be a positive number".to_string())
+                })?;
+        }
+        if let Ok(value) = std::env::var("PRICE_MIN_SOURCES") {
+            config.min_sources = value.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(|| {
+                AppError::Config("PRICE_MIN_SOURCES must be a positive integer".to_string())
+            })?;
+        }
+        Ok(config)
+    }
+}
+
+/// Rate agreed by the sources, with what each of them said
+#[derive(Debug, Clone)]
+pub struct AggregatedPrice {
+    /// Median of the answering sources
+    pub rate: Decimal,
+    /// Widest confidence interval reported, in percent
+    pub confidence_pct: Option<Decimal>,
+    /// Publication time of the oldest source price
+    pub published_at: DateTime<Utc>,
+    /// Largest distance of a source from the median, in basis points
+    pub deviation_bps: Decimal,
+    pub sources: Vec<SourcePrice>,
+    /// Sources that did not answer, with their error
+    pub unavailable: Vec<(String, String)>,
+}
+
+impl AggregatedPrice {
+    /// Sources and values for audit events
+    pub fn audit_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "rate": self.rate.to_string(),
+            "deviation_bps": self.deviation_bps.to_string(),
+            "published_at": self.published_at,
+            "sources": self.sources.iter().map(|s| serde_json::json!({
+                "source": s.source,
+                "rate": s.rate.to_string(),
+                "confidence_pct": s.confidence_pct.map(|c| c.to_string()),
+                "timestamp": s.timestamp,
+            })).collect::<Vec<_>>(),
+            "unavailable": self.unavailable.iter().map(|(source, error)| serde_json::json!({
+                "source": source,
+                "error": error,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+pub struct PriceAggregator {
+    config: PriceAggregatorConfig,
+    sources: Vec<Arc<dyn PriceSource>>,
+}
+
+impl PriceAggregator {
+    pub fn new(config: PriceAggregatorConfig) -> Self {
+        Self {
+            config,
+            sources: Vec::new(),
+        }
+    }
+
+    pub fn register_source(&mut self, source: Arc<dyn PriceSource>) {
+        info!("Registered price source: {}", source.name());
+        if source.needs_cross_check() && self.config.min_sources < 2 {
+            info!("Price source {} needs a cross-check, requiring 2 sources", source.name());
+            self.config.min_sources = 2;
+        }
+        self.sources.push(source);
+    }
+
+    pub fn source_names(&self) -> Vec<String> {
+        self.sources.iter().map(|s| s.name().to_string()).collect()
+    }
+
+    /// Rate of `base` in `quote` on `chain`, checked across all sources
+    ///
+    /// SECURITY: Fails with PriceUnavailable when too few sources answer or
+    /// when they disagree by more than `max_deviation_bps`
+    pub async fn get_rate(&self, base: &str, quote: &str, chain: Chain) -> AppResult<AggregatedPrice> {
+        let results = futures::future::join_all(
+            self.sources.iter().map(|source| source.get_rate(base, quote, chain)),
+        )
+        .await;
+
+        let mut sources = Vec::new();
+        let mut unavailable = Vec::new();
+        for (source, result) in self.sources.iter().zip(results) {
+            match result {
+                Ok(price) if price.rate > Decimal::ZERO => sources.push(price),
+                Ok(price) => unavailable.push((source.name().to_string(), format!("Invalid rate {}", price.rate))),
+                Err(e) => {
+                    warn!("Price source {} unavailable for {}/{} on {}: {}", source.name(), base, quote, chain, e);
+                    unavailable.push((source.name().to_string(), e.to_string()));
+                }
+            }
+        }
+
+        if sources.len() < self.config.min_sources {
+            return Err(QuoteError::PriceUnavailable(format!(
+                "{} of {} required price sources answered for {}/{}",
+                sources.len(),
+                self.config.min_sources,
+                base,
+                quote
+            ))
+            .into());
+        }
+
+        let rates: Vec<Decimal> = sources.iter().map(|s| s.rate).collect();
+        let rate = median(&rates);
+        let deviation_bps = rates
+            .iter()
+            .map(|r| deviation_bps(*r, rate))
+            .max()
+            .unwrap_or_default();
+
+        if deviation_bps > self.config.max_deviation_bps {
+            warn!(
+                "Rejected {}/{} price: sources deviate by {} bps (max {}): {:?}",
+                base, quote, deviation_bps, self.config.max_deviation_bps, sources
+            );
+            return Err(QuoteError::PriceUnavailable(format!(
+                "Price sources deviate by {} bps (max {}) for {}/{}",
+                deviation_bps.round_dp(2),
+                self.config.max_deviation_bps,
+                base,
+                quote
+            ))
+            .into());
+        }
+
+        let confidence_pct = sources.iter().filter_map(|s| s.confidence_pct).max();
+        let published_at = sources
+            .iter()
+            .map(|s| s.timestamp)
+            .min()
+            .unwrap_or_else(Utc::now);
+
+        Ok(AggregatedPrice {
+            rate,
+            confidence_pct,
+            published_at,
+            deviation_bps,
+            sources,
+            unavailable,
+        })
+    }
+}
+
+fn median(rates: &[Decimal]) -> Decimal {
+    let mut sorted = rates.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / Decimal::TWO
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Distance of `rate` from `reference` in basis points
+fn deviation_bps(rate: Decimal, reference: Decimal) -> Decimal {
+    ((rate - reference) / reference).abs() * dec!(10000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(name: &str, rate: Decimal) -> Arc<dyn PriceSource> {
+        let source = StaticPriceSource::new(name);
+        source.set_rate("XLM", "USDC", rate);
+        Arc::new(source)
+    }
+
+    fn aggregator(sources: Vec<Arc<dyn PriceSource>>) -> PriceAggregator {
+        let mut aggregator = PriceAggregator::new(PriceAggregatorConfig::default());
+        for source in sources {
+            aggregator.register_source(source);
+        }
+        aggregator
+    }
+
+    #[tokio::test]
+    async fn test_agreeing_sources_use_median() {
+        let prices = aggregator(vec![
+            source("a", dec!(0.1200)),
+            source("b", dec!(0.1205)),
+            source("c", dec!(0.1199)),
+        ]);
+
+        let price = prices.get_rate("XLM", "USDC", Chain::Stellar).await.unwrap();
+        assert_eq!(price.rate, dec!(0.1200));
+        assert_eq!(price.sources.len(), 3);
+        assert!(price.deviation_bps <= dec!(42));
+    }
+
+    #[tokio::test]
+    async fn test_deviating_sources_are_rejected() {
+        let prices = aggregator(vec![source("pyth", dec!(0.12)), source("dex", dec!(0.15))]);
+
+        let err = prices.get_rate("XLM", "USDC", Chain::Stellar).await.unwrap_err();
+        assert!(matches!(err, AppError::Quote(QuoteError::PriceUnavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_source_is_skipped() {
+        let prices = aggregator(vec![Arc::new(StaticPriceSource::new("empty")), source("manual", dec!(0.12))]);
+
+        let price = prices.get_rate("USDC", "XLM", Chain::Stellar).await.unwrap();
+        assert_eq!(price.rate, Decimal::ONE / dec!(0.12));
+        assert_eq!(price.unavailable.len(), 1);
+
+        let mut strict = PriceAggregator::new(PriceAggregatorConfig {
+            min_sources: 2,
+            ..PriceAggregatorConfig::default()
+        });
+        strict.register_source(Arc::new(StaticPriceSource::new("empty")));
+        strict.register_source(source("manual", dec!(0.12)));
+        assert!(strict.get_rate("XLM", "USDC", Chain::Stellar).await.is_err());
+    }
+
+    /// Static rates flagged like the DEX source
+    struct CrossChecked(StaticPriceSource);
+
+    #[async_trait]
+    impl PriceSource for CrossChecked {
+        fn name(&self) -> &str {
+            self.0.name()
+        }
+
+        fn needs_cross_check(&self) -> bool {
+            true
+        }
+
+        async fn get_rate(&self, base: &str, quote: &str, chain: Chain) -> AppResult<SourcePrice> {
+            self.0.get_rate(base, quote, chain).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cross_checked_source_needs_second_source() {
+        let dex = StaticPriceSource::new("dex");
+        dex.set_rate("XLM", "USDC", dec!(0.12));
+        let mut prices = aggregator(vec![Arc::new(CrossChecked(dex))]);
+        assert!(prices.get_rate("XLM", "USDC", Chain::Stellar).await.is_err());
+
+        prices.register_source(source("pyth", dec!(0.1201)));
+        let price = prices.get_rate("XLM", "USDC", Chain::Stellar).await.unwrap();
+        assert_eq!(price.sources.len(), 2);
+    }
+}
@@ -5,148 +5,99 @@ use tracing::{info, warn};
 use rust_decimal::Decimal;
 use std::str::FromStr;
 use chrono::{DateTime, Utc};
+use crate::ledger::models::{Chain, PriceFeed};
 
-/// Pyth price feed identifiers for different chains/assets
+/// Pyth price feed identifiers per network, chain and asset
+///
+/// Built from the pyth_price_feeds table (see quote_engine::price_feeds).
+/// A mapping without a chain applies on every chain.
+#[derive(Debug, Clone, Default)]
 pub struct PythPriceFeedIds {
-    /// Solana mainnet price feed IDs
-    pub solana_mainnet: HashMap<String, String>,
-    /// Solana devnet price feed IDs
-    pub solana_devnet: HashMap<String, String>,
-    /// Stellar mainnet feed IDs
-    pub stellar_mainnet: HashMap<String, String>,
-    /// Stellar testnet feed IDs
-    pub stellar_testnet: HashMap<String, String>,
-    /// NEAR mainnet feed IDs
-    pub near_mainnet: HashMap<String, String>,
-    /// NEAR testnet feed IDs
-    pub near_testnet: HashMap<String, String>,
+    feeds: HashMap<(String, Option<String>, String), String>,
 }
 
 impl PythPriceFeedIds {
     pub fn new() -> Self {
-        let mut solana_mainnet = HashMap::new();
-        let mut solana_devnet = HashMap::new();
-        let mut stellar_mainnet = HashMap::new();
-        let mut stellar_testnet = HashMap::new();
-        let mut near_mainnet = HashMap::new();
-        let mut near_testnet = HashMap::new();
-
-        // Solana Mainnet Price Feed IDs
-        solana_mainnet.insert(
-            "SOL".to_string(),
-            "H6ARHf6YXhGrYZZAr3qLq9NNUyRfQAccJFvvrH8B8io2".to_string(),
-        );
-        solana_mainnet.insert(
-            "USDC".to_string(),
-            "Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJN2UYLw6".to_string(),
-        );
-        solana_mainnet.insert(
-            "USDT".to_string(),
-            "3Mnn2nBVDwZfWvkjvfYrg57MkeoM6P25TpzxSAzxnUk9".to_string(),
-        );
-        solana_mainnet.insert(
-            "ETH".to_string(),
-            "JF3hscKv78LspujqqVANcNhalysumGKPQmdarumdQLe".to_string(),
-        );
-        solana_mainnet.insert(
-            "BTC".to_string(),
-            "GVXRSv1FM36is6iNcumEjVc2U2iJ7Gx3bBrYb6Ax9rC2".to_string(),
-        );
-        solana_mainnet.insert(
-            "XLM".to_string(),
-            "3Mnn2nBVDwZfWvkjvfYrg57MkeoM6P25TpzxSAzxnUk9".to_string(),
-        );
+        Self::default()
+    }
 
-        // Solana Devnet Price Feed IDs
-        solana_devnet.insert(
-            "SOL".to_string(),
-            "J83w4HKfqxwcq3BEMMkPFSppjv3JW7MVvQ7gdNot2gS7".to_string(),
-        );
-        solana_devnet.insert(
-            "USDC".to_string(),
-            "5VAAA8Nm2kDMwkxwqHSEfQD87Lsmz8GPg6gsQkQr5S1j".to_string(),
-        );
-        solana_devnet.insert(
-            "XLM".to_string(),
-            "8GWTTbNiXXoUctiP6AoN9KaFbFreKr6MkbHRNatXjkxJ".to_string(),
-        );
+    /// Mappings from the enabled feeds; every ID must be a valid Hermes ID
+    pub fn from_feeds(feeds: &[PriceFeed]) -> Result<Self, String> {
+        let invalid: Vec<String> = feeds
+            .iter()
+            .filter(|feed| feed.enabled && !PriceFeed::is_valid_feed_id(&feed.feed_id))
+            .map(|feed| format!("{}/{}: {}", feed.network, feed.asset, feed.feed_id))
+            .collect();
+        if !invalid.is_empty() {
+            return Err(format!("Invalid Pyth feed IDs: {}", invalid.join(", ")));
+        }
 
-        // Stellar Mainnet Feed IDs (via Soroban bridge)
-        stellar_mainnet.insert(
-            "SOL".to_string(),
-            "c1251ae89f0f6e1a645f5953aff3b6eff41db4e4".to_string(),
-        );
-        stellar_mainnet.insert(
-            "USDC".to_string(),
-            "c1251ae89f0f6e1a645f5953aff3b6eff41db4e4".to_string(),
-        );
-        stellar_mainnet.insert(
-            "XLM".to_string(),
-            "native".to_string(),
-        );
+        let feeds = feeds
+            .iter()
+            .filter(|feed| feed.enabled)
+            .map(|feed| {
+                (
+                    (
+                        feed.network.clone(),
+                        feed.chain.map(|c| c.as_str().to_string()),
+                        feed.asset.to_uppercase(),
+                    ),
+                    feed.feed_id.clone(),
+                )
+            })
+            .collect();
+        Ok(Self { feeds })
+    }
 
-        // Stellar Testnet Feed IDs
-        stellar_testnet.insert(
-            "SOL".to_string(),
-            "c1251ae89f0f6e1a645f5953aff3b6eff41db4e4".to_string(),
-        );
-        stellar_testnet.insert(
-            "USDC".to_string(),
-            "c1251ae89f0f6e1a645f5953aff3b6eff41db4e4".to_string(),
-        );
-        stellar_testnet.insert(
-            "XLM".to_string(),
-            "native".to_string(),
-        );
+    /// Number of enabled mappings
+    pub fn count(&self) -> usize {
+        self.feeds.len()
+    }
 
-        // NEAR Mainnet Feed IDs
-        near_mainnet.insert(
-            "NEAR".to_string(),
-            "near.priceoracle".to_string(),
-        );
-        near_mainnet.insert(
-            "USDC".to_string(),
-            "oracle.near".to_string(),
-        );
-        near_mainnet.insert(
-            "USDT".to_string(),
-            "oracle.near".to_string(),
-        );
+    pub fn get_feed_id(&self, asset: &str, chain: &str, network: &str) -> Option<String> {
+        let network = feed_network(network);
+        let asset = asset.to_uppercase();
 
-        // NEAR Testnet Feed IDs
-        near_testnet.insert(
-            "NEAR".to_string(),
-            "priceoracle.testnet".to_string(),
-        );
-        near_testnet.insert(
-            "USDC".to_string(),
-            "oracle.testnet".to_string(),
-        );
+        self.feeds
+            .get(&(network.to_string(), Some(chain.to_string()), asset.clone()))
+            .or_else(|| self.feeds.get(&(network.to_string(), None, asset)))
+            .cloned()
+    }
 
-        Self {
-            solana_mainnet,
-            solana_devnet,
-            stellar_mainnet,
-            stellar_testnet,
-            near_mainnet,
-            near_testnet,
+    /// Feeds of a network by normalized ID, with the (asset, chain) each
+    /// one prices; chain None = every chain
+    pub fn feeds_for_network(&self, network: &str) -> HashMap<String, Vec<(String, Option<String>)>> {
+        let network = feed_network(network);
+        let mut feeds: HashMap<String, Vec<(String, Option<String>)>> = HashMap::new();
+        for ((feed_network, chain, asset), feed_id) in &self.feeds {
+            if feed_network == network {
+                feeds
+                    .entry(normalize_feed_id(feed_id))
+                    .or_default()
+                    .push((asset.clone(), chain.clone()));
+            }
         }
+        feeds
     }
+}
 
-    pub fn get_feed_id(&self, asset: &str, chain: &str, network: &str) -> Option<String> {
-        let map = match (chain, network) {
-            ("solana", "mainnet") => &self.solana_mainnet,
-            ("solana", _) => &self.solana_devnet,
-            ("stellar", "mainnet") => &self.stellar_mainnet,
-            ("stellar", _) => &self.stellar_testnet,
-            ("near", "mainnet") => &self.near_mainnet,
-            ("near", _) => &self.near_testnet,
-            _ => return None,
-        };
-        map.get(asset).cloned()
+/// Every network but mainnet prices from hermes-beta's testnet feeds
+fn feed_network(network: &str) -> &'static str {
+    if network == "mainnet" {
+        "mainnet"
+    } else {
+        "testnet"
     }
 }
 
+/// Feed IDs as Hermes returns them: lowercase hex without 0x
+pub fn normalize_feed_id(feed_id: &str) -> String {
+    feed_id.strip_prefix("0x").unwrap_or(feed_id).to_lowercase()
+}
+
+/// Streamed prices older than this are ignored and fetched over HTTP
+const STREAMED_PRICE_MAX_AGE_SECS: i64 = 5;
+
 /// Response from Pyth REST API
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PythPriceResponse {
@@ -241,8 +192,12 @@ pub struct PythOracle {
     client: Client,
     base_url: String,
     network: String,
-    feed_ids: PythPriceFeedIds,
+    feed_ids: parking_lot::RwLock<PythPriceFeedIds>,
     cache: std::sync::Arc<parking_lot::RwLock<HashMap<String, (PythPriceData, DateTime<Utc>)>>>,
+    /// Latest price per normalized feed ID from the Hermes stream
+    streamed: parking_lot::RwLock<HashMap<String, (PythPrice, DateTime<Utc>)>>,
+    /// Bumped whenever the feed mappings are replaced
+    feeds_version: std::sync::atomic::AtomicU64,
 }
 
 impl PythOracle {
@@ -256,11 +211,55 @@ impl PythOracle {
             client: Client::new(),
             base_url: base_url.to_string(),
             network: network.to_string(),
-            feed_ids: PythPriceFeedIds::new(),
+            feed_ids: parking_lot::RwLock::new(PythPriceFeedIds::new()),
             cache: std::sync::Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            streamed: parking_lot::RwLock::new(HashMap::new()),
+            feeds_version: std::sync::atomic::AtomicU64::new(0),
         }
     }
 
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn network(&self) -> &str {
+        &self.network
+    }
+
+    /// Replace the feed mappings; cached prices are dropped since a feed
+    /// may have changed
+    pub fn set_feed_ids(&self, feed_ids: PythPriceFeedIds) {
+        *self.feed_ids.write() = feed_ids;
+        self.cache.write().clear();
+        self.feeds_version
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Changes whenever set_feed_ids is called
+    pub fn feeds_version(&self) -> u64 {
+        self.feeds_version.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Feeds to stream for this network (see PythPriceFeedIds::feeds_for_network)
+    pub fn stream_feeds(&self) -> HashMap<String, Vec<(String, Option<String>)>> {
+        self.feed_ids.read().feeds_for_network(&self.network)
+    }
+
+    /// Record a price received from the Hermes stream
+    pub fn update_streamed_price(&self, feed_id: &str, price: PythPrice) {
+        self.streamed
+            .write()
+            .insert(normalize_feed_id(feed_id), (price, Utc::now()));
+    }
+
+    /// Whether an asset can be priced on a chain
+    pub fn has_feed(&self, asset: &str, chain: Chain) -> bool {
+        self.feed_ids
+            .read()
+            .get_feed_id(asset, chain.as_str(), &self.network)
+            .is_some()
+    }
+
     /// Get real-time price for asset pair on specific chain
     pub async fn get_price(
         &self,
@@ -288,15 +287,17 @@ impl PythOracle {
         }
 
         // Get fresh price from Pyth
-        let base_feed_id = self
-            .feed_ids
-            .get_feed_id(base, chain, &self.network)
-            .ok_or(format!("Unknown asset: {} on {}", base, chain))?;
-
-        let quote_feed_id = self
-            .feed_ids
-            .get_feed_id(quote, chain, &self.network)
-            .ok_or(format!("Unknown asset: {} on {}", quote, chain))?;
+        let (base_feed_id, quote_feed_id) = {
+            let feed_ids = self.feed_ids.read();
+            (
+                feed_ids
+                    .get_feed_id(base, chain, &self.network)
+                    .ok_or(format!("No price feed for {} on {}", base, chain))?,
+                feed_ids
+                    .get_feed_id(quote, chain, &self.network)
+                    .ok_or(format!("No price feed for {} on {}", quote, chain))?,
+            )
+        };
 
         let base_price = self.fetch_price(&base_feed_id).await?;
         let quote_price = self.fetch_price(&quote_feed_id).await?;
@@ -366,6 +367,13 @@ impl PythOracle {
     }
 
     async fn fetch_price(&self, feed_id: &str) -> Result<PythPrice, Box<dyn std::error::Error>> {
+        // Streamed prices spare the request while the stream is healthy
+        if let Some((price, received_at)) = self.streamed.read().get(&normalize_feed_id(feed_id)) {
+            if Utc::now().signed_duration_since(*received_at).num_seconds() < STREAMED_PRICE_MAX_AGE_SECS {
+                return Ok(price.clone());
+            }
+        }
+
         let url = format!("{}/api/latest_price_feeds?ids={}", self.base_url, feed_id);
 
         let response = self.client.get(&url).send().await?;
@@ -416,4 +424,42 @@ mod tests {
         let conf = price.confidence_pct().unwrap();
         assert!(conf > Decimal::ZERO);
     }
+
+    fn feed(network: &str, chain: Option<Chain>, asset: &str, feed_id: &str) -> PriceFeed {
+        PriceFeed {
+            id: uuid::Uuid::new_v4(),
+            network: network.to_string(),
+            chain,
+            asset: asset.to_string(),
+            feed_id: feed_id.to_string(),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_feed_lookup_prefers_chain_specific() {
+        let any_chain = format!("0x{}", "a".repeat(64));
+        let on_solana = "b".repeat(64);
+        let ids = PythPriceFeedIds::from_feeds(&[
+            feed("mainnet", None, "USDC", &any_chain),
+            feed("mainnet", Some(Chain::Solana), "USDC", &on_solana),
+        ])
+        .unwrap();
+
+        assert_eq!(ids.get_feed_id("usdc", "solana", "mainnet"), Some(on_solana));
+        assert_eq!(ids.get_feed_id("USDC", "stellar", "mainnet"), Some(any_chain));
+        assert_eq!(ids.get_feed_id("USDC", "stellar", "devnet"), None);
+    }
+
+    #[test]
+    fn test_invalid_feed_ids_are_rejected() {
+        assert!(PythPriceFeedIds::from_feeds(&[feed("mainnet", None, "XLM", "native")]).is_err());
+        assert!(PythPriceFeedIds::from_feeds(&[feed("testnet", None, "NEAR", "oracle.testnet")]).is_err());
+
+        let mut disabled = feed("mainnet", None, "XLM", "native");
+        disabled.enabled = false;
+        assert_eq!(PythPriceFeedIds::from_feeds(&[disabled]).unwrap().count(), 0);
+    }
 }
@@ -0,0 +1,479 @@
+// Hermes price streaming
+//
+// Keeps one server-sent events connection to Hermes open for every price
+// feed of the network. Each update goes to the oracle's feed cache (so
+// quoting does not wait on an HTTP request per feed), to the PriceCache and
+// OhlcStore, and out to PriceFeedBroadcaster subscribers.
+//
+// The connection is re-opened with exponential backoff when it fails, when
+// the watchdog sees no update for `stale_after`, and when the feed mappings
+// change. While the stream is down the oracle falls back to HTTP.
+
+use crate::api::websocket::{PriceFeedBroadcaster, PriceUpdate};
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::Chain;
+use crate::quote_engine::ohlc::{OhlcStore, Timeframe};
+use crate::quote_engine::price_cache::PriceCache;
+use crate::quote_engine::pyth_oracle::{PythOracle, PythPrice};
+use chrono::{TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+const TIMEFRAMES: [Timeframe; 6] = [
+    Timeframe::OneMinute,
+    Timeframe::FiveMinutes,
+    Timeframe::FifteenMinutes,
+    Timeframe::OneHour,
+    Timeframe::FourHours,
+    Timeframe::OneDay,
+];
+
+#[derive(Debug, Clone)]
+pub struct PythStreamConfig {
+    pub enabled: bool,
+    /// Hermes base URL; None = the oracle's
+    pub hermes_url: Option<String>,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Reconnect when no price update arrived for this long
+    pub stale_after: Duration,
+}
+
+impl Default for PythStreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hermes_url: None,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            stale_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PythStreamConfig {
+    /// Defaults, overridden by PYTH_STREAM_ENABLED, PYTH_HERMES_URL and
+    /// PYTH_STREAM_STALE_SECS
+    pub fn from_env() -> AppResult<Self> {
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("PYTH_STREAM_ENABLED") {
+            config.enabled = !matches!(value.to_lowercase().as_str(), "false" | "0" | "no");
+        }
+        config.hermes_url = std::env::var("PYTH_HERMES_URL").ok();
+        if let Ok(value) = std::env::var("PYTH_STREAM_STALE_SECS") {
+            let secs = value
+                .parse::<u64>()
+                .ok()
+                .filter(|s| *s > 0)
+                .ok_or_else(|| {
+                    AppError::Config(
+                        "PYTH_STREAM_STALE_SECS must be a positive integer".to_string(),
+                    )
+                })?;
+            config.stale_after = Duration::from_secs(secs);
+        }
+        Ok(config)
+    }
+}
+
+/// One event of the Hermes price stream
+#[derive(Debug, Deserialize)]
+struct StreamEvent {
+    #[serde(default)]
+    parsed: Vec<ParsedPriceUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParsedPriceUpdate {
+    id: String,
+    price: PythPrice,
+}
+
+/// Incremental server-sent events parser
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Add a chunk of the stream and return the data of every event it
+    /// completes; comments and other fields are skipped
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend(chunk.iter().filter(|b| **b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if !data.is_empty() {
+                events.push(data.join("\n"));
+            }
+        }
+        events
+    }
+}
+
+fn next_backoff(current: Duration, max: Duration) -> Duration {
+    (current * 2).min(max)
+}
+
+pub struct PythStreamSubscriber {
+    config: PythStreamConfig,
+    client: reqwest::Client,
+    oracle: Arc<PythOracle>,
+    price_cache: Arc<PriceCache>,
+    ohlc_store: Arc<OhlcStore>,
+    broadcaster: Arc<PriceFeedBroadcaster>,
+}
+
+impl PythStreamSubscriber {
+    pub fn new(
+        config: PythStreamConfig,
+        oracle: Arc<PythOracle>,
+        price_cache: Arc<PriceCache>,
+        ohlc_store: Arc<OhlcStore>,
+        broadcaster: Arc<PriceFeedBroadcaster>,
+    ) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            oracle,
+            price_cache,
+            ohlc_store,
+            broadcaster,
+        }
+    }
+
+    /// Stream until the process exits, reconnecting with backoff
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut backoff = self.config.initial_backoff;
+            loop {
+                match self.stream_once().await {
+                    Ok(received) => {
+                        info!("Pyth stream ended after {} updates, reconnecting", received);
+                        if received > 0 {
+                            backoff = self.config.initial_backoff;
+                        }
+                    }
+                    Err(e) => warn!("Pyth stream failed, reconnecting in {:?}: {}", backoff, e),
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = next_backoff(backoff, self.config.max_backoff);
+            }
+        })
+    }
+
+    /// One connection: returns the number of updates applied once the
+    /// server closes it or the feed mappings change
+    async fn stream_once(&self) -> AppResult<usize> {
+        let feeds_version = self.oracle.feeds_version();
+        let feeds = self.oracle.stream_feeds();
+        if feeds.is_empty() {
+            return Err(AppError::Config(
+                "No Pyth price feeds to stream".to_string(),
+            ));
+        }
+
+        let base_url = self
+            .config
+            .hermes_url
+            .as_deref()
+            .unwrap_or_else(|| self.oracle.base_url());
+        let url = format!("{}/v2/updates/price/stream", base_url.trim_end_matches('/'));
+        let mut query: Vec<(&str, &str)> = feeds.keys().map(|id| ("ids[]", id.as_str())).collect();
+        query.push(("parsed", "true"));
+
+        let mut response = self
+            .client
+            .get(&url)
+            .query(&query)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| AppError::ExternalError(format!("Hermes stream request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::ExternalError(format!(
+                "Hermes stream returned {}",
+                response.status()
+            )));
+        }
+
+        info!(
+            "📡 Streaming {} Pyth price feeds from {}",
+            feeds.len(),
+            base_url
+        );
+
+        let mut parser = SseParser::default();
+        let mut received = 0;
+        let mut last_update = Instant::now();
+
+        loop {
+            // Watchdog: the stream must deliver an update every stale_after
+            let chunk =
+                tokio::time::timeout_at(last_update + self.config.stale_after, response.chunk())
+                    .await
+                    .map_err(|_| {
+                        AppError::ExternalError(format!(
+                            "No Pyth price update for {:?}",
+                            self.config.stale_after
+                        ))
+                    })?
+                    .map_err(|e| {
+                        AppError::ExternalError(format!("Hermes stream read failed: {}", e))
+                    })?;
+
+            let Some(chunk) = chunk else {
+                return Ok(received);
+            };
+
+            for data in parser.push(&chunk) {
+                match self.apply_event(&data, &feeds).await {
+                    Ok(0) => {}
+                    Ok(applied) => {
+                        received += applied;
+                        last_update = Instant::now();
+                    }
+                    Err(e) => warn!("Skipping Pyth stream event: {}", e),
+                }
+            }
+
+            if self.oracle.feeds_version() != feeds_version {
+                info!("Pyth price feeds changed, resubscribing");
+                return Ok(received);
+            }
+        }
+    }
+
+    /// Apply one stream event, returning the number of feed updates in it
+    async fn apply_event(
+        &self,
+        data: &str,
+        feeds: &HashMap<String, Vec<(String, Option<String>)>>,
+    ) -> AppResult<usize> {
+        let event: StreamEvent = serde_json::from_str(data)
+            .map_err(|e| AppError::ExternalError(format!("Invalid Hermes event: {}", e)))?;
+
+        let mut applied = 0;
+        for update in event.parsed {
+            let Some(targets) = feeds.get(&update.id.to_lowercase()) else {
+                continue;
+            };
+
+            let price = update.price.to_decimal().map_err(|e| {
+                AppError::ExternalError(format!("Invalid price for feed {}: {}", update.id, e))
+            })?;
+            let confidence = update
+                .price
+                .confidence_pct()
+                .map(|pct| price * pct / Decimal::from(100))
+                .unwrap_or_default();
+            let timestamp = Utc
+                .timestamp_opt(update.price.publish_time, 0)
+                .single()
+                .unwrap_or_else(Utc::now);
+
+            self.oracle.update_streamed_price(&update.id, update.price);
+
+            for (asset, chain) in targets {
+                let chains: Vec<String> = match chain {
+                    Some(chain) => vec![chain.clone()],
+                    None => Chain::all()
+                        .iter()
+                        .map(|c| c.as_str().to_string())
+                        .collect(),
+                };
+                for chain in chains {
+                    self.price_cache.set(asset, &chain, price, confidence).await;
+                    for timeframe in TIMEFRAMES {
+                        self.ohlc_store
+                            .add_price(asset, &chain, timeframe, price, Decimal::ZERO)
+                            .await?;
+                    }
+                    self.broadcaster.broadcast_price(PriceUpdate {
+                        asset: asset.clone(),
+                        chain,
+                        price,
+                        confidence,
+                        timestamp,
+                    });
+                }
+            }
+
+            debug!("Pyth stream: {} = {}", update.id, price);
+            applied += 1;
+        }
+
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::models::PriceFeed;
+    use crate::quote_engine::pyth_oracle::PythPriceFeedIds;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const SOL_FEED: &str = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+
+    fn sol_event(price: &str) -> String {
+        format!(
+            r#"{{"parsed":[{{"id":"{}","price":{{"price":"{}","conf":"5000000","expo":-8,"publish_time":{}}}}}]}}"#,
+            SOL_FEED,
+            price,
+            Utc::now().timestamp()
+        )
+    }
+
+    /// Hermes stand-in: connection N sends the events of `connections[N]`
+    /// and stays open; connections past the list get no events
+    async fn sse_stand_in(connections: Vec<Vec<String>>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let events = connections
+                    .get(counter.fetch_add(1, Ordering::SeqCst))
+                    .cloned()
+                    .unwrap_or_default();
+                tokio::spawn(async move {
+                    let mut request = vec![0u8; 4096];
+                    let _ = socket.read(&mut request).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n")
+                        .await;
+                    for event in events {
+                        let _ = socket
+                            .write_all(format!("data: {}\n\n", event).as_bytes())
+                            .await;
+                    }
+                    let _ = socket.flush().await;
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                });
+            }
+        });
+
+        (url, accepted)
+    }
+
+    fn subscriber(
+        url: String,
+        stale_after: Duration,
+    ) -> (
+        Arc<PythStreamSubscriber>,
+        Arc<PriceCache>,
+        Arc<OhlcStore>,
+        Arc<PriceFeedBroadcaster>,
+    ) {
+        let oracle = Arc::new(PythOracle::new("mainnet"));
+        oracle.set_feed_ids(
+            PythPriceFeedIds::from_feeds(&[PriceFeed {
+                id: uuid::Uuid::new_v4(),
+                network: "mainnet".to_string(),
+                chain: Some(Chain::Solana),
+                asset: "SOL".to_string(),
+                feed_id: format!("0x{}", SOL_FEED),
+                enabled: true,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }])
+            .unwrap(),
+        );
+
+        let price_cache = Arc::new(PriceCache::new(1000));
+        let ohlc_store = Arc::new(OhlcStore::new(100));
+        let broadcaster = Arc::new(PriceFeedBroadcaster::new());
+        let config = PythStreamConfig {
+            hermes_url: Some(url),
+            initial_backoff: Duration::from_millis(50),
+            stale_after,
+            ..PythStreamConfig::default()
+        };
+        let subscriber = Arc::new(PythStreamSubscriber::new(
+            config,
+            oracle,
+            price_cache.clone(),
+            ohlc_store.clone(),
+            broadcaster.clone(),
+        ));
+        (subscriber, price_cache, ohlc_store, broadcaster)
+    }
+
+    #[test]
+    fn test_sse_parser_joins_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b": keepalive\r\n\r\ndata: {\"a\"").is_empty());
+        assert_eq!(
+            parser.push(b":1}\r\n\r\ndata: 2\n\n"),
+            vec!["{\"a\":1}", "2"]
+        );
+        assert_eq!(
+            next_backoff(Duration::from_secs(40), Duration::from_secs(60)),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_updates_caches_and_broadcasts() {
+        let (url, _) = sse_stand_in(vec![vec![sol_event("15000000000")]]).await;
+        let (subscriber, price_cache, ohlc_store, broadcaster) =
+            subscriber(url, Duration::from_secs(5));
+        let mut prices = broadcaster.subscribe_prices();
+        subscriber.start();
+
+        let update = tokio::time::timeout(Duration::from_secs(5), prices.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.asset, "SOL");
+        assert_eq!(update.chain, "solana");
+        assert_eq!(update.price, Decimal::from(150));
+
+        assert_eq!(
+            price_cache.get("SOL", "solana").await.unwrap().price,
+            Decimal::from(150)
+        );
+        let candle = ohlc_store
+            .get_latest_candle("SOL", "solana", Timeframe::OneMinute)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(candle.close, Decimal::from(150));
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_reconnects_silent_stream() {
+        // First connection never sends an update
+        let (url, accepted) = sse_stand_in(vec![vec![], vec![sol_event("16000000000")]]).await;
+        let (subscriber, _, _, broadcaster) = subscriber(url, Duration::from_millis(200));
+        let mut prices = broadcaster.subscribe_prices();
+        subscriber.start();
+
+        let update = tokio::time::timeout(Duration::from_secs(5), prices.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.price, Decimal::from(160));
+        assert!(accepted.load(Ordering::SeqCst) >= 2);
+    }
+}
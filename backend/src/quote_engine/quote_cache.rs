@@ -0,0 +1,460 @@
+// Realtime quote cache
+//
+// Multi-DEX quotes are cached per (chain, asset_in, asset_out, amount
+// bucket) for a TTL, in a bounded map that evicts the least recently used
+// entry when full. Requests that miss while the same key is already being
+// fetched wait for that fetch instead of fanning out to every adapter again,
+// and get its quote or its error.
+
+use crate::error::{AppError, AppResult};
+use crate::quote_engine::realtime::MultiDexQuote;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+#[derive(Debug, Clone)]
+pub struct QuoteCacheConfig {
+    /// How long a quote is served from the cache
+    pub ttl: Duration,
+    /// Most quotes kept; the least recently used goes first
+    pub max_entries: usize,
+    /// Amounts are rounded to this many significant digits for the key
+    pub amount_significant_digits: u32,
+}
+
+impl Default for QuoteCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(10),
+            max_entries: 10_000,
+            amount_significant_digits: 4,
+        }
+    }
+}
+
+impl QuoteCacheConfig {
+    /// Defaults, overridden by QUOTE_CACHE_TTL_SECS, QUOTE_CACHE_MAX_ENTRIES
+    /// and QUOTE_CACHE_AMOUNT_DIGITS
+    pub fn from_env() -> AppResult<Self> {
+        let positive = |name: &str| -> AppResult<Option<u64>> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .map(Some)
+                    .ok_or_else(|| {
+                        AppError::Config(format!("{} must be a positive integer", name))
+                    }),
+                Err(_) => Ok(None),
+            }
+        };
+
+        let mut config = Self::default();
+        if let Some(secs) = positive("QUOTE_CACHE_TTL_SECS")? {
+            config.ttl = Duration::from_secs(secs);
+        }
+        if let Some(entries) = positive("QUOTE_CACHE_MAX_ENTRIES")? {
+            config.max_entries = entries as usize;
+        }
+        if let Some(digits) = positive("QUOTE_CACHE_AMOUNT_DIGITS")? {
+            config.amount_significant_digits = digits.min(28) as u32;
+        }
+        Ok(config)
+    }
+}
+
+/// Counters since startup and current size
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteCacheStats {
+    pub entries: usize,
+    pub max_entries: usize,
+    pub ttl_secs: u64,
+    pub in_flight: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Requests that waited for a fetch already in flight
+    pub coalesced: u64,
+    pub evictions: u64,
+}
+
+struct CachedQuote {
+    data: MultiDexQuote,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+/// Outcome of a fetch as its waiters see it; None while it runs
+type Fetched = Option<Result<MultiDexQuote, String>>;
+
+#[derive(Default)]
+struct Entries {
+    quotes: HashMap<String, CachedQuote>,
+    in_flight: HashMap<String, watch::Receiver<Fetched>>,
+    /// Incremented on every use, orders entries by recency
+    clock: u64,
+}
+
+pub struct QuoteCache {
+    config: QuoteCacheConfig,
+    entries: parking_lot::Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl QuoteCache {
+    pub fn new(config: QuoteCacheConfig) -> Self {
+        Self {
+            config,
+            entries: parking_lot::Mutex::new(Entries::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Amount quotes are fetched at for `amount`
+    pub fn bucket(&self, amount: Decimal) -> Decimal {
+        amount
+            .round_sf(self.config.amount_significant_digits)
+            .unwrap_or(amount)
+            .normalize()
+    }
+
+    /// Cached quote for `key`, or the result of `fetch`
+    ///
+    /// Only one `fetch` per key runs at a time; concurrent callers share its
+    /// result, errors included. A failed fetch is not cached. If the caller
+    /// running the fetch is dropped, one of its waiters fetches instead.
+    pub async fn get_or_fetch<F, Fut>(&self, key: &str, fetch: F) -> AppResult<MultiDexQuote>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<MultiDexQuote>>,
+    {
+        let mut fetch = Some(fetch);
+        loop {
+            let claim = {
+                let mut entries = self.entries.lock();
+                entries.clock += 1;
+                let now = entries.clock;
+                match entries.quotes.get_mut(key) {
+                    Some(cached) if cached.expires_at > Instant::now() => {
+                        cached.last_used = now;
+                        self.hits.fetch_add(1, Ordering::Relaxed);
+                        return Ok(cached.data.clone());
+                    }
+                    Some(_) => {
+                        entries.quotes.remove(key);
+                    }
+                    None => {}
+                }
+
+                match entries.in_flight.get(key) {
+                    Some(receiver) => {
+                        self.coalesced.fetch_add(1, Ordering::Relaxed);
+                        Claim::Wait(receiver.clone())
+                    }
+                    None => {
+                        self.misses.fetch_add(1, Ordering::Relaxed);
+                        let (sender, receiver) = watch::channel(None);
+                        entries.in_flight.insert(key.to_string(), receiver);
+                        Claim::Fetch(sender)
+                    }
+                }
+            };
+
+            match claim {
+                Claim::Fetch(sender) => {
+                    let fetch = fetch.take().expect("a caller fetches at most once");
+                    return self.lead(key, sender, fetch).await;
+                }
+                Claim::Wait(receiver) => {
+                    if let Some(result) = Self::wait(receiver).await {
+                        return result;
+                    }
+                    // The fetching caller was dropped; the next one around fetches
+                }
+            }
+        }
+    }
+
+    /// Run `fetch` for the callers waiting on `key`
+    async fn lead<F, Fut>(
+        &self,
+        key: &str,
+        sender: watch::Sender<Fetched>,
+        fetch: F,
+    ) -> AppResult<MultiDexQuote>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<MultiDexQuote>>,
+    {
+        let claim = InFlight {
+            cache: self,
+            key,
+            sender,
+        };
+        let result = fetch().await;
+
+        if let Ok(quote) = &result {
+            let mut entries = self.entries.lock();
+            self.insert(&mut entries, key, quote.clone());
+        }
+        claim
+            .sender
+            .send_replace(Some(result.as_ref().cloned().map_err(ToString::to_string)));
+        result
+    }
+
+    /// Result of a fetch in flight; None if it was dropped unfinished
+    async fn wait(mut receiver: watch::Receiver<Fetched>) -> Option<AppResult<MultiDexQuote>> {
+        let fetched = receiver.wait_for(Option::is_some).await.ok()?;
+        match &*fetched {
+            Some(Ok(quote)) => Some(Ok(quote.clone())),
+            Some(Err(message)) => Some(Err(AppError::ExternalError(message.clone()))),
+            None => None,
+        }
+    }
+
+    fn insert(&self, entries: &mut Entries, key: &str, data: MultiDexQuote) {
+        if entries.quotes.len() >= self.config.max_entries && !entries.quotes.contains_key(key) {
+            let now = Instant::now();
+            entries.quotes.retain(|_, cached| cached.expires_at > now);
+        }
+        while entries.quotes.len() >= self.config.max_entries && !entries.quotes.contains_key(key) {
+            let Some(oldest) = entries
+                .quotes
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.quotes.remove(&oldest);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.quotes.insert(
+            key.to_string(),
+            CachedQuote {
+                data,
+                expires_at: Instant::now() + self.config.ttl,
+                last_used,
+            },
+        );
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().quotes.clear();
+    }
+
+    pub fn stats(&self) -> QuoteCacheStats {
+        let entries = self.entries.lock();
+        QuoteCacheStats {
+            entries: entries.quotes.len(),
+            max_entries: self.config.max_entries,
+            ttl_secs: self.config.ttl.as_secs(),
+            in_flight: entries.in_flight.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// What a caller that missed does for a key
+enum Claim {
+    /// Fetch it; the receiver is in flight for the others
+    Fetch(watch::Sender<Fetched>),
+    /// Wait for the fetch in flight
+    Wait(watch::Receiver<Fetched>),
+}
+
+/// A caller's fetch of a key; the key stops being in flight however the
+/// fetch ends, cancellation included, before its waiters are woken
+struct InFlight<'a> {
+    cache: &'a QuoteCache,
+    key: &'a str,
+    sender: watch::Sender<Fetched>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.cache.entries.lock();
+        if entries
+            .in_flight
+            .get(self.key)
+            .is_some_and(|current| current.same_channel(&self.sender.subscribe()))
+        {
+            entries.in_flight.remove(self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::traits::AssetInfo;
+    use crate::ledger::models::Chain;
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    fn quote(amount: Decimal) -> MultiDexQuote {
+        let asset = AssetInfo {
+            chain: Chain::Solana,
+            address: "mint".to_string(),
+            symbol: "SOL".to_string(),
+            name: "Solana".to_string(),
+            decimals: 9,
+            logo_url: None,
+        };
+        MultiDexQuote {
+            asset_in: asset.clone(),
+            asset_out: asset,
+            amount_in: amount,
+            best_dex: "Fixed".to_string(),
+            best_amount_out: amount,
+            best_rate: Decimal::ONE,
+            best_slippage: Decimal::ZERO,
+            all_quotes: vec![],
+            aggregated_liquidity: Decimal::ZERO,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn cache(ttl: Duration, max_entries: usize) -> QuoteCache {
+        QuoteCache::new(QuoteCacheConfig {
+            ttl,
+            max_entries,
+            amount_significant_digits: 4,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_share_one_fetch() {
+        let cache = Arc::new(cache(Duration::from_secs(10), 10));
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let requests = (0..10).map(|_| {
+            let (cache, fetches) = (cache.clone(), fetches.clone());
+            tokio::spawn(async move {
+                cache
+                    .get_or_fetch("SOL/USDC/1", || async move {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok(quote(dec!(1)))
+                    })
+                    .await
+            })
+        });
+        for result in futures::future::join_all(requests).await {
+            assert_eq!(result.unwrap().unwrap().amount_in, dec!(1));
+        }
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        let stats = cache.stats();
+        assert_eq!((stats.misses, stats.coalesced, stats.hits), (1, 9, 0));
+        assert_eq!((stats.entries, stats.in_flight), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire_and_least_recently_used_is_evicted() {
+        let cache = cache(Duration::from_millis(50), 2);
+        let fetch = |amount| move || async move { Ok(quote(amount)) };
+
+        cache.get_or_fetch("a", fetch(dec!(1))).await.unwrap();
+        cache.get_or_fetch("b", fetch(dec!(2))).await.unwrap();
+        cache.get_or_fetch("a", fetch(dec!(1))).await.unwrap();
+        cache.get_or_fetch("c", fetch(dec!(3))).await.unwrap();
+
+        // "b" was used least recently
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 3, 1));
+        cache.get_or_fetch("a", fetch(dec!(1))).await.unwrap();
+        assert_eq!(cache.stats().hits, 2);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        cache.get_or_fetch("a", fetch(dec!(1))).await.unwrap();
+        assert_eq!(cache.stats().misses, 4);
+    }
+
+    #[test]
+    fn test_amounts_share_a_bucket() {
+        let cache = cache(Duration::from_secs(10), 10);
+        assert_eq!(cache.bucket(dec!(1234.56)), dec!(1235));
+        assert_eq!(cache.bucket(dec!(1234.51)), cache.bucket(dec!(1235.4)));
+        assert_eq!(cache.bucket(dec!(2.50)).to_string(), "2.5");
+    }
+
+    #[tokio::test]
+    async fn test_waiters_share_a_failed_fetch() {
+        let cache = Arc::new(cache(Duration::from_secs(10), 10));
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let requests = (0..5).map(|_| {
+            let (cache, fetches) = (cache.clone(), fetches.clone());
+            tokio::spawn(async move {
+                cache
+                    .get_or_fetch("SOL/USDC/1", || async move {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Err(AppError::NoLiquidityAvailable("no pools".to_string()))
+                    })
+                    .await
+            })
+        });
+        for result in futures::future::join_all(requests).await {
+            assert!(result.unwrap().unwrap_err().to_string().contains("no pools"));
+        }
+
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.in_flight), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_waiter_takes_over_a_cancelled_fetch() {
+        let cache = Arc::new(cache(Duration::from_secs(10), 10));
+
+        let leader = {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                cache
+                    .get_or_fetch("SOL/USDC/1", || async {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        Ok(quote(dec!(1)))
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let waiter = {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                cache
+                    .get_or_fetch("SOL/USDC/1", || async { Ok(quote(dec!(2))) })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        leader.abort();
+
+        assert_eq!(waiter.await.unwrap().unwrap().amount_in, dec!(2));
+        let stats = cache.stats();
+        assert_eq!((stats.misses, stats.coalesced), (2, 1));
+        assert_eq!((stats.entries, stats.in_flight), (1, 0));
+    }
+}
@@ -1,11 +1,14 @@
-use crate::adapters::AdapterRegistry;
+use crate::adapters::{AdapterRegistry, DexWhitelist};
 use crate::adapters::traits::{AssetInfo};
 use crate::error::{AppError, AppResult};
+use crate::ledger::models::Asset;
+use crate::quote_engine::quote_cache::{QuoteCache, QuoteCacheConfig, QuoteCacheStats};
+use crate::trading::models::{RouteStep, RouteStepKind};
 use rust_decimal::Decimal;
-use std::collections::HashMap;
 use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MultiDexQuote {
@@ -35,65 +38,90 @@ pub struct DexQuoteOption {
 pub struct RouteOption {
     pub path: Vec<AssetInfo>,
     pub total_amount_out: Decimal,
+    /// Native asset of the chain, summed over the hops
     pub estimated_gas_fees: Decimal,
+    /// total_amount_out less the gas fees converted into the output asset
+    pub net_amount_out: Decimal,
     pub dex_sequence: Vec<String>,
     pub hops: usize,
     pub total_slippage: Decimal,
+    /// One swap per hop, in order
+    pub steps: Vec<RouteStep>,
 }
 
 pub struct RealtimeQuoteEngine {
     adapter_registry: Arc<AdapterRegistry>,
-    cache: tokio::sync::RwLock<QuoteCache>,
+    whitelist: DexWhitelist,
+    cache: QuoteCache,
 }
 
-struct QuoteCache {
-    quotes: HashMap<String, CachedQuote>,
-    cache_ttl_secs: u64,
-}
+impl MultiDexQuote {
+    /// Same rates for another amount in the same cache bucket
+    fn scaled_to(mut self, amount: Decimal) -> Self {
+        if amount == self.amount_in || self.amount_in.is_zero() {
+            return self;
+        }
 
-struct CachedQuote {
-    data: MultiDexQuote,
-    cached_at: DateTime<Utc>,
+        let ratio = amount / self.amount_in;
+        self.amount_in = amount;
+        self.best_amount_out *= ratio;
+        for option in &mut self.all_quotes {
+            option.amount_out *= ratio;
+        }
+        self
+    }
 }
 
 impl RealtimeQuoteEngine {
     pub fn new(adapter_registry: Arc<AdapterRegistry>) -> Self {
         Self {
             adapter_registry,
-            cache: tokio::sync::RwLock::new(QuoteCache {
-                quotes: HashMap::new(),
-                cache_ttl_secs: 10, // 10 second cache
-            }),
+            whitelist: DexWhitelist::new(),
+            cache: QuoteCache::new(QuoteCacheConfig::default()),
         }
     }
 
+    pub fn with_cache_config(mut self, config: QuoteCacheConfig) -> Self {
+        self.cache = QuoteCache::new(config);
+        self
+    }
+
+    /// Best quote across the chain's DEXes
+    ///
+    /// Quotes are fetched for the amount's cache bucket and scaled to the
+    /// amount; concurrent requests for a bucket share one fetch.
     pub async fn get_best_quote(
         &self,
         asset_in: &AssetInfo,
         asset_out: &AssetInfo,
         amount: Decimal,
     ) -> AppResult<MultiDexQuote> {
+        let bucket = self.cache.bucket(amount);
         let cache_key = format!(
             "{}/{}/{}/{}",
-            asset_in.chain.to_string(),
+            asset_in.chain,
             asset_in.address,
             asset_out.address,
-            amount
+            bucket
         );
 
-        // Check cache first
-        {
-            let cache = self.cache.read().await;
-            if let Some(cached) = cache.quotes.get(&cache_key) {
-                let age = Utc::now()
-                    .signed_duration_since(cached.cached_at)
-                    .num_seconds() as u64;
-                if age < cache.cache_ttl_secs {
-                    return Ok(cached.data.clone());
-                }
-            }
-        }
+        let quote = self
+            .cache
+            .get_or_fetch(&cache_key, || self.fetch_best_quote(asset_in, asset_out, bucket))
+            .await?;
+        Ok(quote.scaled_to(amount))
+    }
+
+    pub fn cache_stats(&self) -> QuoteCacheStats {
+        self.cache.stats()
+    }
 
+    async fn fetch_best_quote(
+        &self,
+        asset_in: &AssetInfo,
+        asset_out: &AssetInfo,
+        amount: Decimal,
+    ) -> AppResult<MultiDexQuote> {
         // Fetch from all available DEXes in parallel
         let dexes = self
             .adapter_registry
@@ -159,21 +187,16 @@ impl RealtimeQuoteEngine {
             timestamp: Utc::now(),
         };
 
-        // Cache the result
-        {
-            let mut cache = self.cache.write().await;
-            cache.quotes.insert(
-                cache_key,
-                CachedQuote {
-                    data: multi_quote.clone(),
-                    cached_at: Utc::now(),
-                },
-            );
-        }
-
         Ok(multi_quote)
     }
 
+    /// Routes of up to `max_hops` swaps from `asset_in` to `asset_out`,
+    /// best net output first
+    ///
+    /// Paths go through the chain's whitelisted tokens (USDC, the native
+    /// asset, ...). Every hop is quoted on all registered DEXes and its best
+    /// output is the next hop's input; paths with a hop nobody quotes are
+    /// dropped. Both assets must be on the same chain.
     pub async fn find_multi_hop_routes(
         &self,
         asset_in: &AssetInfo,
@@ -187,31 +210,150 @@ impl RealtimeQuoteEngine {
             ));
         }
 
-        let mut routes = vec![];
+        if asset_in.chain != asset_out.chain {
+            return Ok(vec![]);
+        }
+
+        let paths = self.candidate_paths(asset_in, asset_out, max_hops);
+        let results = futures::future::join_all(
+            paths.into_iter().map(|path| self.evaluate_route(path, amount)),
+        )
+        .await;
+
+        let mut routes: Vec<RouteOption> = results
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(route) => route,
+                Err(e) => {
+                    debug!("Skipping route: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        // Gas is paid in the native asset: price it in the output asset
+        match self.native_rate(asset_out).await {
+            Some(rate) => {
+                for route in &mut routes {
+                    route.net_amount_out = route.total_amount_out - route.estimated_gas_fees * rate;
+                }
+            }
+            None => warn!(
+                "No {} price for gas on {}, ranking routes by gross output",
+                asset_out.symbol, asset_out.chain
+            ),
+        }
+
+        routes.sort_by(|a, b| {
+            b.net_amount_out
+                .cmp(&a.net_amount_out)
+                .then(a.hops.cmp(&b.hops))
+        });
+
+        Ok(routes)
+    }
+
+    /// Simple paths from `asset_in` to `asset_out` of at most `max_hops`
+    /// edges, through the chain's whitelisted tokens
+    fn candidate_paths(
+        &self,
+        asset_in: &AssetInfo,
+        asset_out: &AssetInfo,
+        max_hops: usize,
+    ) -> Vec<Vec<AssetInfo>> {
+        let mut intermediates: Vec<AssetInfo> = self
+            .whitelist
+            .get_tokens_for_chain(asset_in.chain)
+            .into_iter()
+            .filter(|token| token.token_id != asset_in.address && token.token_id != asset_out.address)
+            .map(AssetInfo::from)
+            .collect();
+        intermediates.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![asset_in.clone()]];
+        while let Some(path) = stack.pop() {
+            let mut complete = path.clone();
+            complete.push(asset_out.clone());
+            paths.push(complete);
+
+            // Another intermediate still leaves room for the final hop
+            if path.len() < max_hops {
+                for token in &intermediates {
+                    if !path.contains(token) {
+                        let mut next = path.clone();
+                        next.push(token.clone());
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        paths
+    }
+
+    /// Quote a path hop by hop; None if a hop has no liquidity
+    async fn evaluate_route(&self, path: Vec<AssetInfo>, amount: Decimal) -> AppResult<Option<RouteOption>> {
+        let native = Asset::native(path[0].chain).symbol;
+        let mut amount_out = amount;
+        let mut steps = Vec::new();
+
+        for hop in path.windows(2) {
+            let quote = self.get_best_quote(&hop[0], &hop[1], amount_out).await?;
+            if quote.best_amount_out <= Decimal::ZERO {
+                return Ok(None);
+            }
 
-        // Single-hop: direct swap
-        if asset_in.chain == asset_out.chain {
-            let quote = self.get_best_quote(asset_in, asset_out, amount).await?;
-            let gas_estimate = self
+            let gas = self
                 .adapter_registry
                 .get_dex(&quote.best_dex)
                 .ok_or(AppError::AdapterNotFound)?
-                .estimate_gas(asset_in, asset_out)
+                .estimate_gas(&hop[0], &hop[1])
                 .await?;
-
-            routes.push(RouteOption {
-                path: vec![asset_in.clone(), asset_out.clone()],
-                total_amount_out: quote.best_amount_out,
-                estimated_gas_fees: gas_estimate,
-                dex_sequence: vec![quote.best_dex],
-                hops: 1,
-                total_slippage: quote.best_slippage,
+            steps.push(RouteStep {
+                kind: RouteStepKind::Swap,
+                chain: hop[1].chain,
+                dex: quote.best_dex,
+                asset_in: hop[0].clone(),
+                asset_out: hop[1].clone(),
+                amount_in: amount_out,
+                expected_output: quote.best_amount_out,
+                fee: gas,
+                fee_asset: native.clone(),
+                slippage_percent: quote.best_slippage,
             });
+            amount_out = quote.best_amount_out;
         }
 
-        Ok(routes)
+        Ok(Some(RouteOption {
+            hops: path.len() - 1,
+            path,
+            total_amount_out: amount_out,
+            estimated_gas_fees: steps.iter().map(|step| step.fee).sum(),
+            net_amount_out: amount_out,
+            dex_sequence: steps.iter().map(|step| step.dex.clone()).collect(),
+            total_slippage: compound_slippage(&steps),
+            steps,
+        }))
     }
 
+    /// Output asset per unit of the chain's native asset
+    pub async fn native_rate(&self, asset_out: &AssetInfo) -> Option<Decimal> {
+        let native = Asset::native(asset_out.chain);
+        let native = AssetInfo::from(self.whitelist.get_by_symbol(asset_out.chain, &native.symbol).ok()?);
+        if native.address == asset_out.address {
+            return Some(Decimal::ONE);
+        }
+
+        let quote = self.get_best_quote(&native, asset_out, Decimal::ONE).await.ok()?;
+        (quote.best_amount_out > Decimal::ZERO).then_some(quote.best_amount_out)
+    }
+
+    /// Price impact of swapping `amount` on the best DEX
+    ///
+    /// Computed from the pool's reserves when the DEX reports them;
+    /// otherwise the DEX's quote for the amount is compared with its quote
+    /// for a sliver of it. Either way the spot price has the pool fees
+    /// taken like the execution price, so the impact excludes them.
     pub async fn estimate_execution_price_impact(
         &self,
         asset_in: &AssetInfo,
@@ -219,16 +361,43 @@ impl RealtimeQuoteEngine {
         amount: Decimal,
     ) -> AppResult<PriceImpactEstimate> {
         let quote = self.get_best_quote(asset_in, asset_out, amount).await?;
+        if quote.best_amount_out <= Decimal::ZERO {
+            return Err(AppError::NoLiquidityAvailable(
+                "No DEX quotes the pair".to_string(),
+            ));
+        }
+        let dex = self
+            .adapter_registry
+            .get_dex(&quote.best_dex)
+            .ok_or(AppError::AdapterNotFound)?;
 
-        let spot_price = quote.best_rate;
-        let execution_price = if quote.best_amount_out > Decimal::ZERO {
-            amount / quote.best_amount_out
-        } else {
-            Decimal::ZERO
+        let pool = match dex.get_pool_state(asset_in, asset_out).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                warn!("{} pool state unavailable, estimating impact from quotes: {}", quote.best_dex, e);
+                None
+            }
+        };
+
+        let (spot_price, execution_price, marginal_price) = match pool {
+            Some(pool) => {
+                let amm = pool.quote(amount)?;
+                (
+                    pool.fee().net_price(amm.spot_price),
+                    amm.execution_price,
+                    Some(amm.marginal_price),
+                )
+            }
+            None => {
+                let probe = dex
+                    .get_price(asset_in, asset_out, amount / Decimal::from(SPOT_PROBE_DIVISOR))
+                    .await?;
+                (probe.amount_out / probe.amount_in, quote.best_amount_out / amount, None)
+            }
         };
 
-        let price_impact_percent = if spot_price > Decimal::ZERO {
-            ((spot_price - execution_price) / spot_price) * Decimal::from(100)
+        let price_impact_bps = if spot_price > Decimal::ZERO {
+            (spot_price - execution_price) / spot_price * Decimal::from(10_000)
         } else {
             Decimal::ZERO
         };
@@ -236,24 +405,161 @@ impl RealtimeQuoteEngine {
         Ok(PriceImpactEstimate {
             spot_price,
             execution_price,
-            price_impact_bps: price_impact_percent * Decimal::from(100), // Convert to basis points
+            marginal_price,
+            price_impact_bps,
             slippage_percent: quote.best_slippage,
             recommended_slippage_tolerance: quote.best_slippage + Decimal::from(1), // +1% buffer
         })
     }
 
     pub async fn clear_cache(&self) -> AppResult<()> {
-        let mut cache = self.cache.write().await;
-        cache.quotes.clear();
+        self.cache.clear();
         Ok(())
     }
 }
 
+/// Slippage of consecutive steps, in percent
+pub fn compound_slippage(steps: &[RouteStep]) -> Decimal {
+    let retained = steps.iter().fold(Decimal::ONE, |retained, step| {
+        retained * (Decimal::ONE - step.slippage_percent / Decimal::from(100))
+    });
+    (Decimal::ONE - retained) * Decimal::from(100)
+}
+
+/// Fraction of the amount quoted for the spot price when a DEX does not
+/// report its pool
+const SPOT_PROBE_DIVISOR: u32 = 1_000;
+
+/// Prices in units of the output asset per unit of the input asset
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PriceImpactEstimate {
+    /// Price of a swap too small to move the pool, fees included
     pub spot_price: Decimal,
+    /// Average price of the swap, fees included
     pub execution_price: Decimal,
+    /// Price after the swap, when the pool is known
+    pub marginal_price: Option<Decimal>,
     pub price_impact_bps: Decimal,
     pub slippage_percent: Decimal,
     pub recommended_slippage_tolerance: Decimal,
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::adapters::traits::{DexAdapter, PriceQuote, SwapRequest, SwapResult};
+    use crate::ledger::models::Chain;
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+    use std::collections::HashMap;
+
+    /// Fixed rates by symbol pair on every chain, fixed gas per swap
+    pub(crate) struct FixedRateDex {
+        pub(crate) rates: HashMap<(&'static str, &'static str), Decimal>,
+    }
+
+    #[async_trait]
+    impl DexAdapter for FixedRateDex {
+        fn name(&self) -> &'static str {
+            "Fixed"
+        }
+
+        fn supported_chains(&self) -> Vec<Chain> {
+            Chain::all()
+        }
+
+        async fn get_price(&self, asset_in: &AssetInfo, asset_out: &AssetInfo, amount: Decimal) -> AppResult<PriceQuote> {
+            let rate = self
+                .rates
+                .get(&(asset_in.symbol.as_str(), asset_out.symbol.as_str()))
+                .copied()
+                .ok_or_else(|| AppError::NoLiquidityAvailable("no pool".to_string()))?;
+
+            Ok(PriceQuote {
+                asset_in: asset_in.clone(),
+                asset_out: asset_out.clone(),
+                amount_in: amount,
+                amount_out: amount * rate,
+                rate,
+                dex_name: self.name().to_string(),
+                chain: asset_in.chain,
+                slippage_percent: dec!(0.1),
+                execution_time_seconds: 1,
+                liquidity_available: dec!(1000000),
+                timestamp: 0,
+            })
+        }
+
+        async fn get_supported_assets(&self, _chain: Chain) -> AppResult<Vec<AssetInfo>> {
+            Ok(vec![])
+        }
+
+        async fn swap(&self, _request: SwapRequest) -> AppResult<SwapResult> {
+            Err(AppError::InvalidInput("not supported".to_string()))
+        }
+
+        async fn estimate_gas(&self, _asset_in: &AssetInfo, _asset_out: &AssetInfo) -> AppResult<Decimal> {
+            Ok(dec!(0.01))
+        }
+    }
+
+    fn engine() -> RealtimeQuoteEngine {
+        // The direct SOL/USDT pool is shallow; going through USDC pays more
+        let rates = HashMap::from([
+            (("SOL", "USDC"), dec!(100)),
+            (("USDC", "USDT"), dec!(0.999)),
+            (("SOL", "USDT"), dec!(90)),
+        ]);
+        let mut registry = AdapterRegistry::new();
+        registry.register_dex("Fixed".to_string(), Arc::new(FixedRateDex { rates }));
+        RealtimeQuoteEngine::new(Arc::new(registry))
+    }
+
+    fn token(symbol: &str) -> AssetInfo {
+        AssetInfo::from(DexWhitelist::new().get_by_symbol(Chain::Solana, symbol).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_multi_hop_route_beats_direct() {
+        let routes = engine()
+            .find_multi_hop_routes(&token("SOL"), &token("USDT"), dec!(2), 3)
+            .await
+            .unwrap();
+
+        assert_eq!(routes.len(), 2);
+        let best = &routes[0];
+        assert_eq!(best.hops, 2);
+        assert_eq!(best.path[1].symbol, "USDC");
+        assert_eq!(best.total_amount_out, dec!(199.8));
+        assert_eq!(best.estimated_gas_fees, dec!(0.02));
+        // Gas is priced through the direct SOL/USDT rate
+        assert_eq!(best.net_amount_out, dec!(198.0));
+        assert_eq!(routes[1].hops, 1);
+        assert_eq!(routes[1].net_amount_out, dec!(179.1));
+    }
+
+    #[tokio::test]
+    async fn test_price_impact_without_pool_state() {
+        // A fixed rate does not move with the amount
+        let impact = engine()
+            .estimate_execution_price_impact(&token("SOL"), &token("USDC"), dec!(50))
+            .await
+            .unwrap();
+
+        assert_eq!(impact.spot_price, dec!(100));
+        assert_eq!(impact.execution_price, dec!(100));
+        assert_eq!(impact.price_impact_bps, Decimal::ZERO);
+        assert_eq!(impact.marginal_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_max_hops_limits_paths() {
+        let routes = engine()
+            .find_multi_hop_routes(&token("SOL"), &token("USDT"), dec!(2), 1)
+            .await
+            .unwrap();
+
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].dex_sequence, vec!["Fixed".to_string()]);
+    }
+}
@@ -0,0 +1,462 @@
+// Cross-chain route planning
+//
+// A cross-chain trade is a chain of legs: an optional swap on the funding
+// chain into an asset the treasury accepts, the treasury hop (the user pays
+// the treasury on the funding chain, the treasury delivers on the execution
+// chain) and an optional swap on the execution chain into the asset wanted.
+// The treasury accepts and delivers USDC and each chain's native asset, so
+// every combination of the two is planned and the plans are ranked by what
+// the user ends up with after gas. The treasury hop is priced the way a
+// quote for it would be (see TreasuryPricer).
+
+use crate::adapters::traits::AssetInfo;
+use crate::adapters::DexWhitelist;
+use crate::error::{AppError, AppResult};
+use crate::ledger::models::{Asset, Chain};
+use crate::quote_engine::chain_pairs::ChainPairRegistry;
+use crate::quote_engine::price_source::PriceAggregator;
+use crate::quote_engine::realtime::{compound_slippage, RealtimeQuoteEngine};
+use crate::trading::models::{RouteStep, RouteStepKind};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Assets the treasury takes and delivers, besides the chain's native asset
+const BRIDGE_ASSETS: &[&str] = &["USDC"];
+
+/// Name of the treasury hop in route steps
+pub const TREASURY_DEX: &str = "treasury";
+
+/// What the treasury delivers for a payment on the funding chain
+#[derive(Debug, Clone)]
+pub struct TreasuryHop {
+    /// Delivered on the execution chain, in the bridge asset out
+    pub execution_amount: Decimal,
+    /// Execution cost, service fee and slippage buffer, in the bridge asset in
+    pub costs: Decimal,
+}
+
+/// Prices the treasury hop of a route; implemented by QuoteEngine so
+/// routes are priced like the quotes that execute them
+#[async_trait]
+pub trait TreasuryPricer: Send + Sync {
+    /// Hop paying `amount` of `bridge_in`, costs included
+    async fn price_hop(
+        &self,
+        user_id: Option<Uuid>,
+        bridge_in: &AssetInfo,
+        bridge_out: &AssetInfo,
+        amount: Decimal,
+    ) -> AppResult<TreasuryHop>;
+}
+
+/// Ordered steps from the funding asset to the execution asset
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoutePlan {
+    pub asset_in: AssetInfo,
+    pub asset_out: AssetInfo,
+    pub amount_in: Decimal,
+    /// Output of the last step
+    pub amount_out: Decimal,
+    /// Gas of every swap, in the output asset
+    pub total_gas_estimate: Decimal,
+    /// amount_out less total_gas_estimate
+    pub net_amount_out: Decimal,
+    pub total_slippage_percent: Decimal,
+    pub steps: Vec<RouteStep>,
+}
+
+pub struct RoutePlanner {
+    realtime: Arc<RealtimeQuoteEngine>,
+    prices: Arc<PriceAggregator>,
+    chain_pairs: Arc<ChainPairRegistry>,
+    treasury: Arc<dyn TreasuryPricer>,
+    whitelist: DexWhitelist,
+}
+
+impl RoutePlanner {
+    pub fn new(
+        realtime: Arc<RealtimeQuoteEngine>,
+        prices: Arc<PriceAggregator>,
+        chain_pairs: Arc<ChainPairRegistry>,
+        treasury: Arc<dyn TreasuryPricer>,
+    ) -> Self {
+        Self {
+            realtime,
+            prices,
+            chain_pairs,
+            treasury,
+            whitelist: DexWhitelist::new(),
+        }
+    }
+
+    /// Plans from `asset_in` to `asset_out` on another chain, best net
+    /// output first
+    ///
+    /// Both assets must be whitelisted and their chain pair enabled. Each
+    /// swap leg may take up to `max_hops` swaps. `user_id` selects the
+    /// user's fee tier for the treasury hop.
+    pub async fn plan(
+        &self,
+        user_id: Option<Uuid>,
+        asset_in: &AssetInfo,
+        asset_out: &AssetInfo,
+        amount: Decimal,
+        max_hops: usize,
+    ) -> AppResult<Vec<RoutePlan>> {
+        if amount <= Decimal::ZERO {
+            return Err(AppError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
+        }
+
+        self.chain_pairs
+            .require(asset_in.chain, asset_out.chain)
+            .await?;
+        let asset_in = self.resolve(asset_in)?;
+        let asset_out = self.resolve(asset_out)?;
+
+        self.compose(user_id, &asset_in, &asset_out, amount, max_hops)
+            .await
+    }
+
+    /// Plans through every pair of bridge assets
+    async fn compose(
+        &self,
+        user_id: Option<Uuid>,
+        asset_in: &AssetInfo,
+        asset_out: &AssetInfo,
+        amount: Decimal,
+        max_hops: usize,
+    ) -> AppResult<Vec<RoutePlan>> {
+        let mut plans = Vec::new();
+        for bridge_in in self.bridge_assets(asset_in.chain) {
+            for bridge_out in self.bridge_assets(asset_out.chain) {
+                match self
+                    .compose_via(
+                        user_id,
+                        asset_in,
+                        &bridge_in,
+                        &bridge_out,
+                        asset_out,
+                        amount,
+                        max_hops,
+                    )
+                    .await
+                {
+                    Ok(Some(plan)) => plans.push(plan),
+                    Ok(None) => {}
+                    Err(e) => debug!(
+                        "Skipping route via {} -> {}: {}",
+                        bridge_in.symbol, bridge_out.symbol, e
+                    ),
+                }
+            }
+        }
+
+        if plans.is_empty() {
+            return Err(AppError::NoLiquidityAvailable(format!(
+                "No route from {} on {} to {} on {}",
+                asset_in.symbol, asset_in.chain, asset_out.symbol, asset_out.chain
+            )));
+        }
+
+        plans.sort_by(|a, b| {
+            b.net_amount_out
+                .cmp(&a.net_amount_out)
+                .then(a.steps.len().cmp(&b.steps.len()))
+        });
+        Ok(plans)
+    }
+
+    /// Swap into `bridge_in`, treasury hop to `bridge_out`, swap into
+    /// `asset_out`; None if a swap leg has no liquidity
+    #[allow(clippy::too_many_arguments)]
+    async fn compose_via(
+        &self,
+        user_id: Option<Uuid>,
+        asset_in: &AssetInfo,
+        bridge_in: &AssetInfo,
+        bridge_out: &AssetInfo,
+        asset_out: &AssetInfo,
+        amount: Decimal,
+        max_hops: usize,
+    ) -> AppResult<Option<RoutePlan>> {
+        let mut steps = Vec::new();
+
+        let Some(funded) = self
+            .swap_leg(asset_in, bridge_in, amount, max_hops, &mut steps)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let treasury = self
+            .treasury_step(user_id, bridge_in, bridge_out, funded)
+            .await?;
+        let delivered = treasury.expected_output;
+        steps.push(treasury);
+        let Some(amount_out) = self
+            .swap_leg(bridge_out, asset_out, delivered, max_hops, &mut steps)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut total_gas_estimate = Decimal::ZERO;
+        for step in steps.iter().filter(|step| step.kind == RouteStepKind::Swap) {
+            total_gas_estimate += self
+                .in_output_asset(step.fee, &step.fee_asset, asset_out)
+                .await?;
+        }
+
+        Ok(Some(RoutePlan {
+            asset_in: asset_in.clone(),
+            asset_out: asset_out.clone(),
+            amount_in: amount,
+            amount_out,
+            total_gas_estimate,
+            net_amount_out: amount_out - total_gas_estimate,
+            total_slippage_percent: compound_slippage(&steps),
+            steps,
+        }))
+    }
+
+    /// Best same-chain route from `from` to `to`, appended to `steps`;
+    /// returns its output, or None when no route exists
+    async fn swap_leg(
+        &self,
+        from: &AssetInfo,
+        to: &AssetInfo,
+        amount: Decimal,
+        max_hops: usize,
+        steps: &mut Vec<RouteStep>,
+    ) -> AppResult<Option<Decimal>> {
+        if from.address == to.address {
+            return Ok(Some(amount));
+        }
+
+        let routes = self
+            .realtime
+            .find_multi_hop_routes(from, to, amount, max_hops)
+            .await?;
+        Ok(routes.into_iter().next().map(|route| {
+            steps.extend(route.steps);
+            route.total_amount_out
+        }))
+    }
+
+    /// Treasury takes `amount` of `bridge_in` and delivers `bridge_out`,
+    /// priced like a quote: its costs are kept from the payment
+    async fn treasury_step(
+        &self,
+        user_id: Option<Uuid>,
+        bridge_in: &AssetInfo,
+        bridge_out: &AssetInfo,
+        amount: Decimal,
+    ) -> AppResult<RouteStep> {
+        let hop = self
+            .treasury
+            .price_hop(user_id, bridge_in, bridge_out, amount)
+            .await?;
+
+        Ok(RouteStep {
+            kind: RouteStepKind::TreasuryTransfer,
+            chain: bridge_out.chain,
+            dex: TREASURY_DEX.to_string(),
+            asset_in: bridge_in.clone(),
+            asset_out: bridge_out.clone(),
+            amount_in: amount,
+            expected_output: hop.execution_amount,
+            fee: hop.costs,
+            fee_asset: bridge_in.symbol.clone(),
+            slippage_percent: Decimal::ZERO,
+        })
+    }
+
+    /// `amount` of `symbol` priced in `asset_out`
+    ///
+    /// Fails when no price exists: gas left out would overstate the route.
+    async fn in_output_asset(
+        &self,
+        amount: Decimal,
+        symbol: &str,
+        asset_out: &AssetInfo,
+    ) -> AppResult<Decimal> {
+        if amount.is_zero() || symbol.eq_ignore_ascii_case(&asset_out.symbol) {
+            return Ok(amount);
+        }
+
+        let price = self
+            .prices
+            .get_rate(symbol, &asset_out.symbol, asset_out.chain)
+            .await?;
+        Ok(amount * price.rate)
+    }
+
+    /// Whitelisted token behind an asset given by address
+    fn resolve(&self, asset: &AssetInfo) -> AppResult<AssetInfo> {
+        self.whitelist
+            .get_by_id(asset.chain, &asset.address)
+            .map(AssetInfo::from)
+            .map_err(|_| {
+                AppError::InvalidInput(format!(
+                    "Asset {} is not whitelisted on {}",
+                    asset.address, asset.chain
+                ))
+            })
+    }
+
+    /// Assets the treasury handles on a chain
+    fn bridge_assets(&self, chain: Chain) -> Vec<AssetInfo> {
+        let native = Asset::native(chain).symbol;
+        BRIDGE_ASSETS
+            .iter()
+            .copied()
+            .chain(std::iter::once(native.as_str()))
+            .filter_map(|symbol| self.whitelist.get_by_symbol(chain, symbol).ok())
+            .map(AssetInfo::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::AdapterRegistry;
+    use crate::ledger::repository::LedgerRepository;
+    use crate::quote_engine::price_source::{PriceAggregatorConfig, StaticPriceSource};
+    use crate::quote_engine::realtime::tests::FixedRateDex;
+    use rust_decimal_macros::dec;
+    use sqlx::postgres::PgPoolOptions;
+    use std::collections::HashMap;
+
+    /// Treasury at the aggregated rate, keeping `fee_rate` of the payment
+    struct FixedTreasury {
+        prices: Arc<PriceAggregator>,
+        fee_rate: Decimal,
+    }
+
+    #[async_trait]
+    impl TreasuryPricer for FixedTreasury {
+        async fn price_hop(
+            &self,
+            _user_id: Option<Uuid>,
+            bridge_in: &AssetInfo,
+            bridge_out: &AssetInfo,
+            amount: Decimal,
+        ) -> AppResult<TreasuryHop> {
+            let rate = if bridge_in.symbol == bridge_out.symbol {
+                Decimal::ONE
+            } else {
+                self.prices
+                    .get_rate(&bridge_in.symbol, &bridge_out.symbol, bridge_out.chain)
+                    .await?
+                    .rate
+            };
+            let costs = amount * self.fee_rate;
+            Ok(TreasuryHop {
+                execution_amount: (amount - costs) * rate,
+                costs,
+            })
+        }
+    }
+
+    fn planner() -> RoutePlanner {
+        let rates = HashMap::from([(("SOL", "USDC"), dec!(100)), (("USDC", "XLM"), dec!(10))]);
+        let mut registry = AdapterRegistry::new();
+        registry.register_dex("Fixed".to_string(), Arc::new(FixedRateDex { rates }));
+
+        let manual = StaticPriceSource::new("manual");
+        manual.set_rate("SOL", "USDC", dec!(100));
+        manual.set_rate("USDC", "XLM", dec!(10));
+        manual.set_rate("SOL", "XLM", dec!(1000));
+        let mut prices = PriceAggregator::new(PriceAggregatorConfig::default());
+        prices.register_source(Arc::new(manual));
+        let prices = Arc::new(prices);
+        let treasury = Arc::new(FixedTreasury {
+            prices: prices.clone(),
+            fee_rate: dec!(0.001),
+        });
+
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        RoutePlanner::new(
+            Arc::new(RealtimeQuoteEngine::new(Arc::new(registry))),
+            prices,
+            Arc::new(ChainPairRegistry::new(Arc::new(LedgerRepository::new(
+                pool,
+            )))),
+            treasury,
+        )
+    }
+
+    fn token(chain: Chain, symbol: &str) -> AssetInfo {
+        AssetInfo::from(DexWhitelist::new().get_by_symbol(chain, symbol).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_plans_through_every_bridge_pair() {
+        let plans = planner()
+            .compose(
+                None,
+                &token(Chain::Solana, "SOL"),
+                &token(Chain::Stellar, "XLM"),
+                dec!(2),
+                2,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(plans.len(), 4);
+        // Paying SOL straight into the treasury for XLM has no swap gas
+        assert_eq!(plans[0].steps.len(), 1);
+        assert_eq!(plans[0].steps[0].kind, RouteStepKind::TreasuryTransfer);
+        assert_eq!(plans[0].net_amount_out, dec!(1998));
+        assert!(plans
+            .windows(2)
+            .all(|w| w[0].net_amount_out >= w[1].net_amount_out));
+    }
+
+    #[tokio::test]
+    async fn test_composite_route_chains_steps() {
+        let plans = planner()
+            .compose(
+                None,
+                &token(Chain::Solana, "SOL"),
+                &token(Chain::Stellar, "XLM"),
+                dec!(2),
+                2,
+            )
+            .await
+            .unwrap();
+
+        let plan = plans.iter().find(|plan| plan.steps.len() == 3).unwrap();
+        let kinds: Vec<_> = plan.steps.iter().map(|step| step.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RouteStepKind::Swap,
+                RouteStepKind::TreasuryTransfer,
+                RouteStepKind::Swap
+            ]
+        );
+        assert_eq!(plan.steps[0].chain, Chain::Solana);
+        assert_eq!(plan.steps[2].chain, Chain::Stellar);
+        assert!(plan
+            .steps
+            .windows(2)
+            .all(|w| w[0].expected_output == w[1].amount_in));
+
+        // 200 USDC in, 0.1% kept for the treasury costs
+        let treasury = &plan.steps[1];
+        assert_eq!(treasury.fee, dec!(0.2));
+        assert_eq!(treasury.expected_output, dec!(199.8));
+        assert_eq!(plan.amount_out, dec!(1998));
+        // 0.01 SOL and 0.01 XLM of gas, in XLM
+        assert_eq!(plan.total_gas_estimate, dec!(10.01));
+    }
+}
@@ -0,0 +1,559 @@
+// Split-order routing across DEXes
+//
+// A large order on one pool takes that pool's whole price impact. The split
+// router cuts the order into equal increments, quotes every DEX of the chain
+// at every multiple of the increment, and hands each increment to the DEX
+// whose output grows the most from it (the first increment on a DEX also
+// pays its gas). With the diminishing returns of AMM pools this greedy
+// allocation is optimal up to the increment size; the best single DEX is
+// kept when the split does not beat it after gas.
+
+use crate::adapters::traits::{AssetInfo, DexAdapter, PriceQuote, SwapRequest, SwapResult};
+use crate::adapters::AdapterRegistry;
+use crate::error::{AppError, AppResult};
+use crate::quote_engine::realtime::RealtimeQuoteEngine;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Clone)]
+pub struct SplitRouteConfig {
+    /// Equal parts the order is cut into
+    pub increments: u32,
+    /// Most DEXes one order is split across
+    pub max_dexes: usize,
+}
+
+impl Default for SplitRouteConfig {
+    fn default() -> Self {
+        Self {
+            increments: 10,
+            max_dexes: 3,
+        }
+    }
+}
+
+impl SplitRouteConfig {
+    /// Defaults, overridden by SPLIT_ROUTE_INCREMENTS (2-100) and
+    /// SPLIT_ROUTE_MAX_DEXES
+    pub fn from_env() -> AppResult<Self> {
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("SPLIT_ROUTE_INCREMENTS") {
+            config.increments = value
+                .parse::<u32>()
+                .ok()
+                .filter(|n| (2..=100).contains(n))
+                .ok_or_else(|| {
+                    AppError::Config("SPLIT_ROUTE_INCREMENTS must be between 2 and 100".to_string())
+                })?;
+        }
+        if let Ok(value) = std::env::var("SPLIT_ROUTE_MAX_DEXES") {
+            config.max_dexes = value
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| {
+                    AppError::Config("SPLIT_ROUTE_MAX_DEXES must be a positive integer".to_string())
+                })?;
+        }
+        Ok(config)
+    }
+}
+
+/// Part of an order swapped on one DEX
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SplitLeg {
+    pub dex: String,
+    pub amount_in: Decimal,
+    pub expected_output: Decimal,
+    pub slippage_percent: Decimal,
+    /// Native asset of the chain
+    pub gas_fee: Decimal,
+}
+
+/// Order split across DEXes; the legs are independent and can be swapped
+/// in parallel or one after the other
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SplitPlan {
+    pub asset_in: AssetInfo,
+    pub asset_out: AssetInfo,
+    pub amount_in: Decimal,
+    pub increments: u32,
+    pub legs: Vec<SplitLeg>,
+    pub total_amount_out: Decimal,
+    /// Native asset of the chain, summed over the legs
+    pub estimated_gas_fees: Decimal,
+    /// total_amount_out less the gas fees converted into the output asset
+    pub net_amount_out: Decimal,
+    /// Net output of the whole order on the best single DEX
+    pub single_dex_net_amount_out: Decimal,
+}
+
+impl SplitPlan {
+    /// One swap per leg, with the order's addresses and slippage tolerance
+    pub fn swap_requests(&self, order: &SwapRequest) -> Vec<(String, SwapRequest)> {
+        self.legs
+            .iter()
+            .map(|leg| {
+                let mut request = order.clone();
+                request.asset_in = self.asset_in.clone();
+                request.asset_out = self.asset_out.clone();
+                request.amount_in = leg.amount_in;
+                (leg.dex.clone(), request)
+            })
+            .collect()
+    }
+}
+
+/// Quotes of one DEX for 1..=n increments; stops at the first it refuses
+struct DexCurve {
+    dex: Arc<dyn DexAdapter>,
+    quotes: Vec<PriceQuote>,
+    gas_fee: Decimal,
+}
+
+impl DexCurve {
+    fn output(&self, increments: usize) -> Decimal {
+        match increments {
+            0 => Decimal::ZERO,
+            k => self.quotes[k - 1].amount_out,
+        }
+    }
+}
+
+pub struct SplitRouter {
+    adapter_registry: Arc<AdapterRegistry>,
+    realtime: Arc<RealtimeQuoteEngine>,
+    config: SplitRouteConfig,
+}
+
+impl SplitRouter {
+    pub fn new(
+        adapter_registry: Arc<AdapterRegistry>,
+        realtime: Arc<RealtimeQuoteEngine>,
+        config: SplitRouteConfig,
+    ) -> Self {
+        Self {
+            adapter_registry,
+            realtime,
+            config,
+        }
+    }
+
+    /// Split `amount` of `asset_in` across the chain's DEXes for the most
+    /// `asset_out` net of gas
+    pub async fn plan(
+        &self,
+        asset_in: &AssetInfo,
+        asset_out: &AssetInfo,
+        amount: Decimal,
+    ) -> AppResult<SplitPlan> {
+        if asset_in.chain != asset_out.chain {
+            return Err(AppError::InvalidInput(
+                "Split routes swap on a single chain".to_string(),
+            ));
+        }
+        if amount <= Decimal::ZERO {
+            return Err(AppError::InvalidInput(
+                "Amount must be positive".to_string(),
+            ));
+        }
+
+        let n = self.config.increments as usize;
+        let dexes = self
+            .adapter_registry
+            .get_all_dexes_for_chain(asset_in.chain)
+            .await;
+        let curves: Vec<DexCurve> = futures::future::join_all(
+            dexes
+                .into_iter()
+                .map(|dex| Self::curve(dex, asset_in, asset_out, amount, n)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if curves.is_empty() {
+            return Err(AppError::NoLiquidityAvailable(format!(
+                "No DEX quotes {} -> {} on {}",
+                asset_in.symbol, asset_out.symbol, asset_in.chain
+            )));
+        }
+
+        let gas_rate = match self.realtime.native_rate(asset_out).await {
+            Some(rate) => rate,
+            None => {
+                warn!(
+                    "No {} price for gas on {}, splitting by gross output",
+                    asset_out.symbol, asset_out.chain
+                );
+                Decimal::ZERO
+            }
+        };
+
+        let split = allocate(&curves, n, self.config.max_dexes, gas_rate);
+        let single = (0..curves.len())
+            .filter(|i| curves[*i].quotes.len() == n)
+            .map(|i| {
+                let mut allocation = vec![0; curves.len()];
+                allocation[i] = n;
+                allocation
+            })
+            .max_by_key(|allocation| net_output(&curves, allocation, gas_rate));
+
+        let single_dex_net_amount_out = single.as_ref().map_or(Decimal::ZERO, |allocation| {
+            net_output(&curves, allocation, gas_rate)
+        });
+        let allocation = match (split, single) {
+            (Some(split), Some(single))
+                if net_output(&curves, &single, gas_rate)
+                    >= net_output(&curves, &split, gas_rate) =>
+            {
+                single
+            }
+            (Some(split), _) => split,
+            (None, Some(single)) => single,
+            (None, None) => {
+                return Err(AppError::NoLiquidityAvailable(format!(
+                    "No DEX takes {} {} -> {}, whole or in parts",
+                    amount, asset_in.symbol, asset_out.symbol
+                )))
+            }
+        };
+
+        let mut legs: Vec<SplitLeg> = curves
+            .iter()
+            .zip(&allocation)
+            .filter(|(_, k)| **k > 0)
+            .map(|(curve, k)| {
+                let quote = &curve.quotes[*k - 1];
+                SplitLeg {
+                    dex: curve.dex.name().to_string(),
+                    amount_in: quote.amount_in,
+                    expected_output: quote.amount_out,
+                    slippage_percent: quote.slippage_percent,
+                    gas_fee: curve.gas_fee,
+                }
+            })
+            .collect();
+
+        // Legs are cut to the input token's decimals; the last one takes the
+        // remainder so the legs add up to the order
+        if let Some((last, rest)) = legs.split_last_mut() {
+            let mut allotted = Decimal::ZERO;
+            for leg in rest {
+                leg.amount_in = leg
+                    .amount_in
+                    .round_dp_with_strategy(asset_in.decimals as u32, RoundingStrategy::ToZero);
+                allotted += leg.amount_in;
+            }
+            last.amount_in = amount - allotted;
+        }
+
+        let plan = SplitPlan {
+            asset_in: asset_in.clone(),
+            asset_out: asset_out.clone(),
+            amount_in: amount,
+            increments: self.config.increments,
+            total_amount_out: legs.iter().map(|leg| leg.expected_output).sum(),
+            estimated_gas_fees: legs.iter().map(|leg| leg.gas_fee).sum(),
+            net_amount_out: net_output(&curves, &allocation, gas_rate),
+            single_dex_net_amount_out,
+            legs,
+        };
+        debug!(
+            "Split {} {} across {} DEXes: {} net vs {} on one",
+            amount,
+            asset_in.symbol,
+            plan.legs.len(),
+            plan.net_amount_out,
+            plan.single_dex_net_amount_out
+        );
+        Ok(plan)
+    }
+
+    /// Swap every leg of a plan at once
+    ///
+    /// Results are per leg, in plan order: a failed leg does not undo the
+    /// others.
+    pub async fn execute(
+        &self,
+        plan: &SplitPlan,
+        order: &SwapRequest,
+    ) -> Vec<(String, AppResult<SwapResult>)> {
+        let swaps = plan
+            .swap_requests(order)
+            .into_iter()
+            .map(|(dex, request)| async move {
+                let result = match self.adapter_registry.get_dex(&dex) {
+                    Some(adapter) => adapter.swap(request).await,
+                    None => Err(AppError::AdapterNotFound),
+                };
+                (dex, result)
+            });
+        let results = futures::future::join_all(swaps).await;
+
+        let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+        info!(
+            "🔀 Split swap of {} {}: {} of {} legs submitted",
+            plan.amount_in,
+            plan.asset_in.symbol,
+            results.len() - failed,
+            results.len()
+        );
+        results
+    }
+
+    async fn curve(
+        dex: Arc<dyn DexAdapter>,
+        asset_in: &AssetInfo,
+        asset_out: &AssetInfo,
+        amount: Decimal,
+        n: usize,
+    ) -> Option<DexCurve> {
+        let quotes = futures::future::join_all((1..=n).map(|k| {
+            let part = amount * Decimal::from(k) / Decimal::from(n);
+            dex.get_price(asset_in, asset_out, part)
+        }))
+        .await;
+        let quotes: Vec<PriceQuote> = quotes
+            .into_iter()
+            .map_while(|quote| quote.ok().filter(|q| q.amount_out > Decimal::ZERO))
+            .collect();
+        if quotes.is_empty() {
+            return None;
+        }
+
+        let gas_fee = match dex.estimate_gas(asset_in, asset_out).await {
+            Ok(gas) => gas,
+            Err(e) => {
+                debug!("Leaving {} out of the split: {}", dex.name(), e);
+                return None;
+            }
+        };
+        Some(DexCurve {
+            dex,
+            quotes,
+            gas_fee,
+        })
+    }
+}
+
+/// Increments per DEX, handed out one at a time to the DEX with the largest
+/// marginal net output; None if the DEXes cannot take the whole order
+fn allocate(
+    curves: &[DexCurve],
+    n: usize,
+    max_dexes: usize,
+    gas_rate: Decimal,
+) -> Option<Vec<usize>> {
+    let mut allocation = vec![0; curves.len()];
+    for _ in 0..n {
+        let used = allocation.iter().filter(|k| **k > 0).count();
+        let best = curves
+            .iter()
+            .enumerate()
+            .filter(|(i, curve)| {
+                allocation[*i] < curve.quotes.len() && (allocation[*i] > 0 || used < max_dexes)
+            })
+            .map(|(i, curve)| {
+                let k = allocation[i];
+                let mut gain = curve.output(k + 1) - curve.output(k);
+                if k == 0 {
+                    gain -= curve.gas_fee * gas_rate;
+                }
+                (i, gain)
+            })
+            .fold(
+                None,
+                |best: Option<(usize, Decimal)>, (i, gain)| match best {
+                    Some((_, best_gain)) if best_gain >= gain => best,
+                    _ => Some((i, gain)),
+                },
+            )?;
+        allocation[best.0] += 1;
+    }
+    Some(allocation)
+}
+
+fn net_output(curves: &[DexCurve], allocation: &[usize], gas_rate: Decimal) -> Decimal {
+    curves
+        .iter()
+        .zip(allocation)
+        .filter(|(_, k)| **k > 0)
+        .map(|(curve, k)| curve.output(*k) - curve.gas_fee * gas_rate)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::traits::SwapStatus;
+    use crate::adapters::DexWhitelist;
+    use crate::ledger::models::Chain;
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+
+    /// Constant-product pool with fixed gas
+    struct PoolDex {
+        name: &'static str,
+        reserve_in: Decimal,
+        reserve_out: Decimal,
+        gas: Decimal,
+    }
+
+    #[async_trait]
+    impl DexAdapter for PoolDex {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn supported_chains(&self) -> Vec<Chain> {
+            vec![Chain::Solana]
+        }
+
+        async fn get_price(
+            &self,
+            asset_in: &AssetInfo,
+            asset_out: &AssetInfo,
+            amount: Decimal,
+        ) -> AppResult<PriceQuote> {
+            let amount_out = amount * self.reserve_out / (self.reserve_in + amount);
+            Ok(PriceQuote {
+                asset_in: asset_in.clone(),
+                asset_out: asset_out.clone(),
+                amount_in: amount,
+                amount_out,
+                rate: amount_out / amount,
+                dex_name: self.name.to_string(),
+                chain: Chain::Solana,
+                slippage_percent: dec!(0.1),
+                execution_time_seconds: 1,
+                liquidity_available: self.reserve_out,
+                timestamp: 0,
+            })
+        }
+
+        async fn get_supported_assets(&self, _chain: Chain) -> AppResult<Vec<AssetInfo>> {
+            Ok(vec![])
+        }
+
+        async fn swap(&self, request: SwapRequest) -> AppResult<SwapResult> {
+            Ok(SwapResult {
+                transaction_hash: format!("{}-tx", self.name),
+                amount_in: request.amount_in,
+                amount_out: request.amount_in,
+                actual_rate: Decimal::ONE,
+                gas_fee: Some(self.gas),
+                status: SwapStatus::Pending,
+            })
+        }
+
+        async fn estimate_gas(
+            &self,
+            _asset_in: &AssetInfo,
+            _asset_out: &AssetInfo,
+        ) -> AppResult<Decimal> {
+            Ok(self.gas)
+        }
+    }
+
+    fn router(gas: Decimal) -> SplitRouter {
+        router_with(gas, &["PoolA", "PoolB"], SplitRouteConfig::default())
+    }
+
+    fn router_with(gas: Decimal, pools: &[&'static str], config: SplitRouteConfig) -> SplitRouter {
+        let mut registry = AdapterRegistry::new();
+        for &name in pools {
+            registry.register_dex(
+                name.to_string(),
+                Arc::new(PoolDex {
+                    name,
+                    reserve_in: dec!(1000),
+                    reserve_out: dec!(1000),
+                    gas,
+                }),
+            );
+        }
+        let registry = Arc::new(registry);
+        SplitRouter::new(
+            registry.clone(),
+            Arc::new(RealtimeQuoteEngine::new(registry)),
+            config,
+        )
+    }
+
+    fn token(symbol: &str) -> AssetInfo {
+        AssetInfo::from(
+            DexWhitelist::new()
+                .get_by_symbol(Chain::Solana, symbol)
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_large_order_is_split_evenly_across_equal_pools() {
+        let router = router(dec!(0.01));
+        let plan = router
+            .plan(&token("SOL"), &token("USDC"), dec!(100))
+            .await
+            .unwrap();
+
+        assert_eq!(plan.legs.len(), 2);
+        assert!(plan.legs.iter().all(|leg| leg.amount_in == dec!(50)));
+        // 90.9 on one pool, 95.2 on two
+        assert!(plan.net_amount_out > plan.single_dex_net_amount_out + dec!(4));
+
+        let order = SwapRequest {
+            asset_in: token("SOL"),
+            asset_out: token("USDC"),
+            amount_in: dec!(100),
+            recipient_address: "recipient".to_string(),
+            slippage_tolerance: dec!(1),
+            sender_address: "sender".to_string(),
+            sender_private_key: None,
+        };
+        let results = router.execute(&plan, &order).await;
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|(_, result)| result.as_ref().unwrap().amount_in == dec!(50)));
+    }
+
+    #[tokio::test]
+    async fn test_gas_keeps_order_on_one_pool() {
+        // A second swap costs more than the price impact it saves
+        let plan = router(dec!(10))
+            .plan(&token("SOL"), &token("USDC"), dec!(100))
+            .await
+            .unwrap();
+
+        assert_eq!(plan.legs.len(), 1);
+        assert_eq!(plan.legs[0].amount_in, dec!(100));
+        assert_eq!(plan.net_amount_out, plan.single_dex_net_amount_out);
+    }
+
+    #[tokio::test]
+    async fn test_legs_add_up_to_the_order() {
+        // A third of 100 SOL does not fit in 9 decimals
+        let config = SplitRouteConfig {
+            increments: 3,
+            max_dexes: 3,
+        };
+        let plan = router_with(dec!(0.01), &["PoolA", "PoolB", "PoolC"], config)
+            .plan(&token("SOL"), &token("USDC"), dec!(100))
+            .await
+            .unwrap();
+
+        assert_eq!(plan.legs.len(), 3);
+        assert_eq!(plan.legs[0].amount_in, dec!(33.333333333));
+        assert_eq!(plan.legs[1].amount_in, dec!(33.333333333));
+        assert_eq!(plan.legs[2].amount_in, dec!(33.333333334));
+        assert_eq!(
+            plan.legs.iter().map(|leg| leg.amount_in).sum::<Decimal>(),
+            dec!(100)
+        );
+    }
+}
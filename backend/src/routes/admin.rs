@@ -0,0 +1,2 @@
+/// admin Handler
+pub use crate::api::handler::{create_fee_schedule, get_reconciliation_status, get_trial_balance, list_chain_pairs, list_fee_schedules, list_price_feeds, reload_price_feeds, run_reconciliation, update_chain_pair, update_fee_schedule, upsert_price_feed};
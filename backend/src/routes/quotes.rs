@@ -1,7 +1,7 @@
 // Quotes handlers 
 pub use crate::{api::{
     handler::{create_quote, commit_quote, get_status},
-    phase2_quotes::{get_best_quote, get_price_impact, find_routes},
+    phase2_quotes::{get_best_quote, get_price_impact, find_routes, find_split_route},
     }
     
     
@@ -1,5 +1,5 @@
 use axum::{
-    Router, routing::{get, post},
+    Router, routing::{delete, get, post},
 };
 use http::HeaderName;
 use reqwest::header::HeaderValue;
@@ -13,10 +13,12 @@ use tower_http::{
 };
 use tracing::info;
 use crate::{
-    api::{discovery::{get_chain_discovery, list_assets_on_dex}, handler::{AppState, commit_quote, create_quote, create_spending_approval, get_chain_treasury_balance, get_settlement_status, get_spending_approval_status, get_status, get_treasury_balances, health_check, list_user_approvals, near_webhook, payment_webhook, solana_webhook, stellar_webhook, submit_spending_approval, get_ohlc_chart_query}, streaming::stream_quotes, token_approval::{create_token_approval, submit_token_approval, get_token_approval_status}},
+    middleware::idempotency_middleware,
+    api::{discovery::{get_chain_discovery, get_chain_pairs, list_assets_on_dex}, handler::{AppState, commit_quote, create_quote, create_spending_approval, get_chain_treasury_balance, get_settlement_status, get_spending_approval_status, get_status, get_treasury_balances, health_check, list_user_approvals, near_webhook, payment_webhook, refresh_quote, solana_webhook, stellar_webhook, submit_spending_approval, get_ohlc_chart_query, get_quote_cache_stats, clear_quote_cache}, streaming::stream_quotes, token_approval::{create_token_approval, submit_token_approval, get_token_approval_status}},
     routes::{
+        admin::{create_fee_schedule, get_reconciliation_status, get_trial_balance, list_chain_pairs, list_fee_schedules, list_price_feeds, reload_price_feeds, run_reconciliation, update_chain_pair, update_fee_schedule, upsert_price_feed},
         charts::{get_chart_stats, get_latest_candle, get_ohlc_chart},
-        quotes::{find_routes, get_best_quote, get_price_impact},
+        quotes::{find_routes, find_split_route, get_best_quote, get_price_impact},
         trade::{execute_trade, get_trade_status, get_user_trades_by_chain, initiate_trade},
         wallet::{get_user_portfolio, get_wallet_balance, register_wallet, verify_wallet}
     }, trading::handlers::get_user_trades,
@@ -40,15 +42,19 @@ pub async fn create_app(state: AppState) -> Router {
         // Discovery endpoints at root level
         .route("/discovery/chain/:chain", get(get_chain_discovery))
         .route("/discovery/dex/:dex/:chain", get(list_assets_on_dex))
+        .route("/discovery/pairs", get(get_chain_pairs))
         
         // Quote engine OHLC endpoint (convenience route with query params)
         .route("/quote-engine/ohlc", get(get_ohlc_chart_query))
+        .route("/quote-engine/cache/stats", get(get_quote_cache_stats))
+        .route("/quote-engine/cache", delete(clear_quote_cache))
         
         // API v1 routes with security middleware
         .nest("/api/v1", 
             Router::new()
                 // Quote endpoints
                 .route("/quote", post(create_quote))
+                .route("/quote/:id/refresh", post(refresh_quote))
                 .route("/commit", post(commit_quote))
                 .route("/status/:id", get(get_status))
                 
@@ -66,6 +72,7 @@ pub async fn create_app(state: AppState) -> Router {
                 .route("/best-quote", post(get_best_quote))
                 .route("/price-impact", post(get_price_impact))
                 .route("/routes", post(find_routes))
+                .route("/split-route", post(find_split_route))
                 .route("/stream-quotes", get(stream_quotes))
                 
                 // Wallet management endpoints
@@ -103,6 +110,17 @@ pub async fn create_app(state: AppState) -> Router {
                 // Admin endpoints
                 .route("/admin/treasury", get(get_treasury_balances))
                 .route("/admin/treasury/:chain", get(get_chain_treasury_balance))
+                .route("/admin/reconciliation", get(get_reconciliation_status))
+                .route("/admin/reconciliation/run", post(run_reconciliation))
+                .route("/admin/ledger/trial-balance", get(get_trial_balance))
+                .route("/admin/chain-pairs", get(list_chain_pairs))
+                .route("/admin/chain-pairs/:funding/:execution", post(update_chain_pair))
+                .route("/admin/fee-schedules", get(list_fee_schedules).post(create_fee_schedule))
+                .route("/admin/fee-schedules/:id", post(update_fee_schedule))
+                .route("/admin/price-feeds", get(list_price_feeds).post(upsert_price_feed))
+                .route("/admin/price-feeds/reload", post(reload_price_feeds))
+                // Idempotency-Key handling for every POST above
+                .layer(axum::middleware::from_fn_with_state(state.clone(), idempotency_middleware))
         )
         // Apply CORS layer - allow all origins in dev, restrict in prod
         .layer(CompressionLayer::new())
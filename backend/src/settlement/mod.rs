@@ -3,22 +3,464 @@ pub mod refiller;
 pub mod scheduler;
 
 
+use crate::adapters::dex_whitelist::{DexWhitelist, WhitelistedToken};
+use crate::error::AppResult;
+use crate::funding::{FundingVerifier, IncomingPayment};
+use crate::ledger::amount::TokenAmount;
+use crate::ledger::models::{
+    AuditEventType, Chain, FundingPayment, FundingPaymentStatus, Settlement, UnsettledExecution,
+};
 use crate::ledger::repository::LedgerRepository;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
+/// Executions reconciled per run
+const BATCH_SIZE: i64 = 500;
+
+/// Result of one reconciliation run
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Successful executions without a verified settlement
+    pub checked: usize,
+    /// Settlements recorded with a verified funding payment
+    pub settled: usize,
+    /// Not verified yet, still within the grace period
+    pub awaiting_payment: usize,
+    /// New discrepancies raised
+    pub discrepancies: usize,
+    /// Executions skipped because of RPC or database errors
+    pub errors: usize,
+}
+
+/// What to do with an unsettled execution
+#[derive(Debug, Clone, PartialEq)]
+enum Decision {
+    Settle { txn_hash: String, amount: TokenAmount },
+    Wait,
+    Discrepancy { txn_hash: Option<String>, reason: String },
+}
+
+/// Settlement reconciler - matches successful executions to funding payments
+///
+/// SECURITY: A settlement is only recorded once the funding transaction has
+/// been re-read from the source chain and pays at least the quoted amount.
 pub struct SettlementReconciler {
     ledger: Arc<LedgerRepository>,
+    verifiers: HashMap<Chain, Arc<dyn FundingVerifier>>,
+    whitelist: DexWhitelist,
+    grace_period: chrono::Duration,
+    last_report: RwLock<Option<ReconciliationReport>>,
 }
 
 impl SettlementReconciler {
     pub fn new(ledger: Arc<LedgerRepository>) -> Self {
-        Self { ledger }
+        Self {
+            ledger,
+            verifiers: HashMap::new(),
+            whitelist: DexWhitelist::new(),
+            grace_period: chrono::Duration::minutes(30),
+            last_report: RwLock::new(None),
+        }
+    }
+
+    /// Time an execution may stay unverified before it is flagged
+    pub fn with_grace_period(mut self, grace_period: chrono::Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Register the funding verifier for a chain
+    ///
+    /// SECURITY: Only call this during system initialization
+    pub fn register_verifier(&mut self, chain: Chain, verifier: Arc<dyn FundingVerifier>) {
+        info!("Registering funding verifier for chain: {:?}", chain);
+        self.verifiers.insert(chain, verifier);
+    }
+
+    /// Report of the most recent run
+    pub async fn last_report(&self) -> Option<ReconciliationReport> {
+        self.last_report.read().await.clone()
+    }
+
+    /// Reconcile successful executions that have no verified settlement
+    pub async fn reconcile_pending(&self) -> AppResult<ReconciliationReport> {
+        let started_at = Utc::now();
+        let executions = self.ledger.get_unsettled_executions(BATCH_SIZE).await?;
+
+        let mut report = ReconciliationReport {
+            started_at,
+            finished_at: started_at,
+            checked: executions.len(),
+            settled: 0,
+            awaiting_payment: 0,
+            discrepancies: 0,
+            errors: 0,
+        };
+
+        for execution in executions {
+            match self.reconcile_execution(&execution).await {
+                Ok(Decision::Settle { .. }) => report.settled += 1,
+                Ok(Decision::Wait) => report.awaiting_payment += 1,
+                Ok(Decision::Discrepancy { .. }) => report.discrepancies += 1,
+                Err(e) => {
+                    error!(
+                        "Failed to reconcile execution {}: {:?}",
+                        execution.execution_id, e
+                    );
+                    report.errors += 1;
+                }
+            }
+        }
+
+        report.finished_at = Utc::now();
+        info!(
+            "🧾 Reconciliation: {} checked, {} settled, {} awaiting payment, {} discrepancies, {} errors",
+            report.checked, report.settled, report.awaiting_payment, report.discrepancies, report.errors
+        );
+
+        *self.last_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Run reconcile_pending periodically in the background
+    pub fn start(self: Arc<Self>, interval: std::time::Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reconcile_pending().await {
+                    error!("Settlement reconciliation failed: {:?}", e);
+                }
+            }
+        })
+    }
+
+    /// Payment matched by a chain monitor, or else the one the webhook
+    /// recorded as an unverified settlement
+    async fn recorded_payment(
+        &self,
+        execution: &UnsettledExecution,
+    ) -> AppResult<Option<FundingPayment>> {
+        if let Some(payment) = self.ledger.get_matched_funding_payment(execution.quote_id).await? {
+            return Ok(Some(payment));
+        }
+
+        Ok(self
+            .ledger
+            .get_unverified_settlement(execution.execution_id)
+            .await?
+            .map(|settlement| webhook_payment(execution, settlement)))
+    }
+
+    async fn reconcile_execution(&self, execution: &UnsettledExecution) -> AppResult<Decision> {
+        let payment = self.recorded_payment(execution).await?;
+
+        let on_chain = match (&payment, self.verifiers.get(&execution.funding_chain)) {
+            (Some(payment), Some(verifier)) => verifier.verify(payment).await?,
+            _ => None,
+        };
+
+        let token = self
+            .whitelist
+            .get_by_symbol(execution.funding_chain, &execution.funding_asset)?;
+
+        let decision = decide(
+            execution,
+            &token,
+            payment.as_ref(),
+            on_chain.as_ref(),
+            self.verifiers.contains_key(&execution.funding_chain),
+            self.grace_period,
+            Utc::now(),
+        );
+
+        match &decision {
+            Decision::Settle { txn_hash, amount } => {
+                let settlement_id = self
+                    .ledger
+                    .create_verified_settlement(
+                        execution.execution_id,
+                        execution.funding_chain,
+                        txn_hash,
+                        amount.to_display()?,
+                    )
+                    .await?;
+
+                if let Some(settlement_id) = settlement_id {
+                    self.ledger
+                        .log_audit_event(
+                            AuditEventType::SettlementRecorded,
+                            Some(execution.funding_chain),
+                            Some(execution.quote_id),
+                            Some(execution.user_id),
+                            serde_json::json!({
+                                "settlement_id": settlement_id,
+                                "execution_id": execution.execution_id,
+                                "funding_txn_hash": txn_hash,
+                                "funding_amount": amount.to_display()?.to_string(),
+                                "verified": true,
+                            }),
+                        )
+                        .await?;
+                }
+            }
+            Decision::Wait => {}
+            Decision::Discrepancy { txn_hash, reason } => {
+                warn!(
+                    "🚨 Settlement discrepancy for execution {} (quote {}): {}",
+                    execution.execution_id, execution.quote_id, reason
+                );
+
+                let created = self
+                    .ledger
+                    .create_settlement_discrepancy(execution, txn_hash.as_deref(), reason)
+                    .await?;
+
+                if created {
+                    self.ledger
+                        .log_audit_event(
+                            AuditEventType::SettlementDiscrepancy,
+                            Some(execution.funding_chain),
+                            Some(execution.quote_id),
+                            Some(execution.user_id),
+                            serde_json::json!({
+                                "execution_id": execution.execution_id,
+                                "payment_address": execution.payment_address,
+                                "expected_amount": execution.funding_required.to_string(),
+                                "funding_txn_hash": txn_hash,
+                                "reason": reason,
+                            }),
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        Ok(decision)
+    }
+}
+
+/// Funding payment claimed by a webhook-recorded settlement
+///
+/// The webhook does not record the payment address, so the quote's is used:
+/// the payment must still be re-read on chain before the execution settles.
+fn webhook_payment(execution: &UnsettledExecution, settlement: Settlement) -> FundingPayment {
+    FundingPayment {
+        id: settlement.id,
+        chain: settlement.funding_chain,
+        transaction_hash: settlement.funding_txn_hash,
+        quote_id: Some(execution.quote_id),
+        payment_address: execution.payment_address.clone().unwrap_or_default(),
+        sender: None,
+        asset: execution.funding_asset.clone(),
+        amount: settlement.funding_amount,
+        status: FundingPaymentStatus::Matched.as_str().to_string(),
+        reason: None,
+        detected_at: settlement.settled_at,
     }
+}
 
-    pub async fn reconcile_pending(&self) -> anyhow::Result<()> {
-        // Find executions without settlements
-        // Verify funding payments on source chains
-        // Record settlements
-        Ok(())
+/// Decide the outcome for an execution from its recorded and on-chain payment
+///
+/// Amounts are compared in base units of the funding token.
+fn decide(
+    execution: &UnsettledExecution,
+    token: &WhitelistedToken,
+    payment: Option<&FundingPayment>,
+    on_chain: Option<&IncomingPayment>,
+    has_verifier: bool,
+    grace_period: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Decision {
+    let problem = match (payment, on_chain) {
+        (None, _) => "No funding payment recorded for quote".to_string(),
+        (Some(_), _) if !has_verifier => {
+            format!("No funding verifier for {:?}", execution.funding_chain)
+        }
+        (Some(_), None) => "Funding transaction not found on chain".to_string(),
+        (Some(_), Some(found)) if !found.asset.eq_ignore_ascii_case(&execution.funding_asset) => {
+            format!(
+                "Asset mismatch: expected {}, got {}",
+                execution.funding_asset, found.asset
+            )
+        }
+        (Some(_), Some(found)) => match covers_required(token, found, execution) {
+            Ok(amount) => {
+                return Decision::Settle {
+                    txn_hash: found.transaction_hash.clone(),
+                    amount,
+                }
+            }
+            Err(reason) => reason,
+        },
+    };
+
+    if now - execution.executed_at < grace_period {
+        return Decision::Wait;
     }
-}
\ No newline at end of file
+
+    Decision::Discrepancy {
+        txn_hash: payment.map(|p| p.transaction_hash.clone()),
+        reason: problem,
+    }
+}
+
+/// The on-chain amount, if it pays at least the quoted funding amount
+fn covers_required(
+    token: &WhitelistedToken,
+    found: &IncomingPayment,
+    execution: &UnsettledExecution,
+) -> Result<TokenAmount, String> {
+    let paid = TokenAmount::from_display(token, found.amount)
+        .map_err(|e| format!("Invalid payment amount: {}", e))?;
+    let required = TokenAmount::from_display(token, execution.funding_required)
+        .map_err(|e| format!("Invalid required amount: {}", e))?;
+
+    match paid.checked_cmp(&required) {
+        Ok(Ordering::Less) => Err(format!(
+            "Insufficient payment: {} < {}",
+            found.amount, execution.funding_required
+        )),
+        Ok(_) => Ok(paid),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    fn xlm() -> WhitelistedToken {
+        DexWhitelist::new().get_by_symbol(Chain::Stellar, "XLM").unwrap()
+    }
+
+    fn execution(executed_minutes_ago: i64) -> UnsettledExecution {
+        UnsettledExecution {
+            execution_id: Uuid::new_v4(),
+            quote_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            funding_chain: Chain::Stellar,
+            funding_asset: "XLM".to_string(),
+            payment_address: Some("GCOLLECT?memo=quote_abc".to_string()),
+            funding_required: Decimal::from(100),
+            executed_at: Utc::now() - chrono::Duration::minutes(executed_minutes_ago),
+        }
+    }
+
+    fn recorded(amount: i64) -> FundingPayment {
+        FundingPayment {
+            id: Uuid::new_v4(),
+            chain: Chain::Stellar,
+            transaction_hash: "abc".to_string(),
+            quote_id: None,
+            payment_address: "GCOLLECT?memo=quote_abc".to_string(),
+            sender: Some("GSENDER".to_string()),
+            asset: "XLM".to_string(),
+            amount: Decimal::from(amount),
+            status: "matched".to_string(),
+            reason: None,
+            detected_at: Utc::now(),
+        }
+    }
+
+    fn on_chain(asset: &str, amount: i64) -> IncomingPayment {
+        IncomingPayment {
+            chain: Chain::Stellar,
+            transaction_hash: "abc".to_string(),
+            payment_address: "GCOLLECT?memo=quote_abc".to_string(),
+            sender: Some("GSENDER".to_string()),
+            asset: asset.to_string(),
+            amount: Decimal::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_verified_payment_settles() {
+        let decision = decide(
+            &execution(5),
+            &xlm(),
+            Some(&recorded(100)),
+            Some(&on_chain("XLM", 101)),
+            true,
+            chrono::Duration::minutes(30),
+            Utc::now(),
+        );
+        assert_eq!(
+            decision,
+            Decision::Settle {
+                txn_hash: "abc".to_string(),
+                amount: TokenAmount::from_base(&xlm(), 1_010_000_000),
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_payment_waits_for_grace_period() {
+        let grace = chrono::Duration::minutes(30);
+        assert_eq!(
+            decide(&execution(5), &xlm(), None, None, true, grace, Utc::now()),
+            Decision::Wait
+        );
+
+        match decide(&execution(45), &xlm(), None, None, true, grace, Utc::now()) {
+            Decision::Discrepancy { txn_hash, .. } => assert!(txn_hash.is_none()),
+            other => panic!("expected discrepancy, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_webhook_settlement_is_verified_on_chain() {
+        let grace = chrono::Duration::minutes(30);
+        let execution = execution(45);
+        let settlement = Settlement {
+            id: Uuid::new_v4(),
+            execution_id: execution.execution_id,
+            funding_chain: Chain::Stellar,
+            funding_txn_hash: "abc".to_string(),
+            funding_amount: Decimal::from(100),
+            settled_at: Utc::now(),
+            verified_at: None,
+        };
+        let payment = webhook_payment(&execution, settlement);
+        assert_eq!(payment.payment_address, "GCOLLECT?memo=quote_abc");
+
+        assert_eq!(
+            decide(&execution, &xlm(), Some(&payment), Some(&on_chain("XLM", 100)), true, grace, Utc::now()),
+            Decision::Settle {
+                txn_hash: "abc".to_string(),
+                amount: TokenAmount::from_base(&xlm(), 1_000_000_000),
+            }
+        );
+
+        match decide(&execution, &xlm(), Some(&payment), None, true, grace, Utc::now()) {
+            Decision::Discrepancy { txn_hash, reason } => {
+                assert_eq!(txn_hash.as_deref(), Some("abc"));
+                assert_eq!(reason, "Funding transaction not found on chain");
+            }
+            other => panic!("expected discrepancy, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unverifiable_payment_is_discrepancy() {
+        let grace = chrono::Duration::minutes(30);
+        let payment = recorded(100);
+
+        for found in [None, Some(on_chain("USDC", 100)), Some(on_chain("XLM", 99))] {
+            match decide(&execution(45), &xlm(), Some(&payment), found.as_ref(), true, grace, Utc::now()) {
+                Decision::Discrepancy { txn_hash, .. } => assert_eq!(txn_hash.as_deref(), Some("abc")),
+                other => panic!("expected discrepancy, got {:?}", other),
+            }
+        }
+    }
+}
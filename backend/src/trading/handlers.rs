@@ -1,8 +1,8 @@
-use crate::adapters::traits::AssetInfo;
+use crate::adapters::traits::{AssetInfo, SwapRequest};
 use crate::api::handler::AppState;
 use crate::error::{AppError, AppResult};
 use crate::ledger::models::Chain;
-use crate::trading::models::{Trade};
+use crate::trading::models::{Trade, TradeLeg};
 
 use axum::{
     extract::{Path, State},
@@ -13,6 +13,7 @@ use axum::{
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use tracing::warn;
 use uuid::Uuid;
 
 #[derive(Deserialize)]
@@ -54,6 +55,8 @@ pub struct ExecuteTradeResponse {
     pub trade_id: Uuid,
     pub status: String,
     pub swap_transaction: String,
+    /// Split legs of a same-chain trade, empty otherwise
+    pub legs: Vec<TradeLeg>,
     pub estimated_completion_seconds: u64,
 }
 
@@ -73,6 +76,9 @@ pub struct TradeStatusResponse {
     pub gas_fees_paid: Option<Decimal>,
     pub created_at: String,
     pub completed_at: Option<String>,
+    /// Split legs; only on the single trade status
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legs: Option<Vec<TradeLeg>>,
 }
 
 #[derive(Serialize)]
@@ -176,7 +182,7 @@ pub async fn execute_trade(
     Json(req): Json<ExecuteTradeRequest>,
 ) -> AppResult<(StatusCode, Json<ExecuteTradeResponse>)> {
     // Verify user ownership
-    let mut trade = state.trade_repository.get_trade(req.trade_id).await?;
+    let trade = state.trade_repository.get_trade(req.trade_id).await?;
 
     if trade.user_id != req.user_id {
         return Err(AppError::Unauthorized);
@@ -201,33 +207,138 @@ pub async fn execute_trade(
         ));
     }
 
-    // Mark quote as accepted
-    trade.status = crate::trading::models::TradeStatus::QuoteAccepted;
-    let trade = state.trade_repository.update_trade(trade).await?;
+    let destination_wallet = state
+        .wallet_repository
+        .get_wallet(trade.destination_wallet_id)
+        .await?;
 
-    // Simulate swap execution (in production, would call actual executor)
-    let swap_tx = format!("0x{}", uuid::Uuid::new_v4().simple());
+    // Mark quote as accepted (409 unless the trade is still pending)
+    let trade = state.trade_repository.mark_quote_accepted(trade.id).await?;
+
+    let (swap_tx, legs) = if trade.source_chain == trade.destination_chain {
+        let order = SwapRequest {
+            asset_in: trade.asset_in.clone(),
+            asset_out: trade.asset_out.clone(),
+            amount_in: trade.amount_in,
+            recipient_address: destination_wallet.address,
+            slippage_tolerance: req.accept_slippage_percent,
+            sender_address: source_wallet.address,
+            sender_private_key: None,
+        };
+        swap_split(&state, &trade, &order).await?
+    } else {
+        // Simulate swap execution (in production, would call actual executor)
+        (format!("0x{}", uuid::Uuid::new_v4().simple()), Vec::new())
+    };
 
-    let trade = state
+    let mut trade = state
         .trade_repository
         .mark_executing(trade.id, swap_tx.clone())
         .await?;
+    state.trade_repository.record_legs(&legs).await?;
+
+    // Submitted legs are not undone; the trade keeps what they filled
+    let failed = failed_legs(&legs);
+    if !failed.is_empty() {
+        trade = state
+            .trade_repository
+            .mark_partially_filled(
+                trade.id,
+                format!("Split legs failed: {}", failed.join("; ")),
+            )
+            .await?;
+    }
 
     let response = ExecuteTradeResponse {
         trade_id: trade.id,
         status: format!("{:?}", trade.status).to_lowercase(),
         swap_transaction: swap_tx,
+        legs,
         estimated_completion_seconds: 30,
     };
 
     Ok((StatusCode::ACCEPTED, Json(response)))
 }
 
+/// Swap a same-chain trade split across the chain's DEXes
+///
+/// Returns the transaction hashes of the submitted legs, comma separated,
+/// and every leg with its outcome. The trade is failed, with its legs
+/// recorded, when no leg could be submitted.
+async fn swap_split(
+    state: &AppState,
+    trade: &Trade,
+    order: &SwapRequest,
+) -> AppResult<(String, Vec<TradeLeg>)> {
+    let plan = match state
+        .split_router
+        .plan(&trade.asset_in, &trade.asset_out, trade.amount_in)
+        .await
+    {
+        Ok(plan) => plan,
+        Err(e) => {
+            state
+                .trade_repository
+                .mark_failed(trade.id, e.to_string())
+                .await?;
+            return Err(e);
+        }
+    };
+
+    // Results come back in plan order
+    let results = state.split_router.execute(&plan, order).await;
+    let mut submitted = Vec::new();
+    let mut legs = Vec::new();
+    for (plan_leg, (dex, result)) in plan.legs.iter().zip(results) {
+        match result {
+            Ok(swap) => {
+                submitted.push(swap.transaction_hash.clone());
+                legs.push(TradeLeg::submitted(
+                    trade.id,
+                    dex,
+                    plan_leg.amount_in,
+                    swap.transaction_hash,
+                ));
+            }
+            Err(e) => {
+                warn!("Split leg on {} of trade {} failed: {}", dex, trade.id, e);
+                legs.push(TradeLeg::failed(trade.id, dex, plan_leg.amount_in, e.to_string()));
+            }
+        }
+    }
+
+    if submitted.is_empty() {
+        let error = format!(
+            "No split leg could be submitted: {}",
+            failed_legs(&legs).join("; ")
+        );
+        state.trade_repository.record_legs(&legs).await?;
+        state
+            .trade_repository
+            .mark_failed(trade.id, error.clone())
+            .await?;
+        return Err(AppError::ExternalError(error));
+    }
+    Ok((submitted.join(","), legs))
+}
+
+/// "dex: error" for each failed leg
+fn failed_legs(legs: &[TradeLeg]) -> Vec<String> {
+    legs.iter()
+        .filter_map(|leg| {
+            leg.error_message
+                .as_ref()
+                .map(|error| format!("{}: {}", leg.dex, error))
+        })
+        .collect()
+}
+
 pub async fn get_trade_status(
     State(state): State<AppState>,
     Path(trade_id): Path<Uuid>,
 ) -> AppResult<(StatusCode, Json<TradeStatusResponse>)> {
     let trade = state.trade_repository.get_trade(trade_id).await?;
+    let legs = state.trade_repository.get_trade_legs(trade_id).await?;
 
     let response = TradeStatusResponse {
         trade_id: trade.id,
@@ -244,6 +355,7 @@ pub async fn get_trade_status(
         gas_fees_paid: trade.gas_fees_paid,
         created_at: trade.created_at.to_rfc3339(),
         completed_at: trade.completed_at.map(|t| t.to_rfc3339()),
+        legs: Some(legs),
     };
 
     Ok((StatusCode::OK, Json(response)))
@@ -289,6 +401,7 @@ pub async fn get_user_trades(
             gas_fees_paid: t.gas_fees_paid,
             created_at: t.created_at.to_rfc3339(),
             completed_at: t.completed_at.map(|t| t.to_rfc3339()),
+            legs: None,
         })
         .collect();
 
@@ -355,6 +468,7 @@ pub async fn get_user_trades_by_chain(
             gas_fees_paid: t.gas_fees_paid,
             created_at: t.created_at.to_rfc3339(),
             completed_at: t.completed_at.map(|t| t.to_rfc3339()),
+            legs: None,
         })
         .collect();
 
@@ -1,17 +1,23 @@
 use crate::adapters::traits::AssetInfo;
+use crate::error::{AppError, AppResult};
 use crate::ledger::models::Chain;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sqlx::Type;
 use uuid::Uuid;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Type)]
 #[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "trade_status", rename_all = "snake_case")]
 pub enum TradeStatus {
     Pending,
     QuoteAccepted,
     PaymentReceived,
     ExecutingSwap,
+    /// Some split legs were submitted and others failed; terminal, the
+    /// submitted legs are not undone
+    PartiallyFilled,
     SwapCompleted,
     SettlementInProgress,
     Completed,
@@ -68,13 +74,31 @@ pub struct TradeQuote {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteStepKind {
+    /// DEX swap on one chain
+    Swap,
+    /// User pays the treasury on one chain, the treasury delivers on another
+    TreasuryTransfer,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RouteStep {
+    pub kind: RouteStepKind,
+    /// Chain the step delivers on
     pub chain: Chain,
+    /// DEX of a swap, "treasury" for a treasury transfer
     pub dex: String,
     pub asset_in: AssetInfo,
     pub asset_out: AssetInfo,
+    pub amount_in: Decimal,
     pub expected_output: Decimal,
+    /// Network fee of a swap, service fee of a treasury transfer
+    pub fee: Decimal,
+    /// Symbol `fee` is in
+    pub fee_asset: String,
+    pub slippage_percent: Decimal,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -116,6 +140,38 @@ pub struct SwapAggregatorResult {
     pub settlement_tx: Option<String>,
 }
 
+/// Outcome of one DEX leg of a split trade
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeLegStatus {
+    /// Swap transaction submitted to the DEX
+    Submitted,
+    /// DEX rejected the swap or it could not be sent
+    Failed,
+}
+
+impl TradeLegStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeLegStatus::Submitted => "submitted",
+            TradeLegStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One DEX leg of a split trade
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TradeLeg {
+    pub id: Uuid,
+    pub trade_id: Uuid,
+    pub dex: String,
+    pub amount_in: Decimal,
+    pub status: TradeLegStatus,
+    pub tx_hash: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 impl Trade {
     pub fn new(
         user_id: Uuid,
@@ -201,6 +257,60 @@ impl Trade {
     }
 }
 
+impl Trade {
+    /// Create from database row
+    pub fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<Self> {
+        use sqlx::Row;
+        use sqlx::types::BigDecimal;
+        use std::str::FromStr;
+
+        let decimal = |column: &str| -> AppResult<Decimal> {
+            let value: BigDecimal = row.try_get(column)?;
+            Decimal::from_str(&value.to_string())
+                .map_err(|_| AppError::InvalidInput(format!("Invalid {} format", column)))
+        };
+        let optional_decimal = |column: &str| -> AppResult<Option<Decimal>> {
+            let value: Option<BigDecimal> = row.try_get(column)?;
+            value
+                .map(|v| Decimal::from_str(&v.to_string()))
+                .transpose()
+                .map_err(|_| AppError::InvalidInput(format!("Invalid {} format", column)))
+        };
+        let asset = |column: &str| -> AppResult<AssetInfo> {
+            let value: serde_json::Value = row.try_get(column)?;
+            serde_json::from_value(value)
+                .map_err(|e| AppError::Internal(format!("Invalid {} JSON: {}", column, e)))
+        };
+
+        Ok(Trade {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            source_wallet_id: row.try_get("source_wallet_id")?,
+            destination_wallet_id: row.try_get("destination_wallet_id")?,
+            source_chain: row.try_get("source_chain")?,
+            destination_chain: row.try_get("destination_chain")?,
+            asset_in: asset("asset_in")?,
+            asset_out: asset("asset_out")?,
+            amount_in: decimal("amount_in")?,
+            amount_out_expected: decimal("amount_out_expected")?,
+            amount_out_actual: optional_decimal("amount_out_actual")?,
+            dex_used: row.try_get("dex_used")?,
+            status: row.try_get("status")?,
+            quote_id: row.try_get("quote_id")?,
+            source_tx_hash: row.try_get("source_tx_hash")?,
+            swap_tx_hash: row.try_get("swap_tx_hash")?,
+            destination_tx_hash: row.try_get("destination_tx_hash")?,
+            gas_fees_paid: optional_decimal("gas_fees_paid")?,
+            slippage_actual: optional_decimal("slippage_actual")?,
+            execution_price: optional_decimal("execution_price")?,
+            created_at: row.try_get("created_at")?,
+            executed_at: row.try_get("executed_at")?,
+            completed_at: row.try_get("completed_at")?,
+            error_message: row.try_get("error_message")?,
+        })
+    }
+}
+
 impl TradeQuote {
     pub fn is_expired(&self) -> bool {
         Utc::now() > self.expires_at
@@ -210,3 +320,62 @@ impl TradeQuote {
         (self.expires_at - Utc::now()).num_seconds().max(0)
     }
 }
+
+impl TradeLeg {
+    pub fn submitted(trade_id: Uuid, dex: String, amount_in: Decimal, tx_hash: String) -> Self {
+        Self::new(trade_id, dex, amount_in, TradeLegStatus::Submitted, Some(tx_hash), None)
+    }
+
+    pub fn failed(trade_id: Uuid, dex: String, amount_in: Decimal, error: String) -> Self {
+        Self::new(trade_id, dex, amount_in, TradeLegStatus::Failed, None, Some(error))
+    }
+
+    fn new(
+        trade_id: Uuid,
+        dex: String,
+        amount_in: Decimal,
+        status: TradeLegStatus,
+        tx_hash: Option<String>,
+        error_message: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            trade_id,
+            dex,
+            amount_in,
+            status,
+            tx_hash,
+            error_message,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Create from database row
+    pub fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<Self> {
+        use sqlx::Row;
+        use std::str::FromStr;
+
+        let amount_in: sqlx::types::BigDecimal = row.try_get("amount_in")?;
+        let amount_in = Decimal::from_str(&amount_in.to_string())
+            .map_err(|_| AppError::InvalidInput("Invalid amount_in format".to_string()))?;
+        let status: String = row.try_get("status")?;
+        let status = match status.as_str() {
+            "submitted" => TradeLegStatus::Submitted,
+            "failed" => TradeLegStatus::Failed,
+            other => {
+                return Err(AppError::Internal(format!("Invalid trade leg status: {}", other)))
+            }
+        };
+
+        Ok(TradeLeg {
+            id: row.try_get("id")?,
+            trade_id: row.try_get("trade_id")?,
+            dex: row.try_get("dex")?,
+            amount_in,
+            status,
+            tx_hash: row.try_get("tx_hash")?,
+            error_message: row.try_get("error_message")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
@@ -1,50 +1,138 @@
 use crate::error::{AppError, AppResult};
 use crate::ledger::models::Chain;
-use crate::trading::models::{Trade, TradeStatus};
-use chrono::Utc;
+use crate::ledger::repository::to_big_decimal;
+use crate::trading::models::{Trade, TradeLeg, TradeStatus};
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use sqlx::PgPool;
 use uuid::Uuid;
 
+const TRADE_COLUMNS: &str = r#"
+    id, user_id, source_wallet_id, destination_wallet_id,
+    source_chain, destination_chain, asset_in, asset_out,
+    amount_in, amount_out_expected, amount_out_actual,
+    dex_used, status, quote_id,
+    source_tx_hash, swap_tx_hash, destination_tx_hash,
+    gas_fees_paid, slippage_actual, execution_price,
+    created_at, executed_at, completed_at, error_message
+"#;
+
+/// Trade repository - persists trades in PostgreSQL
+///
+/// State transitions are single UPDATE statements guarded by the current
+/// status, so concurrent callers cannot move a trade through an invalid path.
 pub struct TradeRepository {
-    trades: tokio::sync::RwLock<HashMap<Uuid, Trade>>,
+    pool: PgPool,
 }
 
 impl TradeRepository {
-    pub fn new() -> Self {
-        Self {
-            trades: tokio::sync::RwLock::new(HashMap::new()),
-        }
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
     }
 
     pub async fn create_trade(&self, trade: Trade) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        trades.insert(trade.id, trade.clone());
-        Ok(trade)
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO trades (
+                id, user_id, source_wallet_id, destination_wallet_id,
+                source_chain, destination_chain, asset_in, asset_out,
+                amount_in, amount_out_expected, amount_out_actual,
+                dex_used, status, quote_id,
+                source_tx_hash, swap_tx_hash, destination_tx_hash,
+                gas_fees_paid, slippage_actual, execution_price,
+                created_at, executed_at, completed_at, error_message
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+                $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24
+            )
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade.id)
+        .bind(trade.user_id)
+        .bind(trade.source_wallet_id)
+        .bind(trade.destination_wallet_id)
+        .bind(trade.source_chain as Chain)
+        .bind(trade.destination_chain as Chain)
+        .bind(serde_json::to_value(&trade.asset_in).map_err(|e| AppError::Internal(e.to_string()))?)
+        .bind(serde_json::to_value(&trade.asset_out).map_err(|e| AppError::Internal(e.to_string()))?)
+        .bind(to_big_decimal(trade.amount_in)?)
+        .bind(to_big_decimal(trade.amount_out_expected)?)
+        .bind(trade.amount_out_actual.map(to_big_decimal).transpose()?)
+        .bind(&trade.dex_used)
+        .bind(trade.status.clone())
+        .bind(&trade.quote_id)
+        .bind(&trade.source_tx_hash)
+        .bind(&trade.swap_tx_hash)
+        .bind(&trade.destination_tx_hash)
+        .bind(trade.gas_fees_paid.map(to_big_decimal).transpose()?)
+        .bind(trade.slippage_actual.map(to_big_decimal).transpose()?)
+        .bind(trade.execution_price.map(to_big_decimal).transpose()?)
+        .bind(trade.created_at)
+        .bind(trade.executed_at)
+        .bind(trade.completed_at)
+        .bind(&trade.error_message)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Trade::from_row(&row)
     }
 
     pub async fn get_trade(&self, trade_id: Uuid) -> AppResult<Trade> {
-        let trades = self.trades.read().await;
-        trades
-            .get(&trade_id)
-            .cloned()
-            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))
+        let row = sqlx::query(&format!("SELECT {} FROM trades WHERE id = $1", TRADE_COLUMNS))
+            .bind(trade_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))?;
+
+        Trade::from_row(&row)
     }
 
+    /// Overwrite the mutable fields of a trade
+    ///
+    /// Status is left alone: it only moves through the guarded transitions below.
     pub async fn update_trade(&self, trade: Trade) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        trades.insert(trade.id, trade.clone());
-        Ok(trade)
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET amount_out_actual = $2,
+                source_tx_hash = $3, swap_tx_hash = $4, destination_tx_hash = $5,
+                gas_fees_paid = $6, slippage_actual = $7, execution_price = $8,
+                executed_at = $9, completed_at = $10, error_message = $11
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade.id)
+        .bind(trade.amount_out_actual.map(to_big_decimal).transpose()?)
+        .bind(&trade.source_tx_hash)
+        .bind(&trade.swap_tx_hash)
+        .bind(&trade.destination_tx_hash)
+        .bind(trade.gas_fees_paid.map(to_big_decimal).transpose()?)
+        .bind(trade.slippage_actual.map(to_big_decimal).transpose()?)
+        .bind(trade.execution_price.map(to_big_decimal).transpose()?)
+        .bind(trade.executed_at)
+        .bind(trade.completed_at)
+        .bind(&trade.error_message)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade.id)))?;
+
+        Trade::from_row(&row)
     }
 
     pub async fn get_user_trades(&self, user_id: Uuid) -> AppResult<Vec<Trade>> {
-        let trades = self.trades.read().await;
-        let user_trades = trades
-            .values()
-            .filter(|t| t.user_id == user_id)
-            .cloned()
-            .collect();
-        Ok(user_trades)
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM trades WHERE user_id = $1 ORDER BY created_at DESC",
+            TRADE_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Trade::from_row).collect()
     }
 
     pub async fn get_user_trades_by_status(
@@ -52,13 +140,16 @@ impl TradeRepository {
         user_id: Uuid,
         status: TradeStatus,
     ) -> AppResult<Vec<Trade>> {
-        let trades = self.trades.read().await;
-        let user_trades = trades
-            .values()
-            .filter(|t| t.user_id == user_id && t.status == status)
-            .cloned()
-            .collect();
-        Ok(user_trades)
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM trades WHERE user_id = $1 AND status = $2 ORDER BY created_at DESC",
+            TRADE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Trade::from_row).collect()
     }
 
     pub async fn get_user_trades_by_chain(
@@ -66,28 +157,77 @@ impl TradeRepository {
         user_id: Uuid,
         source_chain: Chain,
     ) -> AppResult<Vec<Trade>> {
-        let trades = self.trades.read().await;
-        let user_trades = trades
-            .values()
-            .filter(|t| t.user_id == user_id && t.source_chain == source_chain)
-            .cloned()
-            .collect();
-        Ok(user_trades)
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM trades WHERE user_id = $1 AND source_chain = $2 ORDER BY created_at DESC",
+            TRADE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(source_chain as Chain)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Trade::from_row).collect()
     }
 
-    pub async fn mark_executing(&self, trade_id: Uuid, swap_tx: String) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        let trade = trades
-            .get_mut(&trade_id)
-            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))?;
+    // ========== STATE TRANSITIONS ==========
 
-        trade.status = TradeStatus::ExecutingSwap;
-        trade.swap_tx_hash = Some(swap_tx);
-        trade.executed_at = Some(Utc::now());
+    /// Pending → QuoteAccepted
+    ///
+    /// A trade is accepted once: a trade that already moved on (or failed)
+    /// cannot be rewound and executed again.
+    pub async fn mark_quote_accepted(&self, trade_id: Uuid) -> AppResult<Trade> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $3
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::Pending)
+        .bind(TradeStatus::QuoteAccepted)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        Ok(trade.clone())
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => {
+                let trade = self.get_trade(trade_id).await?;
+                Err(AppError::Conflict(format!(
+                    "Trade {} is in status {:?} and can no longer be accepted",
+                    trade_id, trade.status
+                )))
+            }
+        }
     }
 
+    /// QuoteAccepted → ExecutingSwap
+    pub async fn mark_executing(&self, trade_id: Uuid, swap_tx: String) -> AppResult<Trade> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $3, swap_tx_hash = $4, executed_at = NOW()
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::QuoteAccepted)
+        .bind(TradeStatus::ExecutingSwap)
+        .bind(swap_tx)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => Err(self.invalid_transition(trade_id, &[TradeStatus::QuoteAccepted]).await),
+        }
+    }
+
+    /// ExecutingSwap → SwapCompleted
     pub async fn mark_swap_completed(
         &self,
         trade_id: Uuid,
@@ -95,73 +235,386 @@ impl TradeRepository {
         slippage: Decimal,
         execution_price: Decimal,
     ) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        let trade = trades
-            .get_mut(&trade_id)
-            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))?;
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $3, amount_out_actual = $4, slippage_actual = $5, execution_price = $6
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::ExecutingSwap)
+        .bind(TradeStatus::SwapCompleted)
+        .bind(to_big_decimal(amount_out)?)
+        .bind(to_big_decimal(slippage)?)
+        .bind(to_big_decimal(execution_price)?)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        trade.status = TradeStatus::SwapCompleted;
-        trade.amount_out_actual = Some(amount_out);
-        trade.slippage_actual = Some(slippage);
-        trade.execution_price = Some(execution_price);
-
-        Ok(trade.clone())
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => Err(self.invalid_transition(trade_id, &[TradeStatus::ExecutingSwap]).await),
+        }
     }
 
+    /// SwapCompleted → SettlementInProgress
     pub async fn mark_settlement_in_progress(
         &self,
         trade_id: Uuid,
         destination_tx: String,
     ) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        let trade = trades
-            .get_mut(&trade_id)
-            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))?;
-
-        trade.status = TradeStatus::SettlementInProgress;
-        trade.destination_tx_hash = Some(destination_tx);
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $3, destination_tx_hash = $4
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::SwapCompleted)
+        .bind(TradeStatus::SettlementInProgress)
+        .bind(destination_tx)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        Ok(trade.clone())
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => Err(self.invalid_transition(trade_id, &[TradeStatus::SwapCompleted]).await),
+        }
     }
 
+    /// SwapCompleted | SettlementInProgress → Completed
     pub async fn mark_completed(&self, trade_id: Uuid) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        let trade = trades
-            .get_mut(&trade_id)
-            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))?;
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $4, completed_at = NOW()
+            WHERE id = $1 AND status IN ($2, $3)
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::SwapCompleted)
+        .bind(TradeStatus::SettlementInProgress)
+        .bind(TradeStatus::Completed)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        trade.status = TradeStatus::Completed;
-        trade.completed_at = Some(Utc::now());
-
-        Ok(trade.clone())
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => Err(self
+                .invalid_transition(
+                    trade_id,
+                    &[TradeStatus::SwapCompleted, TradeStatus::SettlementInProgress],
+                )
+                .await),
+        }
     }
 
+    /// Any non-terminal status → Failed
     pub async fn mark_failed(&self, trade_id: Uuid, error: String) -> AppResult<Trade> {
-        let mut trades = self.trades.write().await;
-        let trade = trades
-            .get_mut(&trade_id)
-            .ok_or_else(|| AppError::NotFound(format!("Trade {} not found", trade_id)))?;
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $6, error_message = $7, completed_at = NOW()
+            WHERE id = $1 AND status NOT IN ($2, $3, $4, $5)
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::Completed)
+        .bind(TradeStatus::Failed)
+        .bind(TradeStatus::Cancelled)
+        .bind(TradeStatus::PartiallyFilled)
+        .bind(TradeStatus::Failed)
+        .bind(error)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => Err(self
+                .invalid_transition(
+                    trade_id,
+                    &[
+                        TradeStatus::Pending,
+                        TradeStatus::QuoteAccepted,
+                        TradeStatus::PaymentReceived,
+                        TradeStatus::ExecutingSwap,
+                        TradeStatus::SwapCompleted,
+                        TradeStatus::SettlementInProgress,
+                    ],
+                )
+                .await),
+        }
+    }
 
-        trade.status = TradeStatus::Failed;
-        trade.error_message = Some(error);
-        trade.completed_at = Some(Utc::now());
+    /// ExecutingSwap → PartiallyFilled, when some split legs failed
+    pub async fn mark_partially_filled(&self, trade_id: Uuid, error: String) -> AppResult<Trade> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE trades
+            SET status = $3, error_message = $4, completed_at = NOW()
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            TRADE_COLUMNS
+        ))
+        .bind(trade_id)
+        .bind(TradeStatus::ExecutingSwap)
+        .bind(TradeStatus::PartiallyFilled)
+        .bind(error)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        Ok(trade.clone())
+        match row {
+            Some(row) => Trade::from_row(&row),
+            None => Err(self.invalid_transition(trade_id, &[TradeStatus::ExecutingSwap]).await),
+        }
+    }
+
+    /// Store the legs of a split trade, all or none
+    pub async fn record_legs(&self, legs: &[TradeLeg]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for leg in legs {
+            sqlx::query(
+                r#"
+                INSERT INTO trade_legs (
+                    id, trade_id, dex, amount_in, status, tx_hash, error_message, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(leg.id)
+            .bind(leg.trade_id)
+            .bind(&leg.dex)
+            .bind(to_big_decimal(leg.amount_in)?)
+            .bind(leg.status.as_str())
+            .bind(&leg.tx_hash)
+            .bind(&leg.error_message)
+            .bind(leg.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_trade_legs(&self, trade_id: Uuid) -> AppResult<Vec<TradeLeg>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, trade_id, dex, amount_in, status, tx_hash, error_message, created_at
+            FROM trade_legs
+            WHERE trade_id = $1
+            ORDER BY created_at ASC, dex ASC
+            "#,
+        )
+        .bind(trade_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(TradeLeg::from_row).collect()
     }
 
     pub async fn get_pending_settlements(&self) -> AppResult<Vec<Trade>> {
-        let trades = self.trades.read().await;
-        let pending = trades
-            .values()
-            .filter(|t| t.status == TradeStatus::SettlementInProgress)
-            .cloned()
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM trades WHERE status = $1 ORDER BY created_at ASC",
+            TRADE_COLUMNS
+        ))
+        .bind(TradeStatus::SettlementInProgress)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Trade::from_row).collect()
+    }
+
+    /// Build the error for a guarded update that matched no rows: the trade
+    /// is not where the caller expected it, usually because another request
+    /// moved it first (409)
+    async fn invalid_transition(&self, trade_id: Uuid, expected: &[TradeStatus]) -> AppError {
+        match self.get_trade(trade_id).await {
+            Ok(trade) => AppError::Conflict(format!(
+                "Trade {} is in status {:?}, expected one of {:?}",
+                trade_id, trade.status, expected
+            )),
+            Err(e) => e,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::traits::AssetInfo;
+    use crate::trading::models::TradeLegStatus;
+    use crate::wallet::models::UserWallet;
+    use crate::wallet::repository::WalletRepository;
+    use rust_decimal_macros::dec;
+    use std::sync::Arc;
+
+    fn asset(symbol: &str) -> AssetInfo {
+        AssetInfo {
+            chain: Chain::Solana,
+            address: format!("{}-mint", symbol),
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            decimals: 9,
+            logo_url: None,
+        }
+    }
+
+    /// Pending SOL -> USDC trade between two fresh wallets
+    async fn pending_trade(pool: &PgPool, repository: &TradeRepository) -> Trade {
+        let wallets = WalletRepository::new(pool.clone());
+        let user = Uuid::new_v4();
+        let mut wallet_ids = vec![];
+        for address in ["source-wallet", "destination-wallet"] {
+            let wallet = wallets
+                .register_wallet(UserWallet::new(user, Chain::Solana, address.to_string()))
+                .await
+                .unwrap();
+            wallet_ids.push(wallet.id);
+        }
+
+        repository
+            .create_trade(Trade::new(
+                user,
+                wallet_ids[0],
+                wallet_ids[1],
+                Chain::Solana,
+                Chain::Solana,
+                asset("SOL"),
+                asset("USDC"),
+                dec!(2),
+                dec!(200),
+                "Raydium".to_string(),
+                Uuid::new_v4().to_string(),
+            ))
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_transitions_follow_the_trade_path(pool: PgPool) {
+        let repository = TradeRepository::new(pool.clone());
+        let trade = pending_trade(&pool, &repository).await;
+        assert_eq!(trade.status, TradeStatus::Pending);
+
+        // Out of order
+        assert!(matches!(
+            repository.mark_executing(trade.id, "tx".to_string()).await,
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            repository.mark_completed(trade.id).await,
+            Err(AppError::Conflict(_))
+        ));
+
+        repository.mark_quote_accepted(trade.id).await.unwrap();
+        let executing = repository.mark_executing(trade.id, "swap-tx".to_string()).await.unwrap();
+        assert_eq!(executing.status, TradeStatus::ExecutingSwap);
+        assert_eq!(executing.swap_tx_hash.as_deref(), Some("swap-tx"));
+        assert!(executing.executed_at.is_some());
+
+        let swapped = repository
+            .mark_swap_completed(trade.id, dec!(199.5), dec!(0.25), dec!(99.75))
+            .await
+            .unwrap();
+        assert_eq!(swapped.amount_out_actual, Some(dec!(199.5)));
+        repository
+            .mark_settlement_in_progress(trade.id, "destination-tx".to_string())
+            .await
+            .unwrap();
+        let completed = repository.mark_completed(trade.id).await.unwrap();
+        assert_eq!(completed.status, TradeStatus::Completed);
+        assert!(completed.completed_at.is_some());
+
+        // Terminal
+        assert!(matches!(
+            repository.mark_failed(trade.id, "late".to_string()).await,
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            repository.mark_failed(Uuid::new_v4(), "missing".to_string()).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_concurrent_transitions_apply_once(pool: PgPool) {
+        let repository = Arc::new(TradeRepository::new(pool.clone()));
+        let trade = pending_trade(&pool, &repository).await;
+        repository.mark_quote_accepted(trade.id).await.unwrap();
+
+        let attempts = (0..8).map(|i| {
+            let repository = repository.clone();
+            tokio::spawn(async move { repository.mark_executing(trade.id, format!("tx-{}", i)).await })
+        });
+        let results: Vec<_> = futures::future::join_all(attempts)
+            .await
+            .into_iter()
+            .map(|joined| joined.unwrap())
             .collect();
-        Ok(pending)
+
+        let applied: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        assert_eq!(applied.len(), 1);
+        assert!(results
+            .iter()
+            .filter(|r| r.is_err())
+            .all(|r| matches!(r, Err(AppError::Conflict(_)))));
+
+        // The winner's transaction is the one stored
+        let stored = repository.get_trade(trade.id).await.unwrap();
+        assert_eq!(stored.swap_tx_hash, applied[0].swap_tx_hash);
     }
 
-    pub async fn clear_all(&self) -> AppResult<()> {
-        let mut trades = self.trades.write().await;
-        trades.clear();
-        Ok(())
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_split_with_failed_legs_is_partially_filled(pool: PgPool) {
+        let repository = TradeRepository::new(pool.clone());
+        let trade = pending_trade(&pool, &repository).await;
+        repository.mark_quote_accepted(trade.id).await.unwrap();
+        repository
+            .mark_executing(trade.id, "raydium-tx,orca-tx".to_string())
+            .await
+            .unwrap();
+
+        repository
+            .record_legs(&[
+                TradeLeg::submitted(trade.id, "Raydium".to_string(), dec!(1), "raydium-tx".to_string()),
+                TradeLeg::failed(trade.id, "Jupiter".to_string(), dec!(0.5), "pool drained".to_string()),
+                TradeLeg::submitted(trade.id, "Orca".to_string(), dec!(0.5), "orca-tx".to_string()),
+            ])
+            .await
+            .unwrap();
+        let partial = repository
+            .mark_partially_filled(trade.id, "Split legs failed: Jupiter: pool drained".to_string())
+            .await
+            .unwrap();
+        assert_eq!(partial.status, TradeStatus::PartiallyFilled);
+        assert_eq!(partial.swap_tx_hash.as_deref(), Some("raydium-tx,orca-tx"));
+        assert!(partial.completed_at.is_some());
+
+        let legs = repository.get_trade_legs(trade.id).await.unwrap();
+        assert_eq!(legs.len(), 3);
+        let leg = |dex: &str| legs.iter().find(|leg| leg.dex == dex).unwrap();
+        assert_eq!(leg("Raydium").status, TradeLegStatus::Submitted);
+        assert_eq!(leg("Raydium").tx_hash.as_deref(), Some("raydium-tx"));
+        assert_eq!(leg("Orca").tx_hash.as_deref(), Some("orca-tx"));
+        assert_eq!(leg("Jupiter").status, TradeLegStatus::Failed);
+        assert_eq!(leg("Jupiter").tx_hash, None);
+        assert_eq!(leg("Jupiter").error_message.as_deref(), Some("pool drained"));
+        assert_eq!(leg("Jupiter").amount_in, dec!(0.5));
+
+        // Terminal: the submitted legs are not undone
+        assert!(matches!(
+            repository.mark_failed(trade.id, "late".to_string()).await,
+            Err(AppError::Conflict(_))
+        ));
     }
 }
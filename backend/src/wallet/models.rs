@@ -1,18 +1,21 @@
+use crate::error::{AppError, AppResult};
 use crate::ledger::models::Chain;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sqlx::{Type, prelude::FromRow};
 use uuid::Uuid;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Type)]
 #[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "wallet_status", rename_all = "lowercase")]
 pub enum WalletStatus {
     Unverified,
     Verified,
     Suspended,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, FromRow)]
 pub struct UserWallet {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -115,6 +118,34 @@ impl UserWallet {
     }
 }
 
+impl WalletBalance {
+    /// Create from database row
+    pub fn from_row(row: &sqlx::postgres::PgRow) -> AppResult<Self> {
+        use sqlx::Row;
+        use sqlx::types::BigDecimal;
+        use std::str::FromStr;
+
+        let balance: BigDecimal = row.try_get("balance")?;
+        let balance = Decimal::from_str(&balance.to_string())
+            .map_err(|_| AppError::InvalidInput("Invalid balance format".to_string()))?;
+
+        let balance_usd: Option<BigDecimal> = row.try_get("balance_usd")?;
+        let balance_usd = balance_usd
+            .map(|usd| Decimal::from_str(&usd.to_string()))
+            .transpose()
+            .map_err(|_| AppError::InvalidInput("Invalid USD balance format".to_string()))?;
+
+        Ok(WalletBalance {
+            wallet_id: row.try_get("wallet_id")?,
+            token_address: row.try_get("token_address")?,
+            token_symbol: row.try_get("token_symbol")?,
+            balance,
+            balance_usd,
+            last_updated: row.try_get("last_updated")?,
+        })
+    }
+}
+
 impl ChainBalance {
     pub fn new(chain: Chain, native_symbol: String) -> Self {
         Self {
@@ -1,47 +1,80 @@
 use crate::error::{AppError, AppResult};
 use crate::ledger::models::Chain;
+use crate::ledger::repository::to_big_decimal;
 use crate::wallet::models::{UserWallet, WalletBalance, ChainBalance, WalletStatus};
-use chrono::Utc;
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use sqlx::PgPool;
 use uuid::Uuid;
 
+const WALLET_COLUMNS: &str =
+    "id, user_id, chain, address, status, verified_at, created_at, last_synced";
+
+/// Wallet repository - persists user wallets and balances in PostgreSQL
 pub struct WalletRepository {
-    // In production, this would be PostgreSQL via sqlx
-    wallets: tokio::sync::RwLock<HashMap<Uuid, UserWallet>>,
-    balances: tokio::sync::RwLock<HashMap<Uuid, Vec<WalletBalance>>>,
+    pool: PgPool,
 }
 
 impl WalletRepository {
-    pub fn new() -> Self {
-        Self {
-            wallets: tokio::sync::RwLock::new(HashMap::new()),
-            balances: tokio::sync::RwLock::new(HashMap::new()),
-        }
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
     }
 
+    /// Register a new wallet
+    ///
+    /// SECURITY: (chain, address) is unique - the same address cannot be
+    /// registered twice, even by different users
     pub async fn register_wallet(&self, wallet: UserWallet) -> AppResult<UserWallet> {
-        let mut wallets = self.wallets.write().await;
-        wallets.insert(wallet.id, wallet.clone());
+        let wallet = sqlx::query_as::<_, UserWallet>(&format!(
+            r#"
+            INSERT INTO user_wallets (
+                id, user_id, chain, address, status, verified_at, created_at, last_synced
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {}
+            "#,
+            WALLET_COLUMNS
+        ))
+        .bind(wallet.id)
+        .bind(wallet.user_id)
+        .bind(wallet.chain as Chain)
+        .bind(&wallet.address)
+        .bind(wallet.status)
+        .bind(wallet.verified_at)
+        .bind(wallet.created_at)
+        .bind(wallet.last_synced)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                AppError::InvalidInput("Wallet already registered".to_string())
+            }
+            other => other.into(),
+        })?;
+
         Ok(wallet)
     }
 
     pub async fn get_wallet(&self, wallet_id: Uuid) -> AppResult<UserWallet> {
-        let wallets = self.wallets.read().await;
-        wallets
-            .get(&wallet_id)
-            .cloned()
-            .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", wallet_id)))
+        sqlx::query_as::<_, UserWallet>(&format!(
+            "SELECT {} FROM user_wallets WHERE id = $1",
+            WALLET_COLUMNS
+        ))
+        .bind(wallet_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", wallet_id)))
     }
 
     pub async fn get_user_wallets(&self, user_id: Uuid) -> AppResult<Vec<UserWallet>> {
-        let wallets = self.wallets.read().await;
-        let user_wallets = wallets
-            .values()
-            .filter(|w| w.user_id == user_id)
-            .cloned()
-            .collect();
-        Ok(user_wallets)
+        let wallets = sqlx::query_as::<_, UserWallet>(&format!(
+            "SELECT {} FROM user_wallets WHERE user_id = $1 ORDER BY created_at ASC",
+            WALLET_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(wallets)
     }
 
     pub async fn get_wallet_by_address(
@@ -49,23 +82,36 @@ impl WalletRepository {
         chain: Chain,
         address: &str,
     ) -> AppResult<Option<UserWallet>> {
-        let wallets = self.wallets.read().await;
-        Ok(wallets
-            .values()
-            .find(|w| w.chain == chain && w.address == address)
-            .cloned())
+        let wallet = sqlx::query_as::<_, UserWallet>(&format!(
+            "SELECT {} FROM user_wallets WHERE chain = $1 AND address = $2",
+            WALLET_COLUMNS
+        ))
+        .bind(chain as Chain)
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(wallet)
     }
 
+    /// Mark wallet as verified
+    ///
+    /// Keeps the original verification timestamp if the wallet was already verified
     pub async fn verify_wallet(&self, wallet_id: Uuid) -> AppResult<UserWallet> {
-        let mut wallets = self.wallets.write().await;
-        let wallet = wallets
-            .get_mut(&wallet_id)
-            .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", wallet_id)))?;
-
-        wallet.status = WalletStatus::Verified;
-        wallet.verified_at = Some(Utc::now());
-
-        Ok(wallet.clone())
+        sqlx::query_as::<_, UserWallet>(&format!(
+            r#"
+            UPDATE user_wallets
+            SET status = $2, verified_at = COALESCE(verified_at, NOW())
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            WALLET_COLUMNS
+        ))
+        .bind(wallet_id)
+        .bind(WalletStatus::Verified as WalletStatus)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Wallet {} not found", wallet_id)))
     }
 
     pub async fn set_wallet_balance(
@@ -75,37 +121,51 @@ impl WalletRepository {
         token_symbol: String,
         balance: Decimal,
     ) -> AppResult<WalletBalance> {
-        let mut balances = self.balances.write().await;
-        let wallet_balances = balances.entry(wallet_id).or_insert_with(Vec::new);
+        let mut tx = self.pool.begin().await?;
 
         // Update existing or insert new
-        if let Some(existing) = wallet_balances.iter_mut().find(|b| b.token_address == token_address) {
-            existing.balance = balance;
-            existing.last_updated = Utc::now();
-        } else {
-            wallet_balances.push(WalletBalance {
-                wallet_id,
-                token_address: token_address.clone(),
-                token_symbol,
-                balance,
-                balance_usd: None,
-                last_updated: Utc::now(),
-            });
-        }
+        let row = sqlx::query(
+            r#"
+            INSERT INTO wallet_balances (wallet_id, token_address, token_symbol, balance, last_updated)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (wallet_id, token_address)
+            DO UPDATE SET
+                balance = EXCLUDED.balance,
+                last_updated = EXCLUDED.last_updated
+            RETURNING wallet_id, token_address, token_symbol, balance, balance_usd, last_updated
+            "#
+        )
+        .bind(wallet_id)
+        .bind(&token_address)
+        .bind(&token_symbol)
+        .bind(to_big_decimal(balance)?)
+        .fetch_one(&mut *tx)
+        .await?;
 
-        Ok(wallet_balances
-            .iter()
-            .find(|b| b.token_address == token_address)
-            .cloned()
-            .unwrap())
+        sqlx::query("UPDATE user_wallets SET last_synced = NOW() WHERE id = $1")
+            .bind(wallet_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        WalletBalance::from_row(&row)
     }
 
     pub async fn get_wallet_balances(&self, wallet_id: Uuid) -> AppResult<Vec<WalletBalance>> {
-        let balances = self.balances.read().await;
-        Ok(balances
-            .get(&wallet_id)
-            .cloned()
-            .unwrap_or_default())
+        let rows = sqlx::query(
+            r#"
+            SELECT wallet_id, token_address, token_symbol, balance, balance_usd, last_updated
+            FROM wallet_balances
+            WHERE wallet_id = $1
+            ORDER BY token_symbol ASC
+            "#
+        )
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(WalletBalance::from_row).collect()
     }
 
     pub async fn get_user_balance_by_chain(
@@ -113,20 +173,52 @@ impl WalletRepository {
         user_id: Uuid,
         chain: Chain,
     ) -> AppResult<ChainBalance> {
-        let wallets = self.wallets.read().await;
-        let user_wallet = wallets
-            .values()
-            .find(|w| w.user_id == user_id && w.chain == chain)
-            .ok_or_else(|| {
-                AppError::NotFound(format!(
-                    "No wallet found for user {} on {}",
-                    user_id, chain
-                ))
-            })?;
-
-        let balances = self.balances.read().await;
-        let wallet_balances = balances.get(&user_wallet.id).cloned().unwrap_or_default();
+        let user_wallet = sqlx::query_as::<_, UserWallet>(&format!(
+            r#"
+            SELECT {} FROM user_wallets
+            WHERE user_id = $1 AND chain = $2
+            ORDER BY created_at ASC
+            LIMIT 1
+            "#,
+            WALLET_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(chain as Chain)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No wallet found for user {} on {}",
+                user_id, chain
+            ))
+        })?;
+
+        let wallet_balances = self.get_wallet_balances(user_wallet.id).await?;
+        Ok(Self::build_chain_balance(chain, wallet_balances))
+    }
+
+    pub async fn get_user_all_balances(&self, user_id: Uuid) -> AppResult<Vec<ChainBalance>> {
+        let user_wallets = self.get_user_wallets(user_id).await?;
+        let mut all_balances = vec![];
+
+        for wallet in user_wallets {
+            let wallet_balances = self.get_wallet_balances(wallet.id).await?;
+            all_balances.push(Self::build_chain_balance(wallet.chain, wallet_balances));
+        }
 
+        Ok(all_balances)
+    }
+
+    /// Remove every wallet; their balances go with them
+    pub async fn clear_all(&self) -> AppResult<()> {
+        sqlx::query("DELETE FROM user_wallets")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Aggregate token balances of a wallet into a chain balance
+    fn build_chain_balance(chain: Chain, wallet_balances: Vec<WalletBalance>) -> ChainBalance {
         let native_symbol = match chain {
             Chain::Solana => "SOL",
             Chain::Stellar => "XLM",
@@ -146,50 +238,95 @@ impl WalletRepository {
         }
 
         chain_balance.calculate_total_usd();
-        Ok(chain_balance)
+        chain_balance
     }
+}
 
-    pub async fn get_user_all_balances(&self, user_id: Uuid) -> AppResult<Vec<ChainBalance>> {
-        let wallets = self.wallets.read().await;
-        let user_wallets: Vec<_> = wallets
-            .values()
-            .filter(|w| w.user_id == user_id)
-            .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
 
-        let balances = self.balances.read().await;
-        let mut all_balances = vec![];
+    const SOLANA_ADDRESS: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const STELLAR_ADDRESS: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4IHTZMWYRZUW3HJSTENCEWXSEOFWYFFM";
 
-        for wallet in user_wallets {
-            let wallet_balances = balances.get(&wallet.id).cloned().unwrap_or_default();
-            let native_symbol = match wallet.chain {
-                Chain::Solana => "SOL",
-                Chain::Stellar => "XLM",
-                Chain::Near => "NEAR",
-            };
-
-            let mut chain_balance = ChainBalance::new(wallet.chain, native_symbol.to_string());
-            chain_balance.tokens = wallet_balances;
-
-            if let Some(native) = chain_balance
-                .tokens
-                .iter()
-                .find(|t| t.token_address == native_symbol.to_lowercase())
-            {
-                chain_balance.native_balance = native.balance;
-            }
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_address_is_registered_once_per_chain(pool: PgPool) {
+        let repository = WalletRepository::new(pool);
+        let owner = Uuid::new_v4();
+        repository
+            .register_wallet(UserWallet::new(owner, Chain::Solana, SOLANA_ADDRESS.to_string()))
+            .await
+            .unwrap();
 
-            chain_balance.calculate_total_usd();
-            all_balances.push(chain_balance);
+        // Not again, not even by another user
+        for user in [owner, Uuid::new_v4()] {
+            let duplicate = repository
+                .register_wallet(UserWallet::new(user, Chain::Solana, SOLANA_ADDRESS.to_string()))
+                .await;
+            assert!(matches!(duplicate, Err(AppError::InvalidInput(_))));
         }
 
-        Ok(all_balances)
+        // The same string on another chain is another wallet
+        repository
+            .register_wallet(UserWallet::new(owner, Chain::Near, SOLANA_ADDRESS.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(repository.get_user_wallets(owner).await.unwrap().len(), 2);
+        let found = repository
+            .get_wallet_by_address(Chain::Solana, SOLANA_ADDRESS)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.user_id, owner);
     }
 
-    pub async fn clear_all(&self) -> AppResult<()> {
-        let mut wallets = self.wallets.write().await;
-        let mut balances = self.balances.write().await;
-        wallets.clear();
-        balances.clear();
-        Ok(())
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_verification_keeps_the_first_timestamp(pool: PgPool) {
+        let repository = WalletRepository::new(pool);
+        let wallet = repository
+            .register_wallet(UserWallet::new(Uuid::new_v4(), Chain::Stellar, STELLAR_ADDRESS.to_string()))
+            .await
+            .unwrap();
+        assert!(!wallet.is_verified());
+        assert_eq!(wallet.verified_at, None);
+
+        let verified = repository.verify_wallet(wallet.id).await.unwrap();
+        assert!(verified.is_verified());
+        let verified_at = verified.verified_at.unwrap();
+
+        let again = repository.verify_wallet(wallet.id).await.unwrap();
+        assert_eq!(again.verified_at, Some(verified_at));
+        assert!(matches!(
+            repository.verify_wallet(Uuid::new_v4()).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_balances_are_replaced_and_cleared(pool: PgPool) {
+        let repository = WalletRepository::new(pool);
+        let user = Uuid::new_v4();
+        let wallet = repository
+            .register_wallet(UserWallet::new(user, Chain::Stellar, STELLAR_ADDRESS.to_string()))
+            .await
+            .unwrap();
+
+        for balance in [dec!(10), dec!(12.5)] {
+            repository
+                .set_wallet_balance(wallet.id, "xlm".to_string(), "XLM".to_string(), balance)
+                .await
+                .unwrap();
+        }
+        let balances = repository.get_wallet_balances(wallet.id).await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].balance, dec!(12.5));
+        let chain = repository.get_user_balance_by_chain(user, Chain::Stellar).await.unwrap();
+        assert_eq!(chain.native_balance, dec!(12.5));
+        assert!(repository.get_wallet(wallet.id).await.unwrap().last_synced.is_some());
+
+        repository.clear_all().await.unwrap();
+        assert!(repository.get_user_wallets(user).await.unwrap().is_empty());
+        assert!(repository.get_wallet_balances(wallet.id).await.unwrap().is_empty());
     }
 }